@@ -0,0 +1,123 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Summaries of the files a mod has in common with other mods.
+
+use std::collections::BTreeMap;
+
+use camino::Utf8PathBuf;
+
+use super::{FileTree, ModVec, TreeNodeKind, node_path};
+use crate::instance::ModIndex;
+
+/// The files of a mod that are also provided by other mods, as found in a [`FileTree<ModVec>`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModConflicts {
+    /// Files of lower priority mods that are overridden by this mod, grouped by the mod that provides them.
+    pub overrides: BTreeMap<ModIndex, Vec<Utf8PathBuf>>,
+    /// Files of this mod that are overridden by higher priority mods, grouped by the mod that overrides them.
+    pub overridden_by: BTreeMap<ModIndex, Vec<Utf8PathBuf>>,
+}
+
+impl ModConflicts {
+    /// Collects the conflicts of the specified mod from a tree built with
+    /// [`FileTreeBuilder::iter_mods`](super::FileTreeBuilder::iter_mods).
+    ///
+    /// Paths are relative to the deployment root, and sorted.
+    #[must_use]
+    pub fn new(tree: &FileTree<ModVec>, mod_index: ModIndex) -> Self {
+        let mut conflicts = Self::default();
+        let root = tree.root().expect("has root node");
+        for node in root.traverse_pre_order() {
+            let TreeNodeKind::File(providing_mods) = &node.data().kind else {
+                continue;
+            };
+            let Some(position) = providing_mods.iter().position(|idx| *idx == mod_index) else {
+                continue;
+            };
+            if providing_mods.len() <= 1 {
+                continue;
+            }
+
+            let path = node_path(&node);
+            // providing mods are sorted from higher priority to lower
+            for higher in &providing_mods[..position] {
+                conflicts.overridden_by.entry(*higher).or_default().push(path.clone());
+            }
+            for lower in &providing_mods[position + 1..] {
+                conflicts.overrides.entry(*lower).or_default().push(path.clone());
+            }
+        }
+
+        for paths in conflicts
+            .overrides
+            .values_mut()
+            .chain(conflicts.overridden_by.values_mut())
+        {
+            paths.sort_unstable();
+        }
+        conflicts
+    }
+
+    /// Returns `true` if the mod has no files in common with other mods.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.overrides.is_empty() && self.overridden_by.is_empty()
+    }
+}
+
+/// The number of files of a mod that are also provided by other mods.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConflictCount {
+    /// Files of this mod that override the files of lower priority mods.
+    pub winning: usize,
+    /// Files of this mod that are overridden by higher priority mods.
+    pub losing: usize,
+}
+
+impl ConflictCount {
+    /// Counts the conflicting files of every mod in a tree built with
+    /// [`FileTreeBuilder::iter_mods`](super::FileTreeBuilder::iter_mods).
+    ///
+    /// Mods without conflicting files aren't included.
+    #[must_use]
+    pub fn count_all(tree: &FileTree<ModVec>) -> BTreeMap<ModIndex, Self> {
+        let mut counts = BTreeMap::<ModIndex, Self>::new();
+        let root = tree.root().expect("has root node");
+        for node in root.traverse_pre_order() {
+            let TreeNodeKind::File(providing_mods) = &node.data().kind else {
+                continue;
+            };
+            let Some((winner, losers)) = providing_mods.split_first() else {
+                continue;
+            };
+            if losers.is_empty() {
+                continue;
+            }
+
+            counts.entry(*winner).or_default().winning += 1;
+            for loser in losers {
+                counts.entry(*loser).or_default().losing += 1;
+            }
+        }
+        counts
+    }
+
+    /// Returns the total number of conflicting files.
+    #[must_use]
+    pub const fn total(&self) -> usize {
+        self.winning + self.losing
+    }
+}
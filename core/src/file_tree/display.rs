@@ -17,8 +17,11 @@
 
 use std::borrow::Cow;
 use std::io;
+use std::iter;
 
+use itertools::Itertools;
 use nary_tree::NodeId;
+use serde::{Serialize, Serializer};
 
 use super::{FileTree, ModVec, TreeNodeKind};
 use crate::instance::Instance;
@@ -138,3 +141,57 @@ impl ptree::TreeItem for ModVecFileTreeDisplay<'_> {
         Cow::Owned(children)
     }
 }
+
+/// Structure to serialize the files in a [`FileTree<ModVec>`], for machine-readable output.
+///
+/// Serializes as a sequence of maps with a `path` field, with `/` as the separator,
+/// and a `mods` field, with the names of the mods that provide the file, from highest to lowest priority.
+/// The first mod is the one whose file is deployed.
+#[derive(Copy, Clone)]
+pub struct ModVecFileList<'a> {
+    tree: &'a FileTree<ModVec>,
+    instance: &'a dyn Instance,
+    kind: FileTreeDisplayKind,
+}
+
+impl<'a> ModVecFileList<'a> {
+    #[must_use]
+    pub fn new(tree: &'a FileTree<ModVec>, instance: &'a dyn Instance, kind: FileTreeDisplayKind) -> Self {
+        Self { tree, instance, kind }
+    }
+}
+
+#[derive(Serialize)]
+struct FileListEntry<'a> {
+    path: String,
+    mods: Vec<&'a str>,
+}
+
+impl Serialize for ModVecFileList<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let root = self.tree.root().expect("has root node");
+        let entries = root.traverse_pre_order().filter_map(|node| {
+            let TreeNodeKind::File(providing_mods) = &node.data().kind else {
+                return None;
+            };
+            if self.kind == FileTreeDisplayKind::Conflicts && providing_mods.len() <= 1 {
+                return None;
+            }
+
+            let mut ancestors: Vec<_> = node.ancestors().collect();
+            ancestors.pop(); // root
+            let path = ancestors
+                .iter()
+                .rev()
+                .chain(iter::once(&node))
+                .map(|node| node.data().name.as_str())
+                .join("/");
+            let mods = providing_mods
+                .iter()
+                .map(|idx| self.instance.mods()[*idx].name().as_str())
+                .collect();
+            Some(FileListEntry { path, mods })
+        });
+        serializer.collect_seq(entries)
+    }
+}
@@ -15,6 +15,7 @@
 
 //! Functions for walking through mod files and representing them as a tree.
 
+pub mod conflicts;
 pub mod display;
 mod node;
 pub mod util;
@@ -37,6 +38,12 @@ pub use self::node::{ModVec, TreeNode, TreeNodeKind};
 use crate::file_tree::util::OptionExt;
 use crate::instance::{Instance, ModDeclaration, ModIndex};
 
+/// Suffix of the names of deletion markers.
+///
+/// A mod file named `<name>` followed by this suffix hides the file or directory `<name>` of the game,
+/// and of lower priority mods, when deployed.
+pub const DELETION_MARKER_SUFFIX: &str = ".mmm-deleted";
+
 /// A tree of files.
 pub type FileTree<F = ()> = Tree<TreeNode<F>>;
 
@@ -107,11 +114,19 @@ impl<F, Value: ProvideValue<F>, Counter: Count> FileTreeBuilder<F, Value, Counte
 
     /// Iterates over the specified directory, creating node that correspond to each entry in the provided tree.
     pub fn iter_dir(&self, tree: &mut FileTree<F>, dir: PathBuf) -> Result<(), IterDirError> {
-        self.iter_dir_inner(tree, dir).map_err(|err| err.without_context(tree))
+        let root = tree.root_id().expect("has root node");
+        self.iter_dir_inner(tree, dir, root)
+            .map_err(|err| err.without_context(tree))
     }
 
-    fn iter_dir_inner(&self, tree: &mut FileTree<F>, dir: PathBuf) -> Result<(), UnresolvedIterDirError> {
-        let mut dirs_to_visit = vec![(dir, tree.root_id().expect("has root node"))];
+    /// Iterates over the specified directory, creating nodes under `start` that correspond to each entry.
+    fn iter_dir_inner(
+        &self,
+        tree: &mut FileTree<F>,
+        dir: PathBuf,
+        start: NodeId,
+    ) -> Result<(), UnresolvedIterDirError> {
+        let mut dirs_to_visit = vec![(dir, start)];
         let mut root = true;
 
         while let Some((dir, node)) = dirs_to_visit.pop() {
@@ -162,27 +177,58 @@ impl<F, Value: ProvideValue<F>, Counter: Count> FileTreeBuilder<F, Value, Counte
     /// Each node in the tree that represents a file contains the list of mods that provide that file,
     /// sorted from higher priority to lower.
     pub fn iter_mods(self, tree: &mut FileTree<ModVec>, instance: &impl Instance) -> Result<(), IterDirError> {
-        let mut iter = self.with_item_value(ModIndex::ZERO);
-        for entry in instance.mod_order().iter().rev() {
-            if !entry.enabled {
-                continue;
-            }
-
-            let mod_index = entry.mod_index();
-            let mod_decl = &instance.mods()[mod_index];
-            let Some(mod_dir) = instance.mod_dir(mod_decl) else {
+        let enabled_mods: Vec<_> = instance
+            .mod_order()
+            .iter()
+            .rev()
+            .filter(|entry| entry.enabled)
+            .filter_map(|entry| {
+                let mod_index = entry.mod_index();
+                let mod_decl = &instance.mods()[mod_index];
                 // skip separators
-                continue;
-            };
+                instance.mod_dir(mod_decl).map(|mod_dir| (mod_index, mod_decl, mod_dir))
+            })
+            .collect();
+        let enabled_mods_len = enabled_mods.len();
 
+        let mut iter = self.with_item_value(ModIndex::ZERO);
+        for (position, (mod_index, mod_decl, mod_dir)) in enabled_mods.into_iter().enumerate() {
+            iter.counter.mod_started(position, enabled_mods_len, mod_decl.name());
             iter = iter.with_item_value(mod_index);
-            iter.iter_dir_inner(tree, mod_dir)
+            // Mods with a target have their files placed under it, instead of at the root.
+            let start = match mod_decl.target() {
+                Some(target) => iter.create_dir_node_with_parents(tree, target),
+                None => Ok(tree.root_id().expect("has root node")),
+            };
+            start
+                .and_then(|start| iter.iter_dir_inner(tree, mod_dir, start))
                 .map_err(|err| err.with_modvec_context(tree, mod_decl, instance))?;
         }
 
         Ok(())
     }
 
+    /// Returns the directory node at the specified path from the root, creating it and its parents if missing.
+    fn create_dir_node_with_parents(
+        &self,
+        tree: &mut FileTree<F>,
+        path: &Utf8Path,
+    ) -> Result<NodeId, UnresolvedIterDirError> {
+        let mut node = tree.root_id().expect("has root node");
+        for name in path.iter() {
+            node = if let Some(child) = find_child_with_name(tree, node, name) {
+                if !matches!(tree.get(child).expect("node exists").data().kind, TreeNodeKind::Dir) {
+                    return Err(UnresolvedIterDirError::TypeMismatch(child));
+                }
+                child
+            } else {
+                self.counter.dir_added();
+                create_dir_node(tree.get_mut(node).expect("node exists"), name)
+            };
+        }
+        Ok(node)
+    }
+
     /// Creates a file node given the specified path from the root, creating any missing parent directory nodes.
     pub fn create_file_node_with_parents(
         &self,
@@ -315,6 +361,11 @@ pub trait Count {
     fn file_added(&self);
     fn file_appended(&self);
     fn dir_added(&self);
+
+    /// Called by [`FileTreeBuilder::iter_mods`] before iterating over the files of a mod.
+    ///
+    /// `position` is the index of the mod among the `total` enabled mods, in the order they're iterated over.
+    fn mod_started(&self, _position: usize, _total: usize, _name: &str) {}
 }
 
 /// A [counter](Count) that does not count.
@@ -389,6 +440,11 @@ impl<C: Count> Count for Arc<C> {
     fn dir_added(&self) {
         self.deref().dir_added();
     }
+
+    #[inline]
+    fn mod_started(&self, position: usize, total: usize, name: &str) {
+        self.deref().mod_started(position, total, name);
+    }
 }
 
 #[derive(Debug)]
@@ -423,10 +479,9 @@ impl UnresolvedIterDirError {
                         continue;
                     }
 
-                    let path_to_check = instance
-                        .mod_dir(other_mod)
-                        .expect("separators don't have files")
-                        .join(&node_path);
+                    let Some(path_to_check) = instance.mod_file_path(other_mod, node_path.as_std_path()) else {
+                        continue;
+                    };
                     match fs::symlink_metadata(&path_to_check) {
                         Ok(m) => {
                             if m.is_dir() != expected_dir {
@@ -30,7 +30,7 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use thiserror::Error;
 use typed_index_collections::TiVec;
 
-use super::{ModDeclaration, ModIndex, Profile};
+use super::{DEFAULT_PROFILE, DEFAULT_PROFILE_NAME, InstanceSettings, ModDeclaration, ModIndex, MountTarget, Profile};
 
 /// File name of the instance data file in the instance's root directory.
 pub const INSTANCE_DATA_FILE: &str = "mmm.cbor";
@@ -42,12 +42,14 @@ const INSTANCE_DATA_VERSION: u32 = 0;
 /// Indices must be kept in sync, mods must have exactly one entry in the mod order, etc.
 ///
 /// Useful for implementing [`Instance`](super::Instance).
-#[derive(Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
 pub struct InstanceData {
     #[serde(serialize_with = "serialize_version")]
     version: PhantomData<u32>, // Keep this at the top of the struct, so it gets (de)serialized first.
     pub mods: TiVec<ModIndex, ModDeclaration>,
     pub profiles: BTreeMap<CompactString, Profile>,
+    #[serde(skip_serializing_if = "InstanceSettings::is_default")]
+    pub settings: InstanceSettings,
 }
 
 #[allow(clippy::trivially_copy_pass_by_ref, reason = "required by serde")]
@@ -55,6 +57,18 @@ fn serialize_version<S: Serializer>(_: &PhantomData<u32>, serializer: S) -> Resu
     serializer.serialize_u32(INSTANCE_DATA_VERSION)
 }
 
+impl Default for InstanceData {
+    /// Creates instance data with no mods and only the default profile.
+    fn default() -> Self {
+        Self {
+            version: PhantomData,
+            mods: TiVec::new(),
+            profiles: BTreeMap::from([(DEFAULT_PROFILE_NAME, DEFAULT_PROFILE)]),
+            settings: InstanceSettings::default(),
+        }
+    }
+}
+
 impl InstanceData {
     /// Deserializes `InstanceData` from the file at the provided path.
     pub fn from_file(path: &Path) -> Result<Self, InstanceDataOpenError> {
@@ -68,6 +82,8 @@ struct UnverifiedInstanceData {
     version: PhantomData<u32>,
     mods: TiVec<ModIndex, ModDeclaration>,
     profiles: BTreeMap<CompactString, Profile>,
+    #[serde(default)]
+    settings: InstanceSettings,
 }
 
 #[allow(clippy::unnecessary_wraps, clippy::needless_pass_by_value, reason = "required by serde")]
@@ -135,11 +151,15 @@ impl UnverifiedInstanceData {
         for profile in self.profiles.values() {
             Self::verify_profile(profile, mods_len)?;
         }
+        if !MountTarget::are_valid(&self.settings.mount_targets) {
+            return Err(InstanceDataVerificationError::InvalidMountTarget);
+        }
 
         Ok(InstanceData {
             version: PhantomData,
             mods: self.mods,
             profiles: self.profiles,
+            settings: self.settings,
         })
     }
 
@@ -153,6 +173,9 @@ impl UnverifiedInstanceData {
                 None => return Err(InstanceDataVerificationError::ModIndexOutOfRange),
             }
         }
+        if profile.notes.keys().any(|idx| usize::from(*idx) >= mods_len) {
+            return Err(InstanceDataVerificationError::ModIndexOutOfRange);
+        }
         Ok(())
     }
 }
@@ -162,6 +185,8 @@ impl UnverifiedInstanceData {
 pub enum InstanceDataVerificationError {
     #[error("mod order contains duplicate mod indices")]
     DuplicateModIndex,
+    #[error("instance settings contain invalid mount targets")]
+    InvalidMountTarget,
     #[error("mod order contains out of range mod index")]
     ModIndexOutOfRange,
 }
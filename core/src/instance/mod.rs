@@ -17,10 +17,12 @@
 
 pub mod data;
 
+use std::collections::BTreeMap;
 use std::fmt;
 use std::iter::FusedIterator;
 use std::path::{Path, PathBuf};
 
+use camino::{Utf8Component, Utf8Path};
 use compact_str::CompactString;
 use serde::de::{self, MapAccess, Unexpected, Visitor};
 use serde::ser::SerializeStruct;
@@ -28,6 +30,9 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use thiserror::Error;
 use typed_index_collections::{TiSlice, TiVec};
 
+/// Name of the directory in the instance's root directory that contains the mod directories.
+pub const MODS_DIR: &str = "mods";
+
 /// Trait that represents an open mmm instance.
 pub trait Instance {
     /// Returns the absolute path to the instance's base directory.
@@ -52,24 +57,45 @@ pub trait Instance {
         &self.mods()[mod_index]
     }
 
+    /// Returns the absolute path to the directory that contains the directories of every mod.
+    fn mods_dir(&self) -> PathBuf {
+        self.dir().join(MODS_DIR)
+    }
+
     /// Returns the absolute path to the specified mod's directory.
     fn mod_dir(&self, mod_declaration: &ModDeclaration) -> Option<PathBuf> {
         if mod_declaration.kind == ModEntryKind::Separator {
             return None;
         }
 
-        let mut path = self.dir().to_owned();
-        path.push("mods");
+        let mut path = self.mods_dir();
         path.push(mod_declaration.name());
         Some(path)
     }
+
+    /// Returns the absolute path to the file of the specified mod that is deployed to `deployed_path`,
+    /// relative to the deployment root, taking the mod's [target](ModDeclaration::target) into account.
+    ///
+    /// Returns `None` if the entry is a separator, or if `deployed_path` is outside of the mod's target.
+    fn mod_file_path(&self, mod_declaration: &ModDeclaration, deployed_path: &Path) -> Option<PathBuf> {
+        let relative_path = match mod_declaration.target() {
+            Some(target) => deployed_path.strip_prefix(target).ok()?,
+            None => deployed_path,
+        };
+        Some(self.mod_dir(mod_declaration)?.join(relative_path))
+    }
 }
 
 /// An entry in the [mod list](Instance::mods).
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ModDeclaration {
     name: CompactString,
     kind: ModEntryKind,
+    category: Option<CompactString>,
+    version: Option<CompactString>,
+    label: Option<ModLabel>,
+    target: Option<CompactString>,
+    source: Option<CompactString>,
 }
 
 impl ModDeclaration {
@@ -85,13 +111,114 @@ impl ModDeclaration {
         self.kind
     }
 
+    /// Returns the entry's category, if it has one.
+    #[must_use]
+    pub const fn category(&self) -> Option<&CompactString> {
+        self.category.as_ref()
+    }
+
+    /// Returns the entry's version, if it has one.
+    #[must_use]
+    pub const fn version(&self) -> Option<&CompactString> {
+        self.version.as_ref()
+    }
+
+    /// Returns the entry's label, if it has one.
+    #[must_use]
+    pub const fn label(&self) -> Option<ModLabel> {
+        self.label
+    }
+
+    /// Returns the directory the entry's files are deployed into, relative to the deployment root
+    /// (usually the game directory). `None` means the deployment root itself.
+    #[must_use]
+    pub fn target(&self) -> Option<&Utf8Path> {
+        self.target.as_deref().map(Utf8Path::new)
+    }
+
+    /// Returns the file name of the archive, in the instance's downloads directory,
+    /// that the entry was installed from, if known.
+    #[must_use]
+    pub const fn source(&self) -> Option<&CompactString> {
+        self.source.as_ref()
+    }
+
     /// Creates a `ModDeclaration` for a mod with the specified name.
     pub fn new(name: CompactString, kind: ModEntryKind) -> Result<Self, InvalidModNameError> {
         Self::is_name_valid(&name)
-            .then_some(Self { name, kind })
+            .then_some(Self {
+                name,
+                kind,
+                category: None,
+                version: None,
+                label: None,
+                target: None,
+                source: None,
+            })
             .ok_or(InvalidModNameError)
     }
 
+    /// Changes the entry's name.
+    pub fn set_name(&mut self, name: CompactString) -> Result<(), InvalidModNameError> {
+        if !Self::is_name_valid(&name) {
+            return Err(InvalidModNameError);
+        }
+        self.name = name;
+        Ok(())
+    }
+
+    /// Sets or clears the entry's category.
+    pub fn set_category(&mut self, category: Option<CompactString>) {
+        self.category = category;
+    }
+
+    /// Sets or clears the entry's version.
+    pub fn set_version(&mut self, version: Option<CompactString>) {
+        self.version = version;
+    }
+
+    /// Sets or clears the entry's label.
+    pub fn set_label(&mut self, label: Option<ModLabel>) {
+        self.label = label;
+    }
+
+    /// Sets or clears the directory the entry's files are deployed into.
+    pub fn set_target(&mut self, target: Option<CompactString>) -> Result<(), InvalidModTargetError> {
+        if let Some(target) = &target
+            && !Self::is_target_valid(target)
+        {
+            return Err(InvalidModTargetError);
+        }
+        self.target = target;
+        Ok(())
+    }
+
+    /// Sets or clears the archive the entry was installed from.
+    pub fn set_source(&mut self, source: Option<CompactString>) {
+        self.source = source;
+    }
+
+    /// Returns `true` if the entry has no data besides its name and type.
+    fn is_plain(&self) -> bool {
+        self.category.is_none()
+            && self.version.is_none()
+            && self.label.is_none()
+            && self.target.is_none()
+            && self.source.is_none()
+    }
+
+    /// Returns `true` if the specified path can be used as a [target](Self::target):
+    /// a non-empty relative path with `/` as separator, and no `.` or `..` components.
+    #[must_use]
+    pub fn is_target_valid(target: &str) -> bool {
+        !target.is_empty()
+            && !target.contains(['\0', '\\'])
+            && target.split('/').all(|c| !c.is_empty())
+            && Utf8Path::new(target)
+                .components()
+                .all(|c| matches!(c, Utf8Component::Normal(_)))
+    }
+
     #[must_use]
     pub fn is_name_valid(name: &str) -> bool {
         !name.is_empty()
@@ -106,17 +233,52 @@ impl ModDeclaration {
 #[error("the specified mod name is invalid")]
 pub struct InvalidModNameError;
 
+#[derive(Debug, Error)]
+#[error("the specified mod target directory is invalid")]
+pub struct InvalidModTargetError;
+
 impl Serialize for ModDeclaration {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        if self.kind == ModEntryKind::Mod {
+        if self.kind == ModEntryKind::Mod && self.is_plain() {
             serializer.serialize_str(&self.name)
         } else {
-            let mut entry = serializer.serialize_struct("ModDeclaration", 2)?;
+            let len = 2
+                + usize::from(self.category.is_some())
+                + usize::from(self.version.is_some())
+                + usize::from(self.label.is_some())
+                + usize::from(self.target.is_some())
+                + usize::from(self.source.is_some());
+            let mut entry = serializer.serialize_struct("ModDeclaration", len)?;
             entry.serialize_field("name", &self.name)?;
             entry.serialize_field("type", &self.kind)?;
+            if let Some(category) = &self.category {
+                entry.serialize_field("category", category)?;
+            } else {
+                entry.skip_field("category")?;
+            }
+            if let Some(version) = &self.version {
+                entry.serialize_field("version", version)?;
+            } else {
+                entry.skip_field("version")?;
+            }
+            if let Some(label) = &self.label {
+                entry.serialize_field("label", label)?;
+            } else {
+                entry.skip_field("label")?;
+            }
+            if let Some(target) = &self.target {
+                entry.serialize_field("target", target)?;
+            } else {
+                entry.skip_field("target")?;
+            }
+            if let Some(source) = &self.source {
+                entry.serialize_field("source", source)?;
+            } else {
+                entry.skip_field("source")?;
+            }
             entry.end()
         }
     }
@@ -132,8 +294,14 @@ impl<'de> Deserialize<'de> for ModDeclaration {
         enum Field {
             Name,
             Type,
+            Category,
+            Version,
+            Label,
+            Target,
+            Source,
         }
         struct ModDeclarationVisitor;
+        const INVALID_TARGET: &str = "invalid target: expected a relative path without . or .. components";
         const INVALID_NAME: &str = "invalid name: expected a string that is not empty, does not contain whitespace at the beginning or end, does not contain NUL or /, and is not equal to . or ..";
 
         impl<'de> Visitor<'de> for ModDeclarationVisitor {
@@ -160,6 +328,11 @@ impl<'de> Deserialize<'de> for ModDeclaration {
             {
                 let mut name = None;
                 let mut kind = None;
+                let mut category = None;
+                let mut version = None;
+                let mut label = None;
+                let mut target = None;
+                let mut source = None;
                 while let Some(key) = map.next_key()? {
                     match key {
                         Field::Name => {
@@ -174,11 +347,47 @@ impl<'de> Deserialize<'de> for ModDeclaration {
                             }
                             kind = Some(map.next_value()?);
                         }
+                        Field::Category => {
+                            if category.is_some() {
+                                return Err(de::Error::duplicate_field("category"));
+                            }
+                            category = Some(map.next_value()?);
+                        }
+                        Field::Version => {
+                            if version.is_some() {
+                                return Err(de::Error::duplicate_field("version"));
+                            }
+                            version = Some(map.next_value()?);
+                        }
+                        Field::Label => {
+                            if label.is_some() {
+                                return Err(de::Error::duplicate_field("label"));
+                            }
+                            label = Some(map.next_value()?);
+                        }
+                        Field::Target => {
+                            if target.is_some() {
+                                return Err(de::Error::duplicate_field("target"));
+                            }
+                            target = Some(map.next_value()?);
+                        }
+                        Field::Source => {
+                            if source.is_some() {
+                                return Err(de::Error::duplicate_field("source"));
+                            }
+                            source = Some(map.next_value()?);
+                        }
                     }
                 }
                 let name = name.ok_or_else(|| de::Error::missing_field("name"))?;
                 let kind = kind.ok_or_else(|| de::Error::missing_field("type"))?;
-                ModDeclaration::new(name, kind).map_err(|_| de::Error::custom(INVALID_NAME))
+                let mut decl = ModDeclaration::new(name, kind).map_err(|_| de::Error::custom(INVALID_NAME))?;
+                decl.category = category;
+                decl.version = version;
+                decl.label = label;
+                decl.source = source;
+                decl.set_target(target).map_err(|_| de::Error::custom(INVALID_TARGET))?;
+                Ok(decl)
             }
         }
 
@@ -196,29 +405,211 @@ pub enum ModEntryKind {
     Separator,
 }
 
+/// A color label that can be assigned to entries in the mod list, for visual grouping.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModLabel {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+    Gray,
+}
+
+impl ModLabel {
+    /// Every label, in display order.
+    pub const ALL: [Self; 7] = [
+        Self::Red,
+        Self::Orange,
+        Self::Yellow,
+        Self::Green,
+        Self::Blue,
+        Self::Purple,
+        Self::Gray,
+    ];
+
+    /// Returns the label's name, for display.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Red => "Red",
+            Self::Orange => "Orange",
+            Self::Yellow => "Yellow",
+            Self::Green => "Green",
+            Self::Blue => "Blue",
+            Self::Purple => "Purple",
+            Self::Gray => "Gray",
+        }
+    }
+}
+
 pub const DEFAULT_PROFILE_NAME: CompactString = CompactString::const_new("default");
 pub const DEFAULT_PROFILE: Profile = Profile {
     display_name: CompactString::const_new("Default"),
     mod_order: TiVec::new(),
+    notes: BTreeMap::new(),
 };
 
 /// Set of configurations that can be swapped within the same instance.
 ///
 /// This includes mod order and activation state.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Profile {
     display_name: CompactString,
     pub mod_order: TiVec<ModOrderIndex, ModOrderEntry>,
+    /// Notes about mods that only apply to this profile, such as why a mod is disabled in it.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub notes: BTreeMap<ModIndex, CompactString>,
 }
 
 impl Profile {
     /// Creates an empty `Profile` with the specified display name.
     #[must_use]
     pub const fn new(display_name: CompactString) -> Self {
-        Self { display_name, mod_order: TiVec::new() }
+        Self {
+            display_name,
+            mod_order: TiVec::new(),
+            notes: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the name of the profile shown to the user.
+    #[must_use]
+    pub fn display_name(&self) -> &str {
+        &self.display_name
+    }
+
+    /// Sets the name of the profile shown to the user.
+    pub fn set_display_name(&mut self, display_name: CompactString) {
+        self.display_name = display_name;
     }
 }
 
+/// Configuration that applies to the whole instance, regardless of profile.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct InstanceSettings {
+    /// Absolute path to the game directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_path: Option<CompactString>,
+    /// Path to the game's executable, relative to the game directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executable: Option<CompactString>,
+    /// Name of the profile that is deployed when none is specified.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<CompactString>,
+    /// Destinations, other than the deployment root, that some of the mod files are deployed to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mount_targets: Vec<MountTarget>,
+    /// Steam app ID of the game, used to launch it through Steam.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub steam_app_id: Option<u32>,
+    /// Shell commands run after the mod files are deployed, before the game is launched.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_launch_hooks: Vec<CompactString>,
+    /// Shell commands run after the game exits, before the mod files are removed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_exit_hooks: Vec<CompactString>,
+    /// Glob patterns of mod files that aren't deployed. `None` means [`DEFAULT_STAGING_EXCLUSIONS`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub staging_exclusions: Option<Vec<CompactString>>,
+    /// Commands the game is run through, outermost first, such as `gamemoderun` or `gamescope -f --`.
+    /// Arguments are split like a shell would.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wrappers: Vec<CompactString>,
+    /// Absolute path to the directory the game keeps its save games in.
+    /// When set, each profile has its own saves directory, which is mounted over it while deploying.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub save_path: Option<CompactString>,
+}
+
+/// Glob patterns of mod files that aren't deployed, unless the instance specifies its own.
+///
+/// Patterns without a slash match file names at any depth, and patterns ending with a slash only match directories.
+pub const DEFAULT_STAGING_EXCLUSIONS: &[&str] = &["*.txt", "screenshots/", ".git/"];
+
+impl InstanceSettings {
+    /// Returns `true` if every setting has its default value.
+    #[must_use]
+    pub fn is_default(&self) -> bool {
+        self.game_path.is_none()
+            && self.executable.is_none()
+            && self.default_profile.is_none()
+            && self.mount_targets.is_empty()
+            && self.steam_app_id.is_none()
+            && self.pre_launch_hooks.is_empty()
+            && self.post_exit_hooks.is_empty()
+            && self.staging_exclusions.is_none()
+            && self.wrappers.is_empty()
+            && self.save_path.is_none()
+    }
+
+    /// Returns the instance's staging exclusion patterns, or the default ones if it doesn't specify any.
+    #[must_use]
+    pub fn effective_staging_exclusions(&self) -> Vec<&str> {
+        self.staging_exclusions.as_ref().map_or_else(
+            || DEFAULT_STAGING_EXCLUSIONS.to_vec(),
+            |patterns| patterns.iter().map(CompactString::as_str).collect(),
+        )
+    }
+}
+
+/// A directory of the merged mod files that is deployed somewhere other than the deployment root,
+/// such as a game's configuration or saves directory.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MountTarget {
+    /// Directory, relative to the deployment root, whose contents are deployed to the destination.
+    source: CompactString,
+    /// Absolute path to the directory the files are deployed to.
+    destination: CompactString,
+}
+
+impl MountTarget {
+    /// Creates a `MountTarget`, if `source` is a valid [mod target](ModDeclaration::is_target_valid)
+    /// and `destination` is an absolute path.
+    pub fn new(source: CompactString, destination: CompactString) -> Result<Self, InvalidMountTargetError> {
+        let target = Self { source, destination };
+        if target.is_valid() {
+            Ok(target)
+        } else {
+            Err(InvalidMountTargetError)
+        }
+    }
+
+    /// Returns the directory, relative to the deployment root, whose contents are deployed to the destination.
+    #[must_use]
+    pub fn source(&self) -> &Utf8Path {
+        Utf8Path::new(&self.source)
+    }
+
+    /// Returns the absolute path to the directory the files are deployed to.
+    #[must_use]
+    pub fn destination(&self) -> &Path {
+        Path::new(self.destination.as_str())
+    }
+
+    #[must_use]
+    fn is_valid(&self) -> bool {
+        ModDeclaration::is_target_valid(&self.source) && self.destination().is_absolute()
+    }
+
+    /// Returns `true` if every target is valid, and no target's source contains another's.
+    #[must_use]
+    pub fn are_valid(targets: &[Self]) -> bool {
+        targets.iter().enumerate().all(|(i, target)| {
+            target.is_valid()
+                && targets[i + 1..].iter().all(|other| {
+                    !target.source().starts_with(other.source()) && !other.source().starts_with(target.source())
+                })
+        })
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("mount target sources must be valid relative paths, and destinations must be absolute paths")]
+pub struct InvalidMountTargetError;
+
 /// Represents a [`ModDeclaration`] in the [mod order](Instance::mod_order).
 #[derive(Copy, Clone, Debug)]
 pub struct ModOrderEntry {
@@ -17,6 +17,7 @@ use std::marker::PhantomData;
 
 use rustix::thread;
 use rustix::thread::{CapabilitySet, CapabilitySets};
+use tracing::error;
 
 const CAPS_DISABLED: CapabilitySets = CapabilitySets {
     effective: CapabilitySet::empty(),
@@ -53,7 +54,7 @@ pub fn have_cap_sys_admin() -> bool {
 
 pub fn ensure_cap_sys_admin() {
     if !have_cap_sys_admin() {
-        eprintln!(
+        error!(
             "The SYS_ADMIN capability, required for mounting and unmounting filesystems, is missing.\nRun `setcap cap_sys_admin=p '{}'` as root to grant it to this program, then try again.",
             std::env::current_exe()
                 .expect("get executable path")
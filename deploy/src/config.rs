@@ -0,0 +1,154 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Per-user defaults for command line options, read from `$XDG_CONFIG_HOME/mmm/config.toml`
+//! (`%APPDATA%\mmm\config.toml` on Windows).
+//!
+//! Values in the file are used as if they were given on the command line, unless the option is given there.
+//! `wrappers` and `exclude` are only used for instances that don't store their own.
+//! The mount options and the `selinux` and `overlay` tables are only read on Linux.
+//!
+//! ```toml
+//! mount-method = "userns"
+//! staging = "profile"
+//! wrappers = ["gamemoderun", "mangohud"]
+//! exclude = ["*.txt", "*.md"]
+//!
+//! # SELinux contexts of the overlay and tmpfs mounts
+//! [selinux]
+//! context = "system_u:object_r:container_file_t:s0"
+//!
+//! # Overlayfs features, for kernels or filesystems where the defaults don't work
+//! [overlay]
+//! metacopy = false
+//! redirect-dir = "off"
+//! index = false
+//! userxattr = true
+//! volatile = true
+//! ```
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use clap::ValueEnum;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::deployment::StagingKind;
+#[cfg(target_os = "linux")]
+use crate::mount::{MountMethodChoice, MountOptions, OverlayOptions, SecurityContext};
+use crate::wrapper::{Wrapper, WrapperParseError};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct ConfigFile {
+    #[cfg(target_os = "linux")]
+    mount_method: Option<String>,
+    staging: Option<String>,
+    wrappers: Vec<String>,
+    exclude: Option<Vec<String>>,
+    #[cfg(target_os = "linux")]
+    selinux: SecurityContext,
+    #[cfg(target_os = "linux")]
+    overlay: OverlayOptions,
+}
+
+/// Defaults for command line options, and options that can only be configured here.
+#[derive(Debug, Default)]
+pub struct Config {
+    #[cfg(target_os = "linux")]
+    pub mount_method: Option<MountMethodChoice>,
+    pub staging: Option<StagingKind>,
+    pub wrappers: Vec<Wrapper>,
+    pub exclude: Option<Vec<String>>,
+    #[cfg(target_os = "linux")]
+    pub mount_options: MountOptions,
+}
+
+impl Config {
+    /// Reads the configuration file, returning the default configuration if it doesn't exist.
+    pub fn load() -> Result<Self, ConfigError> {
+        let Some(path) = config_path() else {
+            return Ok(Self::default());
+        };
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(source) => return Err(ConfigError::Read { path, source }),
+        };
+        let file: ConfigFile = toml::from_str(&contents).map_err(|source| ConfigError::Parse { path, source })?;
+
+        Ok(Self {
+            #[cfg(target_os = "linux")]
+            mount_method: file
+                .mount_method
+                .map(|value| parse_value_enum("mount-method", &value))
+                .transpose()?,
+            staging: file
+                .staging
+                .map(|value| parse_value_enum("staging", &value))
+                .transpose()?,
+            wrappers: file
+                .wrappers
+                .iter()
+                .map(|command| Wrapper::parse(command))
+                .collect::<Result<_, _>>()?,
+            exclude: file.exclude,
+            #[cfg(target_os = "linux")]
+            mount_options: MountOptions {
+                security_context: file.selinux,
+                overlay: file.overlay,
+            },
+        })
+    }
+}
+
+/// Returns the path of the configuration file, if the user's configuration directory is known.
+fn config_path() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(default_config_dir)?;
+    Some(config_dir.join("mmm").join("config.toml"))
+}
+
+#[cfg(unix)]
+fn default_config_dir() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".config"))
+}
+
+#[cfg(windows)]
+fn default_config_dir() -> Option<PathBuf> {
+    env::var_os("APPDATA").map(PathBuf::from)
+}
+
+/// Parses `value` the same way as the command line option of the same name.
+fn parse_value_enum<T: ValueEnum>(key: &'static str, value: &str) -> Result<T, ConfigError> {
+    T::from_str(value, false).map_err(|_| ConfigError::InvalidValue { key, value: value.to_owned() })
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("invalid value '{value}' for '{key}'")]
+    InvalidValue { key: &'static str, value: String },
+    #[error("invalid wrapper command")]
+    InvalidWrapper(#[from] WrapperParseError),
+    #[error("failed to parse configuration file '{path}'")]
+    Parse { path: PathBuf, source: toml::de::Error },
+    #[error("failed to read configuration file '{path}'")]
+    Read { path: PathBuf, source: io::Error },
+}
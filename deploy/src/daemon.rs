@@ -0,0 +1,307 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Long-lived deployment process, controlled through a Unix socket.
+//!
+//! Clients send one command per line, and receive a status line, either `ok` or `error <message>`,
+//! followed by zero or more lines of output and an empty line. The commands are:
+//!
+//! - `mount [profile]`: deploys the mod files of the specified profile, or of the default one.
+//! - `unmount`: removes the deployed files.
+//! - `status`: outputs `mounted <profile> <backend>` or `unmounted`.
+//! - `verify`: outputs the deployed files that are no longer in place, one per line.
+//! - `conflicts [profile]`: outputs the file tree of the specified profile, or of the deployed one,
+//!   showing which mods provide each conflicting file.
+//! - `shutdown`: removes the deployed files, if any, and exits.
+//!
+//! Each connection is read on its own thread, and commands are run one at a time on the thread that called [`serve`],
+//! so a client that stays connected doesn't block other clients, or shutting down when a signal is received.
+
+use std::fmt::Write as _;
+use std::fs::{self, Permissions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::Shutdown;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, anyhow, bail};
+use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+use tracing::{info, warn};
+
+use mmm_core::file_tree::display::{FileTreeDisplayKind, ModVecFileTreeDisplay};
+
+use crate::deployment::{Backend, Deployment, StagingKind, UpperLayerKind};
+use crate::instance::DeployInstance;
+use crate::progress::Progress;
+use crate::validate::build_validated_file_tree;
+use crate::walk::build_file_tree;
+
+/// What the daemon deploys, and where.
+#[derive(Debug)]
+pub struct DaemonConfig {
+    pub instance_path: PathBuf,
+    pub game_path: PathBuf,
+    pub backend: Backend,
+    pub staging: StagingKind,
+    pub upper: Option<UpperLayerKind>,
+    /// Profile used when a command doesn't specify one.
+    pub default_profile: Option<String>,
+    /// Mods to enable (`true`) or disable (`false`) in whichever profile is deployed.
+    pub mod_overrides: Vec<(String, bool)>,
+    /// Exclusion patterns used instead of those stored in the instance.
+    pub exclusions: Option<Vec<String>>,
+    /// Whether to deploy without the mods whose directory is missing, instead of failing.
+    pub skip_missing: bool,
+    /// Whether to let the game use its own saves, instead of those of the deployed profile.
+    pub shared_saves: bool,
+}
+
+/// How long writing a response can take before the client is disconnected,
+/// so that a client that doesn't read its responses can't stall the daemon.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Something for the daemon to do.
+enum Request {
+    /// A command line sent by a client, along with the connection to write the response to.
+    Command { line: String, client: Arc<UnixStream> },
+    /// SIGHUP, SIGINT or SIGTERM was received.
+    Signal,
+}
+
+struct Daemon {
+    config: DaemonConfig,
+    /// The current deployment, and the name of the profile it was created from.
+    deployed: Option<(String, Deployment)>,
+}
+
+/// Listens for commands on a socket at `socket_path` until a `shutdown` command, SIGHUP, SIGINT or SIGTERM is received.
+pub fn serve(socket_path: &Path, config: DaemonConfig) -> anyhow::Result<()> {
+    let listener = bind(socket_path)?;
+    info!("Listening on '{}'", socket_path.display());
+    let (requests, received) = mpsc::channel();
+    spawn_signal_handler(requests.clone()).context("failed to register signal handlers")?;
+    let _ = thread::spawn(move || accept_connections(&listener, &requests));
+
+    let mut daemon = Daemon { config, deployed: None };
+    for request in received {
+        match request {
+            Request::Command { line, client } => {
+                let (response, shutdown) = daemon.handle_command(&line);
+                if let Err(err) = (&*client).write_all(response.as_bytes()) {
+                    warn!("Failed to send response: {err}");
+                    let _ = client.shutdown(Shutdown::Both);
+                }
+                if shutdown {
+                    break;
+                }
+            }
+            Request::Signal => {
+                info!("Shutting down");
+                if let Err(err) = daemon.shutdown() {
+                    warn!("Failed to remove the deployed files: {err:#}");
+                }
+                break;
+            }
+        }
+    }
+
+    if let Err(err) = fs::remove_file(socket_path) {
+        warn!("Failed to remove socket '{}': {err}", socket_path.display());
+    }
+    Ok(())
+}
+
+fn bind(socket_path: &Path) -> anyhow::Result<UnixListener> {
+    if socket_path.exists() {
+        if UnixStream::connect(socket_path).is_ok() {
+            bail!("another daemon is already listening on '{}'", socket_path.display());
+        }
+        fs::remove_file(socket_path)
+            .with_context(|| format!("failed to remove stale socket '{}'", socket_path.display()))?;
+    }
+
+    let listener =
+        UnixListener::bind(socket_path).with_context(|| format!("failed to bind to '{}'", socket_path.display()))?;
+    fs::set_permissions(socket_path, Permissions::from_mode(0o600))
+        .with_context(|| format!("failed to set permissions of '{}'", socket_path.display()))?;
+    Ok(listener)
+}
+
+/// Sends a [`Request::Signal`] when SIGHUP, SIGINT or SIGTERM is received,
+/// so that deployed files are removed before exiting.
+fn spawn_signal_handler(requests: Sender<Request>) -> io::Result<()> {
+    let (mut read, write) = UnixStream::pair()?;
+    signal_hook::low_level::pipe::register(SIGHUP, write.try_clone()?)?;
+    signal_hook::low_level::pipe::register(SIGINT, write.try_clone()?)?;
+    signal_hook::low_level::pipe::register(SIGTERM, write)?;
+
+    let _ = thread::spawn(move || {
+        let mut buff = [0];
+        if read.read_exact(&mut buff).is_ok() {
+            let _ = requests.send(Request::Signal);
+        }
+    });
+    Ok(())
+}
+
+/// Reads the commands of each connection on its own thread, and forwards them to the daemon.
+fn accept_connections(listener: &UnixListener, requests: &Sender<Request>) {
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                warn!("Failed to accept connection: {err}");
+                continue;
+            }
+        };
+        let requests = requests.clone();
+        let _ = thread::spawn(move || {
+            if let Err(err) = forward_commands(stream, &requests) {
+                warn!("Connection error: {err}");
+            }
+        });
+    }
+}
+
+fn forward_commands(stream: UnixStream, requests: &Sender<Request>) -> io::Result<()> {
+    let client = Arc::new(stream.try_clone()?);
+    client.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    for line in BufReader::new(stream).lines() {
+        let request = Request::Command { line: line?, client: Arc::clone(&client) };
+        if requests.send(request).is_err() {
+            // The daemon is shutting down.
+            break;
+        }
+    }
+    Ok(())
+}
+
+impl Daemon {
+    /// Runs a command, returning the response to send to the client, and whether the daemon should shut down.
+    fn handle_command(&mut self, line: &str) -> (String, bool) {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or_default();
+        let argument = words.next();
+
+        let result = match command {
+            "mount" => self.mount(argument).map(|()| Vec::new()),
+            "unmount" => self.unmount().map(|()| Vec::new()),
+            "status" => Ok(vec![self.status()]),
+            "verify" => self.verify(),
+            "conflicts" => self.conflicts(argument),
+            "shutdown" => self.shutdown().map(|()| Vec::new()),
+            _ => Err(anyhow!("unknown command '{command}'")),
+        };
+
+        let mut response = String::new();
+        match result {
+            Ok(output) => {
+                response.push_str("ok\n");
+                for output_line in output {
+                    let _ = writeln!(response, "{output_line}");
+                }
+            }
+            Err(err) => {
+                let _ = writeln!(response, "error {err:#}");
+            }
+        }
+        response.push('\n');
+        (response, command == "shutdown")
+    }
+
+    fn open_instance(&self, profile: Option<&str>) -> anyhow::Result<DeployInstance> {
+        let profile = profile.or(self.config.default_profile.as_deref());
+        let mut mods = DeployInstance::open(&self.config.instance_path, profile).context("failed to open instance")?;
+        mods.override_mods(&self.config.mod_overrides)?;
+        if self.config.shared_saves {
+            mods.share_saves();
+        }
+        if let Some(exclusions) = &self.config.exclusions {
+            mods.override_exclusions(exclusions.iter().map(String::as_str))?;
+        }
+        Ok(mods)
+    }
+
+    fn mount(&mut self, profile: Option<&str>) -> anyhow::Result<()> {
+        if let Some((profile, _)) = &self.deployed {
+            bail!("profile '{profile}' is already mounted");
+        }
+
+        let mut mods = self.open_instance(profile)?;
+        let tree = build_validated_file_tree(&mut mods, self.config.skip_missing, &Progress::hidden())?;
+        let deployment = Deployment::create(
+            self.config.backend,
+            self.config.staging,
+            self.config.upper,
+            &tree,
+            &mods,
+            &self.config.game_path,
+            &Progress::hidden(),
+        )?;
+        self.deployed = Some((mods.profile_name().to_owned(), deployment));
+        Ok(())
+    }
+
+    fn unmount(&mut self) -> anyhow::Result<()> {
+        let Some((_, deployment)) = self.deployed.take() else {
+            bail!("nothing is mounted");
+        };
+        deployment.remove()
+    }
+
+    fn status(&self) -> String {
+        match &self.deployed {
+            Some((profile, _)) => format!("mounted {profile} {}", self.config.backend.name()),
+            None => "unmounted".to_owned(),
+        }
+    }
+
+    fn verify(&self) -> anyhow::Result<Vec<String>> {
+        let Some((_, deployment)) = &self.deployed else {
+            bail!("nothing is mounted");
+        };
+        Ok(deployment.verify()?.iter().map(ToString::to_string).collect())
+    }
+
+    fn conflicts(&self, profile: Option<&str>) -> anyhow::Result<Vec<String>> {
+        let profile = profile.or_else(|| self.deployed.as_ref().map(|(profile, _)| profile.as_str()));
+        let mods = self.open_instance(profile)?;
+        let tree = build_file_tree(&mods, &Progress::hidden()).context("failed to build tree of mod files")?;
+
+        let mut output = Vec::new();
+        ptree::write_tree(
+            &ModVecFileTreeDisplay::new(&tree, &mods, FileTreeDisplayKind::Conflicts),
+            &mut output,
+        )
+        .context("failed to display file tree")?;
+        Ok(String::from_utf8_lossy(&output)
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(str::to_owned)
+            .collect())
+    }
+
+    fn shutdown(&mut self) -> anyhow::Result<()> {
+        if self.deployed.is_some() {
+            self.unmount()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,415 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Deploying mod files with any of the backends, and removing them afterwards.
+
+#[cfg(target_os = "linux")]
+use std::convert::Infallible;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, bail};
+use clap::ValueEnum;
+use tracing::{info, info_span, warn};
+
+use mmm_core::file_tree::{FileTree, ModVec};
+use mmm_core::instance::Instance;
+
+use crate::instance::DeployInstance;
+use crate::link::{LinkDeployment, LinkMethod};
+#[cfg(target_os = "linux")]
+use crate::mount::OverlayMount;
+use crate::progress::Progress;
+#[cfg(target_os = "linux")]
+use crate::saves::SaveRedirect;
+#[cfg(target_os = "linux")]
+use crate::staging::{StagingTree, build_staging_tree};
+#[cfg(target_os = "linux")]
+use crate::upper::UpperLayer;
+#[cfg(target_os = "linux")]
+use crate::validate::check_destination;
+use crate::verify::{Discrepancy, FILE_MAP_FILE, FileMap};
+#[cfg(target_os = "linux")]
+use crate::walk::{DeployNode, walk_tree};
+
+/// The default is the overlay backend on Linux, where it's available, and the hardlink backend elsewhere.
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub enum Backend {
+    /// Mount an overlay filesystem over the game directory. Only available on Linux.
+    Overlay,
+    /// Symlink mod files into the game directory.
+    Symlink,
+    /// Hardlink mod files into the game directory, copying those on other filesystems.
+    Hardlink,
+    /// Copy mod files into the game directory.
+    Copy,
+}
+
+impl Backend {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Overlay => "overlay",
+            Self::Symlink => "symlink",
+            Self::Hardlink => "hardlink",
+            Self::Copy => "copy",
+        }
+    }
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        if cfg!(target_os = "linux") {
+            Self::Overlay
+        } else {
+            Self::Hardlink
+        }
+    }
+}
+
+/// Where the staging tree of the overlay backend is stored.
+#[derive(Copy, Clone, Debug, Default, ValueEnum)]
+pub enum StagingKind {
+    /// Mount the mod directories directly when that gives the same result, otherwise stage in memory.
+    #[default]
+    Auto,
+    /// In memory, rebuilt on every run.
+    Tmpfs,
+    /// In a per-profile directory in the instance directory, updated incrementally between runs.
+    Profile,
+    /// In a new directory in the system's temporary directory, which is left in place for inspection.
+    #[value(skip)]
+    Kept,
+}
+
+/// Where the upper layer of the overlay backend is stored.
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub enum UpperLayerKind {
+    /// In memory, discarded after unmounting.
+    Tmpfs,
+    /// In a per-profile directory in the instance directory, kept between runs.
+    Profile,
+}
+
+/// Mod files deployed to the game directory, and to the instance's mount targets.
+#[derive(Debug)]
+pub struct Deployment {
+    kind: DeploymentKind,
+    /// Path of the [file map](FileMap) written for this deployment.
+    file_map_path: PathBuf,
+    /// The profile's saves, if the instance specifies a save path.
+    #[cfg(target_os = "linux")]
+    saves: Option<SaveRedirect>,
+}
+
+#[derive(Debug)]
+enum DeploymentKind {
+    #[cfg(target_os = "linux")]
+    Overlay {
+        /// `None` if the mod directories are mounted directly.
+        staging: Option<StagingTree>,
+        /// Mounts over the game directory, followed by the mount targets.
+        mounts: Vec<OverlayMount>,
+        upper: Option<UpperLayer>,
+    },
+    Links(LinkDeployment),
+}
+
+impl Deployment {
+    /// Deploys the files in `tree` to `game_path`.
+    ///
+    /// When using the overlay backend, or when the instance specifies a save path, the caller is responsible
+    /// for being in a mount namespace where mounting is possible.
+    #[cfg_attr(
+        not(target_os = "linux"),
+        allow(unused_variables, reason = "the overlay backend is only available on Linux")
+    )]
+    pub fn create(
+        backend: Backend,
+        staging: StagingKind,
+        upper: Option<UpperLayerKind>,
+        tree: &FileTree<ModVec>,
+        mods: &DeployInstance,
+        game_path: &Path,
+        progress: &Progress,
+    ) -> anyhow::Result<Self> {
+        let _span = info_span!(
+            "mount",
+            backend = backend.name(),
+            profile = mods.profile_name(),
+            game_path = %game_path.display(),
+        )
+        .entered();
+        let kind = match backend {
+            #[cfg(target_os = "linux")]
+            Backend::Overlay => Self::create_overlay(staging, upper, tree, mods, game_path, progress)?,
+            #[cfg(not(target_os = "linux"))]
+            Backend::Overlay => bail!("the overlay backend is only available on Linux"),
+            Backend::Symlink => Self::create_links(LinkMethod::Symlink, tree, mods, game_path)?,
+            Backend::Hardlink => Self::create_links(LinkMethod::Hardlink, tree, mods, game_path)?,
+            Backend::Copy => Self::create_links(LinkMethod::Copy, tree, mods, game_path)?,
+        };
+        #[cfg_attr(not(target_os = "linux"), allow(unused_mut))]
+        let mut deployment = Self {
+            kind,
+            file_map_path: mods.dir().join(FILE_MAP_FILE),
+            #[cfg(target_os = "linux")]
+            saves: None,
+        };
+
+        // Mounted after the mod files, in case the saves are in the game directory.
+        #[cfg(target_os = "linux")]
+        match SaveRedirect::mount(mods) {
+            Ok(saves) => deployment.saves = saves,
+            Err(err) => {
+                if let Err(remove_err) = deployment.remove() {
+                    warn!("Failed to remove deployment: {remove_err:#}");
+                }
+                return Err(err).context("failed to redirect saves");
+            }
+        }
+        #[cfg(target_os = "linux")]
+        if let Some(saves) = &deployment.saves {
+            info!(
+                "Mounted the saves of profile '{}' over {}",
+                mods.profile_name(),
+                saves.path().display()
+            );
+        }
+        #[cfg(not(target_os = "linux"))]
+        if mods.settings().save_path.is_some() {
+            warn!("Profile saves are only available on Linux, the game will use its own saves");
+        }
+
+        if let Err(err) = FileMap::new(tree, mods, game_path).write(&deployment.file_map_path) {
+            warn!(
+                "Failed to write file map '{}': {err}",
+                deployment.file_map_path.display()
+            );
+        }
+        Ok(deployment)
+    }
+
+    fn create_links(
+        method: LinkMethod,
+        tree: &FileTree<ModVec>,
+        mods: &DeployInstance,
+        game_path: &Path,
+    ) -> anyhow::Result<DeploymentKind> {
+        if let Some(leftover) = LinkDeployment::open(mods).context("failed to read previous deployment")? {
+            info!(
+                "Removing leftover deployment at '{}' from a previous run",
+                leftover.target().display()
+            );
+            leftover.remove().context("failed to remove leftover deployment")?;
+        }
+
+        if !mods.mount_targets().is_empty() {
+            bail!("mount targets are only supported by the overlay backend");
+        }
+
+        let deployment = LinkDeployment::create(tree, mods, game_path, method)
+            .with_context(|| format!("failed to link mod files into game path '{}'", game_path.display()))?;
+        info!("Linked mod files into {}", deployment.target().display());
+        Ok(DeploymentKind::Links(deployment))
+    }
+
+    #[cfg(target_os = "linux")]
+    fn create_overlay(
+        staging: StagingKind,
+        upper: Option<UpperLayerKind>,
+        tree: &FileTree<ModVec>,
+        mods: &DeployInstance,
+        game_path: &Path,
+        progress: &Progress,
+    ) -> anyhow::Result<DeploymentKind> {
+        let direct_lower_dirs = if matches!(staging, StagingKind::Auto) {
+            direct_lower_dirs(tree, mods)
+        } else {
+            None
+        };
+        let staging = if let Some(dirs) = &direct_lower_dirs {
+            info!("Mounting {} mod directories directly", dirs.len());
+            None
+        } else {
+            let staging = build_staging_tree(tree, mods, staging, progress).context("failed to stage mod files")?;
+            info!("Built staging tree at '{}'", staging.path().display());
+            Some(staging)
+        };
+
+        let upper = upper
+            .map(|kind| UpperLayer::new(kind, mods))
+            .transpose()
+            .context("failed to create upper layer")?;
+        if let Some(upper) = &upper {
+            info!("Capturing written files in '{}'", upper.upper_dir(None).display());
+        }
+
+        let mut destinations = vec![(None, game_path.to_owned())];
+        for (i, target) in mods.mount_targets().iter().enumerate() {
+            let destination = target.destination();
+            fs::create_dir_all(destination)
+                .with_context(|| format!("failed to create mount target directory '{}'", destination.display()))?;
+            let destination = destination
+                .canonicalize()
+                .with_context(|| format!("failed to canonicalize mount target path '{}'", destination.display()))?;
+            check_destination(&destination, mods).context("refusing to deploy to mount target")?;
+            destinations.push((Some(i), destination));
+        }
+
+        let mut mounts = Vec::with_capacity(destinations.len());
+        for (target, destination) in &destinations {
+            let lower_dirs = match &staging {
+                Some(staging) => vec![staging.layer(*target)],
+                None => direct_lower_dirs
+                    .clone()
+                    .expect("either the staging tree or mod directories are mounted"),
+            };
+            let upper_dirs = upper
+                .as_ref()
+                .map(|upper| (upper.upper_dir(*target), upper.work_dir(*target)));
+            let upper_dirs = upper_dirs
+                .as_ref()
+                .map(|(upper_dir, work_dir)| (upper_dir.as_path(), work_dir.as_path()));
+            let overlay_mount = OverlayMount::new(&lower_dirs, destination, upper_dirs)
+                .with_context(|| format!("failed to mount overlay at '{}'", destination.display()))?;
+            info!("Mounted overlay over {}", overlay_mount.path().display());
+            mounts.push(overlay_mount);
+        }
+
+        Ok(DeploymentKind::Overlay { staging, mounts, upper })
+    }
+
+    /// Returns the path of the staging tree, if there is one.
+    pub fn staging_path(&self) -> Option<&Path> {
+        match &self.kind {
+            #[cfg(target_os = "linux")]
+            DeploymentKind::Overlay { staging, .. } => staging.as_ref().map(StagingTree::path),
+            DeploymentKind::Links(_) => None,
+        }
+    }
+
+    /// Returns what is done to remove the deployment, for display purposes.
+    pub const fn removal_action(&self) -> &'static str {
+        match self.kind {
+            #[cfg(target_os = "linux")]
+            DeploymentKind::Overlay { .. } => "unmount the overlay",
+            DeploymentKind::Links(_) => "remove the links",
+        }
+    }
+
+    /// Takes the upper layer out of the deployment, if there is one,
+    /// so that captured files can be harvested after the deployment is removed.
+    #[cfg(target_os = "linux")]
+    pub fn take_upper(&mut self) -> Option<UpperLayer> {
+        match &mut self.kind {
+            DeploymentKind::Overlay { upper, .. } => upper.take(),
+            DeploymentKind::Links(_) => None,
+        }
+    }
+
+    /// Checks that the deployed files are still in place, returning those that aren't.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code, reason = "only used by the daemon"))]
+    pub fn verify(&self) -> anyhow::Result<Vec<Discrepancy>> {
+        let file_map = FileMap::read(&self.file_map_path)?.context("file map is missing")?;
+        Ok(file_map.verify())
+    }
+
+    /// Removes the deployed files.
+    ///
+    /// The upper layer is closed along with the overlay, unless it was taken out with `take_upper` beforehand.
+    pub fn remove(self) -> anyhow::Result<()> {
+        let _span = info_span!("unmount").entered();
+        #[cfg(target_os = "linux")]
+        if let Some(saves) = self.saves {
+            let path = saves.path().to_owned();
+            saves
+                .unmount()
+                .with_context(|| format!("failed to unmount saves from '{}'", path.display()))?;
+        }
+        match self.kind {
+            #[cfg(target_os = "linux")]
+            DeploymentKind::Overlay { staging, mounts, upper } => {
+                for overlay_mount in mounts.into_iter().rev() {
+                    let path = overlay_mount.path().to_owned();
+                    overlay_mount
+                        .unmount()
+                        .with_context(|| format!("failed to unmount overlay at '{}'", path.display()))?;
+                }
+                if let Some(staging) = staging {
+                    staging.close().context("failed to unmount staging tmpfs")?;
+                }
+                if let Some(upper) = upper {
+                    upper.close().context("failed to unmount upper layer tmpfs")?;
+                }
+                info!("Unmount successful");
+            }
+            DeploymentKind::Links(deployment) => {
+                deployment.remove().context("failed to remove links")?;
+                info!("Links removed successfully");
+            }
+        }
+
+        if let Err(err) = fs::remove_file(&self.file_map_path)
+            && err.kind() != io::ErrorKind::NotFound
+        {
+            warn!("Failed to remove file map '{}': {err}", self.file_map_path.display());
+        }
+        Ok(())
+    }
+}
+
+/// Maximum number of lower layers of an overlay mount (`OVL_MAX_STACK` in the kernel).
+#[cfg(target_os = "linux")]
+const MAX_LOWER_DIRS: usize = 500;
+
+/// Returns the directories of the enabled mods, highest priority first, if mounting them directly
+/// as the lower layers of the overlay gives the same result as mounting the staging tree.
+///
+/// This isn't the case if there are mount targets, mods deployed to subdirectories, Git repositories
+/// (which aren't deployed), excluded files, deletion markers, or too many mods.
+#[cfg(target_os = "linux")]
+fn direct_lower_dirs(tree: &FileTree<ModVec>, mods: &DeployInstance) -> Option<Vec<PathBuf>> {
+    if !mods.mount_targets().is_empty() {
+        return None;
+    }
+
+    let mut dirs = Vec::new();
+    for entry in mods.mod_order().iter().rev().filter(|entry| entry.enabled) {
+        let mod_decl = &mods.mods()[entry.mod_index()];
+        let Some(dir) = mods.mod_dir(mod_decl) else {
+            continue;
+        };
+        if mod_decl.target().is_some() || dir.join(".git").exists() {
+            return None;
+        }
+        dirs.push(dir);
+    }
+    // The game directory is the bottom layer.
+    if dirs.len() + 1 > MAX_LOWER_DIRS {
+        return None;
+    }
+
+    let node_count = tree.root().expect("has root node").traverse_pre_order().count() - 1;
+    let mut deployed_count = 0;
+    let mut has_deletions = false;
+    walk_tree(tree, mods, |_, node| {
+        deployed_count += 1;
+        has_deletions |= matches!(node, DeployNode::Deleted);
+        Ok::<_, Infallible>(())
+    })
+    .unwrap_or_else(|never| match never {});
+    (deployed_count == node_count && !has_deletions).then_some(dirs)
+}
@@ -0,0 +1,78 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Glob patterns of mod files that are left out of the deployment.
+
+use std::path::Path;
+
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+
+/// A set of exclusion patterns.
+///
+/// Patterns without a slash match the name of a file or directory at any depth, other patterns match
+/// the whole path relative to the deployment root. Patterns ending with a slash only match directories.
+/// Matching is case-insensitive, as most games are made for Windows.
+#[derive(Debug)]
+pub struct Exclusions {
+    names: GlobSet,
+    paths: GlobSet,
+    dir_names: GlobSet,
+    dir_paths: GlobSet,
+}
+
+impl Exclusions {
+    pub fn new<'a>(patterns: impl IntoIterator<Item = &'a str>) -> Result<Self, globset::Error> {
+        let mut names = GlobSetBuilder::new();
+        let mut paths = GlobSetBuilder::new();
+        let mut dir_names = GlobSetBuilder::new();
+        let mut dir_paths = GlobSetBuilder::new();
+
+        for pattern in patterns {
+            let (pattern, dir_only) = pattern
+                .strip_suffix('/')
+                .map_or((pattern, false), |pattern| (pattern, true));
+            let is_path = pattern.contains('/');
+            let glob = build_glob(pattern.trim_start_matches('/'))?;
+            match (is_path, dir_only) {
+                (false, false) => names.add(glob),
+                (true, false) => paths.add(glob),
+                (false, true) => dir_names.add(glob),
+                (true, true) => dir_paths.add(glob),
+            };
+        }
+
+        Ok(Self {
+            names: names.build()?,
+            paths: paths.build()?,
+            dir_names: dir_names.build()?,
+            dir_paths: dir_paths.build()?,
+        })
+    }
+
+    /// Returns `true` if the file or directory at `relative_path` is excluded.
+    pub fn is_excluded(&self, relative_path: &Path, is_dir: bool) -> bool {
+        let name_matches = |set: &GlobSet| relative_path.file_name().is_some_and(|name| set.is_match(name));
+        name_matches(&self.names)
+            || self.paths.is_match(relative_path)
+            || (is_dir && (name_matches(&self.dir_names) || self.dir_paths.is_match(relative_path)))
+    }
+}
+
+fn build_glob(pattern: &str) -> Result<Glob, globset::Error> {
+    GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .case_insensitive(true)
+        .build()
+}
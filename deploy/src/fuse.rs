@@ -0,0 +1,162 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Overlay mounts through `fuse-overlayfs`, for kernels that don't allow mounting overlayfs unprivileged.
+
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::os::unix::ffi::OsStringExt;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rustix::mount::{UnmountFlags, unmount};
+use thiserror::Error;
+use tracing::warn;
+
+use crate::mount::push_escaped_path;
+
+const PROGRAM: &str = "fuse-overlayfs";
+const MOUNT_TIMEOUT: Duration = Duration::from_secs(10);
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Returns `true` if `fuse-overlayfs` is in `PATH`.
+pub fn is_available() -> bool {
+    env::var_os("PATH").is_some_and(|path| env::split_paths(&path).any(|dir| dir.join(PROGRAM).is_file()))
+}
+
+/// An overlay mounted by a `fuse-overlayfs` process, which serves it until it is unmounted.
+#[derive(Debug)]
+pub struct FuseOverlay {
+    path: PathBuf,
+    /// `None` once unmounted.
+    child: Option<Child>,
+}
+
+impl FuseOverlay {
+    /// Mounts an overlay of `lower_dirs`, topmost first, over `game_dir`, optionally with upper and work directories,
+    /// and waits for the mount to appear.
+    pub fn mount(
+        lower_dirs: &[PathBuf],
+        game_dir: &Path,
+        upper: Option<(&Path, &Path)>,
+    ) -> Result<Self, FuseOverlayError> {
+        let device_before = fs::metadata(game_dir).map_err(FuseOverlayError::Metadata)?.dev();
+
+        let mut options = b"lowerdir=".to_vec();
+        for lower_dir in lower_dirs {
+            push_escaped_path(&mut options, lower_dir);
+            options.push(b':');
+        }
+        push_escaped_path(&mut options, game_dir);
+        if let Some((upper_dir, work_dir)) = upper {
+            options.extend_from_slice(b",upperdir=");
+            push_escaped_path(&mut options, upper_dir);
+            options.extend_from_slice(b",workdir=");
+            push_escaped_path(&mut options, work_dir);
+        }
+
+        let mut child = Command::new(PROGRAM)
+            .arg("-f")
+            .arg("-o")
+            .arg(OsString::from_vec(options))
+            .arg(game_dir)
+            .stdin(Stdio::null())
+            .spawn()
+            .map_err(FuseOverlayError::Spawn)?;
+
+        let start = Instant::now();
+        loop {
+            if let Some(status) = child.try_wait().map_err(FuseOverlayError::Wait)? {
+                return Err(FuseOverlayError::Exited(status));
+            }
+            if fs::metadata(game_dir).is_ok_and(|metadata| metadata.dev() != device_before) {
+                break;
+            }
+            if start.elapsed() > MOUNT_TIMEOUT {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(FuseOverlayError::Timeout);
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+
+        Ok(Self { path: game_dir.to_owned(), child: Some(child) })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn unmount(mut self) -> Result<(), FuseOverlayError> {
+        self.unmount_inner()
+    }
+
+    fn unmount_inner(&mut self) -> Result<(), FuseOverlayError> {
+        let Some(mut child) = self.child.take() else {
+            return Ok(());
+        };
+
+        // Within a user namespace, the mount can be removed directly. Outside of one, fusermount is needed.
+        if unmount(&self.path, UnmountFlags::DETACH | UnmountFlags::NOFOLLOW).is_err() {
+            let status = Command::new("fusermount3")
+                .arg("-u")
+                .arg("-z")
+                .arg(&self.path)
+                .status()
+                .or_else(|_| Command::new("fusermount").arg("-u").arg("-z").arg(&self.path).status())
+                .map_err(FuseOverlayError::Fusermount)?;
+            if !status.success() {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(FuseOverlayError::FusermountFailed(status));
+            }
+        }
+
+        let status = child.wait().map_err(FuseOverlayError::Wait)?;
+        if !status.success() {
+            warn!("{PROGRAM} exited with {status}");
+        }
+        Ok(())
+    }
+}
+
+impl Drop for FuseOverlay {
+    fn drop(&mut self) {
+        let _ = self.unmount_inner();
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FuseOverlayError {
+    #[error("{PROGRAM} exited before mounting ({0})")]
+    Exited(ExitStatus),
+    #[error("failed to run fusermount")]
+    Fusermount(#[source] io::Error),
+    #[error("fusermount failed ({0})")]
+    FusermountFailed(ExitStatus),
+    #[error("failed to get metadata of mount target directory")]
+    Metadata(#[source] io::Error),
+    #[error("failed to run {PROGRAM}")]
+    Spawn(#[source] io::Error),
+    #[error("{PROGRAM} did not mount the overlay within {} seconds", MOUNT_TIMEOUT.as_secs())]
+    Timeout,
+    #[error("failed to wait for {PROGRAM}")]
+    Wait(#[source] io::Error),
+}
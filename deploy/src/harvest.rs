@@ -0,0 +1,136 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Moving files captured in the overlay's upper layer into the instance, so they can be managed like mod files.
+
+use std::fs;
+use std::io;
+use std::os::unix::fs::{FileTypeExt, symlink};
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use mmm_core::instance::{Instance, ModEntryKind};
+
+use crate::instance::DeployInstance;
+
+/// Name of the directory, in the instance directory, that captured files are moved to by default.
+pub const OVERWRITE_DIR: &str = "overwrite";
+
+/// Returns the directory captured files should be moved to: the mod named `mod_name`, or the overwrite directory.
+pub fn harvest_destination(
+    instance: &DeployInstance,
+    mod_name: Option<&str>,
+) -> Result<PathBuf, HarvestDestinationError> {
+    let Some(mod_name) = mod_name else {
+        return Ok(instance.dir().join(OVERWRITE_DIR));
+    };
+
+    let mod_decl = instance
+        .mods()
+        .iter()
+        .find(|mod_decl| mod_decl.name() == mod_name)
+        .ok_or_else(|| HarvestDestinationError::ModNotFound(mod_name.to_owned()))?;
+    if !matches!(mod_decl.kind(), ModEntryKind::Mod) {
+        return Err(HarvestDestinationError::NotAMod(mod_name.to_owned()));
+    }
+    Ok(instance.mod_dir(mod_decl).expect("mods have a directory"))
+}
+
+/// Returns the paths, relative to `upper_dir`, of the files created or modified through the overlay.
+///
+/// Whiteouts, which record deleted files, are not included.
+pub fn captured_files(upper_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(relative_dir) = pending.pop() {
+        for entry in fs::read_dir(upper_dir.join(&relative_dir))? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            let relative_path = relative_dir.join(entry.file_name());
+            if file_type.is_dir() {
+                pending.push(relative_path);
+            } else if !file_type.is_char_device() {
+                files.push(relative_path);
+            }
+        }
+    }
+    files.sort_unstable();
+    Ok(files)
+}
+
+/// Moves `files`, relative to `upper_dir`, into `destination`, replacing existing files.
+///
+/// Directories in the upper layer that are left empty are removed.
+pub fn harvest(upper_dir: &Path, files: &[PathBuf], destination: &Path) -> Result<(), HarvestError> {
+    for relative_path in files {
+        let source_path = upper_dir.join(relative_path);
+        let destination_path = destination.join(relative_path);
+        let destination_parent = destination_path.parent().expect("destination path has parent");
+        fs::create_dir_all(destination_parent)
+            .map_err(|source| HarvestError::Mkdir { path: destination_parent.to_owned(), source })?;
+
+        move_file(&source_path, &destination_path).map_err(|source| HarvestError::Move {
+            source_path,
+            destination_path,
+            source,
+        })?;
+    }
+
+    for relative_path in files {
+        for dir in relative_path.ancestors().skip(1) {
+            if dir.as_os_str().is_empty() || fs::remove_dir(upper_dir.join(dir)).is_err() {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        // The upper layer may be on a tmpfs.
+        Err(err) if err.kind() == io::ErrorKind::CrossesDevices => {
+            if fs::symlink_metadata(from)?.is_symlink() {
+                let _ = fs::remove_file(to);
+                symlink(fs::read_link(from)?, to)?;
+            } else {
+                fs::copy(from, to)?;
+            }
+            fs::remove_file(from)
+        }
+        result => result,
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum HarvestDestinationError {
+    #[error("mod '{0}' does not exist")]
+    ModNotFound(String),
+    #[error("'{0}' is a separator, not a mod")]
+    NotAMod(String),
+}
+
+#[derive(Debug, Error)]
+pub enum HarvestError {
+    #[error("failed to create directory '{path}'")]
+    Mkdir { path: PathBuf, source: io::Error },
+    #[error("failed to move '{source_path}' to '{destination_path}'")]
+    Move {
+        source_path: PathBuf,
+        destination_path: PathBuf,
+        source: io::Error,
+    },
+}
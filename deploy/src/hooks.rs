@@ -0,0 +1,98 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Commands run around each game session, while the mod files are deployed.
+
+use std::io;
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+use std::path::Path;
+use std::process::Command;
+
+use thiserror::Error;
+use tracing::info;
+
+use mmm_core::instance::Instance;
+
+use crate::instance::DeployInstance;
+
+#[derive(Copy, Clone, Debug)]
+pub enum HookStage {
+    PreLaunch,
+    PostExit,
+}
+
+impl HookStage {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::PreLaunch => "pre-launch",
+            Self::PostExit => "post-exit",
+        }
+    }
+}
+
+/// Runs each command with `sh -c` (`cmd /C` on Windows) in the game directory, stopping at the first one that fails.
+///
+/// The instance directory, game directory, profile name and stage are passed to the commands
+/// in the `MMM_INSTANCE`, `MMM_GAME_PATH`, `MMM_PROFILE` and `MMM_HOOK` environment variables.
+pub fn run_hooks(
+    stage: HookStage,
+    commands: &[impl AsRef<str>],
+    instance: &DeployInstance,
+    game_path: &Path,
+) -> Result<(), HookError> {
+    for command in commands {
+        let command = command.as_ref();
+        info!("Running {} hook: {command}", stage.name());
+        let status = shell_command(command)
+            .current_dir(game_path)
+            .env("MMM_INSTANCE", instance.dir())
+            .env("MMM_GAME_PATH", game_path)
+            .env("MMM_PROFILE", instance.profile_name())
+            .env("MMM_HOOK", stage.name())
+            .status()
+            .map_err(|source| HookError::Spawn { command: command.to_owned(), source })?;
+        if !status.success() {
+            return Err(HookError::Failed {
+                command: command.to_owned(),
+                status: status.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn shell_command(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> Command {
+    // cmd doesn't follow the usual quoting rules, so the command is passed as is.
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").raw_arg(command);
+    shell
+}
+
+#[derive(Debug, Error)]
+pub enum HookError {
+    #[error("hook '{command}' failed ({status})")]
+    Failed { command: String, status: String },
+    #[error("failed to run hook '{command}'")]
+    Spawn { command: String, source: io::Error },
+}
@@ -21,45 +21,164 @@ use typed_index_collections::{TiSlice, TiVec};
 
 use mmm_core::instance::data::{INSTANCE_DATA_FILE, InstanceData, InstanceDataOpenError};
 use mmm_core::instance::{
-    DEFAULT_PROFILE_NAME, Instance, ModDeclaration, ModIndex, ModOrderEntry, ModOrderIndex, Profile,
+    DEFAULT_PROFILE_NAME, Instance, InstanceSettings, ModDeclaration, ModEntryKind, ModIndex, ModOrderEntry,
+    ModOrderIndex, MountTarget, Profile,
 };
 
+use crate::exclude::Exclusions;
+
 #[derive(Debug)]
 pub struct DeployInstance {
     dir: PathBuf,
     mods: TiVec<ModIndex, ModDeclaration>,
+    profile_name: String,
     profile: Profile,
+    settings: InstanceSettings,
+    exclusions: Exclusions,
+}
+
+/// Summary of a profile of an instance, for listing profiles.
+#[derive(Debug)]
+pub struct ProfileSummary {
+    pub name: String,
+    pub display_name: String,
+    pub enabled_mods: usize,
+    pub total_mods: usize,
+    /// Whether this profile is deployed when none is specified.
+    pub is_default: bool,
+}
+
+/// Returns a summary of each profile of the instance in `dir`, sorted by name.
+pub fn list_profiles(dir: &Path) -> Result<Vec<ProfileSummary>, DeployInstanceOpenError> {
+    let (_, data) = open_data(dir)?;
+    let default_profile = default_profile_name(&data);
+    Ok(data
+        .profiles
+        .iter()
+        .map(|(name, profile)| {
+            let mods = profile
+                .mod_order
+                .iter()
+                .filter(|entry| matches!(data.mods[entry.mod_index()].kind(), ModEntryKind::Mod));
+            ProfileSummary {
+                name: name.to_string(),
+                display_name: profile.display_name().to_owned(),
+                enabled_mods: mods.clone().filter(|entry| entry.enabled).count(),
+                total_mods: mods.count(),
+                is_default: default_profile == Some(name.as_str()),
+            }
+        })
+        .collect())
+}
+
+/// Returns the canonical path of the instance directory, and the instance data stored in it.
+fn open_data(dir: &Path) -> Result<(PathBuf, InstanceData), DeployInstanceOpenError> {
+    let dir = dir
+        .canonicalize()
+        .map_err(|source| DeployInstanceOpenError::DirCanonicalize { source, dir: dir.to_owned() })?;
+    if !dir
+        .metadata()
+        .map_err(|source| DeployInstanceOpenError::DirMetadata { source, dir: dir.clone() })?
+        .is_dir()
+    {
+        return Err(DeployInstanceOpenError::NotADirectory(dir));
+    }
+
+    let data_file = dir.join(INSTANCE_DATA_FILE);
+    let data = InstanceData::from_file(&data_file)?;
+    Ok((dir, data))
+}
+
+/// Returns the name of the profile that is deployed when none is specified: the instance's default profile,
+/// the profile named [`DEFAULT_PROFILE_NAME`], or the first profile, in that order.
+fn default_profile_name(data: &InstanceData) -> Option<&str> {
+    let name = data
+        .settings
+        .default_profile
+        .as_ref()
+        .filter(|name| data.profiles.contains_key(*name))
+        .or_else(|| data.profiles.get_key_value(&DEFAULT_PROFILE_NAME).map(|(name, _)| name))
+        .or_else(|| data.profiles.keys().next())?;
+    Some(name.as_str())
 }
 
 impl DeployInstance {
     pub fn open(dir: &Path, profile_name: Option<&str>) -> Result<Self, DeployInstanceOpenError> {
-        let dir = dir
-            .canonicalize()
-            .map_err(|source| DeployInstanceOpenError::DirCanonicalize { source, dir: dir.to_owned() })?;
-        if !dir
-            .metadata()
-            .map_err(|source| DeployInstanceOpenError::DirMetadata { source, dir: dir.clone() })?
-            .is_dir()
-        {
-            return Err(DeployInstanceOpenError::NotADirectory(dir));
-        }
+        let (dir, mut data) = open_data(dir)?;
 
-        let data_file = dir.join(INSTANCE_DATA_FILE);
-        let mut data = InstanceData::from_file(&data_file)?;
-
-        let profile = if let Some(profile_name) = profile_name {
-            data.profiles
-                .remove(profile_name)
-                .ok_or_else(|| DeployInstanceOpenError::ProfileNotFound(profile_name.to_owned()))?
-        } else if let Some(profile) = data.profiles.remove(&DEFAULT_PROFILE_NAME) {
-            profile
-        } else if let Some((_, profile)) = data.profiles.pop_first() {
-            profile
-        } else {
-            return Err(DeployInstanceOpenError::NoProfiles);
+        let profile_name = match profile_name {
+            Some(profile_name) => profile_name.to_owned(),
+            None => default_profile_name(&data)
+                .ok_or(DeployInstanceOpenError::NoProfiles)?
+                .to_owned(),
         };
+        let (profile_name, profile) = data
+            .profiles
+            .remove_entry(profile_name.as_str())
+            .ok_or(DeployInstanceOpenError::ProfileNotFound(profile_name))?;
 
-        Ok(Self { dir, mods: data.mods, profile })
+        let exclusions = Exclusions::new(data.settings.effective_staging_exclusions())
+            .map_err(DeployInstanceOpenError::InvalidExclusion)?;
+
+        Ok(Self {
+            dir,
+            mods: data.mods,
+            profile_name: profile_name.into(),
+            profile,
+            settings: data.settings,
+            exclusions,
+        })
+    }
+
+    pub fn profile_name(&self) -> &str {
+        &self.profile_name
+    }
+
+    /// Returns the profile name, if it can be used as the name of a per-profile directory.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code, reason = "only used by the overlay backend"))]
+    pub fn profile_dir_name(&self) -> Option<&str> {
+        let name = self.profile_name.as_str();
+        (!name.is_empty() && name != "." && name != ".." && !name.contains('/')).then_some(name)
+    }
+
+    pub fn mount_targets(&self) -> &[MountTarget] {
+        &self.settings.mount_targets
+    }
+
+    pub const fn settings(&self) -> &InstanceSettings {
+        &self.settings
+    }
+
+    pub const fn exclusions(&self) -> &Exclusions {
+        &self.exclusions
+    }
+
+    /// Replaces the instance's staging exclusion patterns, without writing to the instance data file.
+    pub fn override_exclusions<'a>(
+        &mut self,
+        patterns: impl IntoIterator<Item = &'a str>,
+    ) -> Result<(), globset::Error> {
+        self.exclusions = Exclusions::new(patterns)?;
+        Ok(())
+    }
+
+    /// Makes the game use its own saves instead of the profile's, without writing to the instance data file.
+    pub fn share_saves(&mut self) {
+        self.settings.save_path = None;
+    }
+
+    /// Enables or disables the mods with the specified names, in order, without writing to the instance data file.
+    pub fn override_mods(&mut self, overrides: &[(String, bool)]) -> Result<(), ModNotFoundError> {
+        for (name, enabled) in overrides {
+            let entry = self
+                .profile
+                .mod_order
+                .iter_mut()
+                .find(|entry| self.mods[entry.mod_index()].name() == name)
+                .ok_or_else(|| ModNotFoundError(name.clone()))?;
+            entry.enabled = *enabled;
+        }
+        Ok(())
     }
 }
 
@@ -83,6 +202,8 @@ pub enum DeployInstanceOpenError {
     DirCanonicalize { source: io::Error, dir: PathBuf },
     #[error("failed to get metadata of '{dir}'")]
     DirMetadata { source: io::Error, dir: PathBuf },
+    #[error("instance has an invalid staging exclusion pattern")]
+    InvalidExclusion(#[source] globset::Error),
     #[error("instance has no profiles")]
     NoProfiles,
     #[error("'{0}' is not a directory")]
@@ -92,3 +213,7 @@ pub enum DeployInstanceOpenError {
     #[error("failed to open instance data file")]
     DataOpen(#[from] InstanceDataOpenError),
 }
+
+#[derive(Debug, Error)]
+#[error("mod '{0}' does not exist")]
+pub struct ModNotFoundError(String);
@@ -0,0 +1,661 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Deployment of mod files by linking them directly into the game directory, without mounting anything.
+//!
+//! Files are either symlinked or hardlinked. Hardlinks can't cross filesystems, so files that live on a different
+//! filesystem than the game directory are copied instead. Files can also always be copied, for games that don't
+//! work with links at all. The checksums of copied files are recorded, so that files modified by the game
+//! are left in place when the deployment is removed. On Windows, where the link count of a file can't be
+//! checked, hardlinks are recorded like copies.
+//!
+//! Every change made to the game directory is recorded in a manifest file in the instance directory,
+//! before it is made, so that the deployment can be removed cleanly, even after a crash.
+//! Game files that are replaced by mod files are moved to [`BACKUP_DIR`] and restored on removal.
+
+#[cfg(unix)]
+use std::ffi::OsStr;
+use std::fmt::Write as _;
+use std::fs::{self, File, Metadata};
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::ffi::OsStrExt;
+#[cfg(unix)]
+use std::os::unix::fs::{MetadataExt, symlink};
+#[cfg(windows)]
+use std::os::windows::fs::symlink_file as symlink;
+use std::path::{Path, PathBuf};
+
+#[cfg(unix)]
+use rustix::fs::{CWD, RenameFlags, renameat_with};
+#[cfg(unix)]
+use rustix::io::Errno;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tracing::warn;
+
+use mmm_core::file_tree::{FileTree, ModVec};
+use mmm_core::instance::Instance;
+
+use crate::instance::DeployInstance;
+use crate::walk::{DeployNode, walk_tree};
+
+/// Name of the deployment manifest file, in the instance directory.
+pub const MANIFEST_FILE: &str = ".deployment";
+/// Name of the directory, in the game directory, where replaced game files are kept.
+pub const BACKUP_DIR: &str = ".mmm-backup";
+
+const MANIFEST_HEADER: &[u8] = b"mmm-deployment 1";
+const TARGET_PREFIX: &[u8] = b"target ";
+
+/// SHA-256 checksum of a copied file.
+pub type Checksum = [u8; 32];
+
+/// A change made to the game directory, relative to its root.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Entry {
+    /// A directory was created.
+    Dir(PathBuf),
+    /// A symlink to a mod file was created.
+    Symlink(PathBuf),
+    /// A hardlink to a mod file was created.
+    Hardlink(PathBuf),
+    /// A mod file was copied.
+    Copy(PathBuf, Checksum),
+    /// A game file was moved to the backup directory.
+    Backup(PathBuf),
+}
+
+impl Entry {
+    const fn tag(&self) -> u8 {
+        match self {
+            Self::Dir(_) => b'd',
+            Self::Symlink(_) => b'l',
+            Self::Hardlink(_) => b'h',
+            Self::Copy(..) => b'c',
+            Self::Backup(_) => b'b',
+        }
+    }
+
+    fn path(&self) -> &Path {
+        match self {
+            Self::Dir(path) | Self::Symlink(path) | Self::Hardlink(path) | Self::Copy(path, _) | Self::Backup(path) => {
+                path
+            }
+        }
+    }
+
+    fn parse(line: &[u8]) -> Option<Self> {
+        let (&tag, rest) = line.split_first()?;
+        let mut rest = rest.strip_prefix(b" ")?;
+        let checksum = if tag == b'c' {
+            let (hex, path) = rest.split_at_checked(64)?;
+            rest = path.strip_prefix(b" ")?;
+            Some(checksum_from_hex(hex)?)
+        } else {
+            None
+        };
+
+        let path = path_from_bytes(rest)?;
+        if !path.is_relative() {
+            return None;
+        }
+        match tag {
+            b'd' => Some(Self::Dir(path)),
+            b'l' => Some(Self::Symlink(path)),
+            b'h' => Some(Self::Hardlink(path)),
+            b'c' => Some(Self::Copy(path, checksum?)),
+            b'b' => Some(Self::Backup(path)),
+            _ => None,
+        }
+    }
+}
+
+/// How mod files are placed in the game directory.
+#[derive(Copy, Clone, Debug)]
+pub enum LinkMethod {
+    Symlink,
+    /// Hardlink files, or copy them if they're on a different filesystem than the game directory.
+    Hardlink,
+    Copy,
+}
+
+/// Mod files linked into a game directory.
+#[derive(Debug)]
+pub struct LinkDeployment {
+    manifest_path: PathBuf,
+    target: PathBuf,
+    entries: Vec<Entry>,
+}
+
+impl LinkDeployment {
+    /// Links every file in `tree` into `target`.
+    ///
+    /// If an error occurs, the changes made so far are undone.
+    pub fn create(
+        tree: &FileTree<ModVec>,
+        instance: &DeployInstance,
+        target: &Path,
+        method: LinkMethod,
+    ) -> Result<Self, LinkError> {
+        let target_device = device(target)?;
+        let manifest_path = instance.dir().join(MANIFEST_FILE);
+        let mut manifest = File::create_new(&manifest_path)
+            .map_err(|source| LinkError::Manifest { path: manifest_path.clone(), source })?;
+        write_line(&mut manifest, &[MANIFEST_HEADER])
+            .and_then(|()| write_line(&mut manifest, &[TARGET_PREFIX, path_to_bytes(target)?]))
+            .map_err(|source| LinkError::Manifest { path: manifest_path.clone(), source })?;
+
+        let mut deployment = Self {
+            manifest_path,
+            target: target.to_owned(),
+            entries: Vec::new(),
+        };
+        let result = walk_tree(tree, instance, |relative_path, node| {
+            deployment.deploy_node(&mut manifest, relative_path, node, method, target_device)
+        });
+        drop(manifest);
+
+        match result {
+            Ok(()) => Ok(deployment),
+            Err(err) => {
+                if let Err(remove_err) = deployment.remove() {
+                    warn!("Failed to undo partial deployment: {remove_err}");
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Opens the deployment recorded in the instance's manifest, if there is one.
+    pub fn open(instance: &DeployInstance) -> Result<Option<Self>, ManifestReadError> {
+        let manifest_path = instance.dir().join(MANIFEST_FILE);
+        let contents = match fs::read(&manifest_path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(ManifestReadError::Read { path: manifest_path, source }),
+        };
+
+        let mut lines = contents.split(|&b| b == b'\n').filter(|line| !line.is_empty());
+        if lines.next() != Some(MANIFEST_HEADER) {
+            return Err(ManifestReadError::UnknownFormat(manifest_path));
+        }
+        let target = lines
+            .next()
+            .and_then(|line| line.strip_prefix(TARGET_PREFIX))
+            .and_then(path_from_bytes)
+            .filter(|target| target.is_absolute())
+            .ok_or_else(|| ManifestReadError::Malformed(manifest_path.clone()))?;
+        let entries = lines
+            .map(Entry::parse)
+            .collect::<Option<_>>()
+            .ok_or_else(|| ManifestReadError::Malformed(manifest_path.clone()))?;
+
+        Ok(Some(Self { manifest_path, target, entries }))
+    }
+
+    /// Returns the path of the game directory the mod files were deployed to.
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+
+    /// Removes the deployed links and restores the game files they replaced.
+    ///
+    /// Directories created by the deployment that have since gained other files are left in place.
+    /// If an error occurs, the manifest is updated to contain only the changes that have not been undone yet,
+    /// so removal can be retried.
+    pub fn remove(mut self) -> Result<(), UnlinkError> {
+        while let Some(entry) = self.entries.last() {
+            if let Err(err) = self.undo(entry) {
+                if let Err(write_err) = self.rewrite_manifest() {
+                    warn!(
+                        "Failed to update deployment manifest '{}': {write_err}",
+                        self.manifest_path.display()
+                    );
+                }
+                return Err(err);
+            }
+            self.entries.pop();
+        }
+
+        remove_empty_dirs(&self.target.join(BACKUP_DIR));
+        fs::remove_file(&self.manifest_path)
+            .map_err(|source| UnlinkError::Manifest { path: self.manifest_path.clone(), source })
+    }
+
+    fn deploy_node(
+        &mut self,
+        manifest: &mut File,
+        relative_path: &Path,
+        node: DeployNode,
+        method: LinkMethod,
+        target_device: u64,
+    ) -> Result<(), LinkError> {
+        let path = self.target.join(relative_path);
+        let existing = match fs::symlink_metadata(&path) {
+            Ok(metadata) => Some(metadata),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(source) => return Err(LinkError::Metadata { path, source }),
+        };
+
+        match node {
+            DeployNode::Dir => {
+                if existing.as_ref().is_some_and(Metadata::is_dir) {
+                    return Ok(());
+                }
+                if existing.is_some() {
+                    self.back_up(manifest, relative_path)?;
+                }
+                self.record(manifest, Entry::Dir(relative_path.to_owned()))?;
+                fs::create_dir(&path).map_err(|source| LinkError::Mkdir { path, source })?;
+            }
+            DeployNode::File { source_path } => {
+                if existing.is_some() {
+                    self.back_up(manifest, relative_path)?;
+                }
+                match method {
+                    LinkMethod::Symlink => {
+                        self.record(manifest, Entry::Symlink(relative_path.to_owned()))?;
+                        symlink(&source_path, &path).map_err(|source| LinkError::Symlink {
+                            source_path,
+                            link_path: path,
+                            source,
+                        })?;
+                    }
+                    LinkMethod::Hardlink => {
+                        if cfg!(windows) {
+                            self.hard_link_or_copy(manifest, relative_path, source_path)?;
+                        } else if device(&source_path)? == target_device {
+                            self.record(manifest, Entry::Hardlink(relative_path.to_owned()))?;
+                            fs::hard_link(&source_path, &path).map_err(|source| LinkError::Hardlink {
+                                source_path,
+                                link_path: path,
+                                source,
+                            })?;
+                        } else {
+                            self.copy(manifest, relative_path, source_path)?;
+                        }
+                    }
+                    LinkMethod::Copy => self.copy(manifest, relative_path, source_path)?,
+                }
+            }
+            DeployNode::Deleted => {
+                if existing.is_some() {
+                    self.back_up(manifest, relative_path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Hardlinks the file, or copies it if it's on a different volume than the game directory, recording it as a
+    /// copy either way.
+    ///
+    /// Used on Windows, where the volume of a file and its link count can't be checked.
+    fn hard_link_or_copy(
+        &mut self,
+        manifest: &mut File,
+        relative_path: &Path,
+        source_path: PathBuf,
+    ) -> Result<(), LinkError> {
+        let checksum =
+            checksum_file(&source_path).map_err(|source| LinkError::Checksum { path: source_path.clone(), source })?;
+        self.record(manifest, Entry::Copy(relative_path.to_owned(), checksum))?;
+
+        let path = self.target.join(relative_path);
+        match fs::hard_link(&source_path, &path) {
+            Err(err) if err.kind() == io::ErrorKind::CrossesDevices => copy_file(source_path, path),
+            result => result.map_err(|source| LinkError::Hardlink { source_path, link_path: path, source }),
+        }
+    }
+
+    fn copy(&mut self, manifest: &mut File, relative_path: &Path, source_path: PathBuf) -> Result<(), LinkError> {
+        let checksum =
+            checksum_file(&source_path).map_err(|source| LinkError::Checksum { path: source_path.clone(), source })?;
+        self.record(manifest, Entry::Copy(relative_path.to_owned(), checksum))?;
+
+        copy_file(source_path, self.target.join(relative_path))
+    }
+
+    fn back_up(&mut self, manifest: &mut File, relative_path: &Path) -> Result<(), LinkError> {
+        let path = self.target.join(relative_path);
+        let backup_path = self.target.join(BACKUP_DIR).join(relative_path);
+        let backup_parent = backup_path.parent().expect("backup path has parent");
+        fs::create_dir_all(backup_parent)
+            .map_err(|source| LinkError::Mkdir { path: backup_parent.to_owned(), source })?;
+
+        self.record(manifest, Entry::Backup(relative_path.to_owned()))?;
+        fs::rename(&path, &backup_path).map_err(|source| LinkError::Backup { path, source })
+    }
+
+    fn record(&mut self, manifest: &mut File, entry: Entry) -> Result<(), LinkError> {
+        write_entry(manifest, &entry)
+            .map_err(|source| LinkError::Manifest { path: self.manifest_path.clone(), source })?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    fn undo(&self, entry: &Entry) -> Result<(), UnlinkError> {
+        let path = self.target.join(entry.path());
+        let result = match entry {
+            Entry::Dir(_) => match fs::remove_dir(&path) {
+                Err(err) if err.kind() == io::ErrorKind::DirectoryNotEmpty => {
+                    warn!(
+                        "Not removing '{}', as it contains files not created by mmm",
+                        path.display()
+                    );
+                    Ok(())
+                }
+                result => result,
+            },
+            Entry::Symlink(_) => remove_file_if(&path, Metadata::is_symlink),
+            // If the game replaced the file, the new one isn't linked to the mod file anymore.
+            #[cfg(unix)]
+            Entry::Hardlink(_) => remove_file_if(&path, |metadata| metadata.is_file() && metadata.nlink() > 1),
+            // Not written on Windows, and link counts can't be checked there, so only remove it if it's unchanged.
+            #[cfg(windows)]
+            Entry::Hardlink(_) => remove_file_if(&path, Metadata::is_file),
+            Entry::Copy(_, checksum) => remove_file_if(&path, |metadata| {
+                metadata.is_file() && checksum_file(&path).is_ok_and(|current| current == *checksum)
+            }),
+            Entry::Backup(relative_path) => {
+                let backup_path = self.target.join(BACKUP_DIR).join(relative_path);
+                match rename_noreplace(&backup_path, &path) {
+                    Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                        warn!(
+                            "Not restoring '{}', as a file modified by the game is in its place, keeping it at '{}'",
+                            path.display(),
+                            backup_path.display()
+                        );
+                        Ok(())
+                    }
+                    result => result,
+                }
+            }
+        };
+
+        match result {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result.map_err(|source| UnlinkError::Undo { path, source }),
+        }
+    }
+
+    fn rewrite_manifest(&self) -> io::Result<()> {
+        let mut manifest = File::create(&self.manifest_path)?;
+        write_line(&mut manifest, &[MANIFEST_HEADER])?;
+        write_line(&mut manifest, &[TARGET_PREFIX, path_to_bytes(&self.target)?])?;
+        for entry in &self.entries {
+            write_entry(&mut manifest, entry)?;
+        }
+        Ok(())
+    }
+}
+
+/// Removes the file at `path`, unless `check` returns `false`, in which case it was replaced by a file that
+/// wasn't created by the deployment.
+fn remove_file_if(path: &Path, check: impl FnOnce(&Metadata) -> bool) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(path)?;
+    if check(&metadata) {
+        fs::remove_file(path)
+    } else {
+        warn!("Not removing '{}', as it was modified or replaced", path.display());
+        Ok(())
+    }
+}
+
+/// Returns the identifier of the filesystem `path` is on.
+#[cfg(unix)]
+fn device(path: &Path) -> Result<u64, LinkError> {
+    fs::metadata(path)
+        .map(|metadata| metadata.dev())
+        .map_err(|source| LinkError::Metadata { path: path.to_owned(), source })
+}
+
+/// Volumes can't be identified on Windows, so every path is considered to be on the same one.
+#[cfg(windows)]
+#[allow(clippy::unnecessary_wraps, reason = "same signature as on Unix")]
+fn device(_path: &Path) -> Result<u64, LinkError> {
+    Ok(0)
+}
+
+/// Moves `from` to `to`, failing with [`io::ErrorKind::AlreadyExists`] if `to` exists.
+#[cfg(unix)]
+fn rename_noreplace(from: &Path, to: &Path) -> io::Result<()> {
+    match renameat_with(CWD, from, CWD, to, RenameFlags::NOREPLACE) {
+        Err(Errno::EXIST) => Err(io::ErrorKind::AlreadyExists.into()),
+        result => result.map_err(io::Error::from),
+    }
+}
+
+/// Moves `from` to `to`, failing with [`io::ErrorKind::AlreadyExists`] if `to` exists.
+///
+/// Unlike on Unix, this isn't atomic, but nothing else should be creating files in the game directory
+/// while the deployment is removed.
+#[cfg(windows)]
+fn rename_noreplace(from: &Path, to: &Path) -> io::Result<()> {
+    if fs::exists(to)? {
+        return Err(io::ErrorKind::AlreadyExists.into());
+    }
+    fs::rename(from, to)
+}
+
+/// Returns the bytes `path` is stored as in manifest files.
+#[cfg(unix)]
+#[allow(clippy::unnecessary_wraps, reason = "not every path can be stored on Windows")]
+pub fn path_to_bytes(path: &Path) -> io::Result<&[u8]> {
+    Ok(path.as_os_str().as_bytes())
+}
+
+/// Returns the bytes `path` is stored as in manifest files, which is UTF-8 on Windows.
+#[cfg(windows)]
+pub fn path_to_bytes(path: &Path) -> io::Result<&[u8]> {
+    path.to_str().map(str::as_bytes).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "paths that aren't valid Unicode are not supported",
+        )
+    })
+}
+
+/// Returns the path stored as `bytes` in a manifest file.
+#[cfg(unix)]
+#[allow(clippy::unnecessary_wraps, reason = "not every path can be stored on Windows")]
+pub fn path_from_bytes(bytes: &[u8]) -> Option<PathBuf> {
+    Some(PathBuf::from(OsStr::from_bytes(bytes)))
+}
+
+/// Returns the path stored as `bytes` in a manifest file, or `None` if they're not valid UTF-8.
+#[cfg(windows)]
+pub fn path_from_bytes(bytes: &[u8]) -> Option<PathBuf> {
+    str::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+fn write_entry(manifest: &mut impl Write, entry: &Entry) -> io::Result<()> {
+    let path = path_to_bytes(entry.path())?;
+    if let Entry::Copy(_, checksum) = entry {
+        let hex = checksum_to_hex(checksum);
+        write_line(manifest, &[&[entry.tag(), b' '], hex.as_bytes(), b" ", path])
+    } else {
+        write_line(manifest, &[&[entry.tag(), b' '], path])
+    }
+}
+
+/// Copies a mod file into the game directory, removing the partial copy if copying fails,
+/// so that it isn't mistaken for a deployed file.
+fn copy_file(source_path: PathBuf, destination_path: PathBuf) -> Result<(), LinkError> {
+    match fs::copy(&source_path, &destination_path) {
+        Ok(_) => Ok(()),
+        Err(source) => {
+            let _ = fs::remove_file(&destination_path);
+            Err(LinkError::Copy { source_path, destination_path, source })
+        }
+    }
+}
+
+pub fn checksum_file(path: &Path) -> io::Result<Checksum> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(hasher.finalize().into())
+}
+
+fn checksum_to_hex(checksum: &Checksum) -> String {
+    let mut hex = String::with_capacity(checksum.len() * 2);
+    for byte in checksum {
+        write!(hex, "{byte:02x}").expect("writing to a String doesn't fail");
+    }
+    hex
+}
+
+fn checksum_from_hex(hex: &[u8]) -> Option<Checksum> {
+    let mut checksum = [0; 32];
+    if !hex.iter().all(u8::is_ascii_hexdigit) {
+        return None;
+    }
+    for (byte, pair) in checksum.iter_mut().zip(hex.chunks_exact(2)) {
+        *byte = u8::from_str_radix(str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(checksum)
+}
+
+fn write_line(file: &mut impl Write, parts: &[&[u8]]) -> io::Result<()> {
+    if parts.iter().any(|part| part.contains(&b'\n')) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "paths containing newlines are not supported",
+        ));
+    }
+    let mut line = parts.concat();
+    line.push(b'\n');
+    file.write_all(&line)
+}
+
+/// Removes `path` and every directory below it, as long as they contain no files.
+fn remove_empty_dirs(path: &Path) {
+    let Ok(read_dir) = fs::read_dir(path) else {
+        return;
+    };
+    for entry in read_dir.flatten() {
+        if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            remove_empty_dirs(&entry.path());
+        }
+    }
+    let _ = fs::remove_dir(path);
+}
+
+#[derive(Debug, Error)]
+pub enum LinkError {
+    #[error("failed to move '{path}' to the backup directory")]
+    Backup { path: PathBuf, source: io::Error },
+    #[error("failed to compute checksum of '{path}'")]
+    Checksum { path: PathBuf, source: io::Error },
+    #[error("failed to copy '{source_path}' to '{destination_path}'")]
+    Copy {
+        source_path: PathBuf,
+        destination_path: PathBuf,
+        source: io::Error,
+    },
+    #[error("failed to create hardlink '{link_path}' to '{source_path}'")]
+    Hardlink { source_path: PathBuf, link_path: PathBuf, source: io::Error },
+    #[error("failed to write deployment manifest '{path}'")]
+    Manifest { path: PathBuf, source: io::Error },
+    #[error("failed to get metadata of '{path}'")]
+    Metadata { path: PathBuf, source: io::Error },
+    #[error("failed to create directory '{path}'")]
+    Mkdir { path: PathBuf, source: io::Error },
+    #[error("failed to create symlink '{link_path}' that points to '{source_path}'")]
+    Symlink { source_path: PathBuf, link_path: PathBuf, source: io::Error },
+}
+
+#[derive(Debug, Error)]
+pub enum UnlinkError {
+    #[error("failed to delete deployment manifest '{path}'")]
+    Manifest { path: PathBuf, source: io::Error },
+    #[error("failed to undo deployment of '{path}'")]
+    Undo { path: PathBuf, source: io::Error },
+}
+
+#[derive(Debug, Error)]
+pub enum ManifestReadError {
+    #[error("deployment manifest '{0}' is malformed")]
+    Malformed(PathBuf),
+    #[error("failed to read deployment manifest '{path}'")]
+    Read { path: PathBuf, source: io::Error },
+    #[error("deployment manifest '{0}' has an unknown format")]
+    UnknownFormat(PathBuf),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHECKSUM: Checksum = *b"\x00\x01\x7f\x80\xfe\xffmmm deployment checksum!!!";
+
+    #[test]
+    fn entry_round_trip() {
+        let entries = [
+            Entry::Dir(PathBuf::from("Data/meshes")),
+            Entry::Symlink(PathBuf::from("Data/meshes/with spaces.nif")),
+            Entry::Hardlink(PathBuf::from("Data/textures/ünïcödé.dds")),
+            Entry::Copy(PathBuf::from("Data/plugin.esp"), CHECKSUM),
+            Entry::Backup(PathBuf::from("game.exe")),
+        ];
+        let mut manifest = Vec::new();
+        for entry in &entries {
+            write_entry(&mut manifest, entry).unwrap();
+        }
+
+        let parsed: Vec<_> = manifest.split_inclusive(|&byte| byte == b'\n').collect();
+        assert_eq!(parsed.len(), entries.len());
+        for (line, entry) in parsed.into_iter().zip(&entries) {
+            let line = line.strip_suffix(b"\n").unwrap();
+            assert_eq!(Entry::parse(line).as_ref(), Some(entry));
+        }
+    }
+
+    #[test]
+    fn entry_parse_invalid() {
+        assert_eq!(Entry::parse(b""), None);
+        assert_eq!(Entry::parse(b"d"), None);
+        assert_eq!(Entry::parse(b"dData"), None);
+        assert_eq!(Entry::parse(b"x Data"), None);
+        assert_eq!(Entry::parse(b"d /etc"), None);
+        assert_eq!(Entry::parse(b"c Data/plugin.esp"), None);
+        assert_eq!(Entry::parse(b"c 0123 Data/plugin.esp"), None);
+    }
+
+    #[test]
+    fn write_entry_newline() {
+        let mut manifest = Vec::new();
+        let entry = Entry::Dir(PathBuf::from("Data\nmeshes"));
+        assert!(write_entry(&mut manifest, &entry).is_err());
+        assert!(manifest.is_empty());
+    }
+
+    #[test]
+    fn checksum_hex() {
+        let hex = checksum_to_hex(&CHECKSUM);
+        assert_eq!(hex.len(), 64);
+        assert!(hex.starts_with("00017f80feff"));
+        assert_eq!(checksum_from_hex(hex.as_bytes()), Some(CHECKSUM));
+        assert_eq!(checksum_from_hex(hex.to_uppercase().as_bytes()), Some(CHECKSUM));
+
+        let mut invalid = hex.into_bytes();
+        invalid[0] = b'+';
+        assert_eq!(checksum_from_hex(&invalid), None);
+        invalid[0] = b'g';
+        assert_eq!(checksum_from_hex(&invalid), None);
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Lock preventing more than one deployment to the same game directory at a time.
+//!
+//! Each game directory has a lock file in the user's runtime directory, named after the hash of its canonical path.
+//! The lock is an advisory file lock, so it is released when the process holding it exits, even if it crashes.
+
+use std::env;
+use std::fmt::Write as _;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+#[cfg(unix)]
+use rustix::process::getuid;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// An exclusive lock on deploying to a game directory, held until dropped.
+#[derive(Debug)]
+pub struct DeployLock {
+    _file: File,
+}
+
+impl DeployLock {
+    /// Locks the game directory at `game_path`, which must be canonical.
+    pub fn acquire(game_path: &Path) -> Result<Self, DeployLockError> {
+        let dir = lock_dir();
+        fs::create_dir_all(&dir).map_err(|source| DeployLockError::Mkdir { path: dir.clone(), source })?;
+        let path = dir.join(lock_file_name(game_path));
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .map_err(|source| DeployLockError::Open { path: path.clone(), source })?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let mut pid = String::new();
+                let _ = file.read_to_string(&mut pid);
+                return Err(DeployLockError::Locked(pid.trim().parse().ok()));
+            }
+            Err(TryLockError::Error(err)) => return Err(DeployLockError::Lock(err)),
+        }
+
+        // Record the PID of the lock holder, for the error message of other processes.
+        file.set_len(0)
+            .and_then(|()| file.rewind())
+            .and_then(|()| writeln!(file, "{}", process::id()))
+            .map_err(|source| DeployLockError::Write { path, source })?;
+        Ok(Self { _file: file })
+    }
+}
+
+/// Returns the PID of the process deploying to the game directory at `game_path`, which must be canonical,
+/// or `None` if the game directory isn't locked.
+pub fn lock_holder_pid(game_path: &Path) -> Result<Option<u32>, DeployLockError> {
+    let path = lock_dir().join(lock_file_name(game_path));
+    let mut file = match File::open(&path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(source) => return Err(DeployLockError::Open { path, source }),
+    };
+    match file.try_lock_shared() {
+        // Nobody holds the lock, it is released when `file` is dropped.
+        Ok(()) => Ok(None),
+        Err(TryLockError::WouldBlock) => {
+            let mut pid = String::new();
+            let _ = file.read_to_string(&mut pid);
+            Ok(pid.trim().parse().ok())
+        }
+        Err(TryLockError::Error(err)) => Err(DeployLockError::Lock(err)),
+    }
+}
+
+/// Returns the directory lock files are kept in.
+fn lock_dir() -> PathBuf {
+    env::var_os("XDG_RUNTIME_DIR").map_or_else(
+        || env::temp_dir().join(user_temp_dir_name()),
+        |runtime_dir| PathBuf::from(runtime_dir).join("mmm"),
+    )
+}
+
+/// The temporary directory is shared between users on Unix.
+#[cfg(unix)]
+fn user_temp_dir_name() -> String {
+    format!("mmm-{}", getuid().as_raw())
+}
+
+/// The temporary directory is per user on Windows.
+#[cfg(windows)]
+fn user_temp_dir_name() -> String {
+    "mmm".to_owned()
+}
+
+fn lock_file_name(game_path: &Path) -> String {
+    let hash = Sha256::digest(game_path.as_os_str().as_encoded_bytes());
+    let mut name = String::with_capacity(21);
+    for byte in &hash[..8] {
+        write!(name, "{byte:02x}").expect("writing to a String doesn't fail");
+    }
+    name.push_str(".lock");
+    name
+}
+
+fn lock_holder(pid: Option<u32>) -> String {
+    pid.map_or_else(|| "another process".to_owned(), |pid| format!("process {pid}"))
+}
+
+#[derive(Debug, Error)]
+pub enum DeployLockError {
+    #[error("failed to lock lock file")]
+    Lock(#[source] io::Error),
+    #[error("game directory is already deployed to by {}", lock_holder(*.0))]
+    Locked(Option<u32>),
+    #[error("failed to create lock directory '{path}'")]
+    Mkdir { path: PathBuf, source: io::Error },
+    #[error("failed to open lock file '{path}'")]
+    Open { path: PathBuf, source: io::Error },
+    #[error("failed to write to lock file '{path}'")]
+    Write { path: PathBuf, source: io::Error },
+}
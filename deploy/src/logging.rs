@@ -0,0 +1,53 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Status messages, logged as `tracing` events, either as text for people or as JSON for scripts.
+//!
+//! Deployment steps are logged within spans (`mount`, `staging`, `launch`, `unmount`), so that messages
+//! can be attributed to them.
+
+use std::io;
+
+use clap::ValueEnum;
+use tracing::Level;
+use tracing_subscriber::EnvFilter;
+
+#[derive(Copy, Clone, Debug, Default, ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable messages.
+    #[default]
+    Text,
+    /// One JSON object per message, including the spans it was logged in.
+    Json,
+}
+
+/// Sets the global subscriber, which writes messages to stderr in the specified format.
+///
+/// Messages below the info level are left out, unless configured otherwise with `RUST_LOG`.
+pub fn init(format: LogFormat) {
+    let filter = EnvFilter::builder()
+        .with_default_directive(Level::INFO.into())
+        .from_env()
+        .expect("invalid logging configuration");
+
+    let builder = tracing_subscriber::fmt()
+        .with_writer(io::stderr)
+        .with_env_filter(filter);
+    let result = match format {
+        LogFormat::Text => tracing::subscriber::set_global_default(builder.without_time().with_target(false).finish()),
+        LogFormat::Json => tracing::subscriber::set_global_default(builder.json().with_span_list(true).finish()),
+    };
+    result.expect("failed to set global logger");
+}
@@ -13,124 +13,943 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+// The overlay backend, and everything built around it, such as the daemon, is only available on Linux.
+// The link-based backends are also available on Windows.
+#[cfg(not(any(target_os = "linux", windows)))]
+compile_error!("mmm-deploy only supports Linux and Windows");
+
+#[cfg(target_os = "linux")]
 mod caps;
+mod config;
+#[cfg(target_os = "linux")]
+mod daemon;
+mod deployment;
+mod exclude;
+#[cfg(target_os = "linux")]
+mod fuse;
+#[cfg(target_os = "linux")]
+mod harvest;
+mod hooks;
 mod instance;
+mod link;
+mod lock;
+mod logging;
+#[cfg(target_os = "linux")]
 mod mount;
+#[cfg(target_os = "linux")]
 mod namespace;
+mod progress;
+mod purity;
+#[cfg(target_os = "linux")]
+mod reaper;
+#[cfg(target_os = "linux")]
+mod saves;
+#[cfg(target_os = "linux")]
+mod scope;
+mod signals;
+#[cfg(target_os = "linux")]
 mod staging;
+#[cfg(target_os = "linux")]
+mod stale;
+#[cfg(target_os = "linux")]
+mod status;
+#[cfg(target_os = "linux")]
+mod steam;
+#[cfg(target_os = "linux")]
+mod upper;
+mod validate;
+mod verify;
+mod walk;
+mod wine;
+mod wrapper;
 
-use std::io::Read;
-use std::os::unix::net::UnixStream;
+use std::env;
+use std::ffi::OsString;
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::process::ExitStatusExt;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, ExitStatus};
+use std::thread;
+use std::time::Duration;
 
-use anyhow::Context;
-use clap::Parser;
-use signal_hook::consts::SIGINT;
+use anyhow::{Context, bail};
+use clap::{Parser, ValueEnum};
+use tracing::{error, info, info_span, warn};
 
-use mmm_core::file_tree::display::{FileTreeDisplayKind, ModVecFileTreeDisplay};
-use mmm_core::file_tree::{FileTreeBuilder, new_tree};
+use mmm_core::file_tree::display::{FileTreeDisplayKind, ModVecFileList, ModVecFileTreeDisplay};
+use mmm_core::file_tree::{FileTree, ModVec};
+use mmm_core::instance::Instance;
 
-use crate::instance::DeployInstance;
-use crate::mount::{MountMethod, MountMethodChoice, OverlayMount};
-use crate::staging::build_staging_tree;
+use crate::config::Config;
+#[cfg(target_os = "linux")]
+use crate::daemon::DaemonConfig;
+use crate::deployment::{Backend, Deployment, StagingKind, UpperLayerKind};
+#[cfg(target_os = "linux")]
+use crate::harvest::{captured_files, harvest, harvest_destination};
+use crate::hooks::{HookError, HookStage, run_hooks};
+use crate::instance::{DeployInstance, list_profiles};
+use crate::link::LinkDeployment;
+use crate::lock::{DeployLock, DeployLockError, lock_holder_pid};
+use crate::logging::LogFormat;
+#[cfg(target_os = "linux")]
+use crate::mount::{MountMethod, MountMethodChoice};
+use crate::progress::Progress;
+use crate::purity::{VANILLA_MANIFEST_FILE, VanillaManifest, find_leftovers};
+#[cfg(target_os = "linux")]
+use crate::scope::Scope;
+use crate::signals::{Stopper, Termination};
+use crate::validate::{build_validated_file_tree, check_destination};
+use crate::verify::{FILE_MAP_FILE, FileMap};
+use crate::wine::{Runner, find_proton};
+use crate::wrapper::{Wrapper, wrap};
 
 #[derive(Parser)]
 struct Args {
-    #[arg(value_enum, short, long, required = false, default_value_t)]
-    mount_method: MountMethodChoice,
+    #[arg(value_enum, short, long, default_value_t)]
+    backend: Backend,
+    /// How to get permission to mount the overlay [default: auto]
+    #[cfg(target_os = "linux")]
+    #[arg(value_enum, short, long)]
+    mount_method: Option<MountMethodChoice>,
+    /// Where to store the tree of links to the mod files mounted by the overlay backend [default: auto]
+    #[arg(value_enum, long)]
+    staging: Option<StagingKind>,
+    /// Leave the staging tree in place when exiting, and print its path
+    #[arg(long, conflicts_with = "daemon")]
+    keep_staging: bool,
+    /// Capture files written by the game in a writable upper layer
+    #[arg(value_enum, short, long)]
+    upper: Option<UpperLayerKind>,
+    /// Mod to offer moving files captured in the upper layer into, instead of the overwrite directory
+    #[arg(long, requires = "upper")]
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code, reason = "--upper is only supported on Linux"))]
+    harvest_into: Option<String>,
+    /// Run the executable as a tool, such as a patcher, and move the files it writes into the specified mod
+    #[arg(long, value_name = "MOD", requires = "exec", conflicts_with_all = ["harvest_into", "persist", "daemon"])]
+    run_tool: Option<String>,
     instance_path: PathBuf,
-    game_path: PathBuf,
+    /// Game directory, if the instance doesn't specify one, or to override it
+    game_path: Option<PathBuf>,
     #[arg(short = 'x', long)]
     exec: Option<PathBuf>,
+    /// Launch the game through Steam, overriding the app ID stored in the instance
+    #[arg(long, conflicts_with = "exec")]
+    steam_appid: Option<u32>,
+    /// Run the executable with the specified Proton version, by name or path
+    #[arg(long, requires = "prefix", conflicts_with = "wine")]
+    proton: Option<String>,
+    /// Run the executable with Wine
+    #[arg(long)]
+    wine: bool,
+    /// Wine prefix, or Proton compatibility data directory, to run the executable in
+    #[arg(long)]
+    prefix: Option<PathBuf>,
+    /// Run the executable in a transient systemd user scope, and wait for every process in it to exit
+    #[arg(long)]
+    scope: bool,
+    /// Run the executable through a wrapper command, instead of those stored in the instance
+    #[arg(long, value_name = "COMMAND", value_parser = Wrapper::parse)]
+    wrap: Vec<Wrapper>,
     #[arg(short, long)]
     profile: Option<String>,
+    /// Enable a mod for this run only
+    #[arg(long, value_name = "MOD")]
+    enable: Vec<String>,
+    /// Disable a mod for this run only
+    #[arg(long, value_name = "MOD")]
+    disable: Vec<String>,
+    /// Command to run after deploying, before launching the game, instead of those stored in the instance
+    #[arg(long, value_name = "COMMAND")]
+    pre_launch: Vec<String>,
+    /// Command to run after the game exits, before removing the deployment, instead of those stored in the instance
+    #[arg(long, value_name = "COMMAND")]
+    post_exit: Vec<String>,
+    /// Set an environment variable for the executable
+    #[arg(long = "env", value_name = "KEY=VALUE", value_parser = parse_env_var)]
+    env: Vec<(OsString, OsString)>,
+    /// Arguments to pass to the executable, or the command to run with --attach
+    #[arg(last = true, value_name = "ARGS")]
+    exec_args: Vec<OsString>,
+    /// Glob pattern of mod files not to deploy, instead of those stored in the instance
+    #[arg(long, value_name = "PATTERN")]
+    exclude: Vec<String>,
+    /// Deploy every mod file, ignoring the exclusion patterns stored in the instance
+    #[arg(long, conflicts_with = "exclude")]
+    no_exclude: bool,
+    /// Remove the deployment after the specified time if there is no game to wait for, such as 90s, 15m or 2h
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration, conflicts_with = "daemon")]
+    timeout: Option<Duration>,
+    /// Let the game use its own saves, instead of mounting the profile's saves over its save directory
+    #[arg(long)]
+    shared_saves: bool,
+    /// Deploy without the enabled mods whose directory is missing, instead of failing
+    #[arg(long)]
+    skip_missing: bool,
+    /// Format of the list of deployed files
+    #[arg(value_enum, short, long, default_value_t)]
+    output: OutputFormat,
+    /// Format of the status messages written to stderr
+    #[arg(value_enum, long, default_value_t)]
+    log_format: LogFormat,
+    /// Start a shell in the game directory instead of the game, and remove the deployment when it exits
+    #[arg(long, conflicts_with_all = ["exec", "steam_appid", "proton", "wine", "wrap", "scope", "daemon"])]
+    shell: bool,
+    /// Leave the linked files in place when exiting, instead of removing them
+    #[arg(long)]
+    persist: bool,
+    /// List the profiles of the instance, and exit
+    #[arg(long, conflicts_with_all = ["game_path", "exec", "profile", "persist", "daemon"])]
+    list_profiles: bool,
+    /// Before deploying, report files in the game directory that seem to be left over from other mod managers
+    /// or previous deployments
+    #[arg(long)]
+    check_purity: bool,
+    /// Record the files in the game directory as the vanilla files used by --check-purity, and exit
+    #[arg(long, conflicts_with_all = ["exec", "persist", "purge", "verify", "attach", "daemon", "list_profiles"])]
+    record_vanilla: bool,
+    /// Remove the files left in place by a previous deployment, and exit
+    #[arg(long, conflicts_with_all = ["game_path", "exec", "persist"])]
+    purge: bool,
+    /// Check that the files of a running deployment are still in place, and exit
+    #[arg(long, conflicts_with_all = ["game_path", "exec", "persist", "purge", "daemon"])]
+    verify: bool,
+    /// Run the command given after `--`, or a shell, in the game directory of a running deployment, and exit
+    #[arg(long, conflicts_with_all = ["exec", "steam_appid", "shell", "persist", "purge", "verify", "daemon"])]
+    attach: bool,
+    /// Deploy even if another deployment to the game directory is running
+    #[arg(long)]
+    force: bool,
+    /// Instead of deploying right away, wait for commands on a Unix socket at the specified path
+    #[arg(long, conflicts_with_all = ["exec", "steam_appid", "persist", "purge"])]
+    daemon: Option<PathBuf>,
+    /// Which exit status to exit with
+    #[arg(value_enum, long, default_value_t, conflicts_with = "daemon")]
+    exit_status: ExitStatusSource,
+}
+
+#[derive(Copy, Clone, Debug, Default, ValueEnum)]
+enum ExitStatusSource {
+    /// Whether deploying and removing the mod files succeeded.
+    #[default]
+    Mmm,
+    /// The exit status of the game, if deploying and removing the mod files succeeded.
+    ///
+    /// If the game was killed by a signal, the status is 128 plus the signal number, like in shells.
+    Game,
+}
+
+#[derive(Copy, Clone, Debug, Default, ValueEnum)]
+enum OutputFormat {
+    /// Tree of the files provided by more than one mod.
+    #[default]
+    Tree,
+    /// JSON array of every deployed file, with the mods that provide it.
+    Json,
 }
 
 fn main() -> anyhow::Result<()> {
+    #[cfg(target_os = "linux")]
     caps::init();
     let args = Args::parse();
-    let mount_method = args.mount_method.to_mount_method();
-    if matches!(mount_method, MountMethod::UserNamespace) && args.exec.is_none() {
-        eprintln!("--exec is required when using user namespaces");
-        std::process::exit(1);
-    }
-
-    let mods = DeployInstance::open(&args.instance_path, args.profile.as_deref()).context("failed to open instance")?;
-    let mut tree = new_tree();
-    FileTreeBuilder::new()
-        .iter_mods(&mut tree, &mods)
-        .context("failed to build tree of mod files")?;
-    ptree::print_tree(&ModVecFileTreeDisplay::new(
-        &tree,
-        &mods,
-        FileTreeDisplayKind::Conflicts,
-    ))
-    .context("failed to display file tree")?;
+    logging::init(args.log_format);
+    if args.persist && matches!(args.backend, Backend::Overlay) {
+        bail!("--persist is not supported by the overlay backend");
+    }
+    if args.upper.is_some() && !matches!(args.backend, Backend::Overlay) {
+        bail!("--upper is only supported by the overlay backend");
+    }
+    if args.staging.is_some() && !matches!(args.backend, Backend::Overlay) {
+        bail!("--staging is only supported by the overlay backend");
+    }
+    if args.keep_staging && !matches!(args.backend, Backend::Overlay) {
+        bail!("--keep-staging is only supported by the overlay backend");
+    }
+    if args.run_tool.is_some() && !matches!(args.backend, Backend::Overlay) {
+        bail!("--run-tool is only supported by the overlay backend");
+    }
+
+    if args.list_profiles {
+        return print_profiles(&args.instance_path);
+    }
+    let config = Config::load()?;
+    #[cfg(target_os = "linux")]
+    mount::configure(config.mount_options.clone());
+
+    let mut mods =
+        DeployInstance::open(&args.instance_path, args.profile.as_deref()).context("failed to open instance")?;
+    if args.purge {
+        return purge(&mods);
+    }
+    if args.verify {
+        return verify(&mods);
+    }
+    if args.attach {
+        return attach(&args, &mods);
+    }
+    if args.record_vanilla {
+        return record_vanilla(&args, &mods);
+    }
+
+    let mod_overrides: Vec<(String, bool)> = args
+        .enable
+        .iter()
+        .map(|name| (name.clone(), true))
+        .chain(args.disable.iter().map(|name| (name.clone(), false)))
+        .collect();
+    mods.override_mods(&mod_overrides)?;
+    if args.shared_saves {
+        mods.share_saves();
+    }
+    let exclusions = if args.no_exclude || !args.exclude.is_empty() {
+        Some(args.exclude.clone())
+    } else if mods.settings().staging_exclusions.is_none() {
+        config.exclude.clone()
+    } else {
+        None
+    };
+    if let Some(exclusions) = &exclusions {
+        mods.override_exclusions(exclusions.iter().map(String::as_str))
+            .context("invalid exclusion pattern")?;
+    }
 
-    if matches!(mount_method, MountMethod::UserNamespace) {
+    #[cfg(target_os = "linux")]
+    if let Some(tool_mod) = &args.run_tool {
+        harvest_destination(&mods, Some(tool_mod)).context("invalid tool output mod")?;
+    }
+
+    let progress = Progress::new(args.log_format);
+    let tree = build_validated_file_tree(&mut mods, args.skip_missing, &progress)?;
+    match args.output {
+        OutputFormat::Tree => ptree::print_tree(&ModVecFileTreeDisplay::new(
+            &tree,
+            &mods,
+            FileTreeDisplayKind::Conflicts,
+        ))
+        .context("failed to display file tree")?,
+        OutputFormat::Json => {
+            let mut stdout = io::stdout().lock();
+            serde_json::to_writer(
+                &mut stdout,
+                &ModVecFileList::new(&tree, &mods, FileTreeDisplayKind::All),
+            )
+            .context("failed to write file list")?;
+            writeln!(stdout).context("failed to write file list")?;
+        }
+    }
+
+    let launch = launch(&args, &config, &mods)?;
+    #[cfg(target_os = "linux")]
+    if matches!(args.backend, Backend::Overlay)
+        && matches!(
+            args.mount_method
+                .or(config.mount_method)
+                .unwrap_or_default()
+                .to_mount_method(),
+            MountMethod::UserNamespace
+        )
+    {
+        if args.daemon.is_some() {
+            bail!("--daemon is not supported when using user namespaces");
+        }
+        match launch {
+            Some(Launch::Exec { .. } | Launch::Shell) => {}
+            Some(Launch::Steam(_)) => {
+                bail!("launching through Steam is not supported when using user namespaces");
+            }
+            None => {
+                bail!("--exec or --shell is required when using user namespaces");
+            }
+        }
         namespace::enter_namespace().context("failed to enter user namespace")?;
     }
 
-    let staging_dir = build_staging_tree(&tree, &mods).context("failed to stage mod files")?;
-    println!("Built staging tree at '{}'", staging_dir.path().display());
+    let game_path = canonicalize_game_path(&args, &mods)?;
+    check_destination(&game_path, &mods).context("refusing to deploy to the game path")?;
+    // Held until the deployment is removed.
+    #[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+    let lock = match DeployLock::acquire(&game_path) {
+        Ok(lock) => Some(lock),
+        Err(err @ DeployLockError::Locked(_)) if args.force => {
+            warn!("Ignoring lock: {err}");
+            None
+        }
+        Err(err) => return Err(err).context("failed to lock game directory"),
+    };
+    // Overlays can only be left over by sessions that no longer hold the lock.
+    #[cfg(target_os = "linux")]
+    let reuse_overlay =
+        matches!(args.backend, Backend::Overlay) && lock.is_some() && stale::handle_stale_overlays(&game_path)?;
+    #[cfg(not(target_os = "linux"))]
+    let reuse_overlay = false;
+    if args.check_purity && !reuse_overlay {
+        check_purity(&game_path, &tree, &mods)?;
+    }
+    #[cfg(not(target_os = "linux"))]
+    if args.daemon.is_some() {
+        bail!("--daemon is only supported on Linux");
+    }
+    #[cfg(target_os = "linux")]
+    if let Some(socket_path) = &args.daemon {
+        if reuse_overlay {
+            bail!("reusing an overlay is not supported with --daemon");
+        }
+        let daemon_config = DaemonConfig {
+            instance_path: args.instance_path.clone(),
+            game_path,
+            backend: args.backend,
+            staging: args.staging.or(config.staging).unwrap_or_default(),
+            upper: args.upper,
+            default_profile: args.profile.clone(),
+            mod_overrides,
+            exclusions,
+            skip_missing: args.skip_missing,
+            shared_saves: args.shared_saves,
+        };
+        return daemon::serve(socket_path, daemon_config);
+    }
+
+    let termination = Termination::register().context("failed to register signal handlers")?;
+    if reuse_overlay {
+        if args.run_tool.is_some() {
+            bail!("tool output can't be captured by a reused overlay");
+        }
+        info!("Reusing the mounted overlay, it will be left in place");
+        let game_status = run_session(&args, &mods, launch.as_ref(), &game_path, "exit", &termination)?;
+        exit_with_game_status(&args, game_status);
+        return Ok(());
+    }
+
+    let staging = match args.staging.or(config.staging).unwrap_or_default() {
+        // In-memory staging trees disappear on exit, and mod directories may be mounted directly.
+        StagingKind::Auto | StagingKind::Tmpfs if args.keep_staging => StagingKind::Kept,
+        staging => staging,
+    };
+    // Tools need a writable game directory to write their output to.
+    let upper = args
+        .upper
+        .or_else(|| args.run_tool.is_some().then_some(UpperLayerKind::Tmpfs));
+    #[cfg_attr(not(target_os = "linux"), allow(unused_mut))]
+    let mut deployment = Deployment::create(args.backend, staging, upper, &tree, &mods, &game_path, &progress)?;
+    #[cfg(target_os = "linux")]
+    let status_service = status::publish(&mods, &game_path, &termination)
+        .inspect_err(|err| warn!("Failed to publish deployment status on D-Bus: {err}"))
+        .ok();
+    let session = run_session(
+        &args,
+        &mods,
+        launch.as_ref(),
+        &game_path,
+        deployment.removal_action(),
+        &termination,
+    );
+    #[cfg(target_os = "linux")]
+    drop(status_service);
+    if args.persist {
+        let game_status = session?;
+        info!("Leaving mod files in place, run with --purge to remove them");
+        exit_with_game_status(&args, game_status);
+        return Ok(());
+    }
+
+    let kept_staging = args
+        .keep_staging
+        .then(|| deployment.staging_path().map(Path::to_owned))
+        .flatten();
+    #[cfg(target_os = "linux")]
+    let upper = deployment.take_upper();
+    let removed = deployment.remove();
+    if let Some(path) = kept_staging {
+        info!("Kept staging tree at '{}'", path.display());
+    }
+    let game_status = session?;
+    removed?;
+    #[cfg(target_os = "linux")]
+    if let Some(upper) = upper {
+        offer_harvest(&args, &mods, &upper.upper_dir(None), Path::new(""))?;
+        for (i, target) in mods.mount_targets().iter().enumerate() {
+            offer_harvest(&args, &mods, &upper.upper_dir(Some(i)), target.source().as_std_path())?;
+        }
+        upper.close().context("failed to unmount upper layer tmpfs")?;
+    }
+    exit_with_game_status(&args, game_status);
+    Ok(())
+}
+
+/// Exits with the game's exit status, if it is known and was requested with `--exit-status game`.
+fn exit_with_game_status(args: &Args, game_status: Option<i32>) {
+    if let ExitStatusSource::Game = args.exit_status
+        && let Some(status) = game_status
+        && status != 0
+    {
+        std::process::exit(status);
+    }
+}
+
+/// Runs the pre-launch hooks, then the game, or waits for the user if there is no game to launch,
+/// and then runs the post-exit hooks.
+///
+/// Nothing is run if termination was requested while the deployment was being created.
+/// Returns the exit status of the game, if it was run directly.
+fn run_session(
+    args: &Args,
+    mods: &DeployInstance,
+    launch: Option<&Launch>,
+    game_path: &Path,
+    undo_action: &str,
+    termination: &Termination,
+) -> anyhow::Result<Option<i32>> {
+    if termination.requested() {
+        return Ok(None);
+    }
+    run_stage_hooks(args, mods, game_path, HookStage::PreLaunch).context("pre-launch hook failed")?;
+    let result = if args.persist {
+        launch.map_or(Ok(None), |launch| launch_and_wait(launch, game_path, termination))
+    } else {
+        run_game_or_wait(launch, game_path, undo_action, args.timeout, termination)
+    };
+    if let Err(err) = run_stage_hooks(args, mods, game_path, HookStage::PostExit) {
+        error!("Post-exit hook failed: {:#}", anyhow::Error::from(err));
+    }
+    result
+}
+
+/// Runs the hooks of the specified stage given on the command line, or those stored in the instance if there are none.
+fn run_stage_hooks(args: &Args, mods: &DeployInstance, game_path: &Path, stage: HookStage) -> Result<(), HookError> {
+    let (cli_hooks, instance_hooks) = match stage {
+        HookStage::PreLaunch => (&args.pre_launch, &mods.settings().pre_launch_hooks),
+        HookStage::PostExit => (&args.post_exit, &mods.settings().post_exit_hooks),
+    };
+    if cli_hooks.is_empty() {
+        run_hooks(stage, instance_hooks, mods, game_path)
+    } else {
+        run_hooks(stage, cli_hooks, mods, game_path)
+    }
+}
+
+/// Prints the name, display name and number of enabled mods of each profile, one per line, separated by tabs.
+fn print_profiles(instance_path: &Path) -> anyhow::Result<()> {
+    let profiles = list_profiles(instance_path).context("failed to open instance")?;
+    for profile in profiles {
+        let default_marker = if profile.is_default { "\t(default)" } else { "" };
+        println!(
+            "{}\t{}\t{}/{} mods enabled{default_marker}",
+            profile.name, profile.display_name, profile.enabled_mods, profile.total_mods
+        );
+    }
+    Ok(())
+}
+
+/// Records the files in the game directory as the vanilla files of the game.
+fn record_vanilla(args: &Args, mods: &DeployInstance) -> anyhow::Result<()> {
+    let game_path = canonicalize_game_path(args, mods)?;
+    if lock_holder_pid(&game_path)
+        .context("failed to check whether the game directory is deployed to")?
+        .is_some()
+    {
+        bail!("mod files are deployed to '{}', remove them first", game_path.display());
+    }
+    let manifest = VanillaManifest::record(&game_path).context("failed to list files in the game directory")?;
+    let manifest_path = mods.dir().join(VANILLA_MANIFEST_FILE);
+    manifest
+        .write(&manifest_path)
+        .with_context(|| format!("failed to write vanilla manifest '{}'", manifest_path.display()))?;
+    info!("Recorded {} vanilla files", manifest.file_count());
+    Ok(())
+}
+
+/// Reports the files in the game directory that seem to be left over from other mod managers or previous deployments.
+fn check_purity(game_path: &Path, tree: &FileTree<ModVec>, mods: &DeployInstance) -> anyhow::Result<()> {
+    let manifest = VanillaManifest::read(&mods.dir().join(VANILLA_MANIFEST_FILE))?;
+    if manifest.is_none() {
+        info!("No vanilla files were recorded, only looking for known leftovers (see --record-vanilla)");
+    }
+    let leftovers = find_leftovers(game_path, tree, mods, manifest.as_ref())
+        .context("failed to check the game directory for leftover files")?;
+    if leftovers.is_empty() {
+        info!("No leftover files found in the game directory");
+        return Ok(());
+    }
+    warn!("The game directory contains files that don't seem to belong to the game:");
+    for leftover in &leftovers {
+        warn!("  {leftover}");
+    }
+    Ok(())
+}
+
+fn purge(mods: &DeployInstance) -> anyhow::Result<()> {
+    let Some(deployment) = LinkDeployment::open(mods).context("failed to read deployment manifest")? else {
+        info!("Nothing to purge");
+        return Ok(());
+    };
+    let target = deployment.target().to_owned();
+    deployment
+        .remove()
+        .with_context(|| format!("failed to purge deployment from '{}'", target.display()))?;
+    info!("Purged deployment from '{}'", target.display());
+    Ok(())
+}
+
+fn verify(mods: &DeployInstance) -> anyhow::Result<()> {
+    let Some(file_map) = FileMap::read(&mods.dir().join(FILE_MAP_FILE)).context("failed to read file map")? else {
+        info!("Nothing is deployed");
+        return Ok(());
+    };
+    let discrepancies = file_map.verify();
+    if discrepancies.is_empty() {
+        info!("All {} deployed files are in place", file_map.file_count());
+        return Ok(());
+    }
+    for discrepancy in &discrepancies {
+        error!("  {discrepancy}");
+    }
+    bail!(
+        "{} of {} deployed files are not in place",
+        discrepancies.len(),
+        file_map.file_count()
+    );
+}
+
+/// Runs the command given on the command line, or a shell, in the game directory, with the same view of it
+/// as the process deploying to it, and exits with its exit status.
+fn attach(args: &Args, mods: &DeployInstance) -> anyhow::Result<()> {
+    let game_path = canonicalize_game_path(args, mods)?;
+    let pid = lock_holder_pid(&game_path)
+        .context("failed to check whether the game directory is deployed to")?
+        .with_context(|| format!("nothing is deployed to '{}'", game_path.display()))?;
+    // Only overlays are mounted in separate namespaces, link deployments are visible from everywhere.
+    #[cfg(target_os = "linux")]
+    namespace::enter_namespaces_of(pid).with_context(|| format!("failed to attach to process {pid}"))?;
+
+    let shell = user_shell();
+    let (program, program_args) = args.exec_args.split_first().unwrap_or((&shell, &[]));
+    let status = Command::new(program)
+        .args(program_args)
+        .current_dir(&game_path)
+        .status()
+        .with_context(|| format!("failed to run '{}'", program.display()))?;
+    std::process::exit(exit_code(status));
+}
+
+/// Returns the exit code of a process, or, if it was killed by a signal, 128 plus the signal number, like in shells.
+fn exit_code(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    if let Some(signal) = status.signal() {
+        return 128 + signal;
+    }
+    status.code().unwrap_or_default()
+}
+
+/// Offers moving the files captured in `upper_dir` into the harvest destination, under `subdir`.
+///
+/// The output of tools run with `--run-tool` is moved into their mod without asking.
+#[cfg(target_os = "linux")]
+fn offer_harvest(args: &Args, mods: &DeployInstance, upper_dir: &Path, subdir: &Path) -> anyhow::Result<()> {
+    let files = captured_files(upper_dir).context("failed to list files in the upper layer")?;
+    if files.is_empty() {
+        return Ok(());
+    }
+    let destination = harvest_destination(mods, args.run_tool.as_deref().or(args.harvest_into.as_deref()))
+        .context("invalid harvest destination")?
+        .join(subdir);
+
+    let writer = if args.run_tool.is_some() { "tool" } else { "game" };
+    info!("The {writer} created or modified the following files:");
+    for file in &files {
+        info!("  {}", subdir.join(file).display());
+    }
+    if args.run_tool.is_some() {
+        harvest(upper_dir, &files, &destination).context("failed to move tool output")?;
+        info!("Moved {} files into '{}'", files.len(), destination.display());
+        return Ok(());
+    }
+    print!("Move them into '{}'? [y/N] ", destination.display());
+    io::stdout().flush().context("failed to write to stdout")?;
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .context("failed to read from stdin")?;
+    if !answer.trim().eq_ignore_ascii_case("y") {
+        return Ok(());
+    }
 
+    harvest(upper_dir, &files, &destination).context("failed to move captured files")?;
+    info!("Moved {} files into '{}'", files.len(), destination.display());
+    Ok(())
+}
+
+/// Returns the canonical game path given on the command line, or the one stored in the instance.
+fn canonicalize_game_path(args: &Args, mods: &DeployInstance) -> anyhow::Result<PathBuf> {
     let game_path = args
         .game_path
+        .as_deref()
+        .or_else(|| {
+            mods.settings()
+                .game_path
+                .as_deref()
+                .map(|path| Path::new(path.as_str()))
+        })
+        .context("no game path was specified, and the instance doesn't have one")?;
+    game_path
         .canonicalize()
-        .with_context(|| format!("failed to canonicalize game path '{}'", args.game_path.display()))?;
-    let overlay_mount = OverlayMount::new(staging_dir.path(), &game_path).with_context(|| {
-        format!(
-            "failed to mount overlay '{}' at game path '{}'",
-            staging_dir.path().display(),
-            game_path.display()
-        )
-    })?;
-    println!("Mounted overlay over {}", overlay_mount.path().display());
+        .with_context(|| format!("failed to canonicalize game path '{}'", game_path.display()))
+}
+
+/// How the game is started.
+enum Launch {
+    Exec {
+        exe: PathBuf,
+        runner: Option<Runner>,
+        args: Vec<OsString>,
+        env: Vec<(OsString, OsString)>,
+        wrappers: Vec<Wrapper>,
+        /// Whether to run the game in a systemd scope.
+        #[cfg(target_os = "linux")]
+        scope: bool,
+    },
+    /// An interactive shell, for running modding tools and inspecting the deployed files.
+    Shell,
+    Steam(u32),
+}
+
+/// Parses a duration given as a number of seconds, or as a number followed by `s`, `m` or `h`.
+fn parse_duration(duration: &str) -> Result<Duration, String> {
+    let (number, unit_secs) = match duration.char_indices().last() {
+        Some((i, 's')) => (&duration[..i], 1),
+        Some((i, 'm')) => (&duration[..i], 60),
+        Some((i, 'h')) => (&duration[..i], 60 * 60),
+        _ => (duration, 1),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(unit_secs))
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("expected a duration such as 90, 90s, 15m or 2h, found '{duration}'"))
+}
 
-    if let Some(mut exe) = args.exec {
-        if exe.is_relative() {
-            exe = args.game_path.join(exe);
+fn parse_env_var(var: &str) -> Result<(OsString, OsString), String> {
+    match var.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.into(), value.into())),
+        _ => Err(format!("expected KEY=VALUE, found '{var}'")),
+    }
+}
+
+/// Returns how the game should be started, preferring the command line over the instance settings,
+/// the instance settings over the configuration file, and an executable over Steam.
+fn launch(args: &Args, config: &Config, mods: &DeployInstance) -> anyhow::Result<Option<Launch>> {
+    if args.shell {
+        return Ok(Some(Launch::Shell));
+    }
+    let settings = mods.settings();
+    let exe = args.exec.clone().or_else(|| {
+        args.steam_appid
+            .is_none()
+            .then(|| settings.executable.as_deref().map(PathBuf::from))
+            .flatten()
+    });
+
+    if let Some(exe) = exe {
+        let runner = if let Some(proton) = &args.proton {
+            Some(Runner::Proton {
+                dir: find_proton(proton)?,
+                compat_data: args.prefix.clone().expect("--prefix is required by --proton"),
+            })
+        } else if args.wine {
+            Some(Runner::Wine { prefix: args.prefix.clone() })
+        } else {
+            None
+        };
+        let wrappers = if !args.wrap.is_empty() {
+            args.wrap.clone()
+        } else if !settings.wrappers.is_empty() {
+            settings
+                .wrappers
+                .iter()
+                .map(|command| Wrapper::parse(command))
+                .collect::<Result<_, _>>()
+                .context("invalid wrapper command stored in the instance")?
+        } else {
+            config.wrappers.clone()
+        };
+        #[cfg(not(target_os = "linux"))]
+        if args.scope {
+            bail!("--scope is only supported on Linux");
         }
-        run_game_and_wait(&exe).context("failed to run game and wait for it to quit")?;
+        Ok(Some(Launch::Exec {
+            exe,
+            runner,
+            args: args.exec_args.clone(),
+            env: args.env.clone(),
+            wrappers,
+            #[cfg(target_os = "linux")]
+            scope: args.scope,
+        }))
+    } else if args.proton.is_some() || args.wine {
+        bail!("--proton and --wine require an executable");
+    } else if !args.env.is_empty() || !args.wrap.is_empty() || args.scope || !args.exec_args.is_empty() {
+        bail!("--env, --wrap, --scope and executable arguments require an executable");
     } else {
-        println!("\nPress Control + C to unmount the overlay");
-        wait_for_sigterm();
+        Ok(args.steam_appid.or(settings.steam_app_id).map(Launch::Steam))
     }
+}
+
+/// Launches the game and waits for it to exit, returning its exit status, if it is known.
+fn launch_and_wait(launch: &Launch, game_path: &Path, termination: &Termination) -> anyhow::Result<Option<i32>> {
+    let _span = info_span!("launch").entered();
+    match launch {
+        // Relative paths are relative to the game directory, absolute paths replace it.
+        Launch::Exec {
+            exe,
+            runner,
+            args,
+            env,
+            wrappers,
+            #[cfg(target_os = "linux")]
+            scope,
+        } => run_game_and_wait(
+            &game_path.join(exe),
+            runner.as_ref(),
+            args,
+            env,
+            wrappers,
+            #[cfg(target_os = "linux")]
+            *scope,
+            termination,
+        )
+        .context("failed to run game and wait for it to quit"),
+        Launch::Shell => {
+            run_shell(game_path)?;
+            Ok(None)
+        }
+        #[cfg(not(target_os = "linux"))]
+        Launch::Steam(_) => bail!("launching through Steam is only supported on Linux"),
+        #[cfg(target_os = "linux")]
+        Launch::Steam(app_id) => {
+            steam::launch_and_wait(*app_id, game_path, termination).context("failed to launch game through Steam")?;
+            Ok(None)
+        }
+    }
+}
+
+/// Returns the path of the user's shell.
+#[cfg(unix)]
+fn user_shell() -> OsString {
+    env::var_os("SHELL").unwrap_or_else(|| "/bin/sh".into())
+}
+
+/// Returns the path of the command interpreter.
+#[cfg(windows)]
+fn user_shell() -> OsString {
+    env::var_os("COMSPEC").unwrap_or_else(|| "cmd.exe".into())
+}
 
-    overlay_mount.unmount().context("failed to unmount overlay")?;
-    staging_dir.unmount().context("failed to unmount staging tmpfs")?;
-    println!("\nUnmount successful");
+/// Runs the user's shell in the game directory, and waits for it to exit.
+fn run_shell(game_path: &Path) -> anyhow::Result<()> {
+    let shell = user_shell();
+    info!(
+        "Starting a shell in '{}', exit it when you're done",
+        game_path.display()
+    );
+    // The shell handles the signals sent from the terminal, so it is waited on even if termination is requested.
+    Command::new(&shell)
+        .current_dir(game_path)
+        .status()
+        .with_context(|| format!("failed to run shell '{}'", shell.display()))?;
     Ok(())
 }
 
-fn run_game_and_wait(exe: &Path) -> anyhow::Result<()> {
-    let mut game = Command::new(exe)
-        .current_dir(exe.parent().expect("executable has parent directory"))
+fn run_game_or_wait(
+    launch: Option<&Launch>,
+    game_path: &Path,
+    undo_action: &str,
+    timeout: Option<Duration>,
+    termination: &Termination,
+) -> anyhow::Result<Option<i32>> {
+    if let Some(launch) = launch {
+        launch_and_wait(launch, game_path, termination)
+    } else {
+        match timeout {
+            Some(timeout) => info!(
+                "Press Control + C to {undo_action}, it will be done automatically in {} seconds",
+                timeout.as_secs()
+            ),
+            None => info!("Press Control + C to {undo_action}"),
+        }
+        if !termination.wait(timeout) {
+            info!("Timed out");
+        }
+        Ok(None)
+    }
+}
+
+/// Runs the game and waits for it, and the processes it started, to exit, returning its exit status.
+///
+/// If termination is requested, they are stopped instead.
+/// Processes that outlive the game are only waited for on Linux.
+fn run_game_and_wait(
+    exe: &Path,
+    runner: Option<&Runner>,
+    args: &[OsString],
+    env: &[(OsString, OsString)],
+    wrappers: &[Wrapper],
+    #[cfg(target_os = "linux")] scope: bool,
+    termination: &Termination,
+) -> anyhow::Result<Option<i32>> {
+    #[cfg(target_os = "linux")]
+    reaper::become_subreaper().context("failed to become a child subreaper")?;
+    #[cfg(target_os = "linux")]
+    let existing_children = reaper::children().context("failed to list child processes")?;
+    // Connecting starts a thread, so this must happen after the user namespace was entered,
+    // which requires the process to be single-threaded.
+    #[cfg(target_os = "linux")]
+    let scope = scope
+        .then(Scope::new)
+        .transpose()
+        .context("failed to connect to the systemd user manager")?;
+
+    let mut command = runner.map_or_else(|| Command::new(exe), |runner| runner.command(exe));
+    command
+        .args(args)
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .current_dir(exe.parent().expect("executable has parent directory"));
+    let mut game = wrap(command, wrappers)
         .spawn()
         .with_context(|| format!("failed to run executable '{}'", exe.display()))?;
+    #[cfg(target_os = "linux")]
+    if let Some(scope) = &scope
+        && let Err(err) = scope.start(game.id())
+    {
+        warn!("Failed to move the game into a systemd scope: {err}");
+    }
 
     let exe_name = exe.file_name().expect("executable has file name").display();
-    println!("\nWaiting for {} to exit", exe_name);
+    info!("Waiting for {} to exit", exe_name);
 
-    let exit_status = game.wait().context("waitpid failed")?;
-    match exit_status.code() {
-        Some(code) => {
-            if code != 0 {
-                eprintln!("{} exited with code {}", exe_name, code);
-            }
+    let mut stopper = Stopper::default();
+    let exit_status = loop {
+        if let Some(exit_status) = game.try_wait().context("waitpid failed")? {
+            break exit_status;
+        }
+        if termination.requested() {
+            stopper.stop(i32::try_from(game.id()).ok());
         }
-        None => eprintln!("{} was terminated by a signal", exe_name),
+        thread::sleep(signals::POLL_INTERVAL);
+    };
+    match exit_status.code() {
+        Some(0) => {}
+        Some(code) => warn!("{} exited with code {}", exe_name, code),
+        None => warn!("{} was terminated by a signal", exe_name),
     }
-    Ok(())
-}
-
-fn wait_for_sigterm() {
-    let (mut read, write) = UnixStream::pair().expect("create socket pair");
-    let handler = signal_hook::low_level::pipe::register(SIGINT, write).expect("register SIGTERM handler");
 
-    let mut buff = [0];
-    read.read_exact(&mut buff).expect("read from the self-pipe");
-
-    signal_hook::low_level::unregister(handler);
+    #[cfg(target_os = "linux")]
+    if let Some(scope) = scope {
+        scope
+            .wait(termination)
+            .context("failed to wait for the processes in the game's scope")?;
+    }
+    #[cfg(target_os = "linux")]
+    reaper::wait_for_orphans(&existing_children, termination)
+        .context("failed to wait for processes started by the game")?;
+    Ok(Some(exit_code(exit_status)))
 }
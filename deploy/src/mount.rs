@@ -13,52 +13,306 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+use std::ffi::CString;
+use std::fs;
 use std::io;
-use std::os::fd::OwnedFd;
+use std::os::fd::{AsRawFd, OwnedFd};
+use std::os::unix::ffi::OsStrExt;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 use rustix::fs::{Mode, OFlags, fstat, open};
 use rustix::io::Errno;
 use rustix::mount::{
-    FsMountFlags, FsOpenFlags, MountAttrFlags, MoveMountFlags, UnmountFlags, fsconfig_create, fsconfig_set_fd,
-    fsconfig_set_string, fsmount, fsopen, move_mount, unmount,
+    FsMountFlags, FsOpenFlags, MountAttrFlags, MountFlags, MoveMountFlags, OpenTreeFlags, UnmountFlags,
+    fsconfig_create, fsconfig_set_fd, fsconfig_set_flag, fsconfig_set_string, fsmount, fsopen, mount, mount_bind,
+    move_mount, open_tree, unmount,
 };
 use rustix::process::{getgid, getuid};
+use serde::Deserialize;
 use tempfile::TempDir;
 use thiserror::Error;
+use tracing::{error, info, warn};
 
 use crate::caps::{ElevatedCaps, ensure_cap_sys_admin, have_cap_sys_admin};
+use crate::fuse::{self, FuseOverlay, FuseOverlayError};
 
-fn mount_overlayfs(staging_path: &Path, game_path: &Path) -> Result<(), MountError> {
-    assert!(staging_path.is_absolute());
+/// Options used by every overlay and tmpfs mount, set from the configuration file.
+static MOUNT_OPTIONS: OnceLock<MountOptions> = OnceLock::new();
+
+/// Options given to the kernel when mounting, in addition to those that are always used.
+#[derive(Clone, Debug, Default)]
+pub struct MountOptions {
+    pub security_context: SecurityContext,
+    pub overlay: OverlayOptions,
+}
+
+/// SELinux contexts of the files in a mount, given as the mount options of the same name. See `mount(8)`.
+///
+/// Without these, the files may get a context that the game isn't allowed to read on SELinux-enforcing systems.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SecurityContext {
+    pub context: Option<String>,
+    pub fscontext: Option<String>,
+    pub defcontext: Option<String>,
+    pub rootcontext: Option<String>,
+}
+
+impl SecurityContext {
+    fn options(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            ("context", &self.context),
+            ("fscontext", &self.fscontext),
+            ("defcontext", &self.defcontext),
+            ("rootcontext", &self.rootcontext),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, value.as_deref()?)))
+    }
+}
+
+/// Overlayfs features, which are left to the kernel's defaults unless specified. See the kernel's overlayfs documentation.
+///
+/// These don't apply when falling back to `fuse-overlayfs`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct OverlayOptions {
+    pub metacopy: Option<bool>,
+    pub redirect_dir: Option<RedirectDir>,
+    pub index: Option<bool>,
+    /// Use the `user.overlay.` extended attribute namespace, instead of `trusted.overlay.`.
+    pub userxattr: bool,
+    /// Don't sync the upper layer to disk. Only applies to writable overlays.
+    pub volatile: bool,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RedirectDir {
+    On,
+    Follow,
+    NoFollow,
+    Off,
+}
+
+impl RedirectDir {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::On => "on",
+            Self::Follow => "follow",
+            Self::NoFollow => "nofollow",
+            Self::Off => "off",
+        }
+    }
+}
+
+impl OverlayOptions {
+    /// Returns the specified options as pairs of keys and values, or just keys for flags.
+    fn options(&self, writable: bool) -> Vec<(&'static str, Option<&'static str>)> {
+        let on_off = |enabled: bool| Some(if enabled { "on" } else { "off" });
+        let mut options = Vec::new();
+        if let Some(metacopy) = self.metacopy {
+            options.push(("metacopy", on_off(metacopy)));
+        }
+        if let Some(redirect_dir) = self.redirect_dir {
+            options.push(("redirect_dir", Some(redirect_dir.as_str())));
+        }
+        if let Some(index) = self.index {
+            options.push(("index", on_off(index)));
+        }
+        if self.userxattr {
+            options.push(("userxattr", None));
+        }
+        if self.volatile && writable {
+            options.push(("volatile", None));
+        }
+        options
+    }
+}
+
+/// Sets the options used by the mounts created afterwards.
+///
+/// Must be called at most once, before anything is mounted.
+pub fn configure(options: MountOptions) {
+    MOUNT_OPTIONS
+        .set(options)
+        .expect("mount options are configured before mounting, and only once");
+}
+
+fn mount_options() -> &'static MountOptions {
+    MOUNT_OPTIONS.get_or_init(MountOptions::default)
+}
+
+/// Appends the configured options that apply to any filesystem to a `mount(2)` option string.
+fn push_common_options(options: &mut Vec<u8>) {
+    for (key, value) in mount_options().security_context.options() {
+        // Contexts may contain commas, so they are quoted.
+        options.extend_from_slice(format!(",{key}=\"{value}\"").as_bytes());
+    }
+}
+
+/// Sets the configured options that apply to any filesystem on a filesystem context.
+fn set_common_options(fs_fd: &OwnedFd) -> Result<(), MountError> {
+    for (key, value) in mount_options().security_context.options() {
+        fsconfig_set_string(fs_fd, key, value).map_err(MountError::FsConfigSet)?;
+    }
+    Ok(())
+}
+
+/// Mounts an overlay of `lower_paths`, topmost first, over `game_path`.
+fn mount_overlayfs(lower_paths: &[PathBuf], game_path: &Path, upper: Option<(&Path, &Path)>) -> Result<(), MountError> {
+    assert!(lower_paths.iter().all(|path| path.is_absolute()));
     let game_dir = open_dir_and_check_ownership(game_path)?;
     let _caps = ElevatedCaps::raise();
 
+    if !new_mount_api_available() {
+        let mut options = b"lowerdir=".to_vec();
+        for lower_path in lower_paths {
+            push_escaped_path(&mut options, lower_path);
+            options.push(b':');
+        }
+        push_escaped_path(&mut options, &fd_path(&game_dir));
+        if let Some((upper_dir, work_dir)) = upper {
+            options.extend_from_slice(b",upperdir=");
+            push_escaped_path(&mut options, upper_dir);
+            options.extend_from_slice(b",workdir=");
+            push_escaped_path(&mut options, work_dir);
+        }
+        for (key, value) in mount_options().overlay.options(upper.is_some()) {
+            options.push(b',');
+            options.extend_from_slice(key.as_bytes());
+            if let Some(value) = value {
+                options.push(b'=');
+                options.extend_from_slice(value.as_bytes());
+            }
+        }
+        push_common_options(&mut options);
+        return legacy_mount("overlay", &game_dir, options);
+    }
+
     let fs_fd = fsopen("overlay", FsOpenFlags::FSOPEN_CLOEXEC).map_err(MountError::FsOpen)?;
     fsconfig_set_string(&fs_fd, "source", "overlay").map_err(MountError::FsConfigSet)?;
-    fsconfig_set_string(&fs_fd, "lowerdir+", staging_path).map_err(MountError::FsConfigSet)?;
+    for lower_path in lower_paths {
+        fsconfig_set_string(&fs_fd, "lowerdir+", lower_path).map_err(MountError::FsConfigSet)?;
+    }
     fsconfig_set_fd(&fs_fd, "lowerdir+", &game_dir).map_err(MountError::FsConfigSet)?;
+    if let Some((upper_dir, work_dir)) = upper {
+        fsconfig_set_string(&fs_fd, "upperdir", upper_dir).map_err(MountError::FsConfigSet)?;
+        fsconfig_set_string(&fs_fd, "workdir", work_dir).map_err(MountError::FsConfigSet)?;
+    }
+    for (key, value) in mount_options().overlay.options(upper.is_some()) {
+        match value {
+            Some(value) => fsconfig_set_string(&fs_fd, key, value),
+            None => fsconfig_set_flag(&fs_fd, key),
+        }
+        .map_err(MountError::FsConfigSet)?;
+    }
+    set_common_options(&fs_fd)?;
     fsconfig_create(&fs_fd).map_err(MountError::FsConfigCreate)?;
 
     let mfd = fsmount_with_flags(&fs_fd)?;
     move_mount_fds(&mfd, &game_dir)
 }
 
-fn mount_tmpfs(path: &Path) -> Result<(), MountError> {
+/// Limits of a tmpfs, which is otherwise allowed to grow up to half of the system's memory.
+#[derive(Copy, Clone, Debug)]
+pub struct TmpfsSize {
+    pub bytes: u64,
+    pub inodes: u64,
+}
+
+fn mount_tmpfs(path: &Path, size: Option<TmpfsSize>) -> Result<(), MountError> {
     let dir = open_dir_and_check_ownership(path)?;
     let _caps = ElevatedCaps::raise();
 
+    if !new_mount_api_available() {
+        let mut options = format!("uid={},gid={},mode=750", getuid().as_raw(), getgid().as_raw());
+        if let Some(size) = size {
+            options.push_str(&format!(",size={},nr_inodes={}", size.bytes, size.inodes));
+        }
+        let mut options = options.into_bytes();
+        push_common_options(&mut options);
+        return legacy_mount("tmpfs", &dir, options);
+    }
+
     let fs_fd = fsopen("tmpfs", FsOpenFlags::FSOPEN_CLOEXEC).map_err(MountError::FsOpen)?;
     fsconfig_set_string(&fs_fd, "source", "tmpfs").map_err(MountError::FsConfigSet)?;
     fsconfig_set_string(&fs_fd, "uid", getuid().to_string()).map_err(MountError::FsConfigSet)?;
     fsconfig_set_string(&fs_fd, "gid", getgid().to_string()).map_err(MountError::FsConfigSet)?;
     fsconfig_set_string(&fs_fd, "mode", "750").map_err(MountError::FsConfigSet)?;
+    if let Some(size) = size {
+        fsconfig_set_string(&fs_fd, "size", size.bytes.to_string()).map_err(MountError::FsConfigSet)?;
+        fsconfig_set_string(&fs_fd, "nr_inodes", size.inodes.to_string()).map_err(MountError::FsConfigSet)?;
+    }
+    set_common_options(&fs_fd)?;
     fsconfig_create(&fs_fd).map_err(MountError::FsConfigCreate)?;
 
     let mfd = fsmount_with_flags(&fs_fd)?;
     move_mount_fds(&mfd, &dir)
 }
 
+/// Mounts the directory `source` over the directory `target`, so that it is accessed in its place.
+fn mount_bind_dir(source: &Path, target: &Path) -> Result<(), MountError> {
+    let source_dir = open_dir_and_check_ownership(source)?;
+    let target_dir = open_dir_and_check_ownership(target)?;
+    let _caps = ElevatedCaps::raise();
+
+    if !new_mount_api_available() {
+        return mount_bind(fd_path(&source_dir), fd_path(&target_dir)).map_err(MountError::Mount);
+    }
+
+    let tree_fd = open_tree(
+        &source_dir,
+        "",
+        OpenTreeFlags::OPEN_TREE_CLONE | OpenTreeFlags::OPEN_TREE_CLOEXEC | OpenTreeFlags::AT_EMPTY_PATH,
+    )
+    .map_err(MountError::OpenTree)?;
+    move_mount_fds(&tree_fd, &target_dir)
+}
+
+/// Returns whether the mount API introduced in Linux 5.2 (`fsopen`, `fsmount`, `move_mount`) is available.
+///
+/// If it isn't, mounts are created with `mount(2)` instead.
+fn new_mount_api_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        let available = !matches!(fsopen("tmpfs", FsOpenFlags::FSOPEN_CLOEXEC), Err(Errno::NOSYS));
+        if !available {
+            info!("The new mount API is not supported by the kernel, falling back to mount(2).");
+        }
+        available
+    })
+}
+
+/// Mounts a filesystem of type `fs_type` over the directory `dir` using `mount(2)`.
+fn legacy_mount(fs_type: &str, dir: &OwnedFd, options: Vec<u8>) -> Result<(), MountError> {
+    let options = CString::new(options).map_err(|_| MountError::Mount(Errno::INVAL))?;
+    mount(
+        fs_type,
+        fd_path(dir),
+        fs_type,
+        MountFlags::NODEV | MountFlags::NOSUID | MountFlags::NOATIME,
+        Some(options.as_c_str()),
+    )
+    .map_err(MountError::Mount)
+}
+
+/// Returns a path referring to the directory opened as `fd`, so that it isn't looked up again by path.
+fn fd_path(fd: &OwnedFd) -> PathBuf {
+    PathBuf::from(format!("/proc/self/fd/{}", fd.as_raw_fd()))
+}
+
+/// Appends `path` to a mount option string, escaping the characters that separate options and directories.
+pub fn push_escaped_path(options: &mut Vec<u8>, path: &Path) {
+    for &byte in path.as_os_str().as_bytes() {
+        if matches!(byte, b'\\' | b':' | b',') {
+            options.push(b'\\');
+        }
+        options.push(byte);
+    }
+}
+
 fn open_dir_and_check_ownership(path: &Path) -> Result<OwnedFd, MountError> {
     let fd = open(
         path,
@@ -107,21 +361,90 @@ pub enum MountError {
     FsOpen(#[source] Errno),
     #[error("failed to fstat mount target directory")]
     Fstat(#[source] Errno),
+    #[error("mount failed")]
+    Mount(#[source] Errno),
     #[error("move_mount failed")]
     MoveMount(#[source] Errno),
     #[error("target directory is not owned by the user")]
     NotOwned,
     #[error("failed to open mount target directory")]
     Open(#[source] Errno),
+    #[error("open_tree failed")]
+    OpenTree(#[source] Errno),
 }
 
 #[derive(Debug)]
-pub struct OverlayMount(UnmountWrapper<PathBuf>);
+pub struct OverlayMount(OverlayMountKind);
+
+#[derive(Debug)]
+enum OverlayMountKind {
+    Kernel(UnmountWrapper<PathBuf>),
+    Fuse(FuseOverlay),
+}
 
 impl OverlayMount {
-    pub fn new(staging_dir: &Path, game_dir: &Path) -> Result<Self, MountError> {
-        mount_overlayfs(staging_dir, game_dir)?;
-        Ok(Self(UnmountWrapper::new(game_dir.to_owned())))
+    /// Mounts an overlay of `lower_dirs`, topmost first, over `game_dir`.
+    ///
+    /// If a pair of upper and work directories is specified, the overlay is writable.
+    /// If the kernel refuses to mount the overlay, and `fuse-overlayfs` is installed, it is used instead.
+    pub fn new(
+        lower_dirs: &[PathBuf],
+        game_dir: &Path,
+        upper: Option<(&Path, &Path)>,
+    ) -> Result<Self, OverlayMountError> {
+        match mount_overlayfs(lower_dirs, game_dir, upper) {
+            Ok(()) => Ok(Self(OverlayMountKind::Kernel(UnmountWrapper::new(game_dir.to_owned())))),
+            Err(err @ (MountError::NotOwned | MountError::Open(_))) => Err(err.into()),
+            Err(err) if fuse::is_available() => {
+                warn!("Failed to mount overlayfs ({err}), falling back to fuse-overlayfs");
+                Ok(Self(OverlayMountKind::Fuse(FuseOverlay::mount(
+                    lower_dirs, game_dir, upper,
+                )?)))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        match &self.0 {
+            OverlayMountKind::Kernel(wrapper) => wrapper.path(),
+            OverlayMountKind::Fuse(overlay) => overlay.path(),
+        }
+    }
+
+    pub fn unmount(self) -> Result<(), OverlayUnmountError> {
+        match self.0 {
+            OverlayMountKind::Kernel(wrapper) => wrapper.unmount().and(Ok(())).map_err(OverlayUnmountError::Kernel),
+            OverlayMountKind::Fuse(overlay) => overlay.unmount().map_err(OverlayUnmountError::Fuse),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum OverlayMountError {
+    #[error("failed to mount fuse-overlayfs")]
+    Fuse(#[from] FuseOverlayError),
+    #[error(transparent)]
+    Kernel(#[from] MountError),
+}
+
+#[derive(Debug, Error)]
+pub enum OverlayUnmountError {
+    #[error("failed to unmount fuse-overlayfs")]
+    Fuse(#[source] FuseOverlayError),
+    #[error("failed to unmount overlayfs")]
+    Kernel(#[source] Errno),
+}
+
+/// A directory mounted over another one.
+#[derive(Debug)]
+pub struct BindMount(UnmountWrapper<PathBuf>);
+
+impl BindMount {
+    /// Mounts `source` over `target`, which is unmounted when the returned value is dropped.
+    pub fn new(source: &Path, target: &Path) -> Result<Self, MountError> {
+        mount_bind_dir(source, target)?;
+        Ok(Self(UnmountWrapper::new(target.to_owned())))
     }
 
     pub fn path(&self) -> &Path {
@@ -137,9 +460,9 @@ impl OverlayMount {
 pub struct TempMount(UnmountWrapper<TempDir>);
 
 impl TempMount {
-    pub fn new() -> Result<Self, TempMountCreationError> {
+    pub fn new(size: Option<TmpfsSize>) -> Result<Self, TempMountCreationError> {
         let temp_dir = TempDir::with_prefix("mmm-").map_err(TempMountCreationError::TempDir)?;
-        mount_tmpfs(temp_dir.path())?;
+        mount_tmpfs(temp_dir.path(), size)?;
         Ok(Self(UnmountWrapper::new(temp_dir)))
     }
 
@@ -219,18 +542,67 @@ impl MountMethodChoice {
                 ensure_cap_sys_admin();
                 MountMethod::CapAdmin
             }
-            None => {
-                if have_cap_sys_admin() {
-                    MountMethod::CapAdmin
-                } else {
-                    eprintln!("The SYS_ADMIN capability is missing, falling back to user namespaces.");
-                    MountMethod::UserNamespace
-                }
-            }
+            None => probe_mount_method(),
         }
     }
 }
 
+/// Picks the mount method that is expected to work on this system, and reports which one was chosen.
+///
+/// Mounting directly needs the SYS_ADMIN capability. Otherwise, unprivileged user namespaces must be allowed,
+/// and either the kernel must allow mounting overlayfs in them (Linux 5.11+), or `fuse-overlayfs` must be installed.
+fn probe_mount_method() -> MountMethod {
+    if have_cap_sys_admin() {
+        info!("Mounting directly, using the SYS_ADMIN capability.");
+        return MountMethod::CapAdmin;
+    }
+
+    if let Some(reason) = user_namespaces_disallowed() {
+        error!("The SYS_ADMIN capability is missing, and user namespaces can't be used: {reason}.");
+        ensure_cap_sys_admin();
+        unreachable!("ensure_cap_sys_admin exits if the capability is missing");
+    }
+
+    if kernel_version().is_some_and(|version| version >= (5, 11)) {
+        info!("The SYS_ADMIN capability is missing, mounting in a user namespace.");
+    } else if fuse::is_available() {
+        info!("The SYS_ADMIN capability is missing, mounting with fuse-overlayfs in a user namespace.");
+    } else {
+        warn!(
+            "The SYS_ADMIN capability is missing, and this kernel may not allow mounting overlayfs in a user namespace. \
+             Trying anyway, installing fuse-overlayfs may help if this fails."
+        );
+    }
+    MountMethod::UserNamespace
+}
+
+/// Returns why unprivileged user namespaces can't be created, if the system is configured to disallow them.
+fn user_namespaces_disallowed() -> Option<&'static str> {
+    let sysctl = |name: &str| {
+        fs::read_to_string(Path::new("/proc/sys").join(name))
+            .ok()
+            .and_then(|value| value.trim().parse::<u64>().ok())
+    };
+    if sysctl("user/max_user_namespaces") == Some(0) {
+        Some("user.max_user_namespaces is 0")
+    } else if sysctl("kernel/unprivileged_userns_clone") == Some(0) {
+        Some("kernel.unprivileged_userns_clone is 0")
+    } else if sysctl("kernel/apparmor_restrict_unprivileged_userns") == Some(1) {
+        Some("AppArmor restricts unprivileged user namespaces")
+    } else {
+        None
+    }
+}
+
+/// Returns the major and minor version of the running kernel.
+fn kernel_version() -> Option<(u32, u32)> {
+    let release = fs::read_to_string("/proc/sys/kernel/osrelease").ok()?;
+    let mut parts = release.trim().split(['.', '-']);
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
 impl clap::ValueEnum for MountMethodChoice {
     fn value_variants<'a>() -> &'a [Self] {
         &[
@@ -14,11 +14,16 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use std::fmt::Display;
+use std::fs::{self, File};
+use std::io;
+use std::os::fd::AsFd;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
 
 use rustix::fs::{Mode, OFlags, open};
 use rustix::io::{Errno, write};
 use rustix::process::{Gid, Uid, getgid, getuid};
-use rustix::thread::{self, UnshareFlags};
+use rustix::thread::{self, LinkNameSpaceType, UnshareFlags};
 use thiserror::Error;
 
 use crate::caps::have_cap_sys_admin;
@@ -40,6 +45,35 @@ pub fn enter_namespace() -> Result<(), EnterNamespaceError> {
     Ok(())
 }
 
+/// Moves this process into the user and mount namespaces of the process with the specified PID,
+/// so that it sees the same mounts.
+///
+/// Must be called before any threads are started.
+pub fn enter_namespaces_of(pid: u32) -> Result<(), JoinNamespaceError> {
+    let ns_dir = PathBuf::from(format!("/proc/{pid}/ns"));
+    let open_ns = |name: &str| {
+        let path = ns_dir.join(name);
+        File::open(&path).map_err(|source| JoinNamespaceError::Open { path, source })
+    };
+    let user_ns = open_ns("user")?;
+    let mount_ns = open_ns("mnt")?;
+
+    let same_ns = |ns: &File, name: &str| match (fs::metadata(Path::new("/proc/self/ns").join(name)), ns.metadata()) {
+        (Ok(own), Ok(other)) => own.ino() == other.ino(),
+        _ => false,
+    };
+    if same_ns(&mount_ns, "mnt") {
+        // The deployment is visible to every process already.
+        return Ok(());
+    }
+    if !same_ns(&user_ns, "user") {
+        thread::move_into_link_name_space(user_ns.as_fd(), Some(LinkNameSpaceType::User))
+            .map_err(JoinNamespaceError::User)?;
+    }
+    thread::move_into_link_name_space(mount_ns.as_fd(), Some(LinkNameSpaceType::Mount))
+        .map_err(JoinNamespaceError::Mount)
+}
+
 fn set_up_uid_and_gid_map(uid: Uid, gid: Gid) -> Result<(), EnterNamespaceError> {
     write_map("/proc/self/uid_map", uid).map_err(EnterNamespaceError::WriteUidMap)?;
     write_file("/proc/self/setgroups", "deny").map_err(EnterNamespaceError::WriteSetgroups)?;
@@ -72,6 +106,16 @@ pub enum EnterNamespaceError {
     WriteSetgroups(#[source] WriteFileError),
 }
 
+#[derive(Debug, Error)]
+pub enum JoinNamespaceError {
+    #[error("failed to join mount namespace")]
+    Mount(#[source] Errno),
+    #[error("failed to open namespace '{path}'")]
+    Open { path: PathBuf, source: io::Error },
+    #[error("failed to join user namespace")]
+    User(#[source] Errno),
+}
+
 #[derive(Copy, Clone, Debug, Error)]
 pub enum WriteFileError {
     #[error("open failed")]
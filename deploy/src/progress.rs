@@ -0,0 +1,118 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Progress reporting while building the file tree and the staging tree, which can take minutes for large instances.
+
+use std::cell::Cell;
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+use mmm_core::file_tree::Count;
+
+use crate::logging::LogFormat;
+
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+const BAR_WIDTH: usize = 24;
+
+/// Renders progress as a single line on stderr, if it is a terminal and messages are logged as text.
+#[derive(Debug)]
+pub struct Progress {
+    visible: bool,
+    files: Cell<usize>,
+    last_draw: Cell<Option<Instant>>,
+}
+
+impl Progress {
+    pub fn new(log_format: LogFormat) -> Self {
+        Self {
+            visible: matches!(log_format, LogFormat::Text) && io::stderr().is_terminal(),
+            files: Cell::new(0),
+            last_draw: Cell::new(None),
+        }
+    }
+
+    /// Returns a `Progress` that doesn't render anything.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code, reason = "only used by the daemon"))]
+    pub const fn hidden() -> Self {
+        Self {
+            visible: false,
+            files: Cell::new(0),
+            last_draw: Cell::new(None),
+        }
+    }
+
+    /// Reports that `done` out of `total` entries of the staging tree were created.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code, reason = "only used by the overlay backend"))]
+    pub fn staged(&self, done: usize, total: usize) {
+        self.draw(done == total, || {
+            format!("Staging files {} {done}/{total}", bar(done, total))
+        });
+    }
+
+    /// Clears the progress line.
+    pub fn finish(&self) {
+        if self.visible && self.last_draw.take().is_some() {
+            eprint!("\r\x1b[K");
+            let _ = io::stderr().flush();
+        }
+    }
+
+    fn draw(&self, force: bool, line: impl FnOnce() -> String) {
+        if !self.visible {
+            return;
+        }
+        let now = Instant::now();
+        if !force
+            && self
+                .last_draw
+                .get()
+                .is_some_and(|last_draw| now.duration_since(last_draw) < REDRAW_INTERVAL)
+        {
+            return;
+        }
+        self.last_draw.set(Some(now));
+        eprint!("\r\x1b[K{}", line());
+        let _ = io::stderr().flush();
+    }
+}
+
+impl Count for &Progress {
+    fn file_added(&self) {
+        self.files.set(self.files.get() + 1);
+    }
+
+    fn file_appended(&self) {}
+
+    fn dir_added(&self) {}
+
+    fn mod_started(&self, position: usize, total: usize, name: &str) {
+        let files = self.files.get();
+        self.draw(false, || {
+            format!(
+                "Scanning mods {} {}/{total} ({files} files) {name}",
+                bar(position, total),
+                position + 1
+            )
+        });
+    }
+}
+
+fn bar(done: usize, total: usize) -> String {
+    let filled = (done * BAR_WIDTH)
+        .checked_div(total)
+        .unwrap_or(BAR_WIDTH)
+        .min(BAR_WIDTH);
+    format!("[{}{}]", "#".repeat(filled), " ".repeat(BAR_WIDTH - filled))
+}
@@ -0,0 +1,194 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Checking the game directory for loose files left behind by other mod managers or previous deployments,
+//! which would be merged with the deployed mod files without anyone noticing.
+//!
+//! If a vanilla manifest was recorded while the game directory was unmodded, every file that isn't in it
+//! is reported. Otherwise, only files that look like leftovers are.
+
+use std::collections::{BTreeSet, HashMap};
+use std::convert::Infallible;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use mmm_core::file_tree::{FileTree, ModVec};
+
+use crate::instance::DeployInstance;
+use crate::link::{path_from_bytes, path_to_bytes};
+use crate::verify::matches_source;
+use crate::walk::{DeployNode, resolve_mount_target, walk_tree};
+
+/// Name of the vanilla manifest file, in the instance directory.
+pub const VANILLA_MANIFEST_FILE: &str = ".vanilla-files";
+
+const VANILLA_MANIFEST_HEADER: &[u8] = b"mmm-vanilla 1";
+
+/// File name suffixes and names of files created by other mod managers, along with the name of the manager.
+const MANAGER_FILES: &[(&str, &str)] = &[
+    (".mohidden", "Mod Organizer 2"),
+    (".vortex_backup", "Vortex"),
+    ("vortex.deployment.json", "Vortex"),
+    ("__folder_managed_by_vortex", "Vortex"),
+];
+
+/// Paths, relative to the game directory, of the files of an unmodded game.
+#[derive(Debug)]
+pub struct VanillaManifest {
+    files: BTreeSet<PathBuf>,
+}
+
+impl VanillaManifest {
+    /// Records the files currently in `game_path`.
+    pub fn record(game_path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            files: game_files(game_path)?.into_iter().collect(),
+        })
+    }
+
+    /// Returns the number of files in the manifest.
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Writes the manifest to `path`.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let mut contents = VANILLA_MANIFEST_HEADER.to_vec();
+        contents.push(b'\n');
+        for file in &self.files {
+            let file = path_to_bytes(file)?;
+            if file.contains(&b'\n') {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "paths containing newlines are not supported",
+                ));
+            }
+            contents.extend_from_slice(file);
+            contents.push(b'\n');
+        }
+        File::create(path)?.write_all(&contents)
+    }
+
+    /// Reads the manifest at `path`, if it exists.
+    pub fn read(path: &Path) -> Result<Option<Self>, VanillaManifestReadError> {
+        let contents = match fs::read(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(VanillaManifestReadError::Read { path: path.to_owned(), source }),
+        };
+
+        let mut lines = contents.split(|&b| b == b'\n').filter(|line| !line.is_empty());
+        if lines.next() != Some(VANILLA_MANIFEST_HEADER) {
+            return Err(VanillaManifestReadError::UnknownFormat(path.to_owned()));
+        }
+        let files = lines
+            .map(path_from_bytes)
+            .collect::<Option<_>>()
+            .ok_or_else(|| VanillaManifestReadError::Malformed(path.to_owned()))?;
+        Ok(Some(Self { files }))
+    }
+}
+
+/// A file in the game directory that doesn't seem to belong to the game.
+#[derive(Debug)]
+pub enum Leftover {
+    /// A file created by another mod manager.
+    ManagerFile { path: PathBuf, manager: &'static str },
+    /// A copy of, or a link to, the mod file that is deployed to the same path.
+    ModCopy { path: PathBuf, source: PathBuf },
+    /// A file that isn't in the vanilla manifest.
+    NotVanilla(PathBuf),
+}
+
+impl fmt::Display for Leftover {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ManagerFile { path, manager } => write!(f, "{} (created by {manager})", path.display()),
+            Self::ModCopy { path, source } => {
+                write!(f, "{} (copy of mod file '{}')", path.display(), source.display())
+            }
+            Self::NotVanilla(path) => write!(f, "{} (not a vanilla file)", path.display()),
+        }
+    }
+}
+
+/// Scans `game_path` for files that don't seem to belong to the game, comparing them to the files
+/// deployed from `tree`, and to `manifest`, if there is one.
+pub fn find_leftovers(
+    game_path: &Path,
+    tree: &FileTree<ModVec>,
+    instance: &DeployInstance,
+    manifest: Option<&VanillaManifest>,
+) -> io::Result<Vec<Leftover>> {
+    let targets = instance.mount_targets();
+    let mut deployed_files = HashMap::new();
+    walk_tree(tree, instance, |relative_path, node| {
+        if let (None, _) = resolve_mount_target(targets, relative_path, &node)
+            && let DeployNode::File { source_path } = node
+        {
+            deployed_files.insert(relative_path.to_owned(), source_path);
+        }
+        Ok::<_, Infallible>(())
+    })
+    .unwrap_or_else(|never| match never {});
+
+    let mut leftovers = Vec::new();
+    for path in game_files(game_path)? {
+        let file_name = path.file_name().expect("files have a name").to_string_lossy();
+        if let Some(&(_, manager)) = MANAGER_FILES.iter().find(|(pattern, _)| file_name.ends_with(pattern)) {
+            leftovers.push(Leftover::ManagerFile { path, manager });
+        } else if let Some(source) = deployed_files.get(&path)
+            && matches_source(&game_path.join(&path), source)?
+        {
+            leftovers.push(Leftover::ModCopy { source: source.clone(), path });
+        } else if manifest.is_some_and(|manifest| !manifest.files.contains(&path)) {
+            leftovers.push(Leftover::NotVanilla(path));
+        }
+    }
+    Ok(leftovers)
+}
+
+/// Returns the paths, relative to `game_path`, of the files in it, sorted.
+fn game_files(game_path: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(relative_dir) = pending.pop() {
+        for entry in fs::read_dir(game_path.join(&relative_dir))? {
+            let entry = entry?;
+            let relative_path = relative_dir.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                pending.push(relative_path);
+            } else {
+                files.push(relative_path);
+            }
+        }
+    }
+    files.sort_unstable();
+    Ok(files)
+}
+
+#[derive(Debug, Error)]
+pub enum VanillaManifestReadError {
+    #[error("vanilla manifest '{0}' is malformed")]
+    Malformed(PathBuf),
+    #[error("failed to read vanilla manifest '{path}'")]
+    Read { path: PathBuf, source: io::Error },
+    #[error("vanilla manifest '{0}' has an unknown format")]
+    UnknownFormat(PathBuf),
+}
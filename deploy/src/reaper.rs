@@ -0,0 +1,90 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Waiting for the processes started by the game.
+//!
+//! Launchers often start the actual game and exit right away. To keep the deployment in place until the game exits,
+//! this process becomes a child subreaper, so orphaned descendants of the game are reparented to it, and waits for
+//! those as well.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+use rustix::process::{Pid, WaitOptions, getpid, set_child_subreaper, waitpid};
+use tracing::info;
+
+use crate::signals::{Stopper, Termination};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Makes orphaned descendants of this process be reparented to it, instead of to init.
+pub fn become_subreaper() -> io::Result<()> {
+    set_child_subreaper(Some(getpid())).map_err(Into::into)
+}
+
+/// Returns the PIDs of the children of this process.
+pub fn children() -> io::Result<HashSet<i32>> {
+    let own_pid = getpid().as_raw_nonzero().get();
+    let mut pids = HashSet::new();
+    for entry in fs::read_dir("/proc")? {
+        let entry = entry?;
+        let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse().ok()) else {
+            continue;
+        };
+        // The process may have exited in the meantime.
+        let Ok(stat) = fs::read_to_string(entry.path().join("stat")) else {
+            continue;
+        };
+        // The command name is in parentheses and may contain spaces, the parent PID is the second field after it.
+        let ppid = stat
+            .rsplit_once(')')
+            .and_then(|(_, fields)| fields.split_whitespace().nth(1))
+            .and_then(|ppid| ppid.parse::<i32>().ok());
+        if ppid == Some(own_pid) {
+            pids.insert(pid);
+        }
+    }
+    Ok(pids)
+}
+
+/// Waits for every child of this process that isn't in `existing` to exit, reaping them.
+///
+/// If termination is requested, the children are stopped instead of waited on indefinitely.
+pub fn wait_for_orphans(existing: &HashSet<i32>, termination: &Termination) -> io::Result<()> {
+    let mut announced = false;
+    let mut stopper = Stopper::default();
+    loop {
+        let orphans: Vec<i32> = children()?.difference(existing).copied().collect();
+        if orphans.is_empty() {
+            return Ok(());
+        }
+        if !announced {
+            info!("Waiting for {} processes started by the game to exit", orphans.len());
+            announced = true;
+        }
+        if termination.requested() {
+            stopper.stop(orphans.iter().copied());
+        }
+        for pid in orphans {
+            if let Some(pid) = Pid::from_raw(pid) {
+                let _ = waitpid(Some(pid), WaitOptions::NOHANG);
+            }
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Per-profile save games, mounted over the game's save directory while deploying.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rustix::io::Errno;
+use thiserror::Error;
+
+use mmm_core::instance::Instance;
+
+use crate::instance::DeployInstance;
+use crate::mount::{BindMount, MountError};
+
+/// Name of the directory, in the instance directory, that contains the saves of each profile.
+pub const SAVES_DIR: &str = "saves";
+
+/// The saves directory of the deployed profile, mounted over the game's save directory.
+#[derive(Debug)]
+pub struct SaveRedirect(BindMount);
+
+impl SaveRedirect {
+    /// Mounts the saves directory of the deployed profile over the save path stored in the instance,
+    /// creating either directory if it doesn't exist.
+    ///
+    /// Returns `None` if the instance doesn't specify a save path.
+    pub fn mount(instance: &DeployInstance) -> Result<Option<Self>, SaveRedirectError> {
+        let Some(save_path) = &instance.settings().save_path else {
+            return Ok(None);
+        };
+        let save_path = Path::new(save_path.as_str());
+        if !save_path.is_absolute() {
+            return Err(SaveRedirectError::RelativePath(save_path.to_owned()));
+        }
+        let profile_name = instance
+            .profile_dir_name()
+            .ok_or_else(|| SaveRedirectError::ProfileName(instance.profile_name().to_owned()))?;
+        let saves_dir = instance.dir().join(SAVES_DIR).join(profile_name);
+
+        for dir in [saves_dir.as_path(), save_path] {
+            fs::create_dir_all(dir).map_err(|source| SaveRedirectError::Mkdir { path: dir.to_owned(), source })?;
+        }
+        let mount = BindMount::new(&saves_dir, save_path)
+            .map_err(|source| SaveRedirectError::Mount { path: save_path.to_owned(), source })?;
+        Ok(Some(Self(mount)))
+    }
+
+    /// Returns the game's save directory.
+    pub fn path(&self) -> &Path {
+        self.0.path()
+    }
+
+    pub fn unmount(self) -> Result<(), Errno> {
+        self.0.unmount()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SaveRedirectError {
+    #[error("failed to create directory '{path}'")]
+    Mkdir { path: PathBuf, source: io::Error },
+    #[error("failed to mount profile saves over '{path}'")]
+    Mount { path: PathBuf, source: MountError },
+    #[error("profile name '{0}' can't be used as a directory name")]
+    ProfileName(String),
+    #[error("save path '{0}' is not absolute")]
+    RelativePath(PathBuf),
+}
@@ -0,0 +1,148 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Running the game in a transient systemd user scope.
+//!
+//! Once the game is started, it is moved into a scope created by the user's service manager over D-Bus.
+//! This makes the game show up in `systemctl --user` with resource accounting, and makes it possible to tell when
+//! every process started by the game has exited, even those that escaped this process's subreaper.
+
+use std::process;
+use std::thread;
+
+use tracing::{info, warn};
+use zbus::blocking::Connection;
+use zbus::proxy;
+use zbus::proxy::CacheProperties;
+use zbus::zvariant::{OwnedObjectPath, Value};
+
+use crate::signals::{self, KILL_TIMEOUT, Termination};
+
+#[proxy(
+    interface = "org.freedesktop.systemd1.Manager",
+    default_service = "org.freedesktop.systemd1",
+    default_path = "/org/freedesktop/systemd1"
+)]
+trait Manager {
+    fn subscribe(&self) -> zbus::Result<()>;
+
+    fn start_transient_unit(
+        &self,
+        name: &str,
+        mode: &str,
+        properties: &[(&str, Value<'_>)],
+        aux: &[(&str, &[(&str, Value<'_>)])],
+    ) -> zbus::Result<OwnedObjectPath>;
+
+    fn stop_unit(&self, name: &str, mode: &str) -> zbus::Result<OwnedObjectPath>;
+
+    fn load_unit(&self, name: &str) -> zbus::Result<OwnedObjectPath>;
+
+    #[zbus(signal)]
+    fn job_removed(&self, id: u32, job: OwnedObjectPath, unit: String, result: String) -> zbus::Result<()>;
+}
+
+#[proxy(interface = "org.freedesktop.systemd1.Unit", default_service = "org.freedesktop.systemd1")]
+trait Unit {
+    #[zbus(property)]
+    fn active_state(&self) -> zbus::Result<String>;
+}
+
+/// A transient scope unit, named after this process.
+pub struct Scope {
+    unit: String,
+    manager: ManagerProxyBlocking<'static>,
+}
+
+impl Scope {
+    /// Connects to the user's service manager.
+    pub fn new() -> zbus::Result<Self> {
+        let connection = Connection::session()?;
+        let manager = ManagerProxyBlocking::new(&connection)?;
+        // Job signals are only sent to subscribed clients.
+        manager.subscribe()?;
+        Ok(Self {
+            unit: format!("mmm-game-{}.scope", process::id()),
+            manager,
+        })
+    }
+
+    /// Moves the process with the specified PID into the scope, and waits for the scope to be started.
+    ///
+    /// Processes that it started before being moved stay outside the scope.
+    pub fn start(&self, pid: u32) -> zbus::Result<()> {
+        let jobs = self.manager.receive_job_removed()?;
+        let properties = [
+            ("PIDs", Value::from(vec![pid])),
+            ("CollectMode", Value::from("inactive-or-failed")),
+            (
+                "TimeoutStopUSec",
+                Value::from(u64::try_from(KILL_TIMEOUT.as_micros()).unwrap_or(u64::MAX)),
+            ),
+        ];
+        let job = self
+            .manager
+            .start_transient_unit(&self.unit, "fail", &properties, &[])?;
+
+        for signal in jobs {
+            let args = signal.args()?;
+            if args.job == job {
+                return match args.result.as_str() {
+                    "done" => Ok(()),
+                    result => Err(zbus::Error::Failure(format!(
+                        "starting {} failed with result '{result}'",
+                        self.unit
+                    ))),
+                };
+            }
+        }
+        Err(zbus::Error::Failure(format!(
+            "lost connection while starting {}",
+            self.unit
+        )))
+    }
+
+    /// Waits for every process in the scope to exit, stopping the scope if termination is requested.
+    ///
+    /// Scopes become inactive once they are empty, so this watches the scope's state.
+    pub fn wait(&self, termination: &Termination) -> zbus::Result<()> {
+        let path = self.manager.load_unit(&self.unit)?;
+        let unit = UnitProxyBlocking::builder(self.manager.inner().connection())
+            .path(path)?
+            .cache_properties(CacheProperties::No)
+            .build()?;
+
+        let mut announced = false;
+        let mut stopping = false;
+        loop {
+            // Units that were already collected are loaded again as inactive.
+            if matches!(unit.active_state()?.as_str(), "inactive" | "failed") {
+                return Ok(());
+            }
+            if !announced {
+                info!("Waiting for the processes in {} to exit", self.unit);
+                announced = true;
+            }
+            if termination.requested() && !stopping {
+                // Sends SIGTERM to every process in the scope, and SIGKILL after `TimeoutStopUSec`.
+                if let Err(err) = self.manager.stop_unit(&self.unit, "replace") {
+                    warn!("Failed to stop {}: {err}", self.unit);
+                }
+                stopping = true;
+            }
+            thread::sleep(signals::POLL_INTERVAL);
+        }
+    }
+}
@@ -0,0 +1,174 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Handling of termination signals.
+//!
+//! If this process is killed while mod files are deployed, the overlay and staging mounts are left behind.
+//! So while a deployment exists, SIGHUP, SIGINT and SIGTERM are only recorded, and whatever is being waited on
+//! is stopped, so that the deployment is removed as usual. On Windows, only SIGINT and SIGTERM exist.
+
+use std::collections::HashSet;
+#[cfg(unix)]
+use std::fs::File;
+use std::io;
+#[cfg(windows)]
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(unix)]
+use rustix::process::{Pid, Signal, kill_process};
+use signal_hook::SigId;
+#[cfg(unix)]
+use signal_hook::consts::SIGHUP;
+use signal_hook::consts::{SIGINT, SIGTERM};
+
+/// How often to check whether termination was requested while waiting.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// How long processes have to exit after being sent SIGTERM, before they are sent SIGKILL.
+pub const KILL_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[cfg(unix)]
+const TERMINATION_SIGNALS: [i32; 3] = [SIGHUP, SIGINT, SIGTERM];
+#[cfg(windows)]
+const TERMINATION_SIGNALS: [i32; 2] = [SIGINT, SIGTERM];
+
+/// Records termination signals for as long as it's alive, instead of letting them kill this process.
+pub struct Termination {
+    requested: Arc<AtomicBool>,
+    #[cfg(unix)]
+    hung_up: Arc<AtomicBool>,
+    #[cfg(unix)]
+    detached: AtomicBool,
+    handlers: Vec<SigId>,
+}
+
+impl Termination {
+    pub fn register() -> io::Result<Self> {
+        let requested = Arc::new(AtomicBool::new(false));
+        let mut handlers = Vec::new();
+        #[cfg(unix)]
+        let hung_up = Arc::new(AtomicBool::new(false));
+        #[cfg(unix)]
+        handlers.push(signal_hook::flag::register(SIGHUP, Arc::clone(&hung_up))?);
+        for signal in TERMINATION_SIGNALS {
+            handlers.push(signal_hook::flag::register(signal, Arc::clone(&requested))?);
+        }
+        Ok(Self {
+            requested,
+            #[cfg(unix)]
+            hung_up,
+            #[cfg(unix)]
+            detached: AtomicBool::new(false),
+            handlers,
+        })
+    }
+
+    /// Returns whether a termination signal was received.
+    ///
+    /// If the terminal was hung up, the standard streams are also redirected to `/dev/null`,
+    /// so that writing to them doesn't fail while the deployment is being removed.
+    pub fn requested(&self) -> bool {
+        #[cfg(unix)]
+        if self.hung_up.load(Ordering::Relaxed) && !self.detached.swap(true, Ordering::Relaxed) {
+            let _ = detach_from_terminal();
+        }
+        self.requested.load(Ordering::Relaxed)
+    }
+
+    /// Returns a flag that requests termination when set, as if a termination signal was received.
+    #[cfg_attr(
+        not(target_os = "linux"),
+        allow(dead_code, reason = "only used by the D-Bus status service")
+    )]
+    pub fn flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.requested)
+    }
+
+    /// Blocks until a termination signal is received, or until `timeout` elapses.
+    ///
+    /// Returns `false` if the timeout elapsed.
+    pub fn wait(&self, timeout: Option<Duration>) -> bool {
+        let start = Instant::now();
+        while !self.requested() {
+            if timeout.is_some_and(|timeout| start.elapsed() >= timeout) {
+                return false;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+        true
+    }
+}
+
+impl Drop for Termination {
+    fn drop(&mut self) {
+        for handler in self.handlers.drain(..) {
+            signal_hook::low_level::unregister(handler);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn detach_from_terminal() -> io::Result<()> {
+    let null = File::options().read(true).write(true).open("/dev/null")?;
+    rustix::stdio::dup2_stdin(&null)?;
+    rustix::stdio::dup2_stdout(&null)?;
+    rustix::stdio::dup2_stderr(&null)?;
+    Ok(())
+}
+
+/// Stops processes by sending them SIGTERM, and then SIGKILL if they are still running after [`KILL_TIMEOUT`].
+#[derive(Default)]
+pub struct Stopper {
+    started: Option<Instant>,
+    terminated: HashSet<i32>,
+}
+
+impl Stopper {
+    /// Signals the processes with the specified PIDs, according to how long ago the first call was made.
+    ///
+    /// Meant to be called repeatedly until the processes have exited.
+    pub fn stop(&mut self, pids: impl IntoIterator<Item = i32>) {
+        let kill = self.started.get_or_insert_with(Instant::now).elapsed() > KILL_TIMEOUT;
+        for pid in pids.into_iter().filter(|&pid| pid > 0) {
+            if kill {
+                signal_process(pid, true);
+            } else if self.terminated.insert(pid) {
+                signal_process(pid, false);
+            }
+        }
+    }
+}
+
+/// Sends SIGKILL to the process if `kill` is `true`, or SIGTERM otherwise.
+#[cfg(unix)]
+fn signal_process(pid: i32, kill: bool) {
+    if let Some(pid) = Pid::from_raw(pid) {
+        let _ = kill_process(pid, if kill { Signal::KILL } else { Signal::TERM });
+    }
+}
+
+/// Ends the process if `kill` is `true`, or asks it to close otherwise.
+#[cfg(windows)]
+fn signal_process(pid: i32, kill: bool) {
+    let mut command = Command::new("taskkill");
+    command.arg("/PID").arg(pid.to_string());
+    if kill {
+        command.arg("/F");
+    }
+    let _ = command.stdout(Stdio::null()).stderr(Stdio::null()).status();
+}
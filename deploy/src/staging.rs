@@ -13,72 +13,348 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+use std::collections::HashMap;
+use std::convert::Infallible;
 use std::fs;
 use std::io;
-use std::iter;
-use std::os::unix::fs::symlink;
-use std::path::PathBuf;
+use std::os::fd::{AsFd, OwnedFd};
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::path::{Path, PathBuf};
 
+use rustix::fs::{CWD, FileType, Mode, OFlags, makedev, mkdirat, mknodat, openat, symlinkat};
+use rustix::io::Errno;
 use thiserror::Error;
+use tracing::{info_span, warn};
 
-use mmm_core::file_tree::{FileTree, ModVec, TreeNodeKind};
+use mmm_core::file_tree::{FileTree, ModVec};
 use mmm_core::instance::Instance;
 
+use crate::deployment::StagingKind;
 use crate::instance::DeployInstance;
-use crate::mount::{TempMount, TempMountCreationError};
+use crate::mount::{TempMount, TempMountCreationError, TempMountUnmountError, TmpfsSize};
+use crate::progress::Progress;
+use crate::walk::{DeployNode, resolve_mount_target, walk_tree};
+
+/// Name of the directory, in the instance directory, that contains the persistent staging trees of each profile.
+pub const STAGING_DIR: &str = "staging";
+
+const ROOT_LAYER_DIR: &str = "root";
+const TARGETS_DIR: &str = "targets";
+
+/// A directory containing symlinks to the mod files, with one directory for the deployment root
+/// and one for each [mount target](mmm_core::instance::MountTarget).
+#[derive(Debug)]
+pub enum StagingTree {
+    Tmpfs(TempMount),
+    Persistent(PathBuf),
+}
+
+impl StagingTree {
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Tmpfs(mount) => mount.path(),
+            Self::Persistent(path) => path,
+        }
+    }
+
+    /// Returns the directory with the files to deploy to the specified mount target,
+    /// or to the deployment root if `target` is `None`.
+    pub fn layer(&self, target: Option<usize>) -> PathBuf {
+        self.path().join(layer_path(target))
+    }
+
+    /// Unmounts the staging tree, if it is stored in memory. Persistent staging trees are kept for the next run.
+    pub fn close(self) -> Result<(), TempMountUnmountError> {
+        match self {
+            Self::Tmpfs(mount) => mount.unmount(),
+            Self::Persistent(_) => Ok(()),
+        }
+    }
+}
+
+/// Returns the path of a layer directory relative to the root of the staging tree.
+fn layer_path(target: Option<usize>) -> PathBuf {
+    match target {
+        None => PathBuf::from(ROOT_LAYER_DIR),
+        Some(i) => Path::new(TARGETS_DIR).join(i.to_string()),
+    }
+}
+
+/// An entry of the staging tree, relative to its root.
+type StagedEntry = (PathBuf, StagedKind);
+
+enum StagedKind<P = PathBuf> {
+    Dir,
+    /// A symlink to the file of the winning mod.
+    Symlink(P),
+    /// An overlayfs whiteout, which hides the file or directory of the same name in the lower layers.
+    Whiteout,
+}
+
+impl StagedKind {
+    fn as_deref(&self) -> StagedKind<&Path> {
+        match self {
+            Self::Dir => StagedKind::Dir,
+            Self::Symlink(target) => StagedKind::Symlink(target),
+            Self::Whiteout => StagedKind::Whiteout,
+        }
+    }
+}
 
 pub fn build_staging_tree(
     tree: &FileTree<ModVec>,
     instance: &DeployInstance,
-) -> Result<TempMount, StagingTreeBuildError> {
-    let staging_dir = TempMount::new()?;
-
-    let mut ancestors = Vec::new();
-    for node in tree.root().expect("has root node").traverse_pre_order().skip(1) {
-        ancestors.extend(node.ancestors());
-        let relative_path: PathBuf = ancestors
-            .iter()
-            .rev()
-            .skip(1)
-            .chain(iter::once(&node))
-            .map(|node| &node.data().name)
-            .collect();
-        ancestors.clear();
-        let staging_path = staging_dir.path().join(&relative_path);
-
-        match &node.data().kind {
-            TreeNodeKind::Dir => {
-                fs::create_dir(&staging_path)
-                    .map_err(|source| StagingTreeBuildError::Mkdir { path: staging_path, source })?;
+    kind: StagingKind,
+    progress: &Progress,
+) -> Result<StagingTree, StagingTreeBuildError> {
+    let _span = info_span!("staging", kind = ?kind).entered();
+    let result = populate_staging_tree(&staged_entries(tree, instance), instance, kind, progress);
+    progress.finish();
+    result
+}
+
+fn populate_staging_tree(
+    entries: &[StagedEntry],
+    instance: &DeployInstance,
+    kind: StagingKind,
+    progress: &Progress,
+) -> Result<StagingTree, StagingTreeBuildError> {
+    match kind {
+        StagingKind::Auto | StagingKind::Tmpfs => {
+            let staging = StagingTree::Tmpfs(TempMount::new(Some(tmpfs_size(entries)))?);
+            create_entries(staging.path(), entries, progress)?;
+            Ok(staging)
+        }
+        StagingKind::Profile => {
+            let profile_name = instance
+                .profile_dir_name()
+                .ok_or_else(|| StagingTreeBuildError::ProfileName(instance.profile_name().to_owned()))?;
+            let path = instance.dir().join(STAGING_DIR).join(profile_name);
+            fs::create_dir_all(&path).map_err(|source| StagingTreeBuildError::Mkdir { path: path.clone(), source })?;
+            let wanted = entries
+                .iter()
+                .map(|(path, kind)| (path.as_path(), kind.as_deref()))
+                .collect();
+            prune(&path, Path::new(""), &wanted)?;
+            create_entries(&path, entries, progress)?;
+            Ok(StagingTree::Persistent(path))
+        }
+        StagingKind::Kept => {
+            let path = tempfile::Builder::new()
+                .prefix("mmm-staging-")
+                .tempdir()
+                .map_err(StagingTreeBuildError::KeptDir)?
+                .keep();
+            create_entries(&path, entries, progress)?;
+            Ok(StagingTree::Persistent(path))
+        }
+    }
+}
+
+/// Memory used by the inode and dentry of a staged entry, roughly.
+const ENTRY_OVERHEAD: u64 = 1024;
+/// Length from which tmpfs stores symlink targets in a page of their own, instead of along with the inode.
+const SHORT_SYMLINK_LEN: usize = 128;
+const PAGE_SIZE: u64 = 4096;
+/// Smallest limits of the staging tmpfs, so that small trees don't run into them because of rounding.
+const MIN_TMPFS_SIZE: TmpfsSize = TmpfsSize { bytes: 16 * 1024 * 1024, inodes: 1024 };
+
+/// Returns the limits of a tmpfs that the entries fit in with some headroom,
+/// and warns if storing them is expected to use a large part of the available memory.
+fn tmpfs_size(entries: &[StagedEntry]) -> TmpfsSize {
+    let inodes = entries.len() as u64;
+    let pages = entries
+        .iter()
+        .filter(
+            |(_, kind)| matches!(kind, StagedKind::Symlink(target) if target.as_os_str().len() >= SHORT_SYMLINK_LEN),
+        )
+        .count() as u64;
+
+    let memory = inodes * ENTRY_OVERHEAD + pages * PAGE_SIZE;
+    if let Some(available) = available_memory()
+        && memory > available / 2
+    {
+        warn!(
+            "Staging {inodes} entries in memory needs about {} MiB, but only {} MiB are available. \
+             Consider using --staging profile to stage them on disk instead.",
+            memory / (1024 * 1024),
+            available / (1024 * 1024)
+        );
+    }
+
+    TmpfsSize {
+        bytes: (pages * PAGE_SIZE * 2).max(MIN_TMPFS_SIZE.bytes),
+        inodes: (inodes * 2).max(MIN_TMPFS_SIZE.inodes),
+    }
+}
+
+/// Returns the memory available for starting new applications without swapping, in bytes.
+fn available_memory() -> Option<u64> {
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    let kib: u64 = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemAvailable:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+/// Returns the entries of the staging tree, parents before their children.
+fn staged_entries(tree: &FileTree<ModVec>, instance: &DeployInstance) -> Vec<StagedEntry> {
+    let targets = instance.mount_targets();
+    let mut entries: Vec<StagedEntry> = vec![(layer_path(None), StagedKind::Dir)];
+    if !targets.is_empty() {
+        entries.push((PathBuf::from(TARGETS_DIR), StagedKind::Dir));
+        entries.extend((0..targets.len()).map(|i| (layer_path(Some(i)), StagedKind::Dir)));
+    }
+
+    walk_tree(tree, instance, |relative_path, node| {
+        let (layer, layer_relative_path) = resolve_mount_target(targets, relative_path, &node);
+        if layer_relative_path.as_os_str().is_empty() {
+            // The layer directory itself.
+            return Ok::<_, Infallible>(());
+        }
+
+        let staging_path = layer_path(layer).join(layer_relative_path);
+        match node {
+            DeployNode::Dir => entries.push((staging_path, StagedKind::Dir)),
+            DeployNode::File { source_path } => entries.push((staging_path, StagedKind::Symlink(source_path))),
+            DeployNode::Deleted => entries.push((staging_path, StagedKind::Whiteout)),
+        }
+        Ok(())
+    })
+    .unwrap_or_else(|never| match never {});
+
+    entries
+}
+
+/// Creates the entries that don't exist yet under `root`.
+///
+/// Entries are created relative to their parent directory, which is kept open while its children are created,
+/// so that their full path doesn't need to be built and looked up for each one.
+fn create_entries(root: &Path, entries: &[StagedEntry], progress: &Progress) -> Result<(), StagingTreeBuildError> {
+    let root_fd = open_dir(CWD, root)
+        .map_err(|source| StagingTreeBuildError::Open { path: root.to_owned(), source: source.into() })?;
+    // The directories from the root to the parent of the last created entry.
+    let mut open_dirs: Vec<(&Path, OwnedFd)> = vec![(Path::new(""), root_fd)];
+
+    for (i, (relative_path, kind)) in entries.iter().enumerate() {
+        progress.staged(i + 1, entries.len());
+        let parent = relative_path.parent().expect("entries are under the root");
+        while !parent.starts_with(open_dirs.last().expect("root stays open").0) {
+            open_dirs.pop();
+        }
+        let (open_path, open_fd) = open_dirs.last().expect("root stays open");
+        if *open_path != parent {
+            let fd = open_dir(open_fd, parent.strip_prefix(open_path).expect("checked above"))
+                .map_err(|source| StagingTreeBuildError::Open { path: root.join(parent), source: source.into() })?;
+            open_dirs.push((parent, fd));
+        }
+        let parent_fd = &open_dirs.last().expect("root stays open").1;
+
+        let name = relative_path.file_name().expect("entries have a name");
+        let result = match kind {
+            StagedKind::Dir => mkdirat(parent_fd, name, Mode::RWXU | Mode::RWXG | Mode::RWXO),
+            StagedKind::Symlink(source_path) => symlinkat(source_path, parent_fd, name),
+            // Whiteouts are character devices with device number 0:0. Creating them in an unprivileged
+            // user namespace requires Linux 5.8 or newer.
+            StagedKind::Whiteout => mknodat(parent_fd, name, FileType::CharacterDevice, Mode::empty(), makedev(0, 0)),
+        };
+        let source = match result {
+            // Left over from a previous run, and already checked by `prune`.
+            Ok(()) | Err(Errno::EXIST) => continue,
+            Err(errno) => io::Error::from(errno),
+        };
+        let path = root.join(relative_path);
+        return Err(match kind {
+            StagedKind::Dir => StagingTreeBuildError::Mkdir { path, source },
+            StagedKind::Symlink(source_path) => StagingTreeBuildError::Symlink {
+                source_path: source_path.clone(),
+                link_path: path,
+                source,
+            },
+            StagedKind::Whiteout => StagingTreeBuildError::Whiteout { path, source },
+        });
+    }
+    Ok(())
+}
+
+/// Opens the directory at `path`, relative to `dirfd`, without following a symlink in its last component.
+fn open_dir<Fd: AsFd>(dirfd: Fd, path: &Path) -> Result<OwnedFd, Errno> {
+    openat(
+        dirfd,
+        path,
+        OFlags::RDONLY | OFlags::DIRECTORY | OFlags::NOFOLLOW | OFlags::CLOEXEC,
+        Mode::empty(),
+    )
+}
+
+/// Returns whether `file_type` and `path` describe an overlayfs whiteout.
+fn is_whiteout(file_type: fs::FileType, path: &Path) -> bool {
+    file_type.is_char_device() && fs::symlink_metadata(path).is_ok_and(|metadata| metadata.rdev() == 0)
+}
+
+/// Removes the entries under `root.join(relative_dir)` that aren't in `wanted`, or that differ from it.
+fn prune(
+    root: &Path,
+    relative_dir: &Path,
+    wanted: &HashMap<&Path, StagedKind<&Path>>,
+) -> Result<(), StagingTreeBuildError> {
+    let dir = root.join(relative_dir);
+    let read_dir = fs::read_dir(&dir).map_err(|source| StagingTreeBuildError::ReadDir { path: dir.clone(), source })?;
+    for entry in read_dir {
+        let entry = entry.map_err(|source| StagingTreeBuildError::ReadDir { path: dir.clone(), source })?;
+        let path = entry.path();
+        let relative_path = relative_dir.join(entry.file_name());
+        let file_type = entry
+            .file_type()
+            .map_err(|source| StagingTreeBuildError::ReadDir { path: path.clone(), source })?;
+
+        let keep = match wanted.get(relative_path.as_path()) {
+            Some(StagedKind::Dir) => file_type.is_dir(),
+            Some(StagedKind::Symlink(target)) => {
+                file_type.is_symlink() && fs::read_link(&path).is_ok_and(|link| link == *target)
             }
-            TreeNodeKind::File(providing_mods) => {
-                let mod_index = *providing_mods
-                    .first()
-                    .expect("files are always provided by at least one mod");
-                let mod_decl = &instance.mods()[mod_index];
-                let source_path = instance
-                    .mod_dir(mod_decl)
-                    .expect("separators don't have files")
-                    .join(&relative_path);
-
-                symlink(&source_path, &staging_path).map_err(|source| StagingTreeBuildError::Symlink {
-                    source_path,
-                    link_path: staging_path,
-                    source,
-                })?;
+            Some(StagedKind::Whiteout) => is_whiteout(file_type, &path),
+            None => false,
+        };
+        if keep {
+            if file_type.is_dir() {
+                prune(root, &relative_path, wanted)?;
             }
+            continue;
         }
+        let result = if file_type.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        result.map_err(|source| StagingTreeBuildError::Remove { path, source })?;
     }
-
-    Ok(staging_dir)
+    Ok(())
 }
 
 #[derive(Debug, Error)]
 pub enum StagingTreeBuildError {
+    #[error("failed to create directory to stage mod files in")]
+    KeptDir(#[source] io::Error),
     #[error("failed to create directory '{path}'")]
     Mkdir { path: PathBuf, source: io::Error },
+    #[error("failed to open directory '{path}'")]
+    Open { path: PathBuf, source: io::Error },
+    #[error("profile name '{0}' can't be used as a directory name")]
+    ProfileName(String),
+    #[error("failed to read directory '{path}'")]
+    ReadDir { path: PathBuf, source: io::Error },
+    #[error("failed to remove '{path}'")]
+    Remove { path: PathBuf, source: io::Error },
     #[error("failed to create symlink '{link_path}' that points to '{source_path}'")]
     Symlink { source_path: PathBuf, link_path: PathBuf, source: io::Error },
     #[error("failed to create temporary directory to stage mod files in")]
     TempDir(#[from] TempMountCreationError),
+    #[error("failed to create whiteout '{path}'")]
+    Whiteout { path: PathBuf, source: io::Error },
 }
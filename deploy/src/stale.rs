@@ -0,0 +1,184 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Detection of overlays left mounted over the game directory by a session that crashed.
+
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, bail};
+use rustix::mount::{UnmountFlags, unmount};
+use tracing::{info, warn};
+
+use crate::caps::ElevatedCaps;
+
+/// File system types of the overlays mounted by the overlay backend.
+const OVERLAY_FS_TYPES: &[&str] = &["overlay", "fuse.fuse-overlayfs"];
+
+/// An entry of `/proc/self/mountinfo`.
+#[derive(Debug)]
+struct MountInfo {
+    mount_point: PathBuf,
+    fs_type: String,
+    super_options: String,
+}
+
+/// What to do with an overlay left over from a previous session.
+enum StaleOverlayAction {
+    Remount,
+    Reuse,
+    Abort,
+}
+
+/// Checks whether an overlay is already mounted over `game_path`, and if so, asks the user whether to unmount it
+/// and deploy again, or to reuse it. Returns `true` if the existing overlay should be reused.
+pub fn handle_stale_overlays(game_path: &Path) -> anyhow::Result<bool> {
+    let overlays = overlays_at(game_path).context("failed to read mount table")?;
+    if overlays.is_empty() {
+        return Ok(false);
+    }
+
+    warn!(
+        "An overlay from a previous session is still mounted over '{}'",
+        game_path.display()
+    );
+    if !io::stdin().is_terminal() {
+        bail!("game directory already has an overlay mounted over it, unmount it with `umount` and try again");
+    }
+    match prompt().context("failed to read answer")? {
+        StaleOverlayAction::Remount => {
+            unmount_overlays(game_path, overlays)?;
+            Ok(false)
+        }
+        StaleOverlayAction::Reuse => Ok(true),
+        StaleOverlayAction::Abort => bail!("aborted by the user"),
+    }
+}
+
+fn prompt() -> io::Result<StaleOverlayAction> {
+    print!("Unmount it and deploy again [r], reuse it [u], or abort [A]? ");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(match answer.trim().to_ascii_lowercase().as_str() {
+        "r" => StaleOverlayAction::Remount,
+        "u" => StaleOverlayAction::Reuse,
+        _ => StaleOverlayAction::Abort,
+    })
+}
+
+/// Unmounts the overlays over `game_path`, topmost first, along with the staging tmpfs they were built from.
+fn unmount_overlays(game_path: &Path, mut overlays: Vec<MountInfo>) -> anyhow::Result<()> {
+    while let Some(overlay) = overlays.pop() {
+        if overlay.fs_type == "overlay" {
+            let _caps = ElevatedCaps::raise();
+            unmount(game_path, UnmountFlags::DETACH | UnmountFlags::NOFOLLOW)
+                .with_context(|| format!("failed to unmount overlay at '{}'", game_path.display()))?;
+        } else {
+            let status = Command::new("fusermount3")
+                .arg("-u")
+                .arg("-z")
+                .arg(game_path)
+                .status()
+                .or_else(|_| Command::new("fusermount").arg("-u").arg("-z").arg(game_path).status())
+                .context("failed to run fusermount")?;
+            if !status.success() {
+                bail!("fusermount failed ({status})");
+            }
+        }
+        info!("Unmounted stale overlay over '{}'", game_path.display());
+
+        if let Some(staging_dir) = staging_dir(&overlay) {
+            let unmounted = {
+                let _caps = ElevatedCaps::raise();
+                unmount(&staging_dir, UnmountFlags::DETACH | UnmountFlags::NOFOLLOW).is_ok()
+            };
+            if unmounted {
+                let _ = fs::remove_dir(&staging_dir);
+                info!("Removed stale staging tree '{}'", staging_dir.display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the temporary staging directory the overlay's first lower directory is in, if it is one.
+fn staging_dir(overlay: &MountInfo) -> Option<PathBuf> {
+    let lower = overlay
+        .super_options
+        .split(',')
+        .find_map(|option| option.strip_prefix("lowerdir="))?
+        .split(':')
+        .next()?;
+    Path::new(lower)
+        .ancestors()
+        .find(|dir| {
+            dir.file_name().is_some_and(|name| name.as_bytes().starts_with(b"mmm-")) && dir.starts_with(env::temp_dir())
+        })
+        .map(Path::to_owned)
+}
+
+/// Returns the overlays mounted at `path`, from bottom to top.
+fn overlays_at(path: &Path) -> io::Result<Vec<MountInfo>> {
+    let mountinfo = fs::read("/proc/self/mountinfo")?;
+    Ok(mountinfo
+        .split(|&b| b == b'\n')
+        .filter_map(parse_mountinfo_line)
+        .filter(|info| info.mount_point == path && OVERLAY_FS_TYPES.contains(&info.fs_type.as_str()))
+        .collect())
+}
+
+/// Parses a line of `/proc/self/mountinfo`, as documented in proc_pid_mountinfo(5).
+fn parse_mountinfo_line(line: &[u8]) -> Option<MountInfo> {
+    let mut fields = line.split(|&b| b == b' ');
+    let mount_point = fields.nth(4)?;
+    // Skip the mount options and the optional fields, which end with a lone hyphen.
+    let mut fields = fields.skip_while(|&field| field != b"-").skip(1);
+    let fs_type = fields.next()?;
+    let _source = fields.next()?;
+    let super_options = fields.next()?;
+
+    Some(MountInfo {
+        mount_point: PathBuf::from(OsStr::from_bytes(&unescape(mount_point))),
+        fs_type: String::from_utf8_lossy(fs_type).into_owned(),
+        super_options: String::from_utf8_lossy(&unescape(super_options)).into_owned(),
+    })
+}
+
+/// Decodes the octal escapes (such as `\040` for spaces) used in `/proc/self/mountinfo`.
+fn unescape(field: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(field.len());
+    let mut i = 0;
+    while i < field.len() {
+        if field[i] == b'\\'
+            && let Some(code) = field
+                .get(i + 1..i + 4)
+                .and_then(|digits| str::from_utf8(digits).ok())
+                .and_then(|digits| u8::from_str_radix(digits, 8).ok())
+        {
+            output.push(code);
+            i += 4;
+        } else {
+            output.push(field[i]);
+            i += 1;
+        }
+    }
+    output
+}
@@ -0,0 +1,105 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! D-Bus service publishing the status of the active deployment.
+//!
+//! While mod files are deployed, a name starting with [`BUS_NAME_PREFIX`] is owned on the session bus,
+//! with an object at [`OBJECT_PATH`] describing the deployment, so that desktop widgets and the GUI can show
+//! what is deployed, and remove it.
+
+use std::path::Path;
+use std::process;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::SystemTime;
+
+use zbus::blocking::Connection;
+use zbus::blocking::connection::Builder;
+use zbus::interface;
+
+use mmm_core::instance::Instance;
+
+use crate::instance::DeployInstance;
+use crate::signals::Termination;
+
+/// Prefix of the bus names owned by deployments, which end with `.p<PID>`.
+pub const BUS_NAME_PREFIX: &str = "io.github.MonterraByte.mmm.Deployment";
+pub const OBJECT_PATH: &str = "/io/github/MonterraByte/mmm/Deployment";
+
+struct DeploymentStatus {
+    instance_path: String,
+    game_path: String,
+    profile: String,
+    mount_time: u64,
+    termination: Arc<AtomicBool>,
+}
+
+#[interface(name = "io.github.MonterraByte.mmm.Deployment")]
+impl DeploymentStatus {
+    #[zbus(property)]
+    fn instance_path(&self) -> String {
+        self.instance_path.clone()
+    }
+
+    #[zbus(property)]
+    fn game_path(&self) -> String {
+        self.game_path.clone()
+    }
+
+    #[zbus(property)]
+    fn profile(&self) -> String {
+        self.profile.clone()
+    }
+
+    /// When the deployment was created, in seconds since the Unix epoch.
+    #[zbus(property)]
+    fn mount_time(&self) -> u64 {
+        self.mount_time
+    }
+
+    #[zbus(property)]
+    #[allow(clippy::unused_self, reason = "required by zbus")]
+    fn pid(&self) -> u32 {
+        process::id()
+    }
+
+    /// Removes the deployment, stopping the game if it is running, as if SIGTERM was received.
+    fn unmount(&self) {
+        self.termination.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Keeps the status of the deployment published until dropped.
+pub struct StatusService {
+    _connection: Connection,
+}
+
+/// Publishes the status of the deployment of `instance` to `game_path` on the session bus.
+pub fn publish(instance: &DeployInstance, game_path: &Path, termination: &Termination) -> zbus::Result<StatusService> {
+    let status = DeploymentStatus {
+        instance_path: instance.dir().to_string_lossy().into_owned(),
+        game_path: game_path.to_string_lossy().into_owned(),
+        profile: instance.profile_name().to_owned(),
+        mount_time: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs()),
+        termination: termination.flag(),
+    };
+    let connection = Builder::session()?
+        .name(format!("{BUS_NAME_PREFIX}.p{}", process::id()))?
+        .serve_at(OBJECT_PATH, status)?
+        .build()?;
+    Ok(StatusService { _connection: connection })
+}
@@ -0,0 +1,120 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Launching games through Steam.
+//!
+//! Steam starts the game in its own process tree, so the game is found by looking for processes
+//! running from within the game directory, and waited on by polling.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+use tracing::info;
+
+use crate::signals::{Stopper, Termination};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// How long to wait for the game to start. Steam may need to update the game or set up Proton first.
+const START_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Asks Steam to launch the game with the specified app ID, and waits for it to exit.
+///
+/// If termination is requested, the game processes are stopped.
+pub fn launch_and_wait(app_id: u32, game_path: &Path, termination: &Termination) -> Result<(), SteamLaunchError> {
+    launch(app_id)?;
+    info!("Waiting for Steam to start the game");
+
+    let start = Instant::now();
+    while game_processes(game_path)?.is_empty() {
+        if termination.requested() {
+            return Ok(());
+        }
+        if start.elapsed() > START_TIMEOUT {
+            return Err(SteamLaunchError::Timeout);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    info!("Waiting for the game to exit");
+    let mut stopper = Stopper::default();
+    loop {
+        let pids = game_processes(game_path)?;
+        if pids.is_empty() {
+            return Ok(());
+        }
+        if termination.requested() {
+            stopper.stop(pids.into_iter().filter_map(|pid| i32::try_from(pid).ok()));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn launch(app_id: u32) -> Result<(), SteamLaunchError> {
+    let child = Command::new("steam")
+        .arg("-applaunch")
+        .arg(app_id.to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    match child {
+        Ok(mut child) => {
+            // If Steam wasn't running, this process becomes the Steam client, so don't block on it.
+            let _ = thread::spawn(move || child.wait());
+            Ok(())
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            // Flatpak and other sandboxed installs don't put `steam` in PATH, but register the URL handler.
+            Command::new("xdg-open")
+                .arg(format!("steam://rungameid/{app_id}"))
+                .status()
+                .map_err(SteamLaunchError::Spawn)?;
+            Ok(())
+        }
+        Err(err) => Err(SteamLaunchError::Spawn(err)),
+    }
+}
+
+/// Returns the PIDs of processes whose executable or working directory is within `game_path`.
+pub fn game_processes(game_path: &Path) -> io::Result<Vec<u32>> {
+    let mut pids = Vec::new();
+    for entry in fs::read_dir("/proc")? {
+        let entry = entry?;
+        let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse().ok()) else {
+            continue;
+        };
+        let path = entry.path();
+        let in_game_dir = |link: &str| fs::read_link(path.join(link)).is_ok_and(|target| target.starts_with(game_path));
+        if in_game_dir("exe") || in_game_dir("cwd") {
+            pids.push(pid);
+        }
+    }
+    Ok(pids)
+}
+
+#[derive(Debug, Error)]
+pub enum SteamLaunchError {
+    #[error("failed to list running processes")]
+    ProcessList(#[from] io::Error),
+    #[error("failed to run Steam")]
+    Spawn(#[source] io::Error),
+    #[error("the game did not start within {} seconds", START_TIMEOUT.as_secs())]
+    Timeout,
+}
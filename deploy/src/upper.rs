@@ -0,0 +1,108 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Writable upper layer of the overlay mount, which captures files created or modified by the game.
+
+use std::fs;
+use std::io;
+use std::iter;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use mmm_core::instance::Instance;
+
+use crate::deployment::UpperLayerKind;
+use crate::instance::DeployInstance;
+use crate::mount::{TempMount, TempMountCreationError, TempMountUnmountError};
+
+/// Name of the directory, in the instance directory, that contains the persistent upper layers of each profile.
+pub const OVERLAY_DIR: &str = "overlay";
+
+const UPPER_DIR: &str = "upper";
+const WORK_DIR: &str = "work";
+const TARGETS_DIR: &str = "targets";
+
+/// Upper and work directories of the overlay mounts of the deployment root and of each mount target.
+///
+/// Each pair shares a parent directory, as overlayfs requires them to be on the same filesystem.
+#[derive(Debug)]
+pub enum UpperLayer {
+    Tmpfs(TempMount),
+    Persistent(PathBuf),
+}
+
+impl UpperLayer {
+    pub fn new(kind: UpperLayerKind, instance: &DeployInstance) -> Result<Self, UpperLayerCreationError> {
+        let layer = match kind {
+            UpperLayerKind::Tmpfs => Self::Tmpfs(TempMount::new(None)?),
+            UpperLayerKind::Profile => {
+                let profile_name = instance
+                    .profile_dir_name()
+                    .ok_or_else(|| UpperLayerCreationError::ProfileName(instance.profile_name().to_owned()))?;
+                Self::Persistent(instance.dir().join(OVERLAY_DIR).join(profile_name))
+            }
+        };
+
+        for target in iter::once(None).chain((0..instance.mount_targets().len()).map(Some)) {
+            for dir in [layer.upper_dir(target), layer.work_dir(target)] {
+                fs::create_dir_all(&dir).map_err(|source| UpperLayerCreationError::Mkdir { path: dir, source })?;
+            }
+        }
+        Ok(layer)
+    }
+
+    fn base_dir(&self) -> &Path {
+        match self {
+            Self::Tmpfs(mount) => mount.path(),
+            Self::Persistent(path) => path,
+        }
+    }
+
+    fn target_dir(&self, target: Option<usize>) -> PathBuf {
+        match target {
+            None => self.base_dir().to_owned(),
+            Some(i) => self.base_dir().join(TARGETS_DIR).join(i.to_string()),
+        }
+    }
+
+    /// Returns the upper directory of the specified mount target, or of the deployment root if `target` is `None`.
+    pub fn upper_dir(&self, target: Option<usize>) -> PathBuf {
+        self.target_dir(target).join(UPPER_DIR)
+    }
+
+    /// Returns the work directory of the specified mount target, or of the deployment root if `target` is `None`.
+    pub fn work_dir(&self, target: Option<usize>) -> PathBuf {
+        self.target_dir(target).join(WORK_DIR)
+    }
+
+    /// Unmounts the upper layer, if it is stored in memory, discarding its contents.
+    pub fn close(self) -> Result<(), TempMountUnmountError> {
+        match self {
+            Self::Tmpfs(mount) => mount.unmount(),
+            Self::Persistent(_) => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum UpperLayerCreationError {
+    #[error("failed to create directory '{path}'")]
+    Mkdir { path: PathBuf, source: io::Error },
+    #[error("profile name '{0}' can't be used as a directory name")]
+    ProfileName(String),
+    #[error("failed to create temporary directory for the upper layer")]
+    TempDir(#[from] TempMountCreationError),
+}
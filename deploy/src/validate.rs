@@ -0,0 +1,186 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Checks run before deploying, so that broken mods are reported up front, instead of when the game fails to load
+//! one of their files.
+
+use std::convert::Infallible;
+use std::env;
+use std::fmt;
+#[cfg(windows)]
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, bail};
+#[cfg(unix)]
+use rustix::fs::{Access, access};
+use thiserror::Error;
+use tracing::{error, warn};
+
+use mmm_core::file_tree::{FileTree, ModVec};
+use mmm_core::instance::Instance;
+
+use crate::instance::DeployInstance;
+use crate::progress::Progress;
+use crate::walk::{DeployNode, build_file_tree, walk_tree};
+
+/// A problem that prevents the mod files from being deployed correctly.
+#[derive(Debug)]
+pub enum Problem {
+    /// An enabled mod's directory doesn't exist.
+    MissingModDir { name: String, path: PathBuf },
+    /// A file that would be deployed can't be read.
+    UnreadableFile { path: PathBuf, source_path: PathBuf, error: io::Error },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingModDir { name, path } => {
+                write!(f, "directory of mod '{name}' is missing ('{}')", path.display())
+            }
+            Self::UnreadableFile { path, source_path, error } => write!(
+                f,
+                "'{}' can't be read from '{}': {error}",
+                path.display(),
+                source_path.display()
+            ),
+        }
+    }
+}
+
+/// Builds the tree of files to deploy, after checking that the directory of every enabled mod exists,
+/// and that every file in the tree is readable.
+///
+/// If there are problems, all of them are printed, and an error is returned.
+/// With `skip_missing`, mods whose directory is missing are listed and disabled instead of being a problem.
+pub fn build_validated_file_tree(
+    instance: &mut DeployInstance,
+    skip_missing: bool,
+    progress: &Progress,
+) -> anyhow::Result<FileTree<ModVec>> {
+    let mut problems = missing_mod_dirs(instance);
+
+    // Leave out the missing mods, so that the files of the others can still be checked.
+    let missing: Vec<(String, bool)> = problems
+        .iter()
+        .filter_map(|problem| match problem {
+            Problem::MissingModDir { name, .. } => Some((name.clone(), false)),
+            Problem::UnreadableFile { .. } => None,
+        })
+        .collect();
+    instance.override_mods(&missing)?;
+    if skip_missing && !problems.is_empty() {
+        warn!("Skipping {} mods whose directory is missing:", problems.len());
+        for problem in problems.drain(..) {
+            warn!("  {problem}");
+        }
+    }
+
+    let tree = build_file_tree(instance, progress).context("failed to build tree of mod files")?;
+    problems.extend(unreadable_files(&tree, instance));
+
+    if !problems.is_empty() {
+        error!("Found {} problems with the mod files:", problems.len());
+        for problem in &problems {
+            error!("  {problem}");
+        }
+        if !missing.is_empty() && !skip_missing {
+            error!("Use --skip-missing to deploy without the mods whose directory is missing.");
+        }
+        bail!("mod files failed validation");
+    }
+    Ok(tree)
+}
+
+/// Checks that mod files can be deployed to `destination`, which must be canonical.
+///
+/// Deploying to a directory containing the instance directory, or inside it or a temporary staging tree,
+/// would make the deployment contain itself.
+pub fn check_destination(destination: &Path, instance: &DeployInstance) -> Result<(), UnsafeDestinationError> {
+    let instance_dir = instance
+        .dir()
+        .canonicalize()
+        .unwrap_or_else(|_| instance.dir().to_owned());
+    let temp_dir = env::temp_dir().canonicalize().unwrap_or_else(|_| env::temp_dir());
+    let in_temp_staging = destination
+        .strip_prefix(&temp_dir)
+        .ok()
+        .and_then(|relative_path| relative_path.iter().next())
+        .is_some_and(|name| name.as_encoded_bytes().starts_with(b"mmm-"));
+
+    if destination.parent().is_none() {
+        Err(UnsafeDestinationError::Root)
+    } else if instance_dir.starts_with(destination) {
+        Err(UnsafeDestinationError::ContainsInstance(destination.to_owned()))
+    } else if destination.starts_with(&instance_dir) {
+        Err(UnsafeDestinationError::InsideInstance(destination.to_owned()))
+    } else if in_temp_staging {
+        Err(UnsafeDestinationError::Staging(destination.to_owned()))
+    } else {
+        Ok(())
+    }
+}
+
+fn missing_mod_dirs(instance: &DeployInstance) -> Vec<Problem> {
+    instance
+        .mod_order()
+        .iter()
+        .filter(|entry| entry.enabled)
+        .filter_map(|entry| {
+            let mod_decl = &instance.mods()[entry.mod_index()];
+            let path = instance.mod_dir(mod_decl)?;
+            (!path.is_dir()).then(|| Problem::MissingModDir { name: mod_decl.name().to_string(), path })
+        })
+        .collect()
+}
+
+fn unreadable_files(tree: &FileTree<ModVec>, instance: &DeployInstance) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    walk_tree(tree, instance, |relative_path, node| {
+        if let DeployNode::File { source_path } = node
+            && let Err(error) = check_readable(&source_path)
+        {
+            problems.push(Problem::UnreadableFile { path: relative_path.to_owned(), source_path, error });
+        }
+        Ok::<_, Infallible>(())
+    })
+    .unwrap_or_else(|never| match never {});
+    problems
+}
+
+#[cfg(unix)]
+fn check_readable(path: &Path) -> io::Result<()> {
+    access(path, Access::READ_OK).map_err(io::Error::from)
+}
+
+#[cfg(windows)]
+fn check_readable(path: &Path) -> io::Result<()> {
+    File::open(path).map(drop)
+}
+
+/// Error type returned by [`check_destination`].
+#[derive(Debug, Error)]
+pub enum UnsafeDestinationError {
+    #[error("'{0}' contains the instance directory")]
+    ContainsInstance(PathBuf),
+    #[error("'{0}' is inside the instance directory")]
+    InsideInstance(PathBuf),
+    #[error("mod files can't be deployed to the root directory")]
+    Root,
+    #[error("'{0}' is inside a temporary staging directory")]
+    Staging(PathBuf),
+}
@@ -0,0 +1,171 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Record of where each mod file is deployed, used to check that a deployment is still intact.
+//!
+//! The file map is written to the instance directory when deploying, and removed along with the deployment.
+//! Verifying it catches partial mounts and deployed files that were replaced by other tools.
+//! Overlays mounted in a user namespace aren't visible from outside of it, so they can't be verified.
+
+use std::convert::Infallible;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use mmm_core::file_tree::{FileTree, ModVec};
+
+use crate::instance::DeployInstance;
+use crate::link::{checksum_file, path_from_bytes, path_to_bytes};
+use crate::walk::{DeployNode, resolve_mount_target, walk_tree};
+
+/// Name of the file map file, in the instance directory.
+pub const FILE_MAP_FILE: &str = ".deployed-files";
+
+const FILE_MAP_HEADER: &[u8] = b"mmm-files 1";
+
+/// The absolute path every deployed file is available at, along with the path of the mod file it comes from.
+#[derive(Debug)]
+pub struct FileMap {
+    entries: Vec<(PathBuf, PathBuf)>,
+}
+
+impl FileMap {
+    pub fn new(tree: &FileTree<ModVec>, instance: &DeployInstance, game_path: &Path) -> Self {
+        let targets = instance.mount_targets();
+        let mut entries = Vec::new();
+        walk_tree(tree, instance, |relative_path, node| {
+            let (target, target_relative_path) = resolve_mount_target(targets, relative_path, &node);
+            if let DeployNode::File { source_path } = node {
+                let base = target.map_or(game_path, |i| targets[i].destination());
+                entries.push((base.join(target_relative_path), source_path));
+            }
+            Ok::<_, Infallible>(())
+        })
+        .unwrap_or_else(|never| match never {});
+        Self { entries }
+    }
+
+    /// Returns the number of deployed files.
+    pub fn file_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Writes the file map to `path`.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let mut contents = FILE_MAP_HEADER.to_vec();
+        contents.push(b'\n');
+        for (destination, source) in &self.entries {
+            let (destination, source) = (path_to_bytes(destination)?, path_to_bytes(source)?);
+            if destination.contains(&b'\n') || source.contains(&b'\n') {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "paths containing newlines are not supported",
+                ));
+            }
+            contents.extend_from_slice(destination);
+            contents.push(b'\0');
+            contents.extend_from_slice(source);
+            contents.push(b'\n');
+        }
+        File::create(path)?.write_all(&contents)
+    }
+
+    /// Reads the file map at `path`, if it exists.
+    pub fn read(path: &Path) -> Result<Option<Self>, FileMapReadError> {
+        let contents = match fs::read(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(FileMapReadError::Read { path: path.to_owned(), source }),
+        };
+
+        let mut lines = contents.split(|&b| b == b'\n').filter(|line| !line.is_empty());
+        if lines.next() != Some(FILE_MAP_HEADER) {
+            return Err(FileMapReadError::UnknownFormat(path.to_owned()));
+        }
+        let entries = lines
+            .map(|line| {
+                let separator = line.iter().position(|&b| b == b'\0')?;
+                let (destination, source) = (&line[..separator], &line[separator + 1..]);
+                Some((path_from_bytes(destination)?, path_from_bytes(source)?))
+            })
+            .collect::<Option<_>>()
+            .ok_or_else(|| FileMapReadError::Malformed(path.to_owned()))?;
+        Ok(Some(Self { entries }))
+    }
+
+    /// Checks that every file is still available at its destination, and returns those that aren't.
+    pub fn verify(&self) -> Vec<Discrepancy> {
+        self.entries
+            .iter()
+            .filter_map(|(destination, source)| match matches_source(destination, source) {
+                Ok(true) => None,
+                Ok(false) => Some(Discrepancy::Replaced(destination.clone())),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => Some(Discrepancy::Missing(destination.clone())),
+                Err(err) => Some(Discrepancy::Unreadable(destination.clone(), err)),
+            })
+            .collect()
+    }
+}
+
+/// Returns `true` if the file at `destination` is a link to `source`, or has the same contents.
+pub fn matches_source(destination: &Path, source: &Path) -> io::Result<bool> {
+    let metadata = fs::symlink_metadata(destination)?;
+    if metadata.is_symlink() {
+        return Ok(fs::read_link(destination)? == source);
+    }
+    let source_metadata = fs::metadata(source)?;
+    #[cfg(unix)]
+    if metadata.dev() == source_metadata.dev() && metadata.ino() == source_metadata.ino() {
+        return Ok(true);
+    }
+    if metadata.len() != source_metadata.len() {
+        return Ok(false);
+    }
+    Ok(checksum_file(destination)? == checksum_file(source)?)
+}
+
+/// A deployed file that is no longer in place.
+#[derive(Debug)]
+pub enum Discrepancy {
+    Missing(PathBuf),
+    /// The file was replaced, or its contents changed.
+    Replaced(PathBuf),
+    Unreadable(PathBuf, io::Error),
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(path) => write!(f, "missing: {}", path.display()),
+            Self::Replaced(path) => write!(f, "replaced: {}", path.display()),
+            Self::Unreadable(path, err) => write!(f, "unreadable: {} ({err})", path.display()),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FileMapReadError {
+    #[error("file map '{0}' is malformed")]
+    Malformed(PathBuf),
+    #[error("failed to read file map '{path}'")]
+    Read { path: PathBuf, source: io::Error },
+    #[error("file map '{0}' has an unknown format")]
+    UnknownFormat(PathBuf),
+}
@@ -0,0 +1,150 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Walking the merged tree of mod files, to deploy it with any backend.
+
+use std::collections::HashMap;
+use std::iter;
+use std::path::{Path, PathBuf};
+
+use mmm_core::file_tree::{
+    DELETION_MARKER_SUFFIX, FileTree, FileTreeBuilder, IterDirError, ModVec, TreeNodeKind, TreeNodeRef, new_tree,
+};
+use mmm_core::instance::{Instance, ModIndex, MountTarget};
+
+use crate::instance::DeployInstance;
+use crate::progress::Progress;
+
+/// Builds the tree of files of the enabled mods, recording which mods provide each file.
+pub fn build_file_tree(instance: &DeployInstance, progress: &Progress) -> Result<FileTree<ModVec>, IterDirError> {
+    let mut tree = new_tree();
+    let result = FileTreeBuilder::new()
+        .with_counter(progress)
+        .iter_mods(&mut tree, instance);
+    progress.finish();
+    result.map(|()| tree)
+}
+
+/// Returns the index of the mount target that `relative_path` is deployed to, if any,
+/// along with the path relative to that target.
+pub fn resolve_mount_target<'a>(
+    targets: &[MountTarget],
+    relative_path: &'a Path,
+    node: &DeployNode,
+) -> (Option<usize>, &'a Path) {
+    for (i, target) in targets.iter().enumerate() {
+        if let Ok(rest) = relative_path.strip_prefix(target.source())
+            && (matches!(node, DeployNode::Dir) || !rest.as_os_str().is_empty())
+        {
+            return (Some(i), rest);
+        }
+    }
+    (None, relative_path)
+}
+
+/// An entry of the merged mod file tree, as passed to the callback of [`walk_tree`].
+pub enum DeployNode {
+    Dir,
+    /// A file, along with the path to the file of the winning mod.
+    File {
+        source_path: PathBuf,
+    },
+    /// A file or directory hidden by a [deletion marker](mmm_core::file_tree::DELETION_MARKER_SUFFIX).
+    Deleted,
+}
+
+/// Calls `f` with the relative path of every node in the merged mod file tree, parents before their children.
+///
+/// Nodes matching the instance's [exclusions](DeployInstance::exclusions), and their children, are skipped.
+/// Deletion markers are passed as [`DeployNode::Deleted`] with the path of the file they hide,
+/// unless a higher priority mod provides that file, in which case the marker is skipped instead.
+pub fn walk_tree<E>(
+    tree: &FileTree<ModVec>,
+    instance: &DeployInstance,
+    mut f: impl FnMut(&Path, DeployNode) -> Result<(), E>,
+) -> Result<(), E> {
+    let priorities: HashMap<ModIndex, usize> = instance
+        .mod_order()
+        .iter()
+        .enumerate()
+        .map(|(position, entry)| (entry.mod_index(), position))
+        .collect();
+    // Whether `node` takes precedence over the deletion marker provided by `marker_mods`.
+    let overrides_marker = |node: &TreeNodeRef<ModVec>, marker_mods: &ModVec| match &node.data().kind {
+        TreeNodeKind::Dir => true,
+        TreeNodeKind::File(providing_mods) => priorities[&providing_mods[0]] > priorities[&marker_mods[0]],
+    };
+
+    let mut ancestors = Vec::new();
+    let mut excluded_dir: Option<PathBuf> = None;
+    for node in tree.root().expect("has root node").traverse_pre_order().skip(1) {
+        ancestors.extend(node.ancestors());
+        let relative_path: PathBuf = ancestors
+            .iter()
+            .rev()
+            .skip(1)
+            .chain(iter::once(&node))
+            .map(|node| &node.data().name)
+            .collect();
+        ancestors.clear();
+
+        if excluded_dir.as_ref().is_some_and(|dir| relative_path.starts_with(dir)) {
+            continue;
+        }
+        let is_dir = matches!(node.data().kind, TreeNodeKind::Dir);
+        if instance.exclusions().is_excluded(&relative_path, is_dir) {
+            if is_dir {
+                excluded_dir = Some(relative_path);
+            }
+            continue;
+        }
+
+        match &node.data().kind {
+            TreeNodeKind::Dir => f(&relative_path, DeployNode::Dir)?,
+            TreeNodeKind::File(providing_mods) => {
+                let name = node.data().name.as_str();
+                if let Some(target_name) = name.strip_suffix(DELETION_MARKER_SUFFIX) {
+                    if !target_name.is_empty()
+                        && !sibling(&node, target_name).is_some_and(|target| overrides_marker(&target, providing_mods))
+                    {
+                        f(&relative_path.with_file_name(target_name), DeployNode::Deleted)?;
+                    }
+                    continue;
+                }
+                if let Some(marker) = sibling(&node, &format!("{name}{DELETION_MARKER_SUFFIX}"))
+                    && let TreeNodeKind::File(marker_mods) = &marker.data().kind
+                    && !overrides_marker(&node, marker_mods)
+                {
+                    continue;
+                }
+
+                let mod_index = *providing_mods
+                    .first()
+                    .expect("files are always provided by at least one mod");
+                let mod_decl = &instance.mods()[mod_index];
+                let source_path = instance
+                    .mod_file_path(mod_decl, &relative_path)
+                    .expect("files are within the target of the mods providing them");
+                f(&relative_path, DeployNode::File { source_path })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the sibling of `node` with the specified name.
+fn sibling<'a>(node: &TreeNodeRef<'a, ModVec>, name: &str) -> Option<TreeNodeRef<'a, ModVec>> {
+    node.parent()?.children().find(|child| child.data().name == name)
+}
@@ -0,0 +1,97 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Running Windows games with Proton or Wine.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use thiserror::Error;
+
+/// Program used to run a Windows executable.
+#[derive(Debug)]
+pub enum Runner {
+    Proton {
+        /// Directory containing the `proton` script.
+        dir: PathBuf,
+        /// Directory the Wine prefix is stored in, known to Steam as the compatibility data path.
+        compat_data: PathBuf,
+    },
+    Wine {
+        prefix: Option<PathBuf>,
+    },
+}
+
+impl Runner {
+    /// Returns a command that runs `exe` with this runner.
+    pub fn command(&self, exe: &Path) -> Command {
+        match self {
+            Self::Proton { dir, compat_data } => {
+                let mut command = Command::new(dir.join("proton"));
+                command
+                    .arg("run")
+                    .arg(exe)
+                    .env("STEAM_COMPAT_DATA_PATH", compat_data)
+                    .env("WINEPREFIX", compat_data.join("pfx"));
+                if let Some(steam_dir) = steam_dir() {
+                    command.env("STEAM_COMPAT_CLIENT_INSTALL_PATH", steam_dir);
+                }
+                command
+            }
+            Self::Wine { prefix } => {
+                let mut command = Command::new("wine");
+                command.arg(exe);
+                if let Some(prefix) = prefix {
+                    command.env("WINEPREFIX", prefix);
+                }
+                command
+            }
+        }
+    }
+}
+
+/// Returns the directory of the Proton version with the specified name or path.
+///
+/// Names are looked up in Steam's library and in its directory of custom compatibility tools.
+pub fn find_proton(name_or_path: &str) -> Result<PathBuf, FindProtonError> {
+    let path = Path::new(name_or_path);
+    if path.join("proton").is_file() {
+        return Ok(path.to_owned());
+    }
+
+    steam_dir()
+        .into_iter()
+        .flat_map(|steam_dir| {
+            [
+                steam_dir.join("steamapps/common").join(name_or_path),
+                steam_dir.join("compatibilitytools.d").join(name_or_path),
+            ]
+        })
+        .find(|dir| dir.join("proton").is_file())
+        .ok_or_else(|| FindProtonError(name_or_path.to_owned()))
+}
+
+/// Returns the Steam installation directory, if it can be found.
+fn steam_dir() -> Option<PathBuf> {
+    let home = PathBuf::from(env::var_os("HOME")?);
+    [home.join(".steam/steam"), home.join(".local/share/Steam")]
+        .into_iter()
+        .find(|dir| dir.is_dir())
+}
+
+#[derive(Debug, Error)]
+#[error("Proton version '{0}' was not found")]
+pub struct FindProtonError(String);
@@ -0,0 +1,72 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Running the game through wrapper commands, such as `gamemoderun`, `mangohud` or `gamescope`.
+
+use std::process::Command;
+
+use thiserror::Error;
+
+/// A command that runs the program given as its trailing arguments.
+#[derive(Clone, Debug)]
+pub struct Wrapper {
+    program: String,
+    args: Vec<String>,
+}
+
+impl Wrapper {
+    /// Splits `command` into a program and its arguments, like a shell would.
+    pub fn parse(command: &str) -> Result<Self, WrapperParseError> {
+        let mut words = shlex::split(command)
+            .ok_or_else(|| WrapperParseError::Quoting(command.to_owned()))?
+            .into_iter();
+        let program = words.next().ok_or(WrapperParseError::Empty)?;
+        Ok(Self { program, args: words.collect() })
+    }
+}
+
+/// Returns a command that runs `command` through `wrappers`, outermost first.
+///
+/// The environment and working directory of `command` are kept.
+pub fn wrap(command: Command, wrappers: &[Wrapper]) -> Command {
+    let Some((outermost, inner)) = wrappers.split_first() else {
+        return command;
+    };
+
+    let mut wrapped = Command::new(&outermost.program);
+    wrapped.args(&outermost.args);
+    for wrapper in inner {
+        wrapped.arg(&wrapper.program).args(&wrapper.args);
+    }
+    wrapped.arg(command.get_program()).args(command.get_args());
+    for (key, value) in command.get_envs() {
+        match value {
+            Some(value) => wrapped.env(key, value),
+            None => wrapped.env_remove(key),
+        };
+    }
+    if let Some(dir) = command.get_current_dir() {
+        wrapped.current_dir(dir);
+    }
+    wrapped
+}
+
+#[derive(Debug, Error)]
+pub enum WrapperParseError {
+    #[error("wrapper command is empty")]
+    Empty,
+    #[error("wrapper command '{0}' contains unbalanced quotes or a trailing backslash")]
+    Quoting(String),
+}
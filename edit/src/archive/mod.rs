@@ -28,7 +28,7 @@ use std::sync::Arc;
 use camino::{Utf8Path, Utf8PathBuf};
 use compact_str::ToCompactString;
 use foldhash::HashMap;
-use nary_tree::NodeId;
+use nary_tree::{NodeId, RemoveBehavior};
 use thiserror::Error;
 
 use mmm_core::file_tree::{
@@ -259,6 +259,99 @@ impl ExtractSelection {
         &mut self.tree
     }
 
+    /// Moves the contents of a lone top-level directory to the root of the tree, and removes the directory,
+    /// repeating until the root contains something other than a single directory.
+    ///
+    /// Many archives wrap their contents in a directory named after the mod, which is useless once installed.
+    /// Directories with [well-known names](WELL_KNOWN_DIRS) are kept, as they're likely part of the mod's layout.
+    ///
+    /// Returns `true` if any directory was unwrapped.
+    pub fn unwrap_single_top_level_dir(&mut self) -> bool {
+        let mut unwrapped = false;
+
+        loop {
+            let root = self.tree.root().expect("has root node");
+            let root_id = root.node_id();
+            let (Some(first), Some(last)) = (root.first_child(), root.last_child()) else {
+                break;
+            };
+            if first.node_id() != last.node_id()
+                || !matches!(first.data().kind, TreeNodeKind::Dir)
+                || is_well_known_dir(&first.data().name)
+            {
+                break;
+            }
+
+            let dir_id = first.node_id();
+            let children: Vec<NodeId> = first.children().map(|child| child.node_id()).collect();
+            for child in children {
+                self.move_node(child, root_id);
+            }
+            let _ = self.tree.remove(dir_id, RemoveBehavior::DropChildren);
+            unwrapped = true;
+        }
+
+        unwrapped
+    }
+
+    /// Recursively moves the contents of the directory node `from` into the directory node `to`,
+    /// then removes `from`.
+    ///
+    /// Moved files replace files with the same name in `to`.
+    pub(crate) fn merge_dir_into(&mut self, from: NodeId, to: NodeId) {
+        let children: Vec<NodeId> = self
+            .tree
+            .get(from)
+            .expect("node exists")
+            .children()
+            .map(|child| child.node_id())
+            .collect();
+
+        for child in children {
+            let node = self.tree.get(child).expect("node exists");
+            let is_dir = matches!(node.data().kind, TreeNodeKind::Dir);
+            let existing = self
+                .tree
+                .get(to)
+                .expect("node exists")
+                .children()
+                .find(|other| other.data().name == node.data().name)
+                .map(|other| (other.node_id(), matches!(other.data().kind, TreeNodeKind::Dir)));
+
+            match existing {
+                Some((existing, true)) if is_dir => self.merge_dir_into(child, existing),
+                Some((existing, _)) => {
+                    self.remove_subtree(existing);
+                    self.move_node(child, to);
+                }
+                None => self.move_node(child, to),
+            }
+        }
+
+        let _ = self.tree.remove(from, RemoveBehavior::DropChildren);
+    }
+
+    /// Removes the specified node and all of its descendants from the tree.
+    pub(crate) fn remove_subtree(&mut self, id: NodeId) {
+        let removed: Vec<NodeId> = self
+            .tree
+            .get(id)
+            .expect("node exists")
+            .traverse_pre_order()
+            .map(|node| node.node_id())
+            .collect();
+        self.file_map.retain(|_, target| !removed.contains(target));
+        let _ = self.tree.remove(id, RemoveBehavior::DropChildren);
+    }
+
+    fn move_node(&mut self, id: NodeId, new_parent: NodeId) {
+        self.tree
+            .get_mut(id)
+            .expect("node exists")
+            .append_to(new_parent)
+            .expect("new parent is not a descendant of the node");
+    }
+
     /// Looks up the node in the selection tree that corresponds to the node with the specified path
     /// in the original archive tree.
     #[must_use]
@@ -273,3 +366,23 @@ impl ExtractSelection {
             .take_if(|target_node| matches!(target_node.data().kind, TreeNodeKind::File(true)))
     }
 }
+
+/// Names of directories that are commonly found at the root of a mod, compared case-insensitively.
+///
+/// Used by [`ExtractSelection::unwrap_single_top_level_dir`] to avoid unwrapping meaningful directories.
+pub const WELL_KNOWN_DIRS: &[&str] = &[
+    "bin",
+    "data",
+    "interface",
+    "meshes",
+    "music",
+    "plugins",
+    "scripts",
+    "shaders",
+    "sound",
+    "textures",
+];
+
+fn is_well_known_dir(name: &str) -> bool {
+    WELL_KNOWN_DIRS.iter().any(|dir| dir.eq_ignore_ascii_case(name))
+}
@@ -51,7 +51,7 @@ impl ArchiveFormatDef for Zip {
     }
 
     fn ext_is_archive(ext: &str) -> bool {
-        ext == "zip"
+        ext == "zip" || ext == "omod"
     }
 
     fn open(file: File, _: Arc<Path>) -> Result<Box<dyn ArchiveFormat>, anyhow::Error> {
@@ -0,0 +1,118 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Computation of the disk space used by mods.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use foldhash::HashMap;
+use tracing::warn;
+
+/// Disk space used by a directory.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct DiskUsage {
+    /// Total size of the files in the directory, in bytes.
+    pub size: u64,
+    /// Number of files in the directory.
+    pub files: u64,
+}
+
+/// Computes the disk usage of a directory, recursively. Symbolic links are not followed.
+pub fn compute_disk_usage(dir: &Path) -> Result<DiskUsage, io::Error> {
+    let mut usage = DiskUsage::default();
+    let mut dirs = vec![dir.to_owned()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                dirs.push(entry.path());
+            } else {
+                usage.size = usage.size.saturating_add(entry.metadata()?.len());
+                usage.files = usage.files.saturating_add(1);
+            }
+        }
+    }
+
+    Ok(usage)
+}
+
+/// Cache of the disk usage of mod directories.
+///
+/// Entries are invalidated when the modification time of the directory changes.
+/// Note that this time is only updated when entries are added to or removed from the directory itself,
+/// not when files in subdirectories change.
+#[derive(Debug, Default)]
+pub struct DiskUsageCache {
+    entries: HashMap<PathBuf, (SystemTime, DiskUsage)>,
+}
+
+impl DiskUsageCache {
+    /// Returns the cached disk usage of a directory, without checking if it's up to date.
+    #[must_use]
+    pub fn get(&self, dir: &Path) -> Option<DiskUsage> {
+        self.entries.get(dir).map(|(_, usage)| *usage)
+    }
+
+    /// Removes every entry from the cache.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Recomputes the disk usage of every specified directory whose cached entry is missing or out of date.
+    ///
+    /// The lock is only held while accessing the cache, not while computing disk usage,
+    /// so this can be called from a background thread while the cache is being read elsewhere.
+    pub fn refresh(cache: &Mutex<Self>, dirs: impl IntoIterator<Item = PathBuf>) {
+        for dir in dirs {
+            let mtime = match fs::metadata(&dir).and_then(|metadata| metadata.modified()) {
+                Ok(mtime) => mtime,
+                Err(err) => {
+                    if err.kind() != io::ErrorKind::NotFound {
+                        warn!("failed to get modification time of '{}': {}", dir.display(), err);
+                    }
+                    let _ = cache.lock().expect("lock is not poisoned").entries.remove(&dir);
+                    continue;
+                }
+            };
+
+            let is_fresh = cache
+                .lock()
+                .expect("lock is not poisoned")
+                .entries
+                .get(&dir)
+                .is_some_and(|(cached_mtime, _)| *cached_mtime == mtime);
+            if is_fresh {
+                continue;
+            }
+
+            match compute_disk_usage(&dir) {
+                Ok(usage) => {
+                    let _ = cache
+                        .lock()
+                        .expect("lock is not poisoned")
+                        .entries
+                        .insert(dir, (mtime, usage));
+                }
+                Err(err) => warn!("failed to compute disk usage of '{}': {}", dir.display(), err),
+            }
+        }
+    }
+}
@@ -0,0 +1,168 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Library of archives that mods were installed from.
+//!
+//! Archives are stored in the [downloads directory](DOWNLOADS_DIR) of the instance, and mods remember the
+//! archive they were installed from (see [`ModDeclaration::source`](mmm_core::instance::ModDeclaration::source)),
+//! so they can be reinstalled later with [`reinstall_from_download`](crate::install::reinstall_from_download).
+
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use compact_str::{CompactString, format_compact};
+use tempfile::Builder;
+use thiserror::Error;
+
+use mmm_core::instance::ModIndex;
+
+use crate::install::staging::CopyOrMove;
+
+/// Name of the directory in the instance's root directory that contains stored archives.
+pub const DOWNLOADS_DIR: &str = "downloads";
+
+/// An archive in the downloads directory, as returned by
+/// [`EditableInstance::downloads`](crate::EditableInstance::downloads).
+#[derive(Clone, Debug)]
+pub struct Download {
+    pub(crate) name: CompactString,
+    pub(crate) path: PathBuf,
+    pub(crate) size: u64,
+    pub(crate) mods: Vec<ModIndex>,
+}
+
+impl Download {
+    /// Returns the archive's file name.
+    #[must_use]
+    pub const fn name(&self) -> &CompactString {
+        &self.name
+    }
+
+    /// Returns the absolute path to the archive.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the archive's size in bytes.
+    #[must_use]
+    pub const fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the mods that were installed from this archive.
+    #[must_use]
+    pub fn mods(&self) -> &[ModIndex] {
+        &self.mods
+    }
+}
+
+/// Copies or moves an archive into the downloads directory, returning the name it was stored under.
+///
+/// Copies are made with hard links when possible. If a different archive with the same name is already stored,
+/// a counter is added to the name. Archives that are already in the downloads directory are left as is.
+pub fn store_download(
+    downloads_dir: &Path,
+    path: &Path,
+    mode: CopyOrMove,
+) -> Result<CompactString, StoreDownloadError> {
+    let file_name = path
+        .file_name()
+        .and_then(OsStr::to_str)
+        .filter(|name| !name.starts_with('.'))
+        .ok_or(StoreDownloadError::InvalidName)?;
+
+    fs::create_dir_all(downloads_dir).map_err(StoreDownloadError::CreateDir)?;
+    if let Some(parent) = path.parent()
+        && let Ok(parent) = fs::canonicalize(parent)
+        && fs::canonicalize(downloads_dir).is_ok_and(|dir| dir == parent)
+    {
+        return Ok(file_name.into());
+    }
+
+    let name = free_name(downloads_dir, file_name);
+    let dest = downloads_dir.join(&name);
+    match mode {
+        CopyOrMove::Copy => {
+            if fs::hard_link(path, &dest).is_err() {
+                copy_file(downloads_dir, path, &dest)?;
+            }
+        }
+        CopyOrMove::Move => match fs::rename(path, &dest) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::CrossesDevices => {
+                copy_file(downloads_dir, path, &dest)?;
+                fs::remove_file(path).map_err(StoreDownloadError::RemoveSource)?;
+            }
+            Err(err) => return Err(StoreDownloadError::Move(err)),
+        },
+    }
+    Ok(name)
+}
+
+/// Copies `from` to `to` through a temporary file in `dir`, so that partial copies are never visible.
+fn copy_file(dir: &Path, from: &Path, to: &Path) -> Result<(), StoreDownloadError> {
+    let temp_file = Builder::new()
+        .prefix(".part-")
+        .tempfile_in(dir)
+        .map_err(StoreDownloadError::Copy)?;
+    let _ = fs::copy(from, temp_file.path()).map_err(StoreDownloadError::Copy)?;
+    let _ = temp_file
+        .persist_noclobber(to)
+        .map_err(|err| StoreDownloadError::Copy(err.error))?;
+    Ok(())
+}
+
+/// Returns `file_name`, or `file_name` with a counter before its extension, such that no file with that name
+/// exists in `dir`.
+pub(crate) fn free_name(dir: &Path, file_name: &str) -> CompactString {
+    if !dir.join(file_name).exists() {
+        return file_name.into();
+    }
+
+    let (stem, ext) = match file_name.rsplit_once('.') {
+        Some((stem, ext)) => match stem.strip_suffix(".tar") {
+            Some(stem) => (stem, format_compact!(".tar.{ext}")),
+            None => (stem, format_compact!(".{ext}")),
+        },
+        None => (file_name, CompactString::default()),
+    };
+    (2u32..)
+        .map(|n| format_compact!("{stem} ({n}){ext}"))
+        .find(|name| !dir.join(name.as_str()).exists())
+        .expect("not every name is taken")
+}
+
+/// Returns `true` if `name` can refer to a file directly inside the downloads directory.
+pub(crate) fn is_download_name_valid(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('.') && !name.contains(['\0', '/', '\\'])
+}
+
+/// Error type returned by [`store_download`].
+#[derive(Debug, Error)]
+pub enum StoreDownloadError {
+    #[error("archive file name is not valid UTF-8 or is hidden")]
+    InvalidName,
+    #[error("failed to create downloads directory")]
+    CreateDir(#[source] io::Error),
+    #[error("failed to copy archive")]
+    Copy(#[source] io::Error),
+    #[error("failed to move archive")]
+    Move(#[source] io::Error),
+    #[error("failed to remove archive after copying it")]
+    RemoveSource(#[source] io::Error),
+}
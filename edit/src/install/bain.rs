@@ -0,0 +1,117 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Support for BAIN (Bash Installers) packages.
+//!
+//! A BAIN package is an archive whose top-level directories are numbered options (`00 Core`, `10 Option A`, ...).
+//! Installing it means merging the contents of the chosen options, in order, into the root of the mod.
+//! Loose files at the top level (readmes, wizard scripts, images) are not installed.
+
+use compact_str::CompactString;
+
+use mmm_core::file_tree::{FileTree, TreeNodeKind};
+
+use crate::archive::ExtractSelection;
+
+/// The options of a BAIN package.
+#[derive(Clone, Debug)]
+pub struct BainPackage {
+    options: Vec<CompactString>,
+}
+
+impl BainPackage {
+    /// Checks whether the specified selection tree has the structure of a BAIN package.
+    ///
+    /// The tree is considered a BAIN package if it has at least two top-level directories,
+    /// and every top-level directory is a numbered option.
+    #[must_use]
+    pub fn detect(tree: &FileTree<bool>) -> Option<Self> {
+        let mut options = Vec::new();
+        for child in tree.root().expect("has root node").children() {
+            if !matches!(child.data().kind, TreeNodeKind::Dir) {
+                continue;
+            }
+            if !is_option_name(&child.data().name) {
+                return None;
+            }
+            options.push(child.data().name.clone());
+        }
+
+        if options.len() < 2 {
+            return None;
+        }
+        options.sort_unstable();
+        Some(Self { options })
+    }
+
+    /// Returns the names of the package's options, in installation order.
+    #[must_use]
+    pub fn options(&self) -> &[CompactString] {
+        &self.options
+    }
+
+    /// Returns which options are chosen by default.
+    ///
+    /// Options numbered `00` (usually the core files of the mod) are chosen, and the rest are not.
+    #[must_use]
+    pub fn default_choices(&self) -> Vec<bool> {
+        self.options.iter().map(|name| name.starts_with("00")).collect()
+    }
+
+    /// Rearranges the selection tree so that the chosen options are merged into its root.
+    ///
+    /// `choices` has one element per [option](Self::options). Options that aren't chosen, as well as
+    /// top-level files, are removed from the selection. When several chosen options contain the same file,
+    /// the one from the option that comes last is kept.
+    ///
+    /// This must be called on a selection tree with the same top-level layout as the one passed to
+    /// [`detect`](Self::detect).
+    pub fn apply(&self, selection: &mut ExtractSelection, choices: &[bool]) {
+        assert_eq!(self.options.len(), choices.len());
+
+        let tree = selection.tree();
+        let root_id = tree.root_id().expect("has root node");
+        let top_level: Vec<_> = tree
+            .root()
+            .expect("has root node")
+            .children()
+            .map(|child| (child.node_id(), child.data().name.clone(), child.data().kind))
+            .collect();
+
+        let mut chosen = Vec::new();
+        for (id, name, kind) in top_level {
+            let choice = self
+                .options
+                .iter()
+                .position(|option| *option == name)
+                .and_then(|idx| choices[idx].then_some(idx));
+            match (kind, choice) {
+                (TreeNodeKind::Dir, Some(idx)) => chosen.push((idx, id)),
+                _ => selection.remove_subtree(id),
+            }
+        }
+
+        chosen.sort_unstable_by_key(|(idx, _)| *idx);
+        for (_, id) in chosen {
+            selection.merge_dir_into(id, root_id);
+        }
+    }
+}
+
+/// Returns `true` if the name is a BAIN option name: two digits, followed by a space and the option's name.
+fn is_option_name(name: &str) -> bool {
+    let bytes = name.as_bytes();
+    bytes.len() > 3 && bytes[0].is_ascii_digit() && bytes[1].is_ascii_digit() && bytes[2] == b' '
+}
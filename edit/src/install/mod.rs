@@ -15,4 +15,125 @@
 
 //! Mod installation functionality.
 
+pub mod bain;
+pub mod omod;
 pub mod staging;
+#[cfg(feature = "download")]
+pub mod url;
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use thiserror::Error;
+use tracing::warn;
+
+use mmm_core::file_tree::Counters;
+use mmm_core::instance::{Instance, ModIndex};
+
+use self::bain::BainPackage;
+use self::omod::{OmodError, OmodPackage};
+use self::staging::{StageError, StagedInstall};
+use crate::EditableInstance;
+use crate::archive::{Archive, ExtractSelection, OpenError};
+use crate::instance::{AddStagedModError, ReinstallModError};
+
+/// Installs the archive at the specified path as a new mod with the specified name, returning its index.
+///
+/// Every file in the archive is extracted, after [unwrapping](ExtractSelection::unwrap_single_top_level_dir)
+/// any lone top-level directory. If the archive is a [BAIN package](BainPackage),
+/// its [default options](BainPackage::default_choices) are installed. If it is an [OMOD package](OmodPackage),
+/// all of its files are installed.
+///
+/// This blocks until the archive is fully extracted. Interactive applications should instead
+/// open and [stage](StagedInstall::stage_archive) the archive in the background,
+/// then call [`EditableInstance::add_staged_mod`].
+pub fn install_archive(
+    instance: &mut EditableInstance,
+    path: &Path,
+    name: &str,
+) -> Result<ModIndex, InstallArchiveError> {
+    let staged_mod = stage_archive_with_defaults(&instance.mods_dir(), path)?;
+    instance.add_staged_mod(name, staged_mod).map_err(Into::into)
+}
+
+/// Like [`install_archive`], but installs an archive from the [downloads directory](crate::downloads),
+/// and records it as the new mod's [source](mmm_core::instance::ModDeclaration::source).
+pub fn install_download(
+    instance: &mut EditableInstance,
+    download: &str,
+    name: &str,
+) -> Result<ModIndex, InstallArchiveError> {
+    let path = instance.downloads_dir().join(download);
+    let staged_mod = stage_archive_with_defaults(&instance.mods_dir(), &path)?;
+    let idx = instance.add_staged_mod(name, staged_mod)?;
+    if let Err(err) = instance.set_mod_source(idx, Some(download)) {
+        warn!("not recording source of mod '{}': {}", name, err);
+    }
+    Ok(idx)
+}
+
+/// Replaces the files of the specified mod with the contents of the archive it was installed from,
+/// as with [`install_archive`].
+///
+/// Returns the path to the directory containing the old files, see [`EditableInstance::reinstall_mod`].
+pub fn reinstall_from_download(
+    instance: &mut EditableInstance,
+    idx: ModIndex,
+) -> Result<PathBuf, ReinstallFromDownloadError> {
+    let source = instance.mods()[idx]
+        .source()
+        .ok_or(ReinstallFromDownloadError::NoSource)?;
+    let path = instance.downloads_dir().join(source);
+    if !path.is_file() {
+        return Err(ReinstallFromDownloadError::MissingArchive);
+    }
+
+    let staged_mod = stage_archive_with_defaults(&instance.mods_dir(), &path)?;
+    instance.reinstall_mod(idx, staged_mod).map_err(Into::into)
+}
+
+/// Opens and stages an archive like [`install_archive`] does, without adding it to an instance.
+///
+/// Interactive applications should call this in the background and pass the result to
+/// [`EditableInstance::add_staged_mod`].
+pub fn stage_archive_with_defaults(mods_dir: &Path, path: &Path) -> Result<StagedInstall, InstallArchiveError> {
+    let mut archive = Archive::open(Arc::from(path), Counters::new())?;
+    if omod::is_omod(archive.tree()) {
+        let package = OmodPackage::read(&mut archive)?;
+        return StagedInstall::stage_omod(mods_dir, &mut archive, &package).map_err(Into::into);
+    }
+
+    let mut selection = ExtractSelection::new(&archive);
+    selection.unwrap_single_top_level_dir();
+    if let Some(package) = BainPackage::detect(selection.tree()) {
+        package.apply(&mut selection, &package.default_choices());
+    }
+
+    StagedInstall::stage_archive(mods_dir, &mut archive, &selection).map_err(Into::into)
+}
+
+/// Error type returned by [`install_archive`].
+#[derive(Debug, Error)]
+pub enum InstallArchiveError {
+    #[error("failed to open archive")]
+    Open(#[from] OpenError),
+    #[error("failed to extract archive")]
+    Stage(#[from] StageError),
+    #[error("failed to add installed mod")]
+    Add(#[from] AddStagedModError),
+    #[error("failed to read OMOD package")]
+    Omod(#[from] OmodError),
+}
+
+/// Error type returned by [`reinstall_from_download`].
+#[derive(Debug, Error)]
+pub enum ReinstallFromDownloadError {
+    #[error("mod was not installed from a stored archive")]
+    NoSource,
+    #[error("archive the mod was installed from no longer exists")]
+    MissingArchive,
+    #[error(transparent)]
+    Install(#[from] InstallArchiveError),
+    #[error("failed to replace mod files")]
+    Reinstall(#[from] ReinstallModError),
+}
@@ -0,0 +1,315 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Support for OMOD (Oblivion Mod Manager) packages.
+//!
+//! OMOD packages are ZIP archives, but the mod's files aren't stored as regular archive entries.
+//! Data files are concatenated into a single compressed stream (`data`), and plugins into another (`plugins`),
+//! while `data.crc` and `plugins.crc` list the path and size of each file, in order. The compression method
+//! is stored in `config`, along with the package's name, version and author.
+//!
+//! Packages with an install script choose which files to install by running it, which isn't supported.
+
+use std::fs::{self, File};
+use std::io::{self, Cursor, Read};
+use std::path::{Path, PathBuf};
+
+use camino::{Utf8Component, Utf8PathBuf};
+use lzma_rust2::LzmaReader;
+use nary_tree::NodeId;
+use thiserror::Error;
+use zip::ZipArchive;
+
+use mmm_core::file_tree::{FileTree, TreeNodeKind};
+
+use crate::archive::Archive;
+
+const CONFIG: &str = "config";
+const DATA: &str = "data";
+const DATA_LIST: &str = "data.crc";
+const PLUGINS: &str = "plugins";
+const PLUGINS_LIST: &str = "plugins.crc";
+const SCRIPT: &str = "script";
+
+/// Returns `true` if the specified archive tree has the layout of an OMOD package.
+#[must_use]
+pub fn is_omod(tree: &FileTree) -> bool {
+    root_file(tree, CONFIG).is_some() && root_file(tree, DATA_LIST).is_some()
+}
+
+/// The metadata and file lists of an OMOD package.
+#[derive(Clone, Debug)]
+pub struct OmodPackage {
+    name: String,
+    version: String,
+    author: String,
+    compression: Compression,
+    data_files: Vec<(Utf8PathBuf, u64)>,
+    plugins: Vec<(Utf8PathBuf, u64)>,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Compression {
+    /// A raw LZMA stream, preceded by its properties and uncompressed size.
+    Lzma,
+    /// A ZIP archive with a single entry.
+    Zip,
+}
+
+impl OmodPackage {
+    /// Reads the metadata and file lists of the OMOD package in the specified archive.
+    ///
+    /// Fails with [`OmodError::Script`] if the package has an install script.
+    pub fn read(archive: &mut Archive) -> Result<Self, OmodError> {
+        let tree = archive.tree();
+        let config_id = root_file(tree, CONFIG).ok_or(OmodError::MissingEntry(CONFIG))?;
+        let data_list_id = root_file(tree, DATA_LIST).ok_or(OmodError::MissingEntry(DATA_LIST))?;
+        let plugins_list_id = root_file(tree, PLUGINS_LIST);
+        let script_id = root_file(tree, SCRIPT);
+
+        let ids: Vec<&NodeId> = [
+            Some(&config_id),
+            Some(&data_list_id),
+            plugins_list_id.as_ref(),
+            script_id.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let mut contents = archive.read_files(&ids).map_err(OmodError::Read)?;
+
+        // The first byte of a script is its language, the rest is the script itself.
+        if let Some(script) = script_id.and_then(|id| contents.get(&id))
+            && script.iter().skip(1).any(|b| !b.is_ascii_whitespace())
+        {
+            return Err(OmodError::Script);
+        }
+
+        let config = contents.remove(&config_id).ok_or(OmodError::MissingEntry(CONFIG))?;
+        let (name, version, author, compression) = parse_config(&config)?;
+        let data_list = contents
+            .remove(&data_list_id)
+            .ok_or(OmodError::MissingEntry(DATA_LIST))?;
+        let data_files = parse_file_list(&data_list).ok_or(OmodError::Malformed(DATA_LIST))?;
+        let plugins = match plugins_list_id.and_then(|id| contents.remove(&id)) {
+            Some(list) => parse_file_list(&list).ok_or(OmodError::Malformed(PLUGINS_LIST))?,
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            name,
+            version,
+            author,
+            compression,
+            data_files,
+            plugins,
+        })
+    }
+
+    /// Returns the name of the mod, as set by the package's author.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the version of the mod, as `major.minor`.
+    #[must_use]
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    #[must_use]
+    pub fn author(&self) -> &str {
+        &self.author
+    }
+
+    /// Returns the paths of the files that are installed, relative to the root of the mod.
+    pub fn files(&self) -> impl Iterator<Item = &Utf8PathBuf> {
+        self.data_files.iter().chain(&self.plugins).map(|(path, _)| path)
+    }
+
+    /// Extracts every data file and plugin of the package into the specified directory.
+    pub fn extract(&self, archive: &mut Archive, dir: &Path) -> Result<(), OmodError> {
+        for (entry, files) in [(DATA, &self.data_files), (PLUGINS, &self.plugins)] {
+            if files.is_empty() {
+                continue;
+            }
+            let id = root_file(archive.tree(), entry).ok_or(OmodError::MissingEntry(entry))?;
+            let compressed = archive
+                .read_files(&[&id])
+                .map_err(OmodError::Read)?
+                .remove(&id)
+                .ok_or(OmodError::MissingEntry(entry))?;
+            let decompress_error = |source| OmodError::Decompress { entry, source };
+
+            match self.compression {
+                Compression::Lzma => {
+                    let mut reader =
+                        LzmaReader::new_mem_limit(Cursor::new(compressed), u32::MAX, None).map_err(decompress_error)?;
+                    write_files(&mut reader, entry, files, dir)?;
+                }
+                Compression::Zip => {
+                    let mut zip = ZipArchive::new(Cursor::new(compressed))
+                        .map_err(|err| decompress_error(io::Error::from(err)))?;
+                    let mut reader = zip.by_index(0).map_err(|err| decompress_error(io::Error::from(err)))?;
+                    write_files(&mut reader, entry, files, dir)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the ID of the file with the specified name at the root of the archive tree.
+fn root_file(tree: &FileTree, name: &str) -> Option<NodeId> {
+    tree.root()
+        .expect("has root node")
+        .children()
+        .find(|child| child.data().name == name && matches!(child.data().kind, TreeNodeKind::File(())))
+        .map(|child| child.node_id())
+}
+
+/// Splits the decompressed contents of `entry` into the listed files, writing them to `dir`.
+fn write_files(
+    reader: &mut impl Read,
+    entry: &'static str,
+    files: &[(Utf8PathBuf, u64)],
+    dir: &Path,
+) -> Result<(), OmodError> {
+    for (relative_path, size) in files {
+        let path = dir.join(relative_path);
+        let write_error = |source| OmodError::Write { path: path.clone(), source };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(write_error)?;
+        }
+        let mut file = File::create(&path).map_err(write_error)?;
+        let written = io::copy(&mut reader.by_ref().take(*size), &mut file).map_err(write_error)?;
+        if written != *size {
+            return Err(OmodError::Truncated(entry));
+        }
+    }
+    Ok(())
+}
+
+/// Parses the package's name, version, author and compression method from the `config` entry.
+fn parse_config(config: &[u8]) -> Result<(String, String, String, Compression), OmodError> {
+    let mut reader = BinaryReader(config);
+    let parse = |reader: &mut BinaryReader| {
+        let file_version = reader.u8()?;
+        let name = reader.string()?;
+        let version = format!("{}.{}", reader.i32()?, reader.i32()?);
+        let author = reader.string()?;
+        let _email = reader.string()?;
+        let _website = reader.string()?;
+        let _description = reader.string()?;
+        // The creation time is stored as a .NET `DateTime` since version 2, and as a string before that.
+        if file_version >= 2 {
+            reader.i64()?;
+        } else {
+            reader.string()?;
+        }
+        Some((name, version, author, reader.u8()?))
+    };
+    let (name, version, author, compression) = parse(&mut reader).ok_or(OmodError::Malformed(CONFIG))?;
+    let compression = match compression {
+        0 => Compression::Lzma,
+        1 => Compression::Zip,
+        other => return Err(OmodError::UnknownCompression(other)),
+    };
+    Ok((name, version, author, compression))
+}
+
+/// Parses a list of files (`data.crc` or `plugins.crc`), returning the path and size of each file.
+///
+/// Returns `None` if the list is malformed, or if a path isn't relative to the root of the mod.
+fn parse_file_list(list: &[u8]) -> Option<Vec<(Utf8PathBuf, u64)>> {
+    let mut reader = BinaryReader(list);
+    let mut files = Vec::new();
+    while !reader.0.is_empty() {
+        let path = Utf8PathBuf::from(reader.string()?.replace('\\', "/"));
+        let _crc = reader.u32()?;
+        let size = u64::try_from(reader.i64()?).ok()?;
+        let is_relative =
+            path.components().next().is_some() && path.components().all(|c| matches!(c, Utf8Component::Normal(_)));
+        if !is_relative {
+            return None;
+        }
+        files.push((path, size));
+    }
+    Some(files)
+}
+
+/// Reader for the values written by .NET's `BinaryWriter`, which OBMM uses for its metadata.
+struct BinaryReader<'a>(&'a [u8]);
+
+impl BinaryReader<'_> {
+    fn bytes<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let (bytes, rest) = self.0.split_first_chunk()?;
+        self.0 = rest;
+        Some(*bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes().map(u8::from_le_bytes)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.bytes().map(u32::from_le_bytes)
+    }
+
+    fn i32(&mut self) -> Option<i32> {
+        self.bytes().map(i32::from_le_bytes)
+    }
+
+    fn i64(&mut self) -> Option<i64> {
+        self.bytes().map(i64::from_le_bytes)
+    }
+
+    /// Reads a UTF-8 string, prefixed by its length in bytes, encoded 7 bits at a time.
+    fn string(&mut self) -> Option<String> {
+        let mut len = 0usize;
+        for shift in (0..35).step_by(7) {
+            let byte = self.u8()?;
+            len |= usize::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                let (string, rest) = self.0.split_at_checked(len)?;
+                self.0 = rest;
+                return Some(String::from_utf8_lossy(string).into_owned());
+            }
+        }
+        None
+    }
+}
+
+/// Error type returned by [`OmodPackage::read`] and [`OmodPackage::extract`].
+#[derive(Debug, Error)]
+pub enum OmodError {
+    #[error("failed to decompress the '{entry}' entry")]
+    Decompress { entry: &'static str, source: io::Error },
+    #[error("the '{0}' entry is malformed")]
+    Malformed(&'static str),
+    #[error("the '{0}' entry is missing")]
+    MissingEntry(&'static str),
+    #[error("failed to read package entries")]
+    Read(#[source] anyhow::Error),
+    #[error("packages with an install script are not supported")]
+    Script,
+    #[error("the '{0}' entry is shorter than its file list")]
+    Truncated(&'static str),
+    #[error("unknown compression method {0}")]
+    UnknownCompression(u8),
+    #[error("failed to write '{path}'")]
+    Write { path: PathBuf, source: io::Error },
+}
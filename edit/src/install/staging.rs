@@ -17,17 +17,24 @@
 
 use std::fs;
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use tempfile::TempDir;
 use thiserror::Error;
+use tracing::error;
 
 use crate::archive::{Archive, ExtractSelection};
+use crate::install::omod::OmodPackage;
 
-/// Utility for atomic archive extraction through a temporary directory.
+/// Utility for atomic mod installation through a temporary directory.
 ///
 /// The directory will be deleted when this struct is dropped, unless [`place`](Self::place) is called successfully.
-pub struct StagedInstall(TempDir);
+/// If it was created by [moving](CopyOrMove::Move) a directory, it's moved back to where it came from instead.
+pub struct StagedInstall {
+    dir: TempDir,
+    moved_from: Option<PathBuf>,
+}
 
 impl StagedInstall {
     /// Extracts the specified archive to a temporary directory in the mods directory.
@@ -36,23 +43,153 @@ impl StagedInstall {
         archive: &mut Archive,
         selection: &ExtractSelection,
     ) -> Result<Self, StageError> {
+        fs::create_dir_all(mods_dir).map_err(StageError::CreateStagingDir)?;
         let temp_dir = TempDir::with_prefix_in(".staging-", mods_dir).map_err(StageError::CreateStagingDir)?;
 
         archive
             .extract(temp_dir.path().to_owned(), selection)
             .map_err(StageError::Extract)?;
 
-        Ok(Self(temp_dir))
+        Ok(Self { dir: temp_dir, moved_from: None })
+    }
+
+    /// Extracts the files of the specified OMOD package to a temporary directory in the mods directory.
+    pub fn stage_omod(mods_dir: &Path, archive: &mut Archive, package: &OmodPackage) -> Result<Self, StageError> {
+        fs::create_dir_all(mods_dir).map_err(StageError::CreateStagingDir)?;
+        let temp_dir = TempDir::with_prefix_in(".staging-", mods_dir).map_err(StageError::CreateStagingDir)?;
+
+        package
+            .extract(archive, temp_dir.path())
+            .map_err(|err| StageError::Extract(err.into()))?;
+
+        Ok(Self { dir: temp_dir, moved_from: None })
+    }
+
+    /// Copies or moves the contents of a directory to a temporary directory in the mods directory.
+    ///
+    /// Copying stops with [`StageDirError::Cancelled`] as soon as `cancel` is set.
+    /// When moving across filesystems, the directory is copied, and the source is removed afterwards.
+    pub fn stage_dir(
+        mods_dir: &Path,
+        source: &Path,
+        mode: CopyOrMove,
+        cancel: &AtomicBool,
+    ) -> Result<Self, StageDirError> {
+        if !source.is_dir() {
+            return Err(StageDirError::NotADirectory);
+        }
+
+        fs::create_dir_all(mods_dir).map_err(StageDirError::CreateStagingDir)?;
+        // Otherwise, copying would recurse into the staging directory itself.
+        if let (Ok(mods_dir), Ok(source)) = (mods_dir.canonicalize(), source.canonicalize())
+            && mods_dir.starts_with(source)
+        {
+            return Err(StageDirError::ContainsModsDir);
+        }
+        let temp_dir = TempDir::with_prefix_in(".staging-", mods_dir).map_err(StageDirError::CreateStagingDir)?;
+
+        if mode == CopyOrMove::Move {
+            match fs::rename(source, temp_dir.path()) {
+                Ok(()) => {
+                    return Ok(Self { dir: temp_dir, moved_from: Some(source.to_owned()) });
+                }
+                Err(err) if err.kind() == io::ErrorKind::CrossesDevices => {}
+                Err(err) => return Err(StageDirError::Move(err)),
+            }
+        }
+
+        copy_dir_contents(source, temp_dir.path(), cancel)?;
+
+        if mode == CopyOrMove::Move {
+            fs::remove_dir_all(source).map_err(StageDirError::RemoveSource)?;
+        }
+
+        Ok(Self {
+            dir: temp_dir,
+            moved_from: (mode == CopyOrMove::Move).then(|| source.to_owned()),
+        })
     }
 
     /// Moves the directory containing the extracted files to the specified location.
     pub(crate) fn place(mut self, new_path: &Path) -> Result<(), PlaceError> {
-        fs::rename(self.0.path(), new_path)?;
-        self.0.disable_cleanup(true);
+        fs::rename(self.dir.path(), new_path)?;
+        self.dir.disable_cleanup(true);
+        self.moved_from = None;
         Ok(())
     }
 }
 
+impl Drop for StagedInstall {
+    fn drop(&mut self) {
+        let Some(source) = self.moved_from.take() else {
+            return;
+        };
+        let result = match fs::rename(self.dir.path(), &source) {
+            Err(err) if err.kind() == io::ErrorKind::CrossesDevices => fs::create_dir(&source)
+                .map_err(StageDirError::Copy)
+                .and_then(|()| copy_dir_contents(self.dir.path(), &source, &AtomicBool::new(false))),
+            result => result.map_err(StageDirError::Move),
+        };
+        if let Err(err) = result {
+            self.dir.disable_cleanup(true);
+            error!(
+                "failed to move files back to '{}', they were kept in '{}': {}",
+                source.display(),
+                self.dir.path().display(),
+                err
+            );
+        }
+    }
+}
+
+/// Whether [`StagedInstall::stage_dir`] should copy or move the source directory.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum CopyOrMove {
+    #[default]
+    Copy,
+    Move,
+}
+
+/// Copies the contents of `from` into the existing directory `to`, recursively.
+/// Symbolic links are copied as links, not followed.
+fn copy_dir_contents(from: &Path, to: &Path, cancel: &AtomicBool) -> Result<(), StageDirError> {
+    for entry in fs::read_dir(from).map_err(StageDirError::Copy)? {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(StageDirError::Cancelled);
+        }
+
+        let entry = entry.map_err(StageDirError::Copy)?;
+        let target = to.join(entry.file_name());
+        let file_type = entry.file_type().map_err(StageDirError::Copy)?;
+        if file_type.is_dir() {
+            fs::create_dir(&target).map_err(StageDirError::Copy)?;
+            copy_dir_contents(&entry.path(), &target, cancel)?;
+        } else if file_type.is_symlink() {
+            copy_symlink(&entry.path(), &target).map_err(StageDirError::Copy)?;
+        } else {
+            let _ = fs::copy(entry.path(), &target).map_err(StageDirError::Copy)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+#[cfg(windows)]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    use std::os::windows::fs::FileTypeExt;
+
+    let link_target = fs::read_link(from)?;
+    if fs::symlink_metadata(from)?.file_type().is_symlink_dir() {
+        std::os::windows::fs::symlink_dir(link_target, to)
+    } else {
+        std::os::windows::fs::symlink_file(link_target, to)
+    }
+}
+
 /// Error type returned by [`StagedInstall::stage_archive`].
 #[derive(Debug, Error)]
 pub enum StageError {
@@ -62,6 +199,25 @@ pub enum StageError {
     Extract(#[source] anyhow::Error),
 }
 
+/// Error type returned by [`StagedInstall::stage_dir`].
+#[derive(Debug, Error)]
+pub enum StageDirError {
+    #[error("source is not a directory")]
+    NotADirectory,
+    #[error("source directory contains the mods directory")]
+    ContainsModsDir,
+    #[error("failed to create staging directory")]
+    CreateStagingDir(#[source] io::Error),
+    #[error("failed to move directory")]
+    Move(#[source] io::Error),
+    #[error("failed to copy directory")]
+    Copy(#[source] io::Error),
+    #[error("failed to remove source directory after copying it")]
+    RemoveSource(#[source] io::Error),
+    #[error("operation was cancelled")]
+    Cancelled,
+}
+
 /// Error type returned by [`StagedInstall::place`].
 #[derive(Debug, Error)]
 #[error("failed to move staged mod to its final location")]
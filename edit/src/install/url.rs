@@ -0,0 +1,269 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Downloading archives over HTTP(S).
+
+use std::fmt::Write as _;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+use compact_str::CompactString;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tracing::debug;
+use ureq::Agent;
+
+use mmm_core::instance::ModIndex;
+
+use super::{InstallArchiveError, install_download};
+use crate::EditableInstance;
+use crate::downloads::{free_name, is_download_name_valid};
+
+/// How long to wait for a connection to the server to be established.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long to wait for the server to respond, once the request is sent.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Progress of a [download](download_archive), updated as data is received.
+#[derive(Debug, Default)]
+pub struct DownloadProgress {
+    downloaded: AtomicU64,
+    total: AtomicU64,
+}
+
+impl DownloadProgress {
+    /// Returns the number of bytes downloaded so far, including any resumed partial download.
+    #[must_use]
+    pub fn downloaded(&self) -> u64 {
+        self.downloaded.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total size of the file, if known.
+    #[must_use]
+    pub fn total(&self) -> Option<u64> {
+        match self.total.load(Ordering::Relaxed) {
+            0 => None,
+            total => Some(total),
+        }
+    }
+}
+
+/// A SHA-256 checksum.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Sha256Checksum([u8; 32]);
+
+impl Sha256Checksum {
+    /// Parses a checksum from its hexadecimal representation.
+    pub fn from_hex(hex: &str) -> Result<Self, InvalidChecksumError> {
+        let hex = hex.trim().as_bytes();
+        if hex.len() != 64 {
+            return Err(InvalidChecksumError);
+        }
+
+        let mut bytes = [0; 32];
+        for (byte, pair) in bytes.iter_mut().zip(hex.chunks_exact(2)) {
+            let pair = str::from_utf8(pair).map_err(|_| InvalidChecksumError)?;
+            *byte = u8::from_str_radix(pair, 16).map_err(|_| InvalidChecksumError)?;
+        }
+        Ok(Self(bytes))
+    }
+}
+
+/// Downloads the archive at the specified URL into the downloads directory, returning the name it was stored under.
+///
+/// Data is written to a hidden partial file named after a hash of the URL. If a partial file already exists
+/// (because a previous attempt at downloading the same URL was interrupted), the download is resumed, if the server
+/// supports it. Downloading stops with [`DownloadError::Cancelled`] as soon as `cancel` is set, keeping the partial
+/// file.
+///
+/// Connecting and waiting for the response time out, receiving the body doesn't, since large files can take
+/// arbitrarily long to download.
+pub fn download_archive(
+    url: &str,
+    downloads_dir: &Path,
+    checksum: Option<Sha256Checksum>,
+    progress: &DownloadProgress,
+    cancel: &AtomicBool,
+) -> Result<CompactString, DownloadError> {
+    let file_name = file_name_from_url(url).ok_or(DownloadError::NoFileName)?;
+    fs::create_dir_all(downloads_dir).map_err(DownloadError::File)?;
+    let part_path = downloads_dir.join(partial_file_name(url));
+
+    let agent: Agent = Agent::config_builder()
+        .timeout_connect(Some(CONNECT_TIMEOUT))
+        .timeout_recv_response(Some(RESPONSE_TIMEOUT))
+        .build()
+        .into();
+    let existing = fs::metadata(&part_path).map_or(0, |m| m.len());
+    let mut request = agent.get(url);
+    if existing > 0 {
+        debug!("resuming download of '{}' at byte {}", url, existing);
+        request = request.header("Range", format!("bytes={existing}-"));
+    }
+    let response = match request.call() {
+        // The partial file is already complete, or invalid; start over.
+        Err(ureq::Error::StatusCode(416)) => {
+            fs::remove_file(&part_path).map_err(DownloadError::File)?;
+            agent.get(url).call()?
+        }
+        result => result?,
+    };
+
+    let resumed = existing > 0 && response.status().as_u16() == 206;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(&part_path)
+        .map_err(DownloadError::File)?;
+    let offset = if resumed { existing } else { 0 };
+
+    let length = response
+        .headers()
+        .get("content-length")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    progress.downloaded.store(offset, Ordering::Relaxed);
+    progress
+        .total
+        .store(length.map_or(0, |l| l.saturating_add(offset)), Ordering::Relaxed);
+
+    let mut reader = response.into_body().into_reader();
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(DownloadError::Cancelled);
+        }
+
+        let n = reader.read(&mut buf).map_err(DownloadError::Read)?;
+        if n == 0 {
+            break;
+        }
+        file.write_all(&buf[..n]).map_err(DownloadError::File)?;
+        let _ = progress.downloaded.fetch_add(n as u64, Ordering::Relaxed);
+    }
+    file.sync_all().map_err(DownloadError::File)?;
+    drop(file);
+
+    if let Some(expected) = checksum
+        && hash_file(&part_path).map_err(DownloadError::File)? != expected
+    {
+        let _ = fs::remove_file(&part_path);
+        return Err(DownloadError::ChecksumMismatch);
+    }
+
+    let name = free_name(downloads_dir, &file_name);
+    fs::rename(&part_path, downloads_dir.join(&name)).map_err(DownloadError::File)?;
+    Ok(name)
+}
+
+/// Downloads the archive at the specified URL and installs it as a new mod with the specified name,
+/// returning its index.
+///
+/// This blocks until the archive is downloaded and fully extracted,
+/// see [`download_archive`] and [`install_download`].
+pub fn install_from_url(
+    instance: &mut EditableInstance,
+    url: &str,
+    checksum: Option<Sha256Checksum>,
+    name: &str,
+) -> Result<ModIndex, InstallFromUrlError> {
+    let download = download_archive(
+        url,
+        &instance.downloads_dir(),
+        checksum,
+        &DownloadProgress::default(),
+        &AtomicBool::new(false),
+    )?;
+    install_download(instance, &download, name).map_err(Into::into)
+}
+
+/// Returns the percent-decoded last path segment of the URL, if it's usable as a file name.
+#[must_use]
+pub fn file_name_from_url(url: &str) -> Option<String> {
+    let path = url.split(['?', '#']).next()?;
+    let (_, path) = path.split_once("://")?;
+    let (_, name) = path.rsplit_once('/')?;
+    let name = percent_decode(name)?;
+    is_download_name_valid(&name).then_some(name)
+}
+
+/// Decodes `%XX` escapes, returning `None` if an escape is malformed or the result isn't valid UTF-8.
+fn percent_decode(s: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail.get(..2).filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))?;
+            bytes.push(u8::from_str_radix(str::from_utf8(hex).ok()?, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Returns the name of the partial file of a download, which is derived from the whole URL,
+/// so that a partial file is only ever resumed from the URL it was downloaded from.
+fn partial_file_name(url: &str) -> String {
+    let hash = Sha256::digest(url.as_bytes());
+    let mut name = String::from(".part-");
+    for byte in &hash[..8] {
+        let _ = write!(name, "{byte:02x}");
+    }
+    name
+}
+
+fn hash_file(path: &Path) -> Result<Sha256Checksum, io::Error> {
+    let mut hasher = Sha256::new();
+    let _ = io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(Sha256Checksum(hasher.finalize().into()))
+}
+
+#[derive(Debug, Error)]
+#[error("invalid SHA-256 checksum, expected 64 hexadecimal digits")]
+pub struct InvalidChecksumError;
+
+/// Error type returned by [`download_archive`].
+#[derive(Debug, Error)]
+pub enum DownloadError {
+    #[error("URL does not end with a file name")]
+    NoFileName,
+    #[error("request failed")]
+    Request(#[from] ureq::Error),
+    #[error("failed to receive data")]
+    Read(#[source] io::Error),
+    #[error("failed to write downloaded file")]
+    File(#[source] io::Error),
+    #[error("downloaded file does not match the expected checksum")]
+    ChecksumMismatch,
+    #[error("download was cancelled")]
+    Cancelled,
+}
+
+/// Error type returned by [`install_from_url`].
+#[derive(Debug, Error)]
+pub enum InstallFromUrlError {
+    #[error("failed to download archive")]
+    Download(#[from] DownloadError),
+    #[error("failed to install archive")]
+    Install(#[from] InstallArchiveError),
+}
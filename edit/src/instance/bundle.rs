@@ -0,0 +1,170 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Export and import of whole instances as portable archives.
+//!
+//! A bundle is an uncompressed tar archive with the same layout as an instance directory: the instance data file,
+//! and, optionally, the mods and downloads directories.
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use tar::{Archive, Builder, Header};
+use tempfile::{NamedTempFile, TempDir};
+use thiserror::Error;
+use tracing::trace;
+
+use mmm_core::instance::data::{INSTANCE_DATA_FILE, InstanceData, InstanceDataOpenError};
+use mmm_core::instance::{Instance, MODS_DIR};
+
+use super::{EditableInstance, InstanceOpenError};
+use crate::WriteError;
+use crate::downloads::DOWNLOADS_DIR;
+
+/// What to include in a bundle, besides the instance data, which is always included.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct BundleOptions {
+    /// Include the files of every mod.
+    pub mods: bool,
+    /// Include the archives in the downloads directory.
+    pub downloads: bool,
+}
+
+impl EditableInstance {
+    /// Writes the instance to a bundle at the specified path, replacing it if it exists.
+    ///
+    /// The current instance data is exported, even if it hasn't been saved yet.
+    /// Hidden files in the mods and downloads directories (such as incomplete installations) are skipped.
+    pub fn export_bundle(&self, path: &Path, options: BundleOptions) -> Result<(), ExportBundleError> {
+        let content = self.serialize()?;
+
+        let parent = path
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let temp_file = NamedTempFile::with_prefix_in(".bundle-", parent).map_err(ExportBundleError::Io)?;
+        let mut builder = Builder::new(BufWriter::new(temp_file));
+        builder.follow_symlinks(false);
+
+        let mut header = Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, INSTANCE_DATA_FILE, content.as_slice())
+            .map_err(ExportBundleError::Io)?;
+
+        if options.mods {
+            append_dir_contents(&mut builder, &self.mods_dir(), MODS_DIR)?;
+        }
+        if options.downloads {
+            append_dir_contents(&mut builder, &self.downloads_dir(), DOWNLOADS_DIR)?;
+        }
+
+        let mut writer = builder.into_inner().map_err(ExportBundleError::Io)?;
+        writer.flush().map_err(ExportBundleError::Io)?;
+        let temp_file = writer
+            .into_inner()
+            .map_err(|err| ExportBundleError::Io(err.into_error()))?;
+        temp_file.as_file().sync_all().map_err(ExportBundleError::Io)?;
+        let _ = temp_file
+            .persist(path)
+            .map_err(|err| ExportBundleError::Io(err.error))?;
+
+        trace!("exported instance to '{}'", path.display());
+        Ok(())
+    }
+
+    /// Creates a new instance at `dir` from a bundle, and opens it.
+    ///
+    /// `dir` must not exist yet. The bundle is extracted next to it, and only moved into place once it's verified
+    /// to contain valid instance data.
+    pub fn import_bundle(bundle: &Path, dir: &Path) -> Result<Self, ImportBundleError> {
+        if fs::exists(dir).map_err(ImportBundleError::Io)? {
+            return Err(ImportBundleError::AlreadyExists);
+        }
+
+        let parent = dir
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        fs::create_dir_all(parent).map_err(ImportBundleError::Io)?;
+        let temp_dir = TempDir::with_prefix_in(".import-", parent).map_err(ImportBundleError::Io)?;
+
+        let file = File::open(bundle).map_err(ImportBundleError::Io)?;
+        Archive::new(BufReader::new(file))
+            .unpack(temp_dir.path())
+            .map_err(ImportBundleError::Io)?;
+        let _ = InstanceData::from_file(&temp_dir.path().join(INSTANCE_DATA_FILE))?;
+
+        fs::rename(temp_dir.path(), dir).map_err(ImportBundleError::Io)?;
+        let _ = temp_dir.keep();
+        trace!("imported bundle '{}' to '{}'", bundle.display(), dir.display());
+
+        Self::open(dir).map_err(Into::into)
+    }
+}
+
+fn append_dir_contents(
+    builder: &mut Builder<impl Write>,
+    dir: &Path,
+    name_in_bundle: &str,
+) -> Result<(), ExportBundleError> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(ExportBundleError::Io(err)),
+    };
+
+    for entry in entries {
+        let entry = entry.map_err(ExportBundleError::Io)?;
+        let file_name = entry.file_name();
+        if file_name.as_encoded_bytes().starts_with(b".") {
+            continue;
+        }
+
+        let path_in_bundle = Path::new(name_in_bundle).join(&file_name);
+        if entry.file_type().map_err(ExportBundleError::Io)?.is_dir() {
+            builder.append_dir_all(&path_in_bundle, entry.path())
+        } else {
+            builder.append_path_with_name(entry.path(), &path_in_bundle)
+        }
+        .map_err(ExportBundleError::Io)?;
+    }
+    Ok(())
+}
+
+/// Error type returned by [`EditableInstance::export_bundle`].
+#[derive(Debug, Error)]
+pub enum ExportBundleError {
+    #[error("failed to serialize instance data")]
+    Serialize(#[from] WriteError),
+    #[error("failed to write bundle")]
+    Io(#[source] io::Error),
+}
+
+/// Error type returned by [`EditableInstance::import_bundle`].
+#[derive(Debug, Error)]
+pub enum ImportBundleError {
+    #[error("target directory already exists")]
+    AlreadyExists,
+    #[error("failed to extract bundle")]
+    Io(#[source] io::Error),
+    #[error("bundle does not contain valid instance data")]
+    InvalidData(#[from] InstanceDataOpenError),
+    #[error("failed to open imported instance")]
+    Open(#[from] InstanceOpenError),
+}
@@ -0,0 +1,153 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integrity checks for instances.
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::fs;
+use std::io;
+
+use compact_str::CompactString;
+
+use mmm_core::instance::{Instance, ModEntryKind, ModIndex};
+
+use super::EditableInstance;
+
+/// A problem found by [`EditableInstance::diagnose`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Diagnostic {
+    /// The mod's directory doesn't exist.
+    MissingDirectory(ModIndex),
+    /// The mod's directory is empty.
+    EmptyMod(ModIndex),
+    /// The mod's directory contains nothing but a single directory (such as `Data/`),
+    /// which usually means it was installed one level too deep.
+    SuspiciousNesting { mod_index: ModIndex, dir_name: CompactString },
+    /// Two mods have names that only differ in case,
+    /// which causes problems on case-insensitive file systems.
+    CaseInsensitiveDuplicate(ModIndex, ModIndex),
+    /// A profile's mod order references a mod that doesn't exist.
+    ModIndexOutOfRange { profile: CompactString, mod_index: ModIndex },
+    /// A profile's mod order references the same mod more than once.
+    DuplicateModOrderEntry { profile: CompactString, mod_index: ModIndex },
+    /// A profile's mod order doesn't reference a mod.
+    MissingModOrderEntry { profile: CompactString, mod_index: ModIndex },
+}
+
+impl Diagnostic {
+    /// Returns a human-readable description of the problem.
+    #[must_use]
+    pub fn describe(&self, instance: &impl Instance) -> String {
+        let name = |idx: ModIndex| {
+            instance
+                .mods()
+                .get(idx)
+                .map_or_else(|| format!("#{}", usize::from(idx)), |m| format!("'{}'", m.name()))
+        };
+
+        match self {
+            Self::MissingDirectory(idx) => format!("directory of mod {} is missing", name(*idx)),
+            Self::EmptyMod(idx) => format!("mod {} is empty", name(*idx)),
+            Self::SuspiciousNesting { mod_index, dir_name } => {
+                format!("mod {} only contains the directory '{dir_name}'", name(*mod_index))
+            }
+            Self::CaseInsensitiveDuplicate(a, b) => {
+                format!("mods {} and {} have names that only differ in case", name(*a), name(*b))
+            }
+            Self::ModIndexOutOfRange { profile, mod_index } => {
+                format!("profile '{profile}' references nonexistent mod {}", name(*mod_index))
+            }
+            Self::DuplicateModOrderEntry { profile, mod_index } => {
+                format!("profile '{profile}' contains mod {} more than once", name(*mod_index))
+            }
+            Self::MissingModOrderEntry { profile, mod_index } => {
+                format!("profile '{profile}' doesn't contain mod {}", name(*mod_index))
+            }
+        }
+    }
+}
+
+impl EditableInstance {
+    /// Checks the instance for problems.
+    ///
+    /// Only I/O errors that prevent the check from running are returned as errors;
+    /// everything else is reported as a [`Diagnostic`].
+    pub fn diagnose(&self) -> Result<Vec<Diagnostic>, io::Error> {
+        let mut diagnostics = Vec::new();
+
+        for (idx, mod_decl) in self.mods().iter_enumerated() {
+            let Some(dir) = self.mod_dir(mod_decl) else {
+                continue;
+            };
+
+            let mut entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                    diagnostics.push(Diagnostic::MissingDirectory(idx));
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            match (entries.next().transpose()?, entries.next().transpose()?) {
+                (None, _) => diagnostics.push(Diagnostic::EmptyMod(idx)),
+                (Some(entry), None) if entry.file_type()?.is_dir() => {
+                    diagnostics.push(Diagnostic::SuspiciousNesting {
+                        mod_index: idx,
+                        dir_name: entry.file_name().to_string_lossy().into(),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        let mut lowercase_names: HashMap<String, ModIndex> = HashMap::new();
+        for (idx, mod_decl) in self.mods().iter_enumerated() {
+            if mod_decl.kind() != ModEntryKind::Mod {
+                continue;
+            }
+            match lowercase_names.entry(mod_decl.name().to_lowercase()) {
+                Entry::Occupied(other) => diagnostics.push(Diagnostic::CaseInsensitiveDuplicate(*other.get(), idx)),
+                Entry::Vacant(entry) => {
+                    let _ = entry.insert(idx);
+                }
+            }
+        }
+
+        let mods_len = self.mods().len();
+        for (profile_name, profile) in &self.data.profiles {
+            let mut present = vec![false; mods_len];
+            for entry in &profile.mod_order {
+                let mod_index = entry.mod_index();
+                let profile = profile_name.clone();
+                match present.get_mut(usize::from(mod_index)) {
+                    Some(seen) if *seen => diagnostics.push(Diagnostic::DuplicateModOrderEntry { profile, mod_index }),
+                    Some(seen) => *seen = true,
+                    None => diagnostics.push(Diagnostic::ModIndexOutOfRange { profile, mod_index }),
+                }
+            }
+            for (i, seen) in present.into_iter().enumerate() {
+                if !seen {
+                    diagnostics.push(Diagnostic::MissingModOrderEntry {
+                        profile: profile_name.clone(),
+                        mod_index: ModIndex::from(i),
+                    });
+                }
+            }
+        }
+
+        Ok(diagnostics)
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Management of the archives in the downloads directory.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use compact_str::CompactString;
+use thiserror::Error;
+
+use mmm_core::instance::{Instance, ModIndex};
+
+use super::{EditableInstance, ReadOnlyError};
+use crate::downloads::{DOWNLOADS_DIR, Download, is_download_name_valid};
+
+impl EditableInstance {
+    /// Returns the absolute path to the instance's downloads directory.
+    #[must_use]
+    pub fn downloads_dir(&self) -> PathBuf {
+        self.dir().join(DOWNLOADS_DIR)
+    }
+
+    /// Returns the archives in the downloads directory, sorted by name.
+    pub fn downloads(&self) -> Result<Vec<Download>, io::Error> {
+        let entries = match fs::read_dir(self.downloads_dir()) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+
+        let mut downloads = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let Some(name) = entry
+                .file_name()
+                .to_str()
+                .filter(|name| is_download_name_valid(name))
+                .map(CompactString::from)
+            else {
+                continue;
+            };
+            let metadata = entry.metadata()?;
+            if !metadata.is_file() {
+                continue;
+            }
+
+            let mods = self
+                .mods()
+                .iter_enumerated()
+                .filter(|(_, m)| m.source() == Some(&name))
+                .map(|(idx, _)| idx)
+                .collect();
+            downloads.push(Download {
+                name,
+                path: entry.path(),
+                size: metadata.len(),
+                mods,
+            });
+        }
+        downloads.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        Ok(downloads)
+    }
+
+    /// Sets or clears the archive in the downloads directory that the specified mod was installed from.
+    pub fn set_mod_source(&mut self, idx: ModIndex, source: Option<&str>) -> Result<(), InvalidDownloadNameError> {
+        if let Some(source) = source
+            && !is_download_name_valid(source)
+        {
+            return Err(InvalidDownloadNameError);
+        }
+
+        self.record_change("change source");
+        self.changed = true;
+        self.data.mods[idx].set_source(source.map(CompactString::from));
+        Ok(())
+    }
+
+    /// Deletes an archive from the downloads directory.
+    ///
+    /// Mods installed from it forget their [source](mmm_core::instance::ModDeclaration::source).
+    pub fn delete_download(&mut self, name: &str) -> Result<(), DeleteDownloadError> {
+        self.ensure_files_writable()?;
+        if !is_download_name_valid(name) {
+            return Err(InvalidDownloadNameError.into());
+        }
+
+        match fs::remove_file(self.downloads_dir().join(name)) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(DeleteDownloadError::Io(err)),
+        }
+
+        self.clear_history();
+        for mod_decl in &mut self.data.mods {
+            if mod_decl.source().is_some_and(|source| source == name) {
+                self.changed = true;
+                mod_decl.set_source(None);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("invalid download name")]
+pub struct InvalidDownloadNameError;
+
+#[derive(Debug, Error)]
+pub enum DeleteDownloadError {
+    #[error(transparent)]
+    InvalidName(#[from] InvalidDownloadNameError),
+    #[error("failed to delete archive")]
+    Io(#[source] io::Error),
+    #[error(transparent)]
+    ReadOnly(#[from] ReadOnlyError),
+}
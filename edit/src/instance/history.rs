@@ -0,0 +1,198 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Undo and redo of changes to the mod list.
+//!
+//! Only changes that are confined to the instance data are recorded, such as reordering or toggling mods.
+//! Operations that also touch the filesystem (creating, removing or renaming mods) can't be undone,
+//! and clear the history instead, since they invalidate the recorded states.
+//!
+//! The changes made during a [transaction](EditableInstance::transaction) are recorded as a single change.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::mem;
+
+use compact_str::CompactString;
+use tracing::trace;
+use typed_index_collections::TiVec;
+
+use mmm_core::instance::{ModDeclaration, ModIndex, ModOrderEntry, ModOrderIndex};
+
+use super::EditableInstance;
+
+/// Maximum number of changes that can be undone.
+const HISTORY_LIMIT: usize = 100;
+
+#[derive(Default)]
+pub(super) struct History {
+    undo: VecDeque<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+    transaction: TransactionChanges,
+}
+
+/// Changes made to the mod list during the current transaction.
+#[derive(Clone, Copy, Default, Eq, PartialEq)]
+enum TransactionChanges {
+    #[default]
+    None,
+    /// Only changes that can be undone were made.
+    Recorded,
+    /// The history was cleared, so the transaction can't be undone.
+    Cleared,
+}
+
+struct HistoryEntry {
+    description: String,
+    state: ModListState,
+}
+
+/// The part of the instance data a recorded change can modify.
+pub(super) struct ModListState {
+    profile: CompactString,
+    mods: TiVec<ModIndex, ModDeclaration>,
+    mod_order: TiVec<ModOrderIndex, ModOrderEntry>,
+    notes: BTreeMap<ModIndex, CompactString>,
+}
+
+impl EditableInstance {
+    /// Records the current state of the mod list before it is changed, so that the change can be undone.
+    ///
+    /// Changes made inside a [transaction](Self::transaction) are not recorded individually,
+    /// see [`Self::end_transaction_history`].
+    pub(super) fn record_change(&mut self, description: impl Into<String>) {
+        if self.in_transaction {
+            if self.history.transaction == TransactionChanges::None {
+                self.history.transaction = TransactionChanges::Recorded;
+            }
+            return;
+        }
+        let state = self.mod_list_state();
+        self.push_history_entry(HistoryEntry { description: description.into(), state });
+    }
+
+    /// Forgets every recorded change.
+    pub(super) fn clear_history(&mut self) {
+        self.history.undo.clear();
+        self.history.redo.clear();
+        if self.in_transaction {
+            self.history.transaction = TransactionChanges::Cleared;
+        }
+    }
+
+    /// Returns the state of the mod list before a transaction, to be passed to [`Self::end_transaction_history`]
+    /// once it ends.
+    pub(super) fn begin_transaction_history(&mut self) -> ModListState {
+        self.history.transaction = TransactionChanges::None;
+        self.mod_list_state()
+    }
+
+    /// Records the changes made during a transaction as a single change, if it succeeded, it changed the mod list,
+    /// and the history wasn't cleared in the meantime.
+    pub(super) fn end_transaction_history(&mut self, description: String, state: ModListState, succeeded: bool) {
+        let changes = mem::take(&mut self.history.transaction);
+        if succeeded && changes == TransactionChanges::Recorded {
+            self.push_history_entry(HistoryEntry { description, state });
+        }
+    }
+
+    fn push_history_entry(&mut self, entry: HistoryEntry) {
+        let history = &mut self.history;
+        history.redo.clear();
+        if history.undo.len() == HISTORY_LIMIT {
+            let _ = history.undo.pop_front();
+        }
+        history.undo.push_back(entry);
+    }
+
+    /// Returns a description of the change that [`Self::undo`] would revert, if there is one.
+    #[must_use]
+    pub fn undo_description(&self) -> Option<&str> {
+        self.history.undo.back().map(|entry| entry.description.as_str())
+    }
+
+    /// Returns a description of the change that [`Self::redo`] would reapply, if there is one.
+    #[must_use]
+    pub fn redo_description(&self) -> Option<&str> {
+        self.history.redo.last().map(|entry| entry.description.as_str())
+    }
+
+    /// Reverts the most recent recorded change, switching to the profile it was made in.
+    ///
+    /// Returns `false` if there was nothing to undo. Every [`ModOrderIndex`] is invalidated.
+    pub fn undo(&mut self) -> bool {
+        let Some(entry) = self.history.undo.pop_back() else {
+            return false;
+        };
+        trace!("undoing '{}'", entry.description);
+        let state = self.replace_mod_list_state(entry.state);
+        self.history
+            .redo
+            .push(HistoryEntry { description: entry.description, state });
+        true
+    }
+
+    /// Reapplies the most recently undone change, switching to the profile it was made in.
+    ///
+    /// Returns `false` if there was nothing to redo. Every [`ModOrderIndex`] is invalidated.
+    pub fn redo(&mut self) -> bool {
+        let Some(entry) = self.history.redo.pop() else {
+            return false;
+        };
+        trace!("redoing '{}'", entry.description);
+        let state = self.replace_mod_list_state(entry.state);
+        self.history
+            .undo
+            .push_back(HistoryEntry { description: entry.description, state });
+        true
+    }
+
+    fn mod_list_state(&self) -> ModListState {
+        let profile = self
+            .data
+            .profiles
+            .get(&self.state.current_profile)
+            .expect("profile exists");
+        ModListState {
+            profile: self.state.current_profile.clone(),
+            mods: self.data.mods.clone(),
+            mod_order: profile.mod_order.clone(),
+            notes: profile.notes.clone(),
+        }
+    }
+
+    /// Restores a recorded state, returning the state it replaced.
+    fn replace_mod_list_state(&mut self, mut state: ModListState) -> ModListState {
+        self.changed = true;
+        self.state.current_profile = state.profile.clone();
+        let profile = self
+            .data
+            .profiles
+            .get_mut(&state.profile)
+            .expect("profiles that have recorded changes aren't removed");
+        mem::swap(&mut self.data.mods, &mut state.mods);
+        mem::swap(&mut profile.mod_order, &mut state.mod_order);
+        mem::swap(&mut profile.notes, &mut state.notes);
+        state
+    }
+}
+
+/// Formats a number of mods for a change description, such as "move 12 mods".
+pub(super) fn mod_count(count: usize) -> String {
+    if count == 1 {
+        "1 mod".to_owned()
+    } else {
+        format!("{count} mods")
+    }
+}
@@ -0,0 +1,1339 @@
+// Copyright © 2025-2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+mod bundle;
+mod diagnose;
+mod downloads;
+mod history;
+mod modlist;
+mod orphans;
+mod rename;
+mod snapshot;
+mod sort;
+mod transaction;
+mod trash;
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::time::{Duration, Instant};
+
+use compact_str::{CompactString, format_compact};
+use foldhash::{HashMap, HashSet};
+use tempfile::TempDir;
+use thiserror::Error;
+use tracing::{error, trace, warn};
+use typed_index_collections::{TiSlice, TiVec};
+use unicode_segmentation::UnicodeSegmentation;
+
+use mmm_core::instance::data::{INSTANCE_DATA_FILE, InstanceData, InstanceDataOpenError};
+use mmm_core::instance::{
+    DEFAULT_PROFILE, DEFAULT_PROFILE_NAME, Instance, InstanceSettings, InvalidModNameError, InvalidModTargetError,
+    InvalidMountTargetError, ModDeclaration, ModEntryKind, ModIndex, ModLabel, ModOrderEntry, ModOrderIndex,
+    MountTarget, Profile,
+};
+
+use crate::install::staging::{CopyOrMove, PlaceError, StageDirError, StagedInstall};
+use crate::util::{move_multiple, name_ord};
+use crate::writer::{
+    WriteError, WriteRequest, WriteTarget, WriterMessage, recover_temp_files, spawn_writer_thread, write_blocking,
+};
+use crate::{Mod, ModInitError};
+
+use self::history::{History, mod_count};
+
+pub use self::bundle::BundleOptions;
+pub use self::diagnose::Diagnostic;
+pub use self::modlist::ModListImportReport;
+pub use self::orphans::OrphanReport;
+pub use self::rename::{BulkRenameEntry, BulkRenameProblem, RenamePattern};
+pub use self::snapshot::{SNAPSHOTS_DIR, Snapshot};
+pub use self::sort::{SortCriterion, SortScope};
+pub use self::trash::{TRASH_DIR, TrashEntry};
+
+/// Minimum time between two writes of the instance data by [`EditableInstance::save`].
+pub const SAVE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Implementation of [`Instance`] with editing support (for interactive applications).
+pub struct EditableInstance {
+    dir: Arc<Path>,
+    data: InstanceData,
+    state: EditorState,
+    /// `None` if the instance was opened [read-only](Self::open_read_only).
+    writer: Option<(Sender<WriterMessage>, Receiver<Result<(), WriteError>>)>,
+    write_error: Option<WriteError>,
+    /// Hashes of the instance data most recently read or written by this process,
+    /// to tell its own writes apart from [external changes](Self::data_file_changed_externally).
+    known_contents: VecDeque<u64>,
+    last_save: Option<Instant>,
+    changed: bool,
+    in_transaction: bool,
+    history: History,
+}
+
+impl EditableInstance {
+    /// Opens the instance at the specified path.
+    pub fn open(dir: &Path) -> Result<Self, InstanceOpenError> {
+        Self::open_impl(dir, false)
+    }
+
+    /// Creates a new, empty instance in the specified directory and opens it.
+    ///
+    /// The directory is created if it doesn't exist. Fails if it already contains an instance.
+    pub fn create(dir: &Path) -> Result<Self, InstanceCreateError> {
+        fs::create_dir_all(dir).map_err(|source| InstanceCreateError::CreateDir { source, dir: dir.to_owned() })?;
+        if dir.join(INSTANCE_DATA_FILE).try_exists().unwrap_or(true) {
+            return Err(InstanceCreateError::AlreadyExists(dir.to_owned()));
+        }
+
+        let content = cbor4ii::serde::to_vec(Vec::new(), &InstanceData::default())
+            .map_err(|err| WriteError::Serialize(err.to_string()))?;
+        write_blocking(dir, WriteTarget::InstanceData, &content)?;
+        trace!("created instance at '{}'", dir.display());
+
+        Self::open(dir).map_err(Into::into)
+    }
+
+    /// Opens the instance at the specified path without write access.
+    ///
+    /// Nothing is ever written to the instance directory: methods that modify files return a [`ReadOnlyError`].
+    /// Methods that only change the instance data, such as toggling or moving mods, or editing profiles,
+    /// still succeed, but their changes are kept in memory only, and are lost when the instance is dropped.
+    /// This allows trying out changes, for example to preview the files a different mod order would deploy.
+    pub fn open_read_only(dir: &Path) -> Result<Self, InstanceOpenError> {
+        Self::open_impl(dir, true)
+    }
+
+    #[allow(clippy::assigning_clones, reason = "compact_str clones don't share resources")]
+    fn open_impl(dir: &Path, read_only: bool) -> Result<Self, InstanceOpenError> {
+        let dir: Arc<Path> = Arc::from(
+            dir.canonicalize()
+                .map_err(|source| InstanceOpenError::DirCanonicalize { source, dir: dir.to_owned() })?
+                .into_boxed_path(),
+        );
+        if !dir
+            .metadata()
+            .map_err(|source| InstanceOpenError::DirMetadata { source, dir: Arc::clone(&dir) })?
+            .is_dir()
+        {
+            return Err(InstanceOpenError::NotADirectory(dir));
+        }
+
+        if !read_only && let Err(err) = recover_temp_files(&dir) {
+            warn!("failed to clean up temp files: {}", err);
+        }
+
+        let data_file = dir.join(INSTANCE_DATA_FILE);
+        let mut data = InstanceData::from_file(&data_file)?;
+
+        let mut state = EditorState::default();
+        if !data.profiles.contains_key(state.current_profile()) {
+            let default = DEFAULT_PROFILE_NAME;
+            if data.profiles.contains_key(&default) {
+                state.current_profile = default;
+            } else if let Some((name, _)) = data.profiles.first_key_value() {
+                state.current_profile = name.to_owned();
+            } else {
+                let _ = data.profiles.insert(default.clone(), DEFAULT_PROFILE);
+                state.current_profile = default;
+            }
+        }
+
+        let writer = if read_only {
+            None
+        } else {
+            Some(spawn_writer_thread(&dir).map_err(InstanceOpenError::SpawnWriterThread)?)
+        };
+
+        let mut instance = Self {
+            dir,
+            data,
+            state,
+            writer,
+            write_error: None,
+            known_contents: VecDeque::new(),
+            last_save: None,
+            changed: false,
+            in_transaction: false,
+            history: History::default(),
+        };
+        instance.add_missing_mods_to_mod_order();
+        instance.remember_data_file();
+
+        Ok(instance)
+    }
+
+    /// Replaces the instance data with the contents of the instance data file,
+    /// discarding any changes that weren't saved yet.
+    ///
+    /// Every index is invalidated.
+    pub fn reload(&mut self) -> Result<(), InstanceDataOpenError> {
+        self.wait_for_writer();
+        self.data = InstanceData::from_file(&self.dir.join(INSTANCE_DATA_FILE))?;
+        self.remember_data_file();
+        trace!("reloaded instance data");
+
+        if !self.data.profiles.contains_key(&self.state.current_profile) {
+            if let Some((name, _)) = self.data.profiles.first_key_value() {
+                self.state.current_profile = name.clone();
+            } else {
+                let _ = self.data.profiles.insert(DEFAULT_PROFILE_NAME, DEFAULT_PROFILE);
+                self.state.current_profile = DEFAULT_PROFILE_NAME;
+            }
+        }
+        self.changed = false;
+        self.clear_history();
+        self.add_missing_mods_to_mod_order();
+        Ok(())
+    }
+
+    /// Returns `true` if the instance data file contains data that wasn't read or written by this instance,
+    /// meaning it was modified by another process.
+    ///
+    /// Use [`Self::reload`] to load the new data.
+    #[must_use]
+    pub fn data_file_changed_externally(&self) -> bool {
+        match fs::read(self.dir.join(INSTANCE_DATA_FILE)) {
+            Ok(content) => !self.known_contents.contains(&hash_content(&content)),
+            Err(err) => {
+                warn!("failed to read instance data file: {}", err);
+                false
+            }
+        }
+    }
+
+    fn remember_data_file(&mut self) {
+        match fs::read(self.dir.join(INSTANCE_DATA_FILE)) {
+            Ok(content) => self.remember_content(&content),
+            Err(err) => warn!("failed to read instance data file: {}", err),
+        }
+    }
+
+    fn remember_content(&mut self, content: &[u8]) {
+        const LIMIT: usize = 8;
+        if self.known_contents.len() == LIMIT {
+            let _ = self.known_contents.pop_front();
+        }
+        self.known_contents.push_back(hash_content(content));
+    }
+
+    /// Returns `true` if the instance was opened with [`Self::open_read_only`].
+    #[must_use]
+    pub const fn is_read_only(&self) -> bool {
+        self.writer.is_none()
+    }
+
+    fn ensure_writable(&self) -> Result<(), ReadOnlyError> {
+        if self.is_read_only() {
+            Err(ReadOnlyError::Instance)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Like [`Self::ensure_writable`], but also fails during a [transaction](Self::transaction),
+    /// since changes to files can't be rolled back.
+    pub(crate) fn ensure_files_writable(&self) -> Result<(), ReadOnlyError> {
+        self.ensure_writable()?;
+        if self.in_transaction {
+            Err(ReadOnlyError::Transaction)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Saves the state of the instance and queues writing it to disk.
+    ///
+    /// Does nothing if the state hasn't changed since the last call to this method,
+    /// if called during a [transaction](Self::transaction), or if the instance is [read-only](Self::open_read_only).
+    ///
+    /// To avoid writing to disk repeatedly during rapid changes, the state is saved at most once every
+    /// [`SAVE_INTERVAL`]. If saving is postponed, this method returns how long until the state can be saved,
+    /// and should be called again after that. Use [`Self::flush`] to save immediately.
+    pub fn save(&mut self) -> Option<Duration> {
+        if !self.changed || self.in_transaction || self.is_read_only() {
+            return None;
+        }
+
+        if let Some(last_save) = self.last_save {
+            let elapsed = last_save.elapsed();
+            if elapsed < SAVE_INTERVAL {
+                return Some(SAVE_INTERVAL.saturating_sub(elapsed));
+            }
+        }
+
+        self.queue_write();
+        None
+    }
+
+    /// Saves the state of the instance, if it has changed, and waits until every queued write is finished.
+    ///
+    /// Unlike [`Self::save`], this ignores [`SAVE_INTERVAL`].
+    /// This is called automatically when the instance is dropped.
+    pub fn flush(&mut self) {
+        if self.is_read_only() {
+            return;
+        }
+        if self.changed && !self.in_transaction {
+            self.queue_write();
+        }
+        self.wait_for_writer();
+    }
+
+    /// Saves the state of the instance on the calling thread, returning any error that occurs.
+    ///
+    /// Unlike [`Self::save`], the state is always written, even if it hasn't changed.
+    /// This is meant for non-interactive callers, that need to know if saving succeeded.
+    pub fn save_blocking(&mut self) -> Result<(), WriteError> {
+        if self.is_read_only() {
+            return Err(WriteError::ReadOnly);
+        }
+
+        // Make sure that no previously queued write can overwrite this one,
+        // and discard their results, as this write supersedes them.
+        self.wait_for_writer();
+        let _ = self.write_error();
+
+        let content = self.serialize()?;
+        self.changed = false;
+        self.last_save = Some(Instant::now());
+        self.remember_content(&content);
+        trace!("saving instance data (blocking)");
+
+        let result = write_blocking(&self.dir, WriteTarget::InstanceData, &content);
+        if result.is_ok() {
+            self.write_error = None;
+        } else {
+            self.changed = true;
+        }
+        result
+    }
+
+    fn wait_for_writer(&mut self) {
+        let Some((write_queue, _)) = &self.writer else {
+            return;
+        };
+        let (ack_sender, ack_receiver) = mpsc::channel();
+        if write_queue.send(WriterMessage::Flush(ack_sender)).is_err() || ack_receiver.recv().is_err() {
+            error!("write thread crashed");
+            self.write_error = Some(WriteError::ThreadCrashed);
+        }
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>, WriteError> {
+        cbor4ii::serde::to_vec(Vec::new(), &self.data).map_err(|err| WriteError::Serialize(err.to_string()))
+    }
+
+    fn queue_write(&mut self) {
+        self.changed = false;
+        self.last_save = Some(Instant::now());
+        trace!("saving instance data");
+
+        let content = match self.serialize() {
+            Ok(value) => value,
+            Err(err) => {
+                error!("{}", err);
+                self.write_error = Some(err);
+                return;
+            }
+        };
+
+        self.remember_content(&content);
+        let req = WriteRequest { content, target: WriteTarget::InstanceData };
+        let (write_queue, _) = self.writer.as_ref().expect("instance is not read-only");
+        if write_queue.send(WriterMessage::Write(req)).is_err() {
+            error!("write thread crashed");
+            self.write_error = Some(WriteError::ThreadCrashed);
+        }
+    }
+
+    /// Returns the error that made the most recently completed save fail, if any.
+    ///
+    /// Every save writes the whole instance data, so a failed save is superseded by the next successful one.
+    pub fn write_error(&mut self) -> Option<&WriteError> {
+        let Some((_, write_results)) = &self.writer else {
+            return self.write_error.as_ref();
+        };
+        loop {
+            match write_results.try_recv() {
+                Ok(result) => self.write_error = result.err(),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.write_error = Some(WriteError::ThreadCrashed);
+                    break;
+                }
+            }
+        }
+        self.write_error.as_ref()
+    }
+}
+
+impl Drop for EditableInstance {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// Error type returned by [`EditableInstance::create`].
+#[derive(Debug, Error)]
+pub enum InstanceCreateError {
+    #[error("'{0}' already contains an instance")]
+    AlreadyExists(PathBuf),
+    #[error("failed to create directory '{dir}'")]
+    CreateDir { source: io::Error, dir: PathBuf },
+    #[error("failed to open the new instance")]
+    Open(#[from] InstanceOpenError),
+    #[error("failed to write instance data")]
+    Write(#[from] WriteError),
+}
+
+/// Error type returned by [`EditableInstance::open`].
+#[derive(Debug, Error)]
+pub enum InstanceOpenError {
+    #[error("failed to canonicalize path '{dir}'")]
+    DirCanonicalize { source: io::Error, dir: PathBuf },
+    #[error("failed to get metadata of '{dir}'")]
+    DirMetadata { source: io::Error, dir: Arc<Path> },
+    #[error("'{0}' is not a directory")]
+    NotADirectory(Arc<Path>),
+    #[error("failed to open instance data file")]
+    DataOpen(#[from] InstanceDataOpenError),
+    #[error("failed to spawn writer thread")]
+    SpawnWriterThread(#[source] io::Error),
+}
+
+impl Instance for EditableInstance {
+    fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn mods(&self) -> &TiSlice<ModIndex, ModDeclaration> {
+        &self.data.mods
+    }
+
+    fn mod_order(&self) -> &TiSlice<ModOrderIndex, ModOrderEntry> {
+        &self
+            .data
+            .profiles
+            .get(&self.state.current_profile)
+            .expect("profile exists")
+            .mod_order
+    }
+}
+
+impl EditableInstance {
+    #[must_use]
+    pub fn arc_dir(&self) -> Arc<Path> {
+        Arc::clone(&self.dir)
+    }
+
+    fn mod_order_mut(&mut self) -> &mut TiVec<ModOrderIndex, ModOrderEntry> {
+        &mut self
+            .data
+            .profiles
+            .get_mut(&self.state.current_profile)
+            .expect("profile exists")
+            .mod_order
+    }
+
+    /// Adds missing [`entries`](ModOrderEntry) to the current profile's mod order.
+    ///
+    /// This should be called when switching profiles, as we only add entries to the current profile
+    /// (and we don't know if the deserialized mod order is missing any entries).
+    fn add_missing_mods_to_mod_order(&mut self) {
+        let mods = self.mods().len();
+        let Some(mods_to_add) = mods.checked_sub(self.mod_order().len()) else {
+            // nothing to add
+            return;
+        };
+
+        let mod_order = self.mod_order_mut();
+        mod_order.reserve(mods_to_add);
+
+        let mut mods_present = vec![false; mods];
+        for order_entry in mod_order.iter() {
+            mods_present[Into::<usize>::into(order_entry.mod_index())] = true;
+        }
+
+        for (idx, present) in mods_present.iter().enumerate() {
+            if !present {
+                mod_order.push(ModOrderEntry::new(ModIndex::from(idx)));
+            }
+        }
+    }
+
+    /// Returns the name of the current profile.
+    #[must_use]
+    pub const fn current_profile(&self) -> &CompactString {
+        &self.state.current_profile
+    }
+
+    /// Returns the instance's profiles, by name.
+    #[must_use]
+    pub const fn profiles(&self) -> &BTreeMap<CompactString, Profile> {
+        &self.data.profiles
+    }
+
+    /// Switches the current profile to the specified one.
+    ///
+    /// Does nothing if the profile doesn't exist.
+    pub fn switch_to_profile(&mut self, profile_name: CompactString) {
+        if !self.data.profiles.contains_key(&profile_name) {
+            error!("tried to switch to non-existent profile '{}'", profile_name);
+            return;
+        }
+        self.state.current_profile = profile_name;
+        self.add_missing_mods_to_mod_order();
+    }
+
+    /// Compares the current profile with the specified one, returning what would change
+    /// when [switching](Self::switch_to_profile) to it.
+    ///
+    /// Returns `None` if the profile doesn't exist.
+    #[must_use]
+    pub fn profile_switch_diff(&self, profile_name: &str) -> Option<ProfileSwitchDiff> {
+        let target_profile = self.data.profiles.get(profile_name)?;
+        let mod_count = self.mods().len();
+        // A damaged profile could refer to mods that don't exist, which are ignored.
+        let target = || {
+            target_profile
+                .mod_order
+                .iter()
+                .filter(move |entry| usize::from(entry.mod_index()) < mod_count)
+        };
+
+        let mut target_enabled = vec![false; mod_count];
+        for entry in target() {
+            target_enabled[usize::from(entry.mod_index())] = entry.enabled;
+        }
+
+        let mut diff = ProfileSwitchDiff::default();
+        for entry in self.mod_order() {
+            let idx = entry.mod_index();
+            match (
+                entry.enabled,
+                target_enabled.get(usize::from(idx)).copied().unwrap_or(false),
+            ) {
+                (false, true) => diff.enabled.push(idx),
+                (true, false) => diff.disabled.push(idx),
+                _ => {}
+            }
+        }
+
+        // Mods missing from the target profile's mod order are appended to it when switching,
+        // so only the relative order of the mods present in both is compared.
+        let mut in_target = vec![false; mod_count];
+        for entry in target() {
+            in_target[usize::from(entry.mod_index())] = true;
+        }
+        diff.order_differs = !self
+            .mod_order()
+            .iter()
+            .map(ModOrderEntry::mod_index)
+            .filter(|idx| in_target.get(usize::from(*idx)).copied().unwrap_or(false))
+            .eq(target().map(ModOrderEntry::mod_index));
+
+        Some(diff)
+    }
+
+    /// Creates a [`Profile`] with the specified name.
+    ///
+    /// If the name is too long, or if it's the same as another profile in the instance,
+    /// a new name is selected. This method returns the name that ends up being used.
+    ///
+    /// The profile's display name will always be set to the originally specified name,
+    /// even if this method picks a new name.
+    #[must_use]
+    pub fn add_profile(&mut self, name: &str) -> CompactString {
+        let name = name.trim();
+        self.insert_profile(name, Profile::new(CompactString::new(name)))
+    }
+
+    /// Creates a copy of the specified profile, including its mod order and notes,
+    /// with the specified display name, and returns the copy's name, like [`Self::add_profile`].
+    ///
+    /// Returns `None` if the profile doesn't exist.
+    pub fn duplicate_profile(&mut self, profile_name: &str, display_name: &str) -> Option<CompactString> {
+        let display_name = display_name.trim();
+        let mut profile = self.data.profiles.get(profile_name)?.clone();
+        profile.set_display_name(CompactString::new(display_name));
+        Some(self.insert_profile(display_name, profile))
+    }
+
+    /// Sets the display name of the specified profile. Its name, which identifies it, doesn't change.
+    ///
+    /// Does nothing if the profile doesn't exist.
+    pub fn rename_profile(&mut self, profile_name: &str, display_name: &str) {
+        let Some(profile) = self.data.profiles.get_mut(profile_name) else {
+            error!("tried to rename non-existent profile '{}'", profile_name);
+            return;
+        };
+        profile.set_display_name(CompactString::new(display_name.trim()));
+        self.changed = true;
+    }
+
+    /// Removes the specified profile.
+    ///
+    /// If it's the current profile, the first remaining profile becomes the current one.
+    /// If it's the instance's default profile, the instance no longer has a default profile.
+    /// Does nothing if the profile doesn't exist.
+    pub fn remove_profile(&mut self, profile_name: &str) -> Result<(), LastProfileError> {
+        if !self.data.profiles.contains_key(profile_name) {
+            error!("tried to remove non-existent profile '{}'", profile_name);
+            return Ok(());
+        }
+        if self.data.profiles.len() == 1 {
+            return Err(LastProfileError);
+        }
+
+        self.changed = true;
+        self.clear_history();
+        let _ = self.data.profiles.remove(profile_name);
+        if self.data.settings.default_profile.as_deref() == Some(profile_name) {
+            self.data.settings.default_profile = None;
+        }
+        if self.state.current_profile == profile_name {
+            let (first, _) = self.data.profiles.first_key_value().expect("another profile remains");
+            self.switch_to_profile(first.clone());
+        }
+        Ok(())
+    }
+
+    /// Inserts `profile` with a name derived from `name`, and returns the name that ends up being used.
+    fn insert_profile(&mut self, name: &str, profile: Profile) -> CompactString {
+        // Limit names to 24 bytes to always fit in compact_str's small string optimization
+        const LIMIT: usize = 24;
+        let truncated_name = truncate_str(name, LIMIT);
+        let mut actual_name = truncated_name.clone();
+
+        let mut n: u32 = 0;
+        while self.data.profiles.contains_key(&actual_name) {
+            n = n.strict_add(1);
+            let n_str = format_compact!("{}", n);
+
+            actual_name = truncate_str(&truncated_name, LIMIT.strict_sub(n_str.len()));
+            actual_name.push_str(&n_str);
+        }
+        assert!(!actual_name.is_heap_allocated());
+
+        self.changed = true;
+        let _ = self.data.profiles.insert(actual_name.clone(), profile);
+        actual_name
+    }
+
+    /// Returns `true` if the specified name is used by a mod, or by a directory in the mods directory.
+    #[must_use]
+    pub fn is_mod_name_taken(&self, name: &str) -> bool {
+        self.mods().iter().any(|m| m.name() == name) || self.mods_dir().join(name).exists()
+    }
+
+    /// Returns a mod name based on `name` that [isn't taken](Self::is_mod_name_taken),
+    /// by appending or incrementing a counter (e.g. `Name (2)`).
+    ///
+    /// Returns `name` unchanged (but trimmed) if it's free.
+    #[must_use]
+    pub fn suggest_mod_name(&self, name: &str) -> CompactString {
+        let name = name.trim();
+        if !self.is_mod_name_taken(name) {
+            return name.into();
+        }
+
+        let (base, start) = name
+            .strip_suffix(')')
+            .and_then(|rest| rest.rsplit_once(" ("))
+            .and_then(|(base, n)| Some((base, n.parse::<u32>().ok()?)))
+            .unwrap_or((name, 1));
+        (start.saturating_add(1)..=u32::MAX)
+            .map(|n| format_compact!("{base} ({n})"))
+            .find(|candidate| !self.is_mod_name_taken(candidate))
+            .expect("not every name is taken")
+    }
+
+    /// Creates a new empty mod with the specified name.
+    pub fn create_mod(&mut self, name: &str, kind: ModEntryKind) -> Result<(), CreateModError> {
+        self.ensure_files_writable()?;
+        if self.mods().iter().any(|m| m.name() == name) {
+            return Err(CreateModError::AlreadyExists);
+        }
+
+        let mod_decl = ModDeclaration::new(name.into(), kind)?;
+
+        self.changed = true;
+        self.clear_history();
+        let idx = self.data.mods.push_and_get_key(mod_decl);
+        self.mod_order_mut().push(ModOrderEntry::new(idx));
+
+        Mod::init(self, idx).map_err(Into::into)
+    }
+
+    /// Registers directories in the mods directory that don't belong to any mod as new mods.
+    ///
+    /// New mods are appended, disabled, to the mod order. Hidden directories, and directories whose name
+    /// isn't valid UTF-8 or isn't a [valid mod name](ModDeclaration::is_name_valid), are ignored.
+    ///
+    /// Returns the indices of the added mods.
+    pub fn rescan(&mut self) -> Result<Vec<ModIndex>, io::Error> {
+        let mut new_mods = Vec::new();
+        for dir in self.orphaned_dirs()? {
+            let Some(name) = dir.file_name().and_then(|name| name.to_str()) else {
+                warn!("ignoring mod directory with non-UTF-8 name '{}'", dir.display());
+                continue;
+            };
+            if self.mods().iter().any(|m| m.name() == name) {
+                warn!(
+                    "ignoring mod directory with the same name as a separator '{}'",
+                    dir.display()
+                );
+                continue;
+            }
+
+            match ModDeclaration::new(CompactString::from(name), ModEntryKind::Mod) {
+                Ok(mod_decl) => new_mods.push(mod_decl),
+                Err(_) => warn!("ignoring mod directory with invalid name '{}'", dir.display()),
+            }
+        }
+        new_mods.sort_by(|a, b| name_ord(a.name(), b.name()));
+
+        if !new_mods.is_empty() {
+            self.changed = true;
+            self.clear_history();
+        }
+
+        let mut added = Vec::with_capacity(new_mods.len());
+        for mod_decl in new_mods {
+            trace!("found new mod directory '{}'", mod_decl.name());
+            let idx = self.data.mods.push_and_get_key(mod_decl);
+            self.mod_order_mut().push(ModOrderEntry::new(idx));
+            added.push(idx);
+        }
+        Ok(added)
+    }
+
+    /// Creates a new mod from a [`StagedInstall`] with the specified name, returning its index.
+    pub fn add_staged_mod(&mut self, name: &str, staged_mod: StagedInstall) -> Result<ModIndex, AddStagedModError> {
+        self.ensure_files_writable()?;
+        if self.mods().iter().any(|m| m.name() == name) {
+            return Err(AddStagedModError::AlreadyExists);
+        }
+        let mod_decl = ModDeclaration::new(name.into(), ModEntryKind::Mod)?;
+        let mod_dir = self.mod_dir(&mod_decl).expect("not a separator");
+        if mod_dir.symlink_metadata().is_ok() {
+            return Err(AddStagedModError::DirectoryExists);
+        }
+
+        staged_mod.place(&mod_dir)?;
+
+        self.changed = true;
+        self.clear_history();
+        let idx = self.data.mods.push_and_get_key(mod_decl);
+        self.mod_order_mut().push(ModOrderEntry::new(idx));
+
+        Ok(idx)
+    }
+
+    /// Creates a new mod with the specified name from the contents of an existing directory.
+    ///
+    /// This blocks until the directory is copied or moved. Interactive applications should call
+    /// [`StagedInstall::stage_dir`] in a background thread and pass the result to [`Self::add_staged_mod`] instead.
+    ///
+    /// If the mod can't be added after the directory was moved, it's moved back to `path`.
+    pub fn create_mod_from_dir(
+        &mut self,
+        path: &Path,
+        name: &str,
+        mode: CopyOrMove,
+    ) -> Result<ModIndex, CreateModFromDirError> {
+        self.ensure_files_writable().map_err(AddStagedModError::from)?;
+        if self.mods().iter().any(|m| m.name() == name) {
+            return Err(AddStagedModError::AlreadyExists.into());
+        }
+        if !ModDeclaration::is_name_valid(name) {
+            return Err(AddStagedModError::InvalidName(InvalidModNameError).into());
+        }
+        if self.mods_dir().join(name).symlink_metadata().is_ok() {
+            return Err(AddStagedModError::DirectoryExists.into());
+        }
+
+        let staged_mod = StagedInstall::stage_dir(&self.mods_dir(), path, mode, &AtomicBool::new(false))?;
+        self.add_staged_mod(name, staged_mod).map_err(Into::into)
+    }
+
+    /// Replaces the files of the specified mod with the ones from a [`StagedInstall`].
+    ///
+    /// The mod keeps its name, metadata, and position and enabled state in every profile.
+    /// If the mod's directory is a git repository, its history is carried over to the new files.
+    ///
+    /// The old files are not deleted. This function returns the path to the directory they were moved to,
+    /// so that the caller can delete it.
+    pub fn reinstall_mod(&mut self, idx: ModIndex, staged_mod: StagedInstall) -> Result<PathBuf, ReinstallModError> {
+        self.ensure_files_writable()?;
+        let mod_dir = self.mod_dir(&self.mods()[idx]).ok_or(ReinstallModError::Separator)?;
+
+        let old_files = TempDir::with_prefix_in(".replaced-", self.mods_dir())
+            .map_err(ReinstallModError::MoveOldFiles)?
+            .keep();
+        let old_mod_dir = old_files.join("files");
+        let had_files = match fs::rename(&mod_dir, &old_mod_dir) {
+            Ok(()) => true,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => false,
+            Err(err) => {
+                let _ = fs::remove_dir(&old_files);
+                return Err(ReinstallModError::MoveOldFiles(err));
+            }
+        };
+
+        if let Err(err) = staged_mod.place(&mod_dir) {
+            if had_files && let Err(err) = fs::rename(&old_mod_dir, &mod_dir) {
+                error!("failed to move old files back to '{}': {}", mod_dir.display(), err);
+            } else {
+                let _ = fs::remove_dir(&old_files);
+            }
+            return Err(err.into());
+        }
+
+        let git_dir = old_mod_dir.join(".git");
+        if had_files && git_dir.is_dir() {
+            let target = mod_dir.join(".git");
+            if let Err(err) = fs::rename(&git_dir, &target) {
+                warn!("failed to carry over git repository to '{}': {}", target.display(), err);
+            }
+        }
+
+        Ok(old_files)
+    }
+
+    /// Removes the specified mod.
+    ///
+    /// The mod's files are not deleted. This function returns the path to the mod directory,
+    /// if applicable, so that the caller can delete the files.
+    ///
+    /// `ModIndex`s greater or equal to `idx` are invalidated when this method is called,
+    /// as well as `ModOrderIndex`s greater or equal to the `ModOrderIndex` corresponding to
+    /// the removed mod in each profile's mod order.
+    pub fn remove_mod(&mut self, idx: ModIndex) -> Option<PathBuf> {
+        self.changed = true;
+        self.clear_history();
+
+        self.data.profiles.values_mut().for_each(|p| {
+            p.mod_order.retain_mut(|entry| {
+                let retain = entry.mod_index() != idx;
+                if entry.mod_index() > idx {
+                    entry.decrement_index();
+                }
+                retain
+            });
+
+            let _ = p.notes.remove(&idx);
+            if p.notes.keys().any(|i| *i > idx) {
+                p.notes = mem::take(&mut p.notes)
+                    .into_iter()
+                    .map(|(i, note)| (if i > idx { i.saturating_sub(1u32) } else { i }, note))
+                    .collect();
+            }
+        });
+
+        let mod_decl = self.data.mods.remove(idx);
+        self.mod_dir(&mod_decl)
+    }
+
+    /// Renames the specified mod.
+    pub fn rename_mod(&mut self, idx: ModIndex, new_name: &str) -> Result<(), RenameModError> {
+        self.ensure_files_writable()?;
+        if self.data.mods.iter().any(|m| m.name() == new_name) {
+            return Err(RenameModError::AlreadyExists);
+        }
+
+        if !ModDeclaration::is_name_valid(new_name) {
+            return Err(InvalidModNameError.into());
+        }
+
+        let mod_decl = &self.data.mods[idx];
+        if let Some(from) = self.mod_dir(mod_decl) {
+            let to = from.with_file_name(new_name);
+            fs::rename(from, to)?;
+        }
+
+        self.changed = true;
+        self.clear_history();
+        self.data.mods[idx].set_name(new_name.into())?;
+        Ok(())
+    }
+
+    /// Sets or clears the category of the specified mod.
+    ///
+    /// Empty categories are treated as no category.
+    pub fn set_mod_category(&mut self, idx: ModIndex, category: Option<&str>) {
+        self.record_change("change category");
+        self.changed = true;
+        let category = category
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(CompactString::from);
+        self.data.mods[idx].set_category(category);
+    }
+
+    /// Sets or clears the version of the specified mod.
+    ///
+    /// Empty versions are treated as no version.
+    pub fn set_mod_version(&mut self, idx: ModIndex, version: Option<&str>) {
+        self.record_change("change version");
+        self.changed = true;
+        let version = version
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(CompactString::from);
+        self.data.mods[idx].set_version(version);
+    }
+
+    /// Sets or clears the directory the specified mod is deployed into, relative to the deployment root.
+    ///
+    /// Leading and trailing slashes are ignored. Empty targets are treated as no target (the deployment root).
+    pub fn set_mod_target(&mut self, idx: ModIndex, target: Option<&str>) -> Result<(), InvalidModTargetError> {
+        let target = target
+            .map(|t| t.trim().trim_matches('/'))
+            .filter(|t| !t.is_empty())
+            .map(CompactString::from);
+        let mut mod_decl = self.data.mods[idx].clone();
+        mod_decl.set_target(target)?;
+        self.record_change("change install location");
+        self.changed = true;
+        self.data.mods[idx] = mod_decl;
+        Ok(())
+    }
+
+    /// Returns the note attached to the specified mod in the current profile, if it has one.
+    #[must_use]
+    pub fn mod_note(&self, idx: ModIndex) -> Option<&CompactString> {
+        self.data
+            .profiles
+            .get(&self.state.current_profile)
+            .expect("profile exists")
+            .notes
+            .get(&idx)
+    }
+
+    /// Sets or clears the note attached to the specified mod in the current profile.
+    ///
+    /// Empty notes are treated as no note.
+    pub fn set_mod_note(&mut self, idx: ModIndex, note: Option<&str>) {
+        assert!(usize::from(idx) < self.mods().len(), "mod index is in range");
+        self.record_change("change note");
+        self.changed = true;
+        let notes = &mut self
+            .data
+            .profiles
+            .get_mut(&self.state.current_profile)
+            .expect("profile exists")
+            .notes;
+        match note.map(str::trim).filter(|n| !n.is_empty()) {
+            Some(note) => {
+                let _ = notes.insert(idx, note.into());
+            }
+            None => {
+                let _ = notes.remove(&idx);
+            }
+        }
+    }
+
+    /// Returns the instance-wide settings.
+    #[must_use]
+    pub const fn settings(&self) -> &InstanceSettings {
+        &self.data.settings
+    }
+
+    /// Sets or clears the game directory and the game's executable, relative to it.
+    ///
+    /// Fails if the game directory is not an absolute path.
+    pub fn set_game_path(
+        &mut self,
+        game_path: Option<CompactString>,
+        executable: Option<CompactString>,
+    ) -> Result<(), RelativeGamePathError> {
+        if game_path
+            .as_deref()
+            .is_some_and(|path| !Path::new(path.as_str()).is_absolute())
+        {
+            return Err(RelativeGamePathError);
+        }
+        self.changed = true;
+        self.data.settings.game_path = game_path;
+        self.data.settings.executable = executable;
+        Ok(())
+    }
+
+    /// Sets or clears the profile that is deployed when none is specified.
+    pub fn set_default_profile(&mut self, profile_name: Option<CompactString>) {
+        assert!(
+            profile_name
+                .as_ref()
+                .is_none_or(|name| self.data.profiles.contains_key(name)),
+            "profile exists"
+        );
+        self.changed = true;
+        self.data.settings.default_profile = profile_name;
+    }
+
+    /// Replaces the directories that are deployed somewhere other than the deployment root.
+    ///
+    /// Fails if the sources of two targets overlap.
+    pub fn set_mount_targets(&mut self, targets: Vec<MountTarget>) -> Result<(), InvalidMountTargetError> {
+        if !MountTarget::are_valid(&targets) {
+            return Err(InvalidMountTargetError);
+        }
+        self.changed = true;
+        self.data.settings.mount_targets = targets;
+        Ok(())
+    }
+
+    /// Sets or clears the Steam app ID used to launch the game through Steam.
+    pub fn set_steam_app_id(&mut self, app_id: Option<u32>) {
+        self.changed = true;
+        self.data.settings.steam_app_id = app_id;
+    }
+
+    /// Replaces the shell commands run around each game session.
+    ///
+    /// `pre_launch` commands run after the mod files are deployed, and `post_exit` commands run before
+    /// they are removed. Blank commands are discarded.
+    pub fn set_hooks(&mut self, pre_launch: Vec<CompactString>, post_exit: Vec<CompactString>) {
+        self.changed = true;
+        let non_blank = |hooks: Vec<CompactString>| hooks.into_iter().filter(|hook| !hook.trim().is_empty()).collect();
+        self.data.settings.pre_launch_hooks = non_blank(pre_launch);
+        self.data.settings.post_exit_hooks = non_blank(post_exit);
+    }
+
+    /// Sets the commands the game is run through, outermost first. Blank commands are discarded.
+    pub fn set_wrappers(&mut self, wrappers: Vec<CompactString>) {
+        self.changed = true;
+        self.data.settings.wrappers = wrappers
+            .into_iter()
+            .filter(|wrapper| !wrapper.trim().is_empty())
+            .collect();
+    }
+
+    /// Sets or clears the directory the game keeps its save games in, which makes each profile keep its own saves.
+    pub fn set_save_path(&mut self, save_path: Option<CompactString>) -> Result<(), RelativeSavePathError> {
+        if save_path
+            .as_deref()
+            .is_some_and(|path| !Path::new(path.as_str()).is_absolute())
+        {
+            return Err(RelativeSavePathError);
+        }
+        self.changed = true;
+        self.data.settings.save_path = save_path;
+        Ok(())
+    }
+
+    /// Sets the glob patterns of mod files that aren't deployed, or restores the default ones if `None`.
+    pub fn set_staging_exclusions(&mut self, patterns: Option<Vec<CompactString>>) {
+        self.changed = true;
+        self.data.settings.staging_exclusions = patterns;
+    }
+
+    /// Sets or clears the label of a set of mods in the mod order.
+    pub fn set_mods_label(&mut self, indices: &HashSet<ModOrderIndex>, label: Option<ModLabel>) {
+        self.record_change(format!("label {}", mod_count(indices.len())));
+        self.changed = true;
+        for idx in indices.iter().copied() {
+            let mod_index = self.mod_order()[idx].mod_index();
+            self.data.mods[mod_index].set_label(label);
+        }
+    }
+
+    /// Toggles the enabled state of a mod in the mod order.
+    pub fn toggle_mod_enabled(&mut self, index: ModOrderIndex) {
+        let description = format!("toggle '{}'", self.mod_by_order_index(index).name());
+        self.record_change(description);
+        self.changed = true;
+        let entry = &mut self.mod_order_mut()[index];
+        entry.enabled = !entry.enabled;
+    }
+
+    /// Toggles the enabled state of a set of mods in the mod order.
+    pub fn toggle_mods_enabled(&mut self, indices: &HashSet<ModOrderIndex>) {
+        self.record_change(format!("toggle {}", mod_count(indices.len())));
+        self.changed = true;
+        let mod_order = self.mod_order_mut();
+        for idx in indices.iter().copied() {
+            let entry = &mut mod_order[idx];
+            entry.enabled = !entry.enabled;
+        }
+    }
+
+    /// Enables every mod in the mod order.
+    pub fn enable_all_mods(&mut self) {
+        self.set_all_mods_enabled(true);
+    }
+
+    /// Disables every mod in the mod order.
+    pub fn disable_all_mods(&mut self) {
+        self.set_all_mods_enabled(false);
+    }
+
+    fn set_all_mods_enabled(&mut self, enabled: bool) {
+        self.record_change(if enabled { "enable all mods" } else { "disable all mods" });
+        self.changed = true;
+        let mods = &self.data.mods;
+        let mod_order = &mut self
+            .data
+            .profiles
+            .get_mut(&self.state.current_profile)
+            .expect("profile exists")
+            .mod_order;
+        for entry in mod_order.iter_mut() {
+            if mods[entry.mod_index()].kind() == ModEntryKind::Mod {
+                entry.enabled = enabled;
+            }
+        }
+    }
+
+    /// Moves a set of mods to a specific index in the mod order.
+    ///
+    /// Returns a map from the old to the new index of every moved mod.
+    pub fn move_mods(
+        &mut self,
+        mods_to_move: &HashSet<ModOrderIndex>,
+        to: ModOrderIndex,
+    ) -> HashMap<ModOrderIndex, ModOrderIndex> {
+        self.record_change(format!("move {}", mod_count(mods_to_move.len())));
+        self.changed = true;
+        move_multiple(
+            self.mod_order_mut().as_mut(),
+            mods_to_move.iter().map(|idx| (*idx).into()),
+            to.into(),
+        )
+        .into_iter()
+        .map(|(from, to)| (from.into(), to.into()))
+        .collect()
+    }
+
+    /// Moves a set of mods up by one position, grouping them together.
+    ///
+    /// Returns the new indices of the moved mods.
+    pub fn move_mods_up(&mut self, mods_to_move: &HashSet<ModOrderIndex>) -> HashSet<ModOrderIndex> {
+        let Some(first) = mods_to_move.iter().min() else {
+            return HashSet::default();
+        };
+        self.move_mods_and_get_indices(mods_to_move, first.saturating_sub(1u32))
+    }
+
+    /// Moves a set of mods down by one position, grouping them together.
+    ///
+    /// Returns the new indices of the moved mods.
+    pub fn move_mods_down(&mut self, mods_to_move: &HashSet<ModOrderIndex>) -> HashSet<ModOrderIndex> {
+        let Some(last) = mods_to_move.iter().max() else {
+            return HashSet::default();
+        };
+        let to = last
+            .saturating_add(2u32)
+            .min(ModOrderIndex::from(self.mod_order().len()));
+        self.move_mods_and_get_indices(mods_to_move, to)
+    }
+
+    /// Moves a set of mods to the start of the mod order.
+    ///
+    /// Returns the new indices of the moved mods.
+    pub fn move_mods_to_top(&mut self, mods_to_move: &HashSet<ModOrderIndex>) -> HashSet<ModOrderIndex> {
+        self.move_mods_and_get_indices(mods_to_move, ModOrderIndex::from(0u32))
+    }
+
+    /// Moves a set of mods to the end of the mod order.
+    ///
+    /// Returns the new indices of the moved mods.
+    pub fn move_mods_to_bottom(&mut self, mods_to_move: &HashSet<ModOrderIndex>) -> HashSet<ModOrderIndex> {
+        let len = ModOrderIndex::from(self.mod_order().len());
+        self.move_mods_and_get_indices(mods_to_move, len)
+    }
+
+    /// Moves a separator, along with every entry in its group, to a specific index in the mod order,
+    /// preserving the order of the group.
+    ///
+    /// If `to` is in the middle of another group, the group is placed after it instead, so that it doesn't take
+    /// over part of that group.
+    ///
+    /// Returns a map from the old to the new index of every moved entry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the entry at `separator` is not a separator.
+    pub fn move_separator_group(
+        &mut self,
+        separator: ModOrderIndex,
+        to: ModOrderIndex,
+    ) -> HashMap<ModOrderIndex, ModOrderIndex> {
+        let mut group: HashSet<ModOrderIndex> = self.separator_group(separator).collect();
+        let _ = group.insert(separator);
+
+        let len = self.mod_order().len();
+        let to = (usize::from(to)..len)
+            .map(ModOrderIndex::from)
+            .find(|idx| group.contains(idx) || self.is_separator(*idx))
+            .unwrap_or(ModOrderIndex::from(len));
+        self.move_mods(&group, to)
+    }
+
+    fn move_mods_and_get_indices(
+        &mut self,
+        mods_to_move: &HashSet<ModOrderIndex>,
+        to: ModOrderIndex,
+    ) -> HashSet<ModOrderIndex> {
+        self.move_mods(mods_to_move, to).into_values().collect()
+    }
+}
+
+/// Differences between two profiles, as returned by [`EditableInstance::profile_switch_diff`].
+#[derive(Debug, Default)]
+pub struct ProfileSwitchDiff {
+    /// Mods that would be enabled.
+    pub enabled: Vec<ModIndex>,
+    /// Mods that would be disabled.
+    pub disabled: Vec<ModIndex>,
+    /// Whether the mod order would be different.
+    pub order_differs: bool,
+}
+
+impl ProfileSwitchDiff {
+    /// Returns `true` if switching profiles would change nothing.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.enabled.is_empty() && self.disabled.is_empty() && !self.order_differs
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CreateModError {
+    #[error("there already exists a mod with the specified name")]
+    AlreadyExists,
+    #[error(transparent)]
+    InvalidName(#[from] InvalidModNameError),
+    #[error("failed to initialize mod directory")]
+    Init(#[from] ModInitError),
+    #[error(transparent)]
+    ReadOnly(#[from] ReadOnlyError),
+}
+
+#[derive(Debug, Error)]
+pub enum AddStagedModError {
+    #[error("there already exists a mod with the specified name")]
+    AlreadyExists,
+    #[error("the mods directory already contains a directory with the specified name")]
+    DirectoryExists,
+    #[error(transparent)]
+    InvalidName(#[from] InvalidModNameError),
+    #[error(transparent)]
+    Place(#[from] PlaceError),
+    #[error(transparent)]
+    ReadOnly(#[from] ReadOnlyError),
+}
+
+#[derive(Debug, Error)]
+pub enum CreateModFromDirError {
+    #[error(transparent)]
+    Stage(#[from] StageDirError),
+    #[error(transparent)]
+    Add(#[from] AddStagedModError),
+}
+
+#[derive(Debug, Error)]
+pub enum ReinstallModError {
+    #[error("failed to move old files out of the way")]
+    MoveOldFiles(#[source] io::Error),
+    #[error(transparent)]
+    Place(#[from] PlaceError),
+    #[error("separators can't be reinstalled")]
+    Separator,
+    #[error(transparent)]
+    ReadOnly(#[from] ReadOnlyError),
+}
+
+#[derive(Debug, Error)]
+pub enum RenameModError {
+    #[error("there already exists a mod with the specified name")]
+    AlreadyExists,
+    #[error(transparent)]
+    InvalidName(#[from] InvalidModNameError),
+    #[error("failed to rename mod directory")]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    ReadOnly(#[from] ReadOnlyError),
+}
+
+/// Error type returned by methods that modify files in the instance directory,
+/// when the instance was opened with [`EditableInstance::open_read_only`] or during a
+/// [transaction](EditableInstance::transaction).
+///
+/// Changes to the instance data alone are allowed in both cases, see [`EditableInstance::open_read_only`].
+#[derive(Debug, Error)]
+pub enum ReadOnlyError {
+    #[error("instance was opened read-only")]
+    Instance,
+    #[error("files can't be modified during a transaction")]
+    Transaction,
+}
+
+/// Error type returned by [`EditableInstance::remove_profile`].
+#[derive(Debug, Error)]
+#[error("an instance must have at least one profile")]
+pub struct LastProfileError;
+
+/// Error type returned by [`EditableInstance::set_game_path`].
+#[derive(Debug, Error)]
+#[error("the game path must be absolute")]
+pub struct RelativeGamePathError;
+
+/// Error type returned by [`EditableInstance::set_save_path`].
+#[derive(Debug, Error)]
+#[error("the save path must be absolute")]
+pub struct RelativeSavePathError;
+
+struct EditorState {
+    current_profile: CompactString,
+}
+
+impl Default for EditorState {
+    fn default() -> Self {
+        Self { current_profile: DEFAULT_PROFILE_NAME }
+    }
+}
+
+impl EditorState {
+    #[must_use]
+    pub const fn current_profile(&self) -> &CompactString {
+        &self.current_profile
+    }
+}
+
+fn hash_content(content: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn truncate_str(s: &str, len: usize) -> CompactString {
+    let mut truncated = CompactString::default();
+    for cluster in s.graphemes(true) {
+        if truncated.len() + cluster.len() > len {
+            break;
+        }
+        truncated.push_str(cluster);
+    }
+    truncated
+}
@@ -0,0 +1,79 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Applying imported mod lists to the mod order.
+
+use compact_str::CompactString;
+use typed_index_collections::TiVec;
+
+use mmm_core::instance::{Instance, ModEntryKind, ModIndex, ModOrderEntry, ModOrderIndex};
+
+use super::EditableInstance;
+use crate::modlist::ModListEntry;
+
+/// Result of [`EditableInstance::apply_mod_list`].
+#[derive(Debug, Default)]
+pub struct ModListImportReport {
+    /// Names of the entries in the list that don't match any mod in the instance.
+    pub unmatched: Vec<CompactString>,
+    /// Mods in the instance that aren't in the list.
+    pub unlisted: Vec<ModIndex>,
+}
+
+impl EditableInstance {
+    /// Reorders and enables or disables mods in the current profile's mod order according to a mod list.
+    ///
+    /// Entries are matched to mods by name and type. Mods that aren't in the list are disabled
+    /// and placed after the listed ones, keeping their relative order.
+    pub fn apply_mod_list(&mut self, entries: &[ModListEntry]) -> ModListImportReport {
+        let mut report = ModListImportReport::default();
+        let mut used = vec![false; self.mod_order().len()];
+        let mut new_order = TiVec::<ModOrderIndex, ModOrderEntry>::with_capacity(self.mod_order().len());
+
+        for list_entry in entries {
+            let found = self.mod_order().iter_enumerated().find(|(idx, entry)| {
+                let mod_decl = &self.mods()[entry.mod_index()];
+                !used[usize::from(*idx)] && mod_decl.kind() == list_entry.kind && mod_decl.name() == &list_entry.name
+            });
+
+            match found {
+                Some((idx, entry)) => {
+                    used[usize::from(idx)] = true;
+                    let mut entry = *entry;
+                    entry.enabled = list_entry.enabled;
+                    new_order.push(entry);
+                }
+                None => report.unmatched.push(list_entry.name.clone()),
+            }
+        }
+
+        for (idx, entry) in self.mod_order().iter_enumerated() {
+            if used[usize::from(idx)] {
+                continue;
+            }
+            let mut entry = *entry;
+            if self.mods()[entry.mod_index()].kind() == ModEntryKind::Mod {
+                report.unlisted.push(entry.mod_index());
+            }
+            entry.enabled = false;
+            new_order.push(entry);
+        }
+
+        self.record_change("import mod list");
+        self.changed = true;
+        *self.mod_order_mut() = new_order;
+        report
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Detection and cleanup of mod directories that don't match the mod list.
+
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use compact_str::CompactString;
+use thiserror::Error;
+
+use mmm_core::instance::{Instance, InvalidModNameError, ModDeclaration, ModEntryKind, ModIndex, ModOrderEntry};
+
+use super::{EditableInstance, ReadOnlyError};
+
+/// Mismatches between the mods directory and the mod list, as returned by [`EditableInstance::find_orphans`].
+#[derive(Debug, Default)]
+pub struct OrphanReport {
+    /// Directories in the mods directory that don't belong to any mod.
+    ///
+    /// These can be [adopted](EditableInstance::adopt_orphan), used to
+    /// [relink](EditableInstance::relink_mod) a mod, or deleted by the caller.
+    pub orphaned_dirs: Vec<PathBuf>,
+    /// Mods whose directory doesn't exist.
+    ///
+    /// These can be [relinked](EditableInstance::relink_mod) or [removed](EditableInstance::remove_mod).
+    pub missing_dirs: Vec<ModIndex>,
+}
+
+impl OrphanReport {
+    /// Returns `true` if no mismatches were found.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.orphaned_dirs.is_empty() && self.missing_dirs.is_empty()
+    }
+}
+
+impl EditableInstance {
+    /// Compares the contents of the mods directory with the mod list.
+    pub fn find_orphans(&self) -> Result<OrphanReport, io::Error> {
+        let orphaned_dirs = self.orphaned_dirs()?;
+
+        let mut missing_dirs = Vec::new();
+        for (idx, mod_decl) in self.mods().iter_enumerated() {
+            let Some(dir) = self.mod_dir(mod_decl) else {
+                continue;
+            };
+            if !fs::exists(&dir)? {
+                missing_dirs.push(idx);
+            }
+        }
+
+        Ok(OrphanReport { orphaned_dirs, missing_dirs })
+    }
+
+    /// Returns the paths of the directories in the mods directory that don't belong to any mod.
+    ///
+    /// Hidden directories, such as those used while installing or replacing mods, are ignored.
+    pub(crate) fn orphaned_dirs(&self) -> Result<Vec<PathBuf>, io::Error> {
+        let entries = match fs::read_dir(self.mods_dir()) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+
+        let mut orphans = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+
+            let file_name = entry.file_name();
+            if !file_name.as_encoded_bytes().starts_with(b".") && !self.is_mod_dir_name(&file_name) {
+                orphans.push(entry.path());
+            }
+        }
+        orphans.sort_unstable();
+        Ok(orphans)
+    }
+
+    /// Registers an orphaned directory as a new mod, with the same name as the directory.
+    ///
+    /// The new mod is appended, disabled, to the mod order.
+    pub fn adopt_orphan(&mut self, dir: &Path) -> Result<ModIndex, AdoptOrphanError> {
+        self.ensure_writable()?;
+        let name = self.orphan_name(dir)?;
+        if self.mods().iter().any(|m| m.name() == name.as_str()) {
+            return Err(AdoptOrphanError::AlreadyExists);
+        }
+        let mod_decl = ModDeclaration::new(name, ModEntryKind::Mod)?;
+
+        self.changed = true;
+        self.clear_history();
+        let idx = self.data.mods.push_and_get_key(mod_decl);
+        self.mod_order_mut().push(ModOrderEntry::new(idx));
+        Ok(idx)
+    }
+
+    /// Moves an orphaned directory into place as the directory of the specified mod.
+    ///
+    /// Fails if the mod's directory already exists, or if `dir` isn't one of the
+    /// [orphaned directories](OrphanReport::orphaned_dirs).
+    pub fn relink_mod(&mut self, idx: ModIndex, dir: &Path) -> Result<(), RelinkModError> {
+        self.ensure_files_writable()?;
+        let _ = self.orphan_name(dir)?;
+        let mod_dir = self.mod_dir(&self.mods()[idx]).ok_or(RelinkModError::Separator)?;
+        if fs::exists(&mod_dir).map_err(RelinkModError::Io)? {
+            return Err(RelinkModError::AlreadyExists);
+        }
+
+        fs::rename(dir, mod_dir).map_err(RelinkModError::Io)
+    }
+
+    /// Checks that the specified path is a non-hidden directory directly inside the mods directory
+    /// that doesn't belong to any mod, and returns its name.
+    fn orphan_name(&self, dir: &Path) -> Result<CompactString, NotAnOrphanError> {
+        if dir.parent() != Some(self.mods_dir().as_path()) || !dir.is_dir() {
+            return Err(NotAnOrphanError(dir.to_owned()));
+        }
+        dir.file_name()
+            .and_then(|name| name.to_str())
+            .filter(|name| !name.starts_with('.') && !self.is_mod_dir_name(name.as_ref()))
+            .map(CompactString::from)
+            .ok_or_else(|| NotAnOrphanError(dir.to_owned()))
+    }
+
+    /// Returns `true` if the directory with the specified name belongs to a mod.
+    fn is_mod_dir_name(&self, name: &OsStr) -> bool {
+        self.mods()
+            .iter()
+            .any(|m| m.kind() == ModEntryKind::Mod && m.name().as_str() == name)
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("'{0}' is not an orphaned directory in the mods directory")]
+pub struct NotAnOrphanError(PathBuf);
+
+#[derive(Debug, Error)]
+pub enum AdoptOrphanError {
+    #[error(transparent)]
+    NotAnOrphan(#[from] NotAnOrphanError),
+    #[error("there already exists a mod with the same name as the directory")]
+    AlreadyExists,
+    #[error(transparent)]
+    InvalidName(#[from] InvalidModNameError),
+    #[error(transparent)]
+    ReadOnly(#[from] ReadOnlyError),
+}
+
+#[derive(Debug, Error)]
+pub enum RelinkModError {
+    #[error(transparent)]
+    NotAnOrphan(#[from] NotAnOrphanError),
+    #[error("separators don't have a directory")]
+    Separator,
+    #[error("the mod's directory already exists")]
+    AlreadyExists,
+    #[error("failed to move directory")]
+    Io(#[source] io::Error),
+    #[error(transparent)]
+    ReadOnly(#[from] ReadOnlyError),
+}
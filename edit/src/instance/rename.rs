@@ -0,0 +1,199 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Renaming multiple mods at once.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use compact_str::CompactString;
+use foldhash::HashMap;
+use tempfile::TempDir;
+use thiserror::Error;
+use tracing::error;
+
+use mmm_core::instance::{Instance, ModDeclaration, ModIndex};
+
+use super::{EditableInstance, ReadOnlyError};
+
+/// Pattern used to compute new names in [`EditableInstance::bulk_rename`].
+#[derive(Copy, Clone, Debug)]
+pub enum RenamePattern<'a> {
+    /// Replace every occurrence of `find` with `replace`.
+    FindReplace { find: &'a str, replace: &'a str },
+    /// Add a prefix and a suffix.
+    Affix { prefix: &'a str, suffix: &'a str },
+}
+
+impl RenamePattern<'_> {
+    /// Returns the result of applying this pattern to a name.
+    #[must_use]
+    pub fn apply(self, name: &str) -> CompactString {
+        match self {
+            Self::FindReplace { find: "", .. } => CompactString::from(name),
+            Self::FindReplace { find, replace } => CompactString::from(name.replace(find, replace)),
+            Self::Affix { prefix, suffix } => {
+                let mut new_name = CompactString::with_capacity(prefix.len() + name.len() + suffix.len());
+                new_name.push_str(prefix);
+                new_name.push_str(name);
+                new_name.push_str(suffix);
+                new_name
+            }
+        }
+    }
+}
+
+/// The result of applying a [`RenamePattern`] to a mod, as returned by [`EditableInstance::preview_bulk_rename`].
+#[derive(Clone, Debug)]
+pub struct BulkRenameEntry {
+    pub idx: ModIndex,
+    pub new_name: CompactString,
+    pub problem: Option<BulkRenameProblem>,
+}
+
+/// Reason why a mod can't be renamed.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum BulkRenameProblem {
+    /// The new name isn't [valid](ModDeclaration::is_name_valid).
+    InvalidName,
+    /// The new name is the same as the name of another mod, after renaming.
+    Collision,
+}
+
+impl EditableInstance {
+    /// Computes the new names of the specified mods, without renaming anything.
+    pub fn preview_bulk_rename(&self, mods: &[ModIndex], pattern: RenamePattern) -> Vec<BulkRenameEntry> {
+        let new_names: HashMap<ModIndex, CompactString> = mods
+            .iter()
+            .map(|idx| (*idx, pattern.apply(self.mods()[*idx].name())))
+            .collect();
+
+        // Count how many mods end up with each name.
+        let mut name_count: HashMap<&str, usize> = HashMap::default();
+        for (idx, mod_decl) in self.mods().iter_enumerated() {
+            let name = new_names.get(&idx).unwrap_or(mod_decl.name());
+            *name_count.entry(name.as_str()).or_default() += 1;
+        }
+
+        mods.iter()
+            .map(|idx| {
+                let new_name = new_names[idx].clone();
+                let problem = if !ModDeclaration::is_name_valid(&new_name) {
+                    Some(BulkRenameProblem::InvalidName)
+                } else if name_count[new_name.as_str()] > 1 {
+                    Some(BulkRenameProblem::Collision)
+                } else {
+                    None
+                };
+                BulkRenameEntry { idx: *idx, new_name, problem }
+            })
+            .collect()
+    }
+
+    /// Renames the specified mods according to a pattern, renaming their directories accordingly.
+    ///
+    /// Nothing is renamed if any of the new names has a [problem](BulkRenameProblem).
+    /// Use [`Self::preview_bulk_rename`] to see the new names beforehand.
+    pub fn bulk_rename(&mut self, mods: &[ModIndex], pattern: RenamePattern) -> Result<(), BulkRenameError> {
+        self.ensure_files_writable()?;
+        let entries: Vec<_> = self
+            .preview_bulk_rename(mods, pattern)
+            .into_iter()
+            .filter(|entry| entry.new_name != *self.mods()[entry.idx].name())
+            .collect();
+        if entries.iter().any(|entry| entry.problem.is_some()) {
+            return Err(BulkRenameError::Conflict);
+        }
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        // Directories are first moved into a temporary directory, and only then to their new location,
+        // so that mods can take each other's names (e.g. when swapping names).
+        let moves: Vec<(PathBuf, PathBuf)> = entries
+            .iter()
+            .filter_map(|entry| {
+                let mod_decl = &self.mods()[entry.idx];
+                let from = self.mod_dir(mod_decl)?;
+                let to = from.with_file_name(entry.new_name.as_str());
+                Some((from, to))
+            })
+            .filter(|(from, _)| from.exists())
+            .collect();
+
+        // The temporary directory is kept (and removed manually, only if empty), so that files are never deleted
+        // if moving them back fails.
+        let staging = TempDir::with_prefix_in(".renaming-", self.mods_dir())
+            .map_err(BulkRenameError::Io)?
+            .keep();
+        let staged_path = |i: usize| staging.join(i.to_string());
+
+        for (i, (from, _)) in moves.iter().enumerate() {
+            if let Err(err) = fs::rename(from, staged_path(i)) {
+                for (j, (from, _)) in moves.iter().enumerate().take(i) {
+                    restore(&staged_path(j), from);
+                }
+                let _ = fs::remove_dir(&staging);
+                return Err(BulkRenameError::Io(err));
+            }
+        }
+
+        for (i, (_, to)) in moves.iter().enumerate() {
+            if let Err(err) = fs::rename(staged_path(i), to) {
+                for (j, (from, to)) in moves.iter().enumerate() {
+                    if j < i {
+                        restore(to, from);
+                    } else {
+                        restore(&staged_path(j), from);
+                    }
+                }
+                let _ = fs::remove_dir(&staging);
+                return Err(BulkRenameError::Io(err));
+            }
+        }
+        let _ = fs::remove_dir(&staging);
+
+        self.changed = true;
+        self.clear_history();
+        for entry in entries {
+            self.data.mods[entry.idx]
+                .set_name(entry.new_name)
+                .expect("name was validated");
+        }
+        Ok(())
+    }
+}
+
+fn restore(from: &Path, to: &Path) {
+    if let Err(err) = fs::rename(from, to) {
+        error!(
+            "failed to move '{}' back to '{}': {}",
+            from.display(),
+            to.display(),
+            err
+        );
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum BulkRenameError {
+    #[error("some of the new names are invalid or conflict with other mods")]
+    Conflict,
+    #[error("failed to rename mod directory")]
+    Io(#[source] io::Error),
+    #[error(transparent)]
+    ReadOnly(#[from] ReadOnlyError),
+}
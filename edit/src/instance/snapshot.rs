@@ -0,0 +1,193 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Snapshots of the instance data, that can be restored later.
+//!
+//! Each snapshot is a directory in the snapshots directory, named `<unix timestamp>-<random suffix>`,
+//! containing a copy of the instance data file and a file with the snapshot's label.
+//! Mod files are not included.
+
+use std::cmp::Reverse;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use tempfile::TempDir;
+use thiserror::Error;
+use tracing::{trace, warn};
+
+use mmm_core::instance::Instance;
+use mmm_core::instance::data::{INSTANCE_DATA_FILE, InstanceData, InstanceDataOpenError};
+
+use super::{EditableInstance, ReadOnlyError};
+use crate::writer::{WriteError, WriteTarget, write_blocking};
+
+/// Name of the directory in the instance's root directory that contains snapshots.
+pub const SNAPSHOTS_DIR: &str = "snapshots";
+
+const LABEL_FILE: &str = "label";
+
+/// A snapshot of the instance data.
+#[derive(Debug)]
+pub struct Snapshot {
+    path: PathBuf,
+    label: String,
+    created_at: SystemTime,
+}
+
+impl Snapshot {
+    /// Returns the snapshot's label.
+    #[must_use]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the time at which the snapshot was created.
+    #[must_use]
+    pub const fn created_at(&self) -> SystemTime {
+        self.created_at
+    }
+
+    /// Returns the path to the snapshot's directory.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl EditableInstance {
+    /// Returns the absolute path to the instance's snapshots directory.
+    #[must_use]
+    pub fn snapshots_dir(&self) -> PathBuf {
+        self.dir().join(SNAPSHOTS_DIR)
+    }
+
+    /// Saves a copy of the current instance data (but not of the mod files) with the specified label.
+    pub fn snapshot(&mut self, label: &str) -> Result<Snapshot, SnapshotError> {
+        self.ensure_files_writable()?;
+        let snapshots_dir = self.snapshots_dir();
+        fs::create_dir_all(&snapshots_dir).map_err(SnapshotError::CreateDir)?;
+
+        let created_at = SystemTime::now();
+        let timestamp = created_at
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let dir = TempDir::with_prefix_in(format!("{timestamp}-"), &snapshots_dir).map_err(SnapshotError::CreateDir)?;
+
+        fs::write(dir.path().join(LABEL_FILE), label).map_err(SnapshotError::CreateDir)?;
+        let content = self.serialize()?;
+        write_blocking(dir.path(), WriteTarget::InstanceData, &content)?;
+
+        trace!("created snapshot '{}'", label);
+        Ok(Snapshot {
+            path: dir.keep(),
+            label: label.to_owned(),
+            created_at,
+        })
+    }
+
+    /// Returns the instance's snapshots, most recent first.
+    ///
+    /// Snapshots that can't be read are skipped.
+    pub fn snapshots(&self) -> Result<Vec<Snapshot>, io::Error> {
+        let dir_entries = match fs::read_dir(self.snapshots_dir()) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+
+        let mut snapshots = Vec::new();
+        for dir_entry in dir_entries {
+            let path = dir_entry?.path();
+            match read_snapshot(&path) {
+                Some(snapshot) => snapshots.push(snapshot),
+                None => warn!("ignoring invalid snapshot '{}'", path.display()),
+            }
+        }
+        snapshots.sort_by_key(|snapshot| Reverse(snapshot.created_at));
+        Ok(snapshots)
+    }
+
+    /// Permanently deletes the specified snapshot.
+    pub fn delete_snapshot(&self, snapshot: Snapshot) -> Result<(), DeleteSnapshotError> {
+        self.ensure_files_writable()?;
+        trace!("deleting snapshot '{}'", snapshot.path.display());
+        fs::remove_dir_all(&snapshot.path).map_err(DeleteSnapshotError::Io)
+    }
+
+    /// Replaces the instance data with the contents of a snapshot.
+    ///
+    /// Mod files are not affected, so mods created after the snapshot was taken won't be part of the mod list
+    /// anymore (see [`Self::find_orphans`]). Every index is invalidated.
+    pub fn restore_snapshot(&mut self, snapshot: &Snapshot) -> Result<(), RestoreSnapshotError> {
+        let data = InstanceData::from_file(&snapshot.path.join(INSTANCE_DATA_FILE))?;
+        if data.profiles.is_empty() {
+            return Err(RestoreSnapshotError::NoProfiles);
+        }
+
+        trace!("restoring snapshot '{}'", snapshot.label);
+        self.data = data;
+        if !self.data.profiles.contains_key(self.state.current_profile()) {
+            let (name, _) = self
+                .data
+                .profiles
+                .first_key_value()
+                .expect("there's at least one profile");
+            self.state.current_profile = name.clone();
+        }
+        self.add_missing_mods_to_mod_order();
+        self.changed = true;
+        self.clear_history();
+        Ok(())
+    }
+}
+
+fn read_snapshot(path: &Path) -> Option<Snapshot> {
+    let (timestamp, _) = path.file_name()?.to_str()?.split_once('-')?;
+    let created_at = SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(timestamp.parse().ok()?))?;
+    if !path.join(INSTANCE_DATA_FILE).is_file() {
+        return None;
+    }
+    let label = fs::read_to_string(path.join(LABEL_FILE)).unwrap_or_default();
+
+    Some(Snapshot { path: path.to_owned(), label, created_at })
+}
+
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("failed to create snapshot directory")]
+    CreateDir(#[source] io::Error),
+    #[error("failed to write snapshot")]
+    Write(#[from] WriteError),
+    #[error(transparent)]
+    ReadOnly(#[from] ReadOnlyError),
+}
+
+#[derive(Debug, Error)]
+pub enum DeleteSnapshotError {
+    #[error("failed to delete snapshot")]
+    Io(#[source] io::Error),
+    #[error(transparent)]
+    ReadOnly(#[from] ReadOnlyError),
+}
+
+#[derive(Debug, Error)]
+pub enum RestoreSnapshotError {
+    #[error("failed to read snapshot")]
+    Open(#[from] InstanceDataOpenError),
+    #[error("snapshot doesn't contain any profiles")]
+    NoProfiles,
+}
@@ -0,0 +1,155 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Sorting of the mod order.
+
+use std::cmp::Ordering;
+use std::fs;
+use std::time::SystemTime;
+
+use foldhash::HashSet;
+
+use mmm_core::instance::{Instance, ModEntryKind, ModOrderEntry, ModOrderIndex};
+
+use super::EditableInstance;
+use crate::util::name_ord;
+
+/// Criterion used by [`EditableInstance::sort_mod_order`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SortCriterion {
+    /// Sort by mod name.
+    Name,
+    /// Sort by category. Mods without a category are placed last.
+    Category,
+    /// Sort by the creation time of each mod's directory, oldest first.
+    /// Mods whose creation time can't be determined are placed last.
+    InstallDate,
+}
+
+/// Controls which entries are affected by [`EditableInstance::sort_mod_order`].
+#[derive(Copy, Clone, Debug)]
+pub enum SortScope<'a> {
+    /// Sort the mods under each separator separately, so that every mod stays in its group.
+    All,
+    /// Sort the specified entries among themselves, keeping the set of positions they occupy.
+    Selection(&'a HashSet<ModOrderIndex>),
+    /// Sort the mods under the specified separator.
+    Group(ModOrderIndex),
+}
+
+impl EditableInstance {
+    /// Sorts (part of) the mod order according to the specified criterion.
+    ///
+    /// The sort is stable, so entries that compare equal keep their relative order.
+    /// Separators are never moved.
+    pub fn sort_mod_order(&mut self, criterion: SortCriterion, scope: SortScope) {
+        let runs: Vec<Vec<ModOrderIndex>> = match scope {
+            SortScope::All => {
+                let mut runs = vec![Vec::new()];
+                for idx in 0..self.mod_order().len() {
+                    let idx = ModOrderIndex::from(idx);
+                    if self.is_separator(idx) {
+                        runs.push(Vec::new());
+                    } else {
+                        runs.last_mut().expect("there's at least one run").push(idx);
+                    }
+                }
+                runs
+            }
+            SortScope::Selection(selection) => {
+                let mut run: Vec<_> = selection
+                    .iter()
+                    .copied()
+                    .filter(|idx| !self.is_separator(*idx))
+                    .collect();
+                run.sort_unstable();
+                vec![run]
+            }
+            SortScope::Group(separator) => vec![self.separator_group(separator).collect()],
+        };
+
+        self.record_change("sort mods");
+        self.changed = true;
+        for run in runs.iter().filter(|run| run.len() > 1) {
+            self.sort_run(criterion, run);
+        }
+    }
+
+    fn sort_run(&mut self, criterion: SortCriterion, positions: &[ModOrderIndex]) {
+        let entries: Vec<ModOrderEntry> = positions.iter().map(|idx| self.mod_order()[*idx]).collect();
+
+        let sorted: Vec<ModOrderEntry> = match criterion {
+            SortCriterion::Name => {
+                let mut entries = entries;
+                entries.sort_by(|a, b| name_ord(self.mods()[a.mod_index()].name(), self.mods()[b.mod_index()].name()));
+                entries
+            }
+            SortCriterion::Category => {
+                let mut entries = entries;
+                entries.sort_by(|a, b| {
+                    cmp_none_last(
+                        self.mods()[a.mod_index()].category(),
+                        self.mods()[b.mod_index()].category(),
+                        |a, b| name_ord(a, b),
+                    )
+                });
+                entries
+            }
+            SortCriterion::InstallDate => {
+                let mut keyed: Vec<(Option<SystemTime>, ModOrderEntry)> = entries
+                    .into_iter()
+                    .map(|entry| (self.install_date(entry), entry))
+                    .collect();
+                keyed.sort_by(|(a, _), (b, _)| cmp_none_last(*a, *b, |a, b| a.cmp(&b)));
+                keyed.into_iter().map(|(_, entry)| entry).collect()
+            }
+        };
+
+        let mod_order = self.mod_order_mut();
+        for (idx, entry) in positions.iter().zip(sorted) {
+            mod_order[*idx] = entry;
+        }
+    }
+
+    fn install_date(&self, entry: ModOrderEntry) -> Option<SystemTime> {
+        let dir = self.mod_dir(&self.mods()[entry.mod_index()])?;
+        let metadata = fs::metadata(dir).ok()?;
+        metadata.created().or_else(|_| metadata.modified()).ok()
+    }
+
+    /// Returns `true` if the entry at the specified index is a separator.
+    pub(crate) fn is_separator(&self, idx: ModOrderIndex) -> bool {
+        self.mod_by_order_index(idx).kind() == ModEntryKind::Separator
+    }
+
+    /// Returns the indices of the entries between the specified separator and the next one.
+    pub(crate) fn separator_group(&self, separator: ModOrderIndex) -> impl Iterator<Item = ModOrderIndex> + use<> {
+        assert!(self.is_separator(separator));
+        let start = usize::from(separator) + 1;
+        let end = (start..self.mod_order().len())
+            .find(|idx| self.is_separator(ModOrderIndex::from(*idx)))
+            .unwrap_or(self.mod_order().len());
+        (start..end).map(ModOrderIndex::from)
+    }
+}
+
+fn cmp_none_last<T>(left: Option<T>, right: Option<T>, cmp: impl FnOnce(T, T) -> Ordering) -> Ordering {
+    match (left, right) {
+        (Some(left), Some(right)) => cmp(left, right),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
@@ -0,0 +1,59 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Grouping of multiple operations into a single change.
+
+use std::mem;
+
+use tracing::trace;
+
+use super::EditableInstance;
+
+impl EditableInstance {
+    /// Runs a group of operations as a single change, which is [undone](Self::undo) as a whole.
+    ///
+    /// If `f` returns an error, every change it made to the instance data (the mod list, profiles,
+    /// and the current profile) is rolled back. Since changes to the filesystem can't be rolled back,
+    /// methods that modify files, such as [`Self::rename_mod`] or [`Self::create_mod`],
+    /// return [`ReadOnlyError::Transaction`](super::ReadOnlyError::Transaction) while `f` runs.
+    ///
+    /// [`Self::save`] does nothing while `f` runs, so the whole group is written to disk at once
+    /// by the first call after the transaction ends.
+    pub fn transaction<T, E>(
+        &mut self,
+        description: impl Into<String>,
+        f: impl FnOnce(&mut Self) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let data = self.data.clone();
+        let current_profile = self.state.current_profile.clone();
+        let changed = self.changed;
+        let was_in_transaction = mem::replace(&mut self.in_transaction, true);
+        let history_state = (!was_in_transaction).then(|| self.begin_transaction_history());
+
+        let result = f(self);
+
+        self.in_transaction = was_in_transaction;
+        if result.is_err() {
+            trace!("rolling back transaction");
+            self.data = data;
+            self.state.current_profile = current_profile;
+            self.changed = changed;
+        }
+        if let Some(state) = history_state {
+            self.end_transaction_history(description.into(), state, result.is_ok());
+        }
+        result
+    }
+}
@@ -0,0 +1,236 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Removal of mods to an instance-local trash directory, from which they can be restored.
+//!
+//! Each removed mod gets its own directory in the trash, named `<unix timestamp>-<random suffix>`,
+//! containing the mod's declaration (`declaration.cbor`), its position in each profile's mod order
+//! (`profiles.cbor`), and its files (`files/`).
+
+use std::cmp::Reverse;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use compact_str::CompactString;
+use tempfile::TempDir;
+use thiserror::Error;
+use tracing::{trace, warn};
+
+use mmm_core::instance::{Instance, ModDeclaration, ModIndex, ModOrderEntry, ModOrderIndex};
+
+use super::{EditableInstance, ReadOnlyError};
+
+/// Name of the directory in the instance's root directory that contains removed mods.
+pub const TRASH_DIR: &str = ".trash";
+
+const DECLARATION_FILE: &str = "declaration.cbor";
+const PROFILES_FILE: &str = "profiles.cbor";
+const FILES_DIR: &str = "files";
+
+/// A mod in the trash.
+#[derive(Debug)]
+pub struct TrashEntry {
+    path: PathBuf,
+    declaration: ModDeclaration,
+    /// The name of each profile the mod was in, with its position in the mod order, and whether it was enabled.
+    profiles: Vec<(CompactString, usize, bool)>,
+    removed_at: SystemTime,
+}
+
+impl TrashEntry {
+    /// Returns the name of the removed mod.
+    #[must_use]
+    pub const fn name(&self) -> &CompactString {
+        self.declaration.name()
+    }
+
+    /// Returns the time at which the mod was removed.
+    #[must_use]
+    pub const fn removed_at(&self) -> SystemTime {
+        self.removed_at
+    }
+
+    /// Returns the path to the entry's directory.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Permanently deletes the entry.
+    pub fn purge(self) -> Result<(), io::Error> {
+        trace!("purging trash entry '{}'", self.path.display());
+        fs::remove_dir_all(&self.path)
+    }
+}
+
+impl EditableInstance {
+    /// Returns the absolute path to the instance's trash directory.
+    #[must_use]
+    pub fn trash_dir(&self) -> PathBuf {
+        self.dir().join(TRASH_DIR)
+    }
+
+    /// Removes the specified mod, moving its files to the trash.
+    ///
+    /// Indices are invalidated in the same way as [`Self::remove_mod`].
+    pub fn trash_mod(&mut self, idx: ModIndex) -> Result<(), TrashModError> {
+        self.ensure_files_writable()?;
+        let mod_decl = &self.mods()[idx];
+
+        let trash_dir = self.trash_dir();
+        fs::create_dir_all(&trash_dir).map_err(TrashModError::CreateEntry)?;
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let entry = TempDir::with_prefix_in(format!("{timestamp}-"), &trash_dir).map_err(TrashModError::CreateEntry)?;
+
+        let declaration = cbor4ii::serde::to_vec(Vec::new(), mod_decl).expect("serialization doesn't fail");
+        fs::write(entry.path().join(DECLARATION_FILE), declaration).map_err(TrashModError::CreateEntry)?;
+
+        let profiles: Vec<_> = self
+            .data
+            .profiles
+            .iter()
+            .filter_map(|(name, profile)| {
+                profile
+                    .mod_order
+                    .iter()
+                    .enumerate()
+                    .find(|(_, entry)| entry.mod_index() == idx)
+                    .map(|(position, entry)| (name.clone(), position, entry.enabled))
+            })
+            .collect();
+        let profiles = cbor4ii::serde::to_vec(Vec::new(), &profiles).expect("serialization doesn't fail");
+        fs::write(entry.path().join(PROFILES_FILE), profiles).map_err(TrashModError::CreateEntry)?;
+
+        if let Some(mod_dir) = self.mod_dir(mod_decl) {
+            match fs::rename(&mod_dir, entry.path().join(FILES_DIR)) {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(TrashModError::MoveFiles(err)),
+            }
+        }
+
+        trace!("moved mod '{}' to the trash", mod_decl.name());
+        let _ = entry.keep();
+        let _ = self.remove_mod(idx);
+        Ok(())
+    }
+
+    /// Returns the mods in the trash, most recently removed first.
+    ///
+    /// Entries that can't be read are skipped.
+    pub fn trash_entries(&self) -> Result<Vec<TrashEntry>, io::Error> {
+        let dir_entries = match fs::read_dir(self.trash_dir()) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+
+        let mut entries = Vec::new();
+        for dir_entry in dir_entries {
+            let path = dir_entry?.path();
+            match read_trash_entry(&path) {
+                Some(entry) => entries.push(entry),
+                None => warn!("ignoring invalid trash entry '{}'", path.display()),
+            }
+        }
+        entries.sort_by_key(|entry| Reverse(entry.removed_at));
+        Ok(entries)
+    }
+
+    /// Restores a mod from the trash, at the position it had in the mod order of each profile, enabled if it was.
+    ///
+    /// Mods removed before their positions were recorded are appended, disabled, to the mod order.
+    /// Returns the index of the restored mod.
+    pub fn restore_from_trash(&mut self, entry: TrashEntry) -> Result<ModIndex, RestoreFromTrashError> {
+        self.ensure_files_writable()?;
+        if self.mods().iter().any(|m| m.name() == entry.name()) {
+            return Err(RestoreFromTrashError::AlreadyExists);
+        }
+
+        let files = entry.path.join(FILES_DIR);
+        if let Some(mod_dir) = self.mod_dir(&entry.declaration)
+            && files.exists()
+        {
+            fs::create_dir_all(self.mods_dir()).map_err(RestoreFromTrashError::MoveFiles)?;
+            fs::rename(&files, &mod_dir).map_err(RestoreFromTrashError::MoveFiles)?;
+        }
+
+        if let Err(err) = fs::remove_dir_all(&entry.path) {
+            warn!("failed to remove trash entry '{}': {}", entry.path.display(), err);
+        }
+
+        trace!("restored mod '{}' from the trash", entry.name());
+        self.changed = true;
+        self.clear_history();
+        let idx = self.data.mods.push_and_get_key(entry.declaration);
+        for (profile_name, position, enabled) in entry.profiles {
+            // The profile might have been removed since.
+            let Some(profile) = self.data.profiles.get_mut(&profile_name) else {
+                continue;
+            };
+            let mut order_entry = ModOrderEntry::new(idx);
+            order_entry.enabled = enabled;
+            let position = ModOrderIndex::from(position.min(profile.mod_order.len()));
+            profile.mod_order.insert(position, order_entry);
+        }
+        // Profiles without a recorded position get the mod appended when they're switched to.
+        self.add_missing_mods_to_mod_order();
+        Ok(idx)
+    }
+}
+
+fn read_trash_entry(path: &Path) -> Option<TrashEntry> {
+    let (timestamp, _) = path.file_name()?.to_str()?.split_once('-')?;
+    let removed_at = SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(timestamp.parse().ok()?))?;
+
+    let declaration = fs::read(path.join(DECLARATION_FILE)).ok()?;
+    let declaration = cbor4ii::serde::from_slice(&declaration).ok()?;
+    let profiles = match fs::read(path.join(PROFILES_FILE)) {
+        Ok(profiles) => cbor4ii::serde::from_slice(&profiles).ok()?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(_) => return None,
+    };
+
+    Some(TrashEntry {
+        path: path.to_owned(),
+        declaration,
+        profiles,
+        removed_at,
+    })
+}
+
+#[derive(Debug, Error)]
+pub enum TrashModError {
+    #[error("failed to create trash entry")]
+    CreateEntry(#[source] io::Error),
+    #[error("failed to move mod files to the trash")]
+    MoveFiles(#[source] io::Error),
+    #[error(transparent)]
+    ReadOnly(#[from] ReadOnlyError),
+}
+
+#[derive(Debug, Error)]
+pub enum RestoreFromTrashError {
+    #[error("there already exists a mod with the same name")]
+    AlreadyExists,
+    #[error("failed to move mod files out of the trash")]
+    MoveFiles(#[source] io::Error),
+    #[error(transparent)]
+    ReadOnly(#[from] ReadOnlyError),
+}
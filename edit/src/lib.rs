@@ -16,11 +16,22 @@
 #![forbid(unsafe_code)]
 
 pub mod archive;
+pub mod disk_usage;
+pub mod downloads;
 pub mod install;
 mod instance;
 mod r#mod;
+pub mod modlist;
 pub mod util;
+#[cfg(feature = "watch")]
+pub mod watch;
 mod writer;
 
-pub use instance::{EditableInstance, InstanceOpenError};
+pub use instance::{
+    BulkRenameEntry, BulkRenameProblem, BundleOptions, Diagnostic, EditableInstance, InstanceCreateError,
+    InstanceOpenError, LastProfileError, ModListImportReport, OrphanReport, ReadOnlyError, RelativeGamePathError,
+    RelativeSavePathError, RenamePattern, SAVE_INTERVAL, SNAPSHOTS_DIR, Snapshot, SortCriterion, SortScope, TRASH_DIR,
+    TrashEntry,
+};
 pub use r#mod::{Mod, ModInitError};
+pub use writer::WriteError;
@@ -17,13 +17,14 @@ use thiserror::Error;
 
 use mmm_core::instance::{Instance, ModIndex};
 
-use crate::EditableInstance;
+use crate::{EditableInstance, ReadOnlyError};
 
 pub struct Mod;
 
 impl Mod {
     /// Initializes a mod's directory, creating it if it doesn't exist.
     pub fn init(instance: &EditableInstance, idx: ModIndex) -> Result<(), ModInitError> {
+        instance.ensure_files_writable()?;
         let mod_decl = &instance.mods()[idx];
         let Some(path) = instance.mod_dir(mod_decl) else {
             // it's a separator, do nothing
@@ -36,5 +37,9 @@ impl Mod {
 }
 
 #[derive(Debug, Error)]
-#[error("git init failed")]
-pub struct ModInitError(#[from] git2::Error);
+pub enum ModInitError {
+    #[error("git init failed")]
+    Git(#[from] git2::Error),
+    #[error(transparent)]
+    ReadOnly(#[from] ReadOnlyError),
+}
@@ -0,0 +1,458 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Exporting and importing the mod order as a human-readable list.
+
+use std::fmt::Write;
+use std::mem;
+
+use compact_str::CompactString;
+use thiserror::Error;
+
+use mmm_core::instance::{Instance, ModEntryKind};
+
+/// Format of an exported mod list.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ModListFormat {
+    /// One entry per line, prefixed by `+` (enabled mod), `-` (disabled mod) or `*` (separator).
+    /// The version and category, if present, follow the name, separated by tabs.
+    /// Backslashes, tabs and line breaks are escaped with a backslash (`\\`, `\t`, `\n` and `\r`).
+    Text,
+    /// Comma-separated values with a header row, as described in RFC 4180.
+    Csv,
+    /// A Markdown table.
+    Markdown,
+}
+
+impl ModListFormat {
+    /// Returns the file extension usually used for this format.
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Text => "txt",
+            Self::Csv => "csv",
+            Self::Markdown => "md",
+        }
+    }
+
+    /// Guesses the format of a list exported by [`export_mod_list`].
+    #[must_use]
+    pub fn detect(input: &str) -> Self {
+        let first_line = input.trim_start().lines().next().unwrap_or_default();
+        if first_line.starts_with("name,type,") {
+            Self::Csv
+        } else if first_line.starts_with('|') {
+            Self::Markdown
+        } else {
+            Self::Text
+        }
+    }
+}
+
+/// An entry of an imported mod list.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ModListEntry {
+    pub name: CompactString,
+    pub kind: ModEntryKind,
+    /// Whether the mod is enabled. Always `false` for separators.
+    pub enabled: bool,
+}
+
+/// Exports the mod order of the instance's current profile, in order.
+#[must_use]
+pub fn export_mod_list(instance: &impl Instance, format: ModListFormat) -> String {
+    let mut out = String::new();
+
+    match format {
+        ModListFormat::Text => {}
+        ModListFormat::Csv => out.push_str("name,type,enabled,version,category\r\n"),
+        ModListFormat::Markdown => {
+            out.push_str("| Name | Enabled | Version | Category |\n");
+            out.push_str("| --- | --- | --- | --- |\n");
+        }
+    }
+
+    for entry in instance.mod_order() {
+        let mod_decl = &instance.mods()[entry.mod_index()];
+        let name = mod_decl.name().as_str();
+        let version = mod_decl.version().map_or("", |v| v.as_str());
+        let category = mod_decl.category().map_or("", |c| c.as_str());
+        let is_separator = mod_decl.kind() == ModEntryKind::Separator;
+
+        match format {
+            ModListFormat::Text => {
+                let marker = match (is_separator, entry.enabled) {
+                    (true, _) => '*',
+                    (false, true) => '+',
+                    (false, false) => '-',
+                };
+                let _ = write!(out, "{marker} {}", text_field(name));
+                if !version.is_empty() || !category.is_empty() {
+                    let _ = write!(out, "\t{}", text_field(version));
+                }
+                if !category.is_empty() {
+                    let _ = write!(out, "\t{}", text_field(category));
+                }
+                out.push('\n');
+            }
+            ModListFormat::Csv => {
+                let (kind, enabled) = if is_separator {
+                    ("separator", "")
+                } else {
+                    ("mod", if entry.enabled { "true" } else { "false" })
+                };
+                let _ = write!(
+                    out,
+                    "{},{kind},{enabled},{},{}\r\n",
+                    csv_field(name),
+                    csv_field(version),
+                    csv_field(category),
+                );
+            }
+            ModListFormat::Markdown => {
+                if is_separator {
+                    let _ = writeln!(out, "| **{}** | | | |", markdown_cell(name));
+                } else {
+                    let _ = writeln!(
+                        out,
+                        "| {} | {} | {} | {} |",
+                        markdown_cell(name),
+                        if entry.enabled { "✓" } else { "" },
+                        markdown_cell(version),
+                        markdown_cell(category),
+                    );
+                }
+            }
+        }
+    }
+
+    out
+}
+
+/// Escapes backslashes, and the characters that separate fields and entries in the text format.
+fn text_field(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Quotes a CSV field, if necessary.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+/// Escapes characters that have special meaning in Markdown tables.
+fn markdown_cell(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '|' | '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' | '\r' => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Parses a list exported by [`export_mod_list`].
+///
+/// Versions and categories are ignored.
+pub fn parse_mod_list(input: &str, format: ModListFormat) -> Result<Vec<ModListEntry>, ParseModListError> {
+    match format {
+        ModListFormat::Text => parse_text(input),
+        ModListFormat::Csv => parse_csv(input),
+        ModListFormat::Markdown => parse_markdown(input),
+    }
+}
+
+fn parse_text(input: &str) -> Result<Vec<ModListEntry>, ParseModListError> {
+    let mut entries = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let mut chars = line.chars();
+        let (kind, enabled) = match chars.next() {
+            Some('+') => (ModEntryKind::Mod, true),
+            Some('-') => (ModEntryKind::Mod, false),
+            Some('*') => (ModEntryKind::Separator, false),
+            _ => return Err(ParseModListError::InvalidLine(i + 1)),
+        };
+        let rest = chars.as_str().strip_prefix(' ').unwrap_or(chars.as_str());
+        let name = rest.split('\t').next().unwrap_or_default();
+        entries.push(ModListEntry { name: unescape_text(name), kind, enabled });
+    }
+    Ok(entries)
+}
+
+fn unescape_text(value: &str) -> CompactString {
+    let mut unescaped = CompactString::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some(next) => unescaped.push(next),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+fn parse_csv(input: &str) -> Result<Vec<ModListEntry>, ParseModListError> {
+    let mut records = csv_records(input)?.into_iter();
+    let header = records.next().ok_or(ParseModListError::MissingHeader)?;
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|column| column == name)
+            .ok_or(ParseModListError::MissingHeader)
+    };
+    let (name_column, type_column, enabled_column) = (column("name")?, column("type")?, column("enabled")?);
+
+    let mut entries = Vec::new();
+    for (i, record) in records.enumerate() {
+        if record.len() == 1 && record[0].is_empty() {
+            continue;
+        }
+
+        let line = i + 2;
+        let field = |column: usize| record.get(column).ok_or(ParseModListError::InvalidLine(line));
+        let kind = match field(type_column)?.as_str() {
+            "mod" => ModEntryKind::Mod,
+            "separator" => ModEntryKind::Separator,
+            _ => return Err(ParseModListError::InvalidLine(line)),
+        };
+        let enabled = field(enabled_column)? == "true";
+        entries.push(ModListEntry { name: field(name_column)?.into(), kind, enabled });
+    }
+    Ok(entries)
+}
+
+/// Splits CSV input into records, as described in RFC 4180.
+fn csv_records(input: &str) -> Result<Vec<Vec<String>>, ParseModListError> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                let _ = chars.next();
+                field.push('"');
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => record.push(mem::take(&mut field)),
+            ('\r', false) if chars.peek() == Some(&'\n') => {}
+            ('\n', false) => {
+                record.push(mem::take(&mut field));
+                records.push(mem::take(&mut record));
+            }
+            (c, _) => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(ParseModListError::UnterminatedQuote);
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+fn parse_markdown(input: &str) -> Result<Vec<ModListEntry>, ParseModListError> {
+    let mut entries = Vec::new();
+    // skip the header and delimiter rows
+    for (i, line) in input.lines().enumerate().skip(2) {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let cells = markdown_cells(line).ok_or(ParseModListError::InvalidLine(i + 1))?;
+        let [name, enabled, ..] = cells.as_slice() else {
+            return Err(ParseModListError::InvalidLine(i + 1));
+        };
+
+        let entry = match name.strip_prefix("**").and_then(|name| name.strip_suffix("**")) {
+            Some(name) => ModListEntry {
+                name: unescape_markdown(name),
+                kind: ModEntryKind::Separator,
+                enabled: false,
+            },
+            None => ModListEntry {
+                name: unescape_markdown(name),
+                kind: ModEntryKind::Mod,
+                enabled: !enabled.is_empty(),
+            },
+        };
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Splits a Markdown table row into its (still escaped) cells.
+fn markdown_cells(line: &str) -> Option<Vec<&str>> {
+    let line = line.strip_prefix('|')?;
+    let mut cells = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '|' => {
+                cells.push(line[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    Some(cells)
+}
+
+fn unescape_markdown(value: &str) -> CompactString {
+    let mut unescaped = CompactString::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\'
+            && let Some(next) = chars.next()
+        {
+            unescaped.push(next);
+        } else {
+            unescaped.push(c);
+        }
+    }
+    unescaped
+}
+
+#[derive(Debug, Error)]
+pub enum ParseModListError {
+    #[error("line {0} is not a valid mod list entry")]
+    InvalidLine(usize),
+    #[error("missing or invalid header")]
+    MissingHeader,
+    #[error("unterminated quoted field")]
+    UnterminatedQuote,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mmm_core::instance::{ModDeclaration, ModIndex, ModOrderEntry, ModOrderIndex};
+    use std::path::Path;
+    use typed_index_collections::{TiSlice, TiVec};
+
+    struct TestInstance {
+        mods: TiVec<ModIndex, ModDeclaration>,
+        mod_order: TiVec<ModOrderIndex, ModOrderEntry>,
+    }
+
+    impl Instance for TestInstance {
+        fn dir(&self) -> &Path {
+            Path::new("/")
+        }
+
+        fn mods(&self) -> &TiSlice<ModIndex, ModDeclaration> {
+            &self.mods
+        }
+
+        fn mod_order(&self) -> &TiSlice<ModOrderIndex, ModOrderEntry> {
+            &self.mod_order
+        }
+    }
+
+    fn entries() -> Vec<ModListEntry> {
+        let entry = |name: &str, kind, enabled| ModListEntry { name: name.into(), kind, enabled };
+        vec![
+            entry(
+                "Separator, \"with\" | *special*\tcharacters",
+                ModEntryKind::Separator,
+                false,
+            ),
+            entry("Comma, Mod", ModEntryKind::Mod, true),
+            entry("\"Quoted\" Mod", ModEntryKind::Mod, false),
+            entry("Pipe | Mod", ModEntryKind::Mod, true),
+            entry("*Starred* Mod", ModEntryKind::Mod, true),
+            entry("Tab\tMod", ModEntryKind::Mod, false),
+            entry("Back\\slash\\t Mod", ModEntryKind::Mod, true),
+        ]
+    }
+
+    fn instance(entries: &[ModListEntry]) -> TestInstance {
+        let mut instance = TestInstance { mods: TiVec::new(), mod_order: TiVec::new() };
+        for entry in entries {
+            let mut mod_decl = ModDeclaration::new(entry.name.clone(), entry.kind).expect("valid mod name");
+            mod_decl.set_version(Some("1.0\t\"beta\", |*".into()));
+            mod_decl.set_category(Some("Category, \"with\" | *special*\tcharacters".into()));
+            let idx = instance.mods.push_and_get_key(mod_decl);
+            let mut order_entry = ModOrderEntry::new(idx);
+            order_entry.enabled = entry.enabled;
+            instance.mod_order.push(order_entry);
+        }
+        instance
+    }
+
+    fn round_trip(format: ModListFormat) {
+        let entries = entries();
+        let exported = export_mod_list(&instance(&entries), format);
+        assert_eq!(ModListFormat::detect(&exported), format);
+        assert_eq!(
+            parse_mod_list(&exported, format).expect("exported list is valid"),
+            entries
+        );
+    }
+
+    #[test]
+    fn text_round_trip() {
+        round_trip(ModListFormat::Text);
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        round_trip(ModListFormat::Csv);
+    }
+
+    #[test]
+    fn markdown_round_trip() {
+        round_trip(ModListFormat::Markdown);
+    }
+}
@@ -37,6 +37,12 @@ static COLLATOR: LazyLock<CollatorBorrowed<'static>> = LazyLock::new(|| {
     Collator::try_new(prefs, options).unwrap()
 });
 
+/// Compares two names using the CLDR Collation Algorithm provided by ICU4X.
+#[must_use]
+pub fn name_ord(left: &str, right: &str) -> Ordering {
+    COLLATOR.compare(left, right)
+}
+
 /// A comparator for [`TreeNode`]s that sorts directories before files
 /// and sorts names using the CLDR Collation Algorithm provided by ICU4X.
 pub fn node_ord<F>(left: &TreeNode<F>, right: &TreeNode<F>) -> Ordering {
@@ -51,7 +57,9 @@ pub fn node_ord<F>(left: &TreeNode<F>, right: &TreeNode<F>) -> Ordering {
 ///
 /// When moving items to the right, the target index needs to be adjusted to compensate for the items shifted left,
 /// so that the items move still end up in between the items before and at the initial target index.
-/// The adjusted index is the value returned by this function.
+///
+/// Returns the old and new index of every moved item, in the order they end up in.
+/// The new index of the first item is the adjusted target index.
 ///
 /// # Implementation
 ///
@@ -104,7 +112,7 @@ pub fn node_ord<F>(left: &TreeNode<F>, right: &TreeNode<F>) -> Ordering {
 /// │0 2 4 1 3 8 5 6 7 9│
 /// └───────────────────┘
 /// ```
-pub fn move_multiple<T>(slice: &mut [T], from: impl Iterator<Item = usize>, to: usize) -> usize {
+pub fn move_multiple<T>(slice: &mut [T], from: impl Iterator<Item = usize>, to: usize) -> Vec<(usize, usize)> {
     let item_indices = {
         let mut vec: Vec<_> = from.collect();
         vec.sort_unstable();
@@ -136,5 +144,9 @@ pub fn move_multiple<T>(slice: &mut [T], from: impl Iterator<Item = usize>, to:
         }
     }
 
-    to
+    item_indices
+        .into_iter()
+        .enumerate()
+        .map(|(i, from)| (from, to + i))
+        .collect()
 }
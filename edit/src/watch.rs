@@ -0,0 +1,121 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Detection of changes made to an instance by other processes.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+
+use notify::event::{AccessKind, EventKind};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use tracing::{trace, warn};
+
+use mmm_core::instance::Instance;
+use mmm_core::instance::data::INSTANCE_DATA_FILE;
+
+use crate::EditableInstance;
+
+/// Changes reported by [`InstanceWatcher::changes`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ExternalChanges {
+    /// The instance data file was modified by another process, and should be [reloaded](EditableInstance::reload).
+    pub data: bool,
+    /// Entries were added, removed, or renamed in the mods directory.
+    pub mods: bool,
+}
+
+impl ExternalChanges {
+    /// Returns `true` if nothing changed.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        !self.data && !self.mods
+    }
+}
+
+/// Watches an instance's data file and mods directory for changes.
+pub struct InstanceWatcher {
+    _watcher: RecommendedWatcher,
+    events: Receiver<Event>,
+    data_file: PathBuf,
+    mods_dir: PathBuf,
+}
+
+impl InstanceWatcher {
+    /// Starts watching the specified instance.
+    ///
+    /// `on_event` is called from a background thread whenever something happens in the watched directories,
+    /// and can be used to wake up the thread that calls [`Self::changes`].
+    pub fn new(instance: &EditableInstance, on_event: impl Fn() + Send + 'static) -> Result<Self, notify::Error> {
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+            Ok(event) => {
+                if sender.send(event).is_ok() {
+                    on_event();
+                }
+            }
+            Err(err) => warn!("error while watching instance: {}", err),
+        })?;
+
+        let mods_dir = instance.mods_dir();
+        watcher.watch(instance.dir(), RecursiveMode::NonRecursive)?;
+        if mods_dir.is_dir() {
+            watcher.watch(&mods_dir, RecursiveMode::NonRecursive)?;
+        }
+
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            data_file: instance.dir().join(INSTANCE_DATA_FILE),
+            mods_dir,
+        })
+    }
+
+    /// Returns the changes that happened since the last call to this method.
+    ///
+    /// Changes to the instance data file made by `instance` itself are not reported.
+    pub fn changes(&self, instance: &EditableInstance) -> ExternalChanges {
+        let mut changes = ExternalChanges::default();
+        let mut data_touched = false;
+
+        for event in self.events.try_iter() {
+            if matches!(
+                event.kind,
+                EventKind::Access(AccessKind::Read | AccessKind::Open(_)) | EventKind::Other
+            ) {
+                continue;
+            }
+            for path in &event.paths {
+                if *path == self.data_file {
+                    data_touched = true;
+                } else if path.parent() == Some(self.mods_dir.as_path()) && !is_hidden(path) {
+                    changes.mods = true;
+                }
+            }
+        }
+
+        changes.data = data_touched && instance.data_file_changed_externally();
+        if !changes.is_empty() {
+            trace!(?changes, "detected external changes");
+        }
+        changes
+    }
+}
+
+/// Returns `true` for staging and other temporary directories in the mods directory.
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with('.'))
+}
@@ -15,14 +15,24 @@
 
 use std::fs::{self, File};
 use std::io::{self, Write};
+use std::iter;
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::{self, Sender};
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread;
 
+use thiserror::Error;
 use tracing::Level;
-use tracing::{error, span};
+use tracing::{error, span, warn};
 
-use mmm_core::instance::data::INSTANCE_DATA_FILE;
+use mmm_core::instance::data::{INSTANCE_DATA_FILE, InstanceData};
+
+/// Message sent to the writer thread.
+#[derive(Debug)]
+pub enum WriterMessage {
+    Write(WriteRequest),
+    /// Asks the writer thread to reply once every previously sent request has been written.
+    Flush(Sender<()>),
+}
 
 #[derive(Debug)]
 pub struct WriteRequest {
@@ -35,45 +45,118 @@ pub enum WriteTarget {
     InstanceData,
 }
 
-pub fn spawn_writer_thread(instance_dir: &Path) -> Result<Sender<WriteRequest>, io::Error> {
-    let (sender, receiver) = mpsc::channel::<WriteRequest>();
+pub fn spawn_writer_thread(
+    instance_dir: &Path,
+) -> Result<(Sender<WriterMessage>, Receiver<Result<(), WriteError>>), io::Error> {
+    let (sender, receiver) = mpsc::channel::<WriterMessage>();
+    let (result_sender, result_receiver) = mpsc::channel::<Result<(), WriteError>>();
     let paths = FilePaths::from_dir(instance_dir);
 
     thread::Builder::new().name("writer".to_owned()).spawn(move || {
-        while let Ok(req) = receiver.recv() {
-            let (path, tmp_path) = paths.path_of_target(req.target);
-            let _span = span!(Level::TRACE, "writer", path = %path.display(), tmp_path = %tmp_path.display()).entered();
-
-            let mut file = match File::create(tmp_path) {
-                Ok(file) => file,
-                Err(err) => {
-                    error!("failed to create file: {}", err);
-                    continue;
+        while let Ok(msg) = receiver.recv() {
+            // Each request contains the whole content of the file, so when several requests for the same target
+            // are queued, only the most recent one needs to be written.
+            let mut requests: Vec<WriteRequest> = Vec::new();
+            let mut flushes = Vec::new();
+            for msg in iter::once(msg).chain(iter::from_fn(|| receiver.try_recv().ok())) {
+                match msg {
+                    WriterMessage::Write(req) => {
+                        requests.retain(|r| r.target != req.target);
+                        requests.push(req);
+                    }
+                    WriterMessage::Flush(ack) => flushes.push(ack),
                 }
-            };
-
-            if let Err(err) = file.write_all(&req.content) {
-                error!("failed to write data to file: {}", err);
-                continue;
             }
 
-            if let Err(err) = file.sync_data() {
-                error!("failed to sync file to disk: {}", err);
-                continue;
-            }
+            for req in requests {
+                let (path, tmp_path) = paths.path_of_target(req.target);
+                let _span =
+                    span!(Level::TRACE, "writer", path = %path.display(), tmp_path = %tmp_path.display()).entered();
 
-            drop(file);
+                let result = write_file(&paths.dir, path, tmp_path, &req.content);
+                if let Err(err) = &result {
+                    error!(?err, "failed to write file");
+                }
+                let _ = result_sender.send(result);
+            }
 
-            if let Err(err) = fs::rename(tmp_path, path) {
-                error!("failed to rename temp file over target file: {}", err);
+            for ack in flushes {
+                let _ = ack.send(());
             }
         }
     })?;
 
-    Ok(sender)
+    Ok((sender, result_receiver))
+}
+
+/// Writes a file on the calling thread, in the same way the writer thread does.
+pub fn write_blocking(instance_dir: &Path, target: WriteTarget, content: &[u8]) -> Result<(), WriteError> {
+    let paths = FilePaths::from_dir(instance_dir);
+    let (path, tmp_path) = paths.path_of_target(target);
+    write_file(&paths.dir, path, tmp_path, content)
+}
+
+fn write_file(dir: &Path, path: &Path, tmp_path: &Path, content: &[u8]) -> Result<(), WriteError> {
+    let mut file = File::create(tmp_path).map_err(WriteError::Create)?;
+    file.write_all(content).map_err(WriteError::Write)?;
+    file.sync_data().map_err(WriteError::Sync)?;
+    drop(file);
+
+    fs::rename(tmp_path, path).map_err(WriteError::Rename)?;
+
+    // The rename is only durable once the directory entry itself is synced.
+    File::open(dir)
+        .and_then(|dir| dir.sync_all())
+        .map_err(WriteError::SyncDir)
+}
+
+/// Error type returned when saving instance data fails.
+#[derive(Debug, Error)]
+pub enum WriteError {
+    #[error("failed to serialize instance data: {0}")]
+    Serialize(String),
+    #[error("writer thread crashed")]
+    ThreadCrashed,
+    #[error("instance was opened read-only")]
+    ReadOnly,
+    #[error("failed to create temp file")]
+    Create(#[source] io::Error),
+    #[error("failed to write data to temp file")]
+    Write(#[source] io::Error),
+    #[error("failed to sync temp file to disk")]
+    Sync(#[source] io::Error),
+    #[error("failed to rename temp file over target file")]
+    Rename(#[source] io::Error),
+    #[error("failed to sync instance directory to disk")]
+    SyncDir(#[source] io::Error),
+}
+
+/// Cleans up temporary files left behind by a writer thread that was interrupted (e.g. by a crash or power loss).
+///
+/// Temporary files are fully written and synced before being renamed over their target, so a temporary file
+/// that contains valid data is newer than its target, and is moved into place. Otherwise, it's deleted.
+pub fn recover_temp_files(instance_dir: &Path) -> Result<(), io::Error> {
+    let paths = FilePaths::from_dir(instance_dir);
+    let (path, tmp_path) = paths.path_of_target(WriteTarget::InstanceData);
+    if !fs::exists(tmp_path)? {
+        return Ok(());
+    }
+
+    match InstanceData::from_file(tmp_path) {
+        Ok(_) => {
+            warn!("recovering instance data from '{}'", tmp_path.display());
+            fs::rename(tmp_path, path)?;
+            File::open(&paths.dir)?.sync_all()
+        }
+        Err(err) => {
+            warn!("removing incomplete temp file '{}': {}", tmp_path.display(), err);
+            fs::remove_file(tmp_path)
+        }
+    }
 }
 
 struct FilePaths {
+    dir: PathBuf,
     data_file: PathBuf,
     data_file_tmp: PathBuf,
 }
@@ -82,7 +165,11 @@ impl FilePaths {
     fn from_dir(instance_dir: &Path) -> Self {
         let data_file = instance_dir.join(INSTANCE_DATA_FILE);
         let data_file_tmp = data_file.with_added_extension("tmp");
-        Self { data_file, data_file_tmp }
+        Self {
+            dir: instance_dir.to_owned(),
+            data_file,
+            data_file_tmp,
+        }
     }
 
     fn path_of_target(&self, target: WriteTarget) -> (&Path, &Path) {
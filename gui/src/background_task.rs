@@ -13,10 +13,15 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+use std::collections::VecDeque;
 use std::io;
-use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::mpsc::{self, Receiver, SendError, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::Duration;
+
+use eframe::egui;
+use egui::{Popup, Ui};
 
 use crate::ModManagerUi;
 
@@ -24,11 +29,77 @@ pub type StatusString = Arc<Mutex<String>>;
 pub type BackgroundTask = Box<dyn FnOnce(&StatusString) -> Option<Finalizer> + Send>;
 pub type Finalizer = Box<dyn FnOnce(&mut ModManagerUi) + Send>;
 
-pub fn spawn_background_thread() -> Result<(Sender<BackgroundTask>, Receiver<Finalizer>, StatusString), io::Error> {
+/// Sends tasks to the background thread, keeping track of which ones haven't finished yet.
+#[derive(Clone)]
+pub struct TaskQueue {
+    sender: Sender<BackgroundTask>,
+    /// Names of the unfinished tasks, in the order they run in. The first one is running.
+    names: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl TaskQueue {
+    /// Queues a task to run after every task queued before it.
+    pub fn push(&self, name: impl Into<String>, task: BackgroundTask) -> Result<(), SendError<BackgroundTask>> {
+        // hold the lock while sending, so that names are in the same order as the tasks
+        let mut names = self.names.lock().expect("lock is not poisoned");
+        self.sender.send(task)?;
+        names.push_back(name.into());
+        Ok(())
+    }
+
+    /// Returns the names of the unfinished tasks, starting with the running one.
+    pub fn names(&self) -> Vec<String> {
+        self.names
+            .lock()
+            .expect("lock is not poisoned")
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Shows a button with the number of unfinished tasks, which opens a list of them, if there are any.
+    pub fn list_button(&self, ui: &mut Ui, status: &StatusString) {
+        let names = self.names();
+        if names.is_empty() {
+            return;
+        }
+        // tasks without a finalizer don't cause a repaint when they finish
+        ui.ctx().request_repaint_after(Duration::from_millis(250));
+
+        let text = if names.len() == 1 {
+            "1 task".to_owned()
+        } else {
+            format!("{} tasks", names.len())
+        };
+        let response = ui.button(text);
+        Popup::menu(&response).show(|ui| {
+            let status = status.lock().expect("lock is not poisoned").clone();
+            for (i, name) in names.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if i == 0 {
+                        ui.spinner();
+                        ui.label(name.as_str());
+                        if !status.is_empty() && status != *name {
+                            ui.weak(status.as_str());
+                        }
+                    } else {
+                        ui.weak("Queued");
+                        ui.label(name.as_str());
+                    }
+                });
+            }
+        });
+        ui.separator();
+    }
+}
+
+pub fn spawn_background_thread() -> Result<(TaskQueue, Receiver<Finalizer>, StatusString), io::Error> {
     let (task_sender, task_receiver) = mpsc::channel::<BackgroundTask>();
     let (finalizer_sender, finalizer_receiver) = mpsc::channel::<Finalizer>();
     let status = Arc::new(Mutex::new(String::new()));
     let status_clone = Arc::clone(&status);
+    let names = Arc::new(Mutex::new(VecDeque::new()));
+    let names_clone = Arc::clone(&names);
 
     thread::Builder::new().name("background".to_owned()).spawn(move || {
         while let Ok(req) = task_receiver.recv() {
@@ -36,8 +107,10 @@ pub fn spawn_background_thread() -> Result<(Sender<BackgroundTask>, Receiver<Fin
                 let _ = finalizer_sender.send(finalizer);
             }
             status.lock().expect("lock is not poisoned").clear();
+            let _ = names_clone.lock().expect("lock is not poisoned").pop_front();
         }
     })?;
 
-    Ok((task_sender, finalizer_receiver, status_clone))
+    let queue = TaskQueue { sender: task_sender, names };
+    Ok((queue, finalizer_receiver, status_clone))
 }
@@ -0,0 +1,120 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Optional columns of the mod table, and sorting of the rows shown in it.
+
+use std::cmp::Ordering;
+
+use eframe::egui;
+use egui::Ui;
+use serde::{Deserialize, Serialize};
+
+/// Which optional columns of the mod table are shown. Stored in the [settings](crate::settings::Settings).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TableColumns {
+    pub version: bool,
+    pub category: bool,
+    pub size: bool,
+    pub conflicts: bool,
+}
+
+impl Default for TableColumns {
+    fn default() -> Self {
+        Self {
+            version: true,
+            category: true,
+            size: true,
+            conflicts: false,
+        }
+    }
+}
+
+impl TableColumns {
+    /// Returns `true` if the specified column is shown.
+    pub const fn shows(self, column: SortColumn) -> bool {
+        match column {
+            SortColumn::Name => true,
+            SortColumn::Version => self.version,
+            SortColumn::Category => self.category,
+            SortColumn::Size => self.size,
+            SortColumn::Conflicts => self.conflicts,
+        }
+    }
+
+    /// Shows a checkbox for each optional column, returning `true` if any was toggled.
+    pub fn checkboxes(&mut self, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        changed |= ui.checkbox(&mut self.version, "Version").changed();
+        changed |= ui.checkbox(&mut self.category, "Category").changed();
+        changed |= ui.checkbox(&mut self.size, "Size").changed();
+        changed |= ui.checkbox(&mut self.conflicts, "Conflicts").changed();
+        changed
+    }
+}
+
+/// A column the rows of the mod table can be sorted by.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SortColumn {
+    Name,
+    Version,
+    Category,
+    Size,
+    Conflicts,
+}
+
+/// How the rows of the mod table are sorted.
+///
+/// This only changes the order the rows are shown in, the mod order stays the same.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TableSort {
+    pub column: SortColumn,
+    pub descending: bool,
+}
+
+impl TableSort {
+    /// Returns the sort to use after the header of `column` is clicked.
+    ///
+    /// Clicking the same header cycles between ascending order, descending order, and priority order.
+    pub fn clicked(sort: Option<Self>, column: SortColumn) -> Option<Self> {
+        match sort {
+            Some(sort) if sort.column == column && sort.descending => None,
+            Some(sort) if sort.column == column => Some(Self { column, descending: true }),
+            _ => Some(Self { column, descending: false }),
+        }
+    }
+
+    /// Returns the text of the header of `column`, with an arrow if the rows are sorted by it.
+    pub fn header_text(sort: Option<Self>, column: SortColumn, text: &str) -> String {
+        match sort {
+            Some(sort) if sort.column == column && sort.descending => format!("{text} ⏷"),
+            Some(sort) if sort.column == column => format!("{text} ⏶"),
+            _ => text.to_owned(),
+        }
+    }
+
+    /// Compares two values of the sorted column, placing missing values last regardless of direction.
+    pub fn compare<T>(self, left: Option<T>, right: Option<T>, cmp: impl FnOnce(T, T) -> Ordering) -> Ordering {
+        match (left, right) {
+            (Some(left), Some(right)) => {
+                let ordering = cmp(left, right);
+                if self.descending { ordering.reverse() } else { ordering }
+            }
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+}
@@ -0,0 +1,279 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Panel that shows the files the selected mod has in common with other mods,
+//! and per-mod conflict counts for the mod table.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use camino::Utf8PathBuf;
+use eframe::egui;
+use egui::{CollapsingHeader, ScrollArea, Ui};
+use tracing::error;
+use typed_index_collections::{TiSlice, TiVec};
+
+use mmm_core::file_tree::conflicts::{ConflictCount, ModConflicts};
+use mmm_core::file_tree::{FileTree, FileTreeBuilder, ModVec, new_tree};
+use mmm_core::instance::{Instance, ModDeclaration, ModEntryKind, ModIndex, ModOrderEntry, ModOrderIndex};
+use mmm_edit::EditableInstance;
+
+use crate::ModManagerUi;
+use crate::background_task::BackgroundTask;
+
+#[derive(Default)]
+pub struct ConflictPanel {
+    pub open: bool,
+    report: Report,
+}
+
+#[derive(Default)]
+enum Report {
+    /// No mod is selected.
+    #[default]
+    None,
+    Pending(ReportKey),
+    Ready(ReportKey, ModConflicts),
+    Error(ReportKey, Box<str>),
+}
+
+/// What a report is computed from, used to tell when it's out of date.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ReportKey {
+    mod_index: ModIndex,
+    /// The enabled mods, in mod order.
+    enabled_mods: Vec<ModIndex>,
+}
+
+impl ReportKey {
+    fn new(instance: &EditableInstance, mod_index: ModIndex) -> Self {
+        Self { mod_index, enabled_mods: enabled_mods(instance) }
+    }
+}
+
+/// Returns the enabled mods, in mod order.
+fn enabled_mods(instance: &EditableInstance) -> Vec<ModIndex> {
+    instance
+        .mod_order()
+        .iter()
+        .filter(|entry| entry.enabled)
+        .map(ModOrderEntry::mod_index)
+        .collect()
+}
+
+impl ConflictPanel {
+    /// Shows the conflicts of `selected`, returning a task to run if they need to be computed.
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        instance: &EditableInstance,
+        selected: Option<ModIndex>,
+    ) -> Option<BackgroundTask> {
+        ui.heading("Conflicts");
+
+        let selected = selected.filter(|idx| instance.mods()[*idx].kind() == ModEntryKind::Mod);
+        let Some(mod_index) = selected else {
+            self.report = Report::None;
+            ui.label("Select a mod to see its conflicts.");
+            return None;
+        };
+
+        let key = ReportKey::new(instance, mod_index);
+        let refresh = ui.button("Refresh").clicked();
+        let up_to_date = matches!(
+            &self.report,
+            Report::Pending(current) | Report::Ready(current, _) | Report::Error(current, _) if *current == key
+        );
+        let task = if up_to_date && !refresh {
+            None
+        } else {
+            self.report = Report::Pending(key.clone());
+            Some(compute_conflicts(key, instance))
+        };
+
+        ui.separator();
+        match &self.report {
+            Report::None => {}
+            Report::Pending(_) => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Finding conflicts…");
+                });
+            }
+            Report::Error(_, err) => {
+                ui.colored_label(ui.visuals().error_fg_color, &**err);
+            }
+            Report::Ready(key, conflicts) => {
+                if !instance
+                    .mod_order()
+                    .iter()
+                    .any(|e| e.enabled && e.mod_index() == key.mod_index)
+                {
+                    ui.label("This mod is disabled, so it doesn't conflict with other mods.");
+                } else if conflicts.is_empty() {
+                    ui.label("This mod doesn't conflict with other mods.");
+                }
+
+                ScrollArea::vertical().show(ui, |ui| {
+                    conflict_list(ui, instance, "Overrides", &conflicts.overrides);
+                    conflict_list(ui, instance, "Overridden by", &conflicts.overridden_by);
+                });
+            }
+        }
+
+        task
+    }
+
+    /// Stores the result of a task returned by [`Self::show`], unless the report it was computed for is out of date.
+    fn finish(&mut self, key: ReportKey, result: Result<ModConflicts, Box<str>>) {
+        if !matches!(&self.report, Report::Pending(current) if *current == key) {
+            return;
+        }
+        self.report = match result {
+            Ok(conflicts) => Report::Ready(key, conflicts),
+            Err(err) => Report::Error(key, err),
+        };
+    }
+}
+
+fn conflict_list(
+    ui: &mut Ui,
+    instance: &EditableInstance,
+    heading: &str,
+    files_by_mod: &BTreeMap<ModIndex, Vec<Utf8PathBuf>>,
+) {
+    if files_by_mod.is_empty() {
+        return;
+    }
+
+    ui.strong(heading);
+    for (mod_index, paths) in files_by_mod {
+        let name = instance.mods().get(*mod_index).map_or("?", |m| m.name().as_str());
+        CollapsingHeader::new(format!("{name} ({} files)", paths.len()))
+            .id_salt((heading, mod_index))
+            .show(ui, |ui| {
+                for path in paths {
+                    ui.label(path.as_str());
+                }
+            });
+    }
+}
+
+/// The number of conflicting files of every mod, shown in the mod table.
+#[derive(Default)]
+pub struct ConflictCounts {
+    /// The enabled mods the counts were computed for, or are being computed for.
+    enabled_mods: Option<Vec<ModIndex>>,
+    counts: BTreeMap<ModIndex, ConflictCount>,
+    pending: bool,
+}
+
+impl ConflictCounts {
+    /// Returns a task that recomputes the counts, if the enabled mods changed since they were last computed.
+    pub fn update(&mut self, instance: &EditableInstance) -> Option<BackgroundTask> {
+        let enabled_mods = enabled_mods(instance);
+        if self.enabled_mods.as_ref() == Some(&enabled_mods) {
+            return None;
+        }
+
+        self.enabled_mods = Some(enabled_mods.clone());
+        self.pending = true;
+        let view = InstanceView::new(instance);
+        Some(Box::new(move |status| {
+            status
+                .lock()
+                .expect("lock is not poisoned")
+                .push_str("Counting conflicts");
+
+            let result = view.file_tree().map(|tree| ConflictCount::count_all(&tree));
+            Some(Box::new(move |app: &mut ModManagerUi| {
+                app.conflict_counts.finish(enabled_mods, result);
+            }))
+        }))
+    }
+
+    /// Returns the count of the specified mod, or `None` if it's still being computed.
+    pub fn get(&self, mod_index: ModIndex) -> Option<ConflictCount> {
+        (!self.pending).then(|| self.counts.get(&mod_index).copied().unwrap_or_default())
+    }
+
+    fn finish(&mut self, enabled_mods: Vec<ModIndex>, result: Result<BTreeMap<ModIndex, ConflictCount>, Box<str>>) {
+        if self.enabled_mods.as_ref() != Some(&enabled_mods) {
+            return;
+        }
+        self.pending = false;
+        self.counts = result.unwrap_or_else(|err| {
+            error!("failed to count conflicts: {}", err);
+            BTreeMap::new()
+        });
+    }
+}
+
+/// A copy of the parts of an instance needed to build its file tree on the background thread.
+struct InstanceView {
+    dir: PathBuf,
+    mods: TiVec<ModIndex, ModDeclaration>,
+    mod_order: TiVec<ModOrderIndex, ModOrderEntry>,
+}
+
+impl InstanceView {
+    fn new(instance: &EditableInstance) -> Self {
+        Self {
+            dir: instance.dir().to_owned(),
+            mods: instance.mods().to_owned(),
+            mod_order: instance.mod_order().to_owned(),
+        }
+    }
+
+    /// Builds the tree of files of the enabled mods.
+    fn file_tree(&self) -> Result<FileTree<ModVec>, Box<str>> {
+        let mut tree = new_tree();
+        FileTreeBuilder::new()
+            .iter_mods(&mut tree, self)
+            .map_err(|err| format!("Failed to build file tree:\n{err}").into_boxed_str())?;
+        Ok(tree)
+    }
+}
+
+impl Instance for InstanceView {
+    fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn mods(&self) -> &TiSlice<ModIndex, ModDeclaration> {
+        &self.mods
+    }
+
+    fn mod_order(&self) -> &TiSlice<ModOrderIndex, ModOrderEntry> {
+        &self.mod_order
+    }
+}
+
+/// Returns a [`BackgroundTask`] that builds the file tree of the enabled mods, and finds the selected mod's conflicts.
+fn compute_conflicts(key: ReportKey, instance: &EditableInstance) -> BackgroundTask {
+    let view = InstanceView::new(instance);
+
+    Box::new(move |status| {
+        status
+            .lock()
+            .expect("lock is not poisoned")
+            .push_str("Finding conflicts");
+
+        let result = view.file_tree().map(|tree| ModConflicts::new(&tree, key.mod_index));
+        Some(Box::new(move |app: &mut ModManagerUi| {
+            app.conflict_panel.finish(key, result)
+        }))
+    })
+}
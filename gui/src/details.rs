@@ -14,22 +14,19 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use std::assert_matches;
-use std::fmt::Write;
 use std::fs;
 use std::io;
 use std::path::PathBuf;
-use std::sync::Arc;
-use std::thread::{self, JoinHandle};
 use std::time::Instant;
 
-use compact_str::CompactString;
 use eframe::egui;
 use egui::{CentralPanel, CornerRadius, Frame, Ui, ViewportCommand, ViewportId};
-use nary_tree::NodeId;
+use foldhash::HashSet;
+use nary_tree::{NodeId, RemoveBehavior};
 use tracing::error;
 
 use mmm_core::file_tree::util::NodePathBuilder;
-use mmm_core::file_tree::{Counters, FileTree, FileTreeBuilder, IterDirError, TreeNodeKind, new_tree};
+use mmm_core::file_tree::{FileTree, TreeNode, TreeNodeKind, new_tree};
 use mmm_core::instance::{Instance, ModEntryKind, ModIndex};
 use mmm_edit::EditableInstance;
 use mmm_edit::util::node_ord;
@@ -37,63 +34,76 @@ use mmm_edit::util::node_ord;
 use crate::tree::{TreeDisplay, dnd_handle_actions_fn};
 use crate::utils::{Viewport, ViewportResult, show_immediate};
 
-enum Tree {
-    Some(FileTree),
-    Pending {
-        handle: Option<ThreadHandle>,
-        counter: Arc<Counters>,
-        previous_count: usize,
-        message: CompactString,
-    },
-    Error(Box<str>),
+/// The files of a mod, where the contents of each directory are only read once it's opened,
+/// so that large mods can be browsed without walking through all of their files first.
+struct LazyTree {
+    tree: FileTree,
+    dir: PathBuf,
+    /// Directory nodes whose contents haven't been read yet.
+    unloaded: HashSet<NodeId>,
 }
 
-type ThreadHandle = JoinHandle<Result<FileTree, IterDirError>>;
-const _: () = assert!(size_of::<ThreadHandle>() == size_of::<Option<ThreadHandle>>());
-
-impl Tree {
-    fn from_dir(dir: PathBuf) -> Result<Self, io::Error> {
-        let counter = Counters::new();
-        let tree_builder = FileTreeBuilder::new().with_counter(Arc::clone(&counter));
-
-        let handle = thread::Builder::new().spawn(move || {
-            let mut tree = new_tree();
-            tree_builder.iter_dir(&mut tree, dir)?;
-            tree.root_mut().expect("has root node").sort_recursive_by(node_ord);
-            Ok(tree)
-        })?;
-
-        Ok(Self::Pending {
-            handle: Some(handle),
-            counter,
-            previous_count: 0,
-            message: CompactString::const_new("0 files counted"),
-        })
+impl LazyTree {
+    fn new(dir: PathBuf) -> Result<Self, io::Error> {
+        let tree = new_tree();
+        let root = tree.root_id().expect("has root node");
+        let mut lazy_tree = Self { tree, dir, unloaded: HashSet::default() };
+        lazy_tree.load(root)?;
+        Ok(lazy_tree)
     }
 
-    fn update(&mut self) {
-        if let Tree::Pending { handle, .. } = self
-            && handle.as_ref().expect("not joined yet").is_finished()
-        {
-            let handle = handle.take().expect("not joined yet");
-            match handle.join() {
-                Ok(Ok(tree)) => *self = Tree::Some(tree),
-                Ok(Err(err)) => {
-                    error!(?err, "failed to build file tree");
-                    *self = Tree::Error(format!("Failed to build file tree:\n{}", err).into_boxed_str());
-                }
-                Err(_) => {
-                    error!("file tree thread panicked");
-                    *self = Tree::Error(Box::from("Failed to build file tree:\nThread panicked."));
+    /// Reads the contents of the specified directory node's directory, creating nodes for each entry.
+    fn load(&mut self, node_id: NodeId) -> Result<(), io::Error> {
+        let mut path = NodePathBuilder::new(self.dir.clone());
+        let node = self.tree.get(node_id).expect("node exists");
+        let is_root = node.parent().is_none();
+        let path = path.reset_and_push(&node).to_owned();
+
+        let mut node = self.tree.get_mut(node_id).expect("node exists");
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            if is_root && name == ".git" {
+                continue;
+            }
+
+            let is_dir = entry.file_type()?.is_dir();
+            let kind = if is_dir {
+                TreeNodeKind::Dir
+            } else {
+                TreeNodeKind::File(())
+            };
+            let child = node.append(TreeNode { name: name.into(), kind }).node_id();
+            if is_dir {
+                self.unloaded.insert(child);
+            }
+        }
+        node.sort_children_by(node_ord);
+        Ok(())
+    }
+
+    /// Reads the contents of the directories in `dirs` that haven't been read yet.
+    ///
+    /// Returns `true` if any were read.
+    fn load_dirs(&mut self, dirs: &[NodeId]) -> bool {
+        let mut loaded = false;
+        for dir in dirs {
+            if self.unloaded.remove(dir) {
+                if let Err(err) = self.load(*dir) {
+                    error!(?err, "failed to read directory");
                 }
+                loaded = true;
             }
         }
+        loaded
     }
 }
 
 pub struct ModDetailsWindow {
     viewport: Box<Viewport>,
-    tree: Tree,
+    tree: LazyTree,
     tree_display: TreeDisplay,
     raise: bool,
 }
@@ -101,7 +111,7 @@ pub struct ModDetailsWindow {
 impl ModDetailsWindow {
     pub fn new(instance: &EditableInstance, idx: ModIndex) -> Result<Self, io::Error> {
         let dir = instance.mod_dir(&instance.mods()[idx]).expect("mod is not a separator");
-        let tree = Tree::from_dir(dir)?;
+        let tree = LazyTree::new(dir)?;
 
         let mod_decl = &instance.mods()[idx];
         assert_eq!(mod_decl.kind(), ModEntryKind::Mod);
@@ -118,8 +128,6 @@ impl ModDetailsWindow {
     }
 
     pub fn update(&mut self, ui: &mut Ui, instance: &EditableInstance, mod_index: ModIndex) -> ViewportResult {
-        self.tree.update();
-
         show_immediate!(self.viewport, ui, |ui: &mut Ui, _viewport| {
             if self.raise {
                 self.raise = false;
@@ -130,68 +138,55 @@ impl ModDetailsWindow {
     }
 
     fn files(&mut self, ui: &mut Ui, instance: &EditableInstance, mod_index: ModIndex) {
-        match &mut self.tree {
-            Tree::Some(tree) => {
-                let dnd = dnd_handle_actions_fn(|tree, dnd| {
-                    let target_node = tree.get(dnd.target).expect("node exists");
-                    assert_matches!(target_node.data().kind, TreeNodeKind::Dir);
-
-                    let mod_dir = instance.mod_dir(&instance.mods()[mod_index]).expect("not a separator");
-
-                    let mut target = NodePathBuilder::new(mod_dir.clone());
-                    target.reset_and_push(&target_node);
-                    let mut target = target.into_inner();
-                    target.set_base_to_current();
-
-                    let mut source = NodePathBuilder::new(mod_dir);
-
-                    for node in dnd.source {
-                        let mut source_node = tree.get_mut(node).expect("node exists");
-                        let from = source.reset_and_push(&source_node.as_ref());
-
-                        target.reset_to_base();
-                        let to = target.push(&source_node.data().name);
-
-                        if let Err(err) = fs::rename(from, to) {
-                            error!(?err, "failed to move '{}' to '{}'", from.display(), to.display());
-                            // TODO: consider refreshing the tree on "not found" errors
-                            continue;
-                        }
-
-                        source_node.append_to(dnd.target).unwrap();
-                    }
-
-                    tree.get_mut(dnd.target).unwrap().sort_children_by(node_ord);
-                });
-
-                let tree_height = ui.available_height() - ui.style().spacing.interact_size.y;
-                Frame::new()
-                    .stroke(ui.style().visuals.window_stroke)
-                    .corner_radius(CornerRadius::same(4))
-                    .show(ui, |ui| {
-                        self.tree_display.display(ui, tree, label_fn, dnd, tree_height);
-                    });
-            }
-            Tree::Pending { counter, previous_count, message, .. } => {
-                let count = counter.unique_files();
-                if count != *previous_count {
-                    *previous_count = count;
+        let unloaded = &self.tree.unloaded;
+        let dnd = dnd_handle_actions_fn(|tree, dnd| {
+            let target_node = tree.get(dnd.target).expect("node exists");
+            assert_matches!(target_node.data().kind, TreeNodeKind::Dir);
 
-                    message.clear();
-                    let _ = write!(message, "{} files counted", count);
+            let mod_dir = instance.mod_dir(&instance.mods()[mod_index]).expect("not a separator");
 
-                    ui.request_repaint();
+            let mut target = NodePathBuilder::new(mod_dir.clone());
+            target.reset_and_push(&target_node);
+            let mut target = target.into_inner();
+            target.set_base_to_current();
+
+            let mut source = NodePathBuilder::new(mod_dir);
+
+            for node in dnd.source {
+                let mut source_node = tree.get_mut(node).expect("node exists");
+                let from = source.reset_and_push(&source_node.as_ref());
+
+                target.reset_to_base();
+                let to = target.push(&source_node.data().name);
+
+                if let Err(err) = fs::rename(from, to) {
+                    error!(?err, "failed to move '{}' to '{}'", from.display(), to.display());
+                    // TODO: consider refreshing the tree on "not found" errors
+                    continue;
                 }
 
-                ui.centered_and_justified(|ui| {
-                    ui.label(message.as_str());
-                });
-            }
-            Tree::Error(err) => {
-                ui.centered_and_justified(|ui| {
-                    ui.label(err.as_ref());
-                });
+                if unloaded.contains(&dnd.target) {
+                    // the moved entry will be found when the target is read
+                    let _ = tree.remove(node, RemoveBehavior::DropChildren);
+                } else {
+                    source_node.append_to(dnd.target).unwrap();
+                }
             }
+
+            tree.get_mut(dnd.target).unwrap().sort_children_by(node_ord);
+        });
+
+        let tree_height = ui.available_height() - ui.style().spacing.interact_size.y;
+        Frame::new()
+            .stroke(ui.style().visuals.window_stroke)
+            .corner_radius(CornerRadius::same(4))
+            .show(ui, |ui| {
+                self.tree_display
+                    .display(ui, &mut self.tree.tree, label_fn, dnd, tree_height);
+            });
+
+        if self.tree.load_dirs(self.tree_display.open_dirs()) {
+            ui.request_repaint();
         }
     }
 }
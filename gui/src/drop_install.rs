@@ -0,0 +1,105 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Installation of archives dropped onto the window.
+
+use std::ffi::OsStr;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use eframe::egui;
+use egui::{Align2, Color32, Id, LayerId, Order, TextStyle, Ui};
+use tracing::{error, info};
+
+use mmm_core::instance::Instance;
+use mmm_edit::install::stage_archive_with_defaults;
+
+use crate::ModManagerUi;
+use crate::background_task::{BackgroundTask, Finalizer, StatusString};
+use crate::install::ARCHIVE_EXTENSIONS;
+
+impl ModManagerUi {
+    /// Installs archives dropped onto the window with the default options, like archives installed from a URL.
+    ///
+    /// While files are dragged over the window, shows a hint that they can be dropped.
+    pub(crate) fn dropped_archives_ui(&mut self, ui: &Ui) {
+        let (hovering, dropped) = ui.input(|i| (!i.raw.hovered_files.is_empty(), i.raw.dropped_files.clone()));
+
+        if hovering {
+            let painter = ui
+                .ctx()
+                .layer_painter(LayerId::new(Order::Foreground, Id::new("drop_hint")));
+            let rect = ui.ctx().content_rect();
+            painter.rect_filled(rect, 0.0, Color32::from_black_alpha(192));
+            painter.text(
+                rect.center(),
+                Align2::CENTER_CENTER,
+                "Drop archives to install them",
+                TextStyle::Heading.resolve(ui.style()),
+                Color32::WHITE,
+            );
+        }
+
+        for path in dropped.into_iter().filter_map(|file| file.path) {
+            if !is_archive(&path) {
+                info!(
+                    "ignoring dropped file '{}', as it isn't a supported archive",
+                    path.display()
+                );
+                continue;
+            }
+            let name = format!("Installing '{}'", path.display());
+            let task = install_dropped_archive(self.instance.mods_dir(), path);
+            self.spawn_background_task(name, task);
+        }
+    }
+}
+
+fn is_archive(path: &Path) -> bool {
+    path.extension()
+        .and_then(OsStr::to_str)
+        .is_some_and(|ext| ARCHIVE_EXTENSIONS.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+}
+
+/// Returns a [`BackgroundTask`] that extracts an archive into the mods directory, and then adds it as a new mod
+/// named after the archive.
+fn install_dropped_archive(mods_dir: PathBuf, path: PathBuf) -> BackgroundTask {
+    Box::new(move |status: &StatusString| {
+        let file_stem = path.file_stem().and_then(OsStr::to_str).unwrap_or_default().to_owned();
+        {
+            let mut s = status.lock().expect("lock is not poisoned");
+            s.clear();
+            let _ = write!(s, "Installing mod {file_stem}");
+        }
+        let result = stage_archive_with_defaults(&mods_dir, &path);
+
+        let finalizer: Finalizer = Box::new(move |mm: &mut ModManagerUi| {
+            let staged = match result {
+                Ok(staged) => staged,
+                Err(err) => {
+                    error!(?err, "failed to install archive '{}'", path.display());
+                    return;
+                }
+            };
+            // the archive's name may be taken by a mod added in the meantime
+            let name = mm.instance.suggest_mod_name(&file_stem);
+            match mm.instance.add_staged_mod(&name, staged) {
+                Ok(_) => mm.mod_added(),
+                Err(err) => error!("failed to create mod '{}': {}", name, err),
+            }
+        });
+        Some(finalizer)
+    })
+}
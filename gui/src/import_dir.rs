@@ -0,0 +1,173 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Dialog for creating a mod from an existing directory.
+
+use std::ffi::OsStr;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Context, Poll};
+
+use eframe::egui;
+use egui::{Button, Id, Modal, Sides, Ui};
+use futures::task::noop_waker;
+use rfd::AsyncFileDialog;
+use tracing::{error, info};
+
+use mmm_core::instance::{Instance, ModDeclaration};
+use mmm_edit::install::staging::{CopyOrMove, StageDirError, StagedInstall};
+
+use crate::ModManagerUi;
+use crate::background_task::{BackgroundTask, Finalizer, StatusString};
+
+pub enum DirectoryImport {
+    FolderPicker(Pin<Box<dyn Future<Output = Option<rfd::FileHandle>> + Send>>),
+    Dialog { path: PathBuf, name: String, mode: CopyOrMove },
+}
+
+impl DirectoryImport {
+    pub fn new_with_folder_picker(frame: &eframe::Frame) -> Self {
+        let picker = AsyncFileDialog::new().set_parent(frame).pick_folder();
+        Self::FolderPicker(Box::pin(picker))
+    }
+}
+
+impl ModManagerUi {
+    pub(crate) fn directory_import_ui(&mut self, ui: &mut Ui) {
+        let Some(import) = &mut self.directory_import else {
+            return;
+        };
+
+        let (path, name, mode) = match import {
+            DirectoryImport::FolderPicker(picker) => {
+                match picker.as_mut().poll(&mut Context::from_waker(&noop_waker())) {
+                    Poll::Pending => {}
+                    Poll::Ready(Some(folder)) => {
+                        let path = PathBuf::from(folder);
+                        let name = path.file_name().and_then(OsStr::to_str).unwrap_or_default().to_owned();
+                        *import = DirectoryImport::Dialog { path, name, mode: CopyOrMove::Copy };
+                    }
+                    Poll::Ready(None) => self.directory_import = None,
+                }
+                return;
+            }
+            DirectoryImport::Dialog { path, name, mode } => (path, name, mode),
+        };
+
+        let name_exists = self.instance.mods().iter().any(|m| m.name() == name.as_str());
+        let name_valid = ModDeclaration::is_name_valid(name) && !name_exists;
+        let busy = self.directory_import_cancel.is_some();
+        let mut accepted = false;
+
+        let modal = Modal::new(Id::new("import_directory")).show(ui.ctx(), |ui| {
+            ui.set_width(350.0);
+            ui.heading("Create mod from folder");
+            ui.label(path.display().to_string());
+            ui.add_space(4.0);
+
+            ui.horizontal(|ui| {
+                ui.label("Mod name:");
+                ui.text_edit_singleline(name);
+            });
+            if name_exists {
+                ui.colored_label(ui.visuals().error_fg_color, "A mod with this name already exists.");
+            }
+
+            ui.horizontal(|ui| {
+                ui.radio_value(mode, CopyOrMove::Copy, "Copy files");
+                ui.radio_value(mode, CopyOrMove::Move, "Move files");
+            });
+            if busy {
+                ui.label("Another folder is still being imported.");
+            }
+
+            Sides::new().show(
+                ui,
+                |_| (),
+                |ui| {
+                    if ui.button("Cancel").clicked() {
+                        ui.close();
+                    }
+
+                    if ui.add_enabled(name_valid && !busy, Button::new("Create")).clicked() {
+                        accepted = true;
+                        ui.close();
+                    }
+                },
+            );
+        });
+
+        if accepted {
+            let cancel = Arc::new(AtomicBool::new(false));
+            self.directory_import_cancel = Some(Arc::clone(&cancel));
+            let task_name = format!("Creating mod '{name}' from a folder");
+            let task = stage_directory(self.instance.mods_dir(), path.clone(), name.clone(), *mode, cancel);
+            self.spawn_background_task(task_name, task);
+        }
+
+        if modal.should_close() {
+            self.directory_import = None;
+        }
+    }
+
+    pub(crate) fn cancel_directory_import(&self) {
+        if let Some(cancel) = &self.directory_import_cancel {
+            cancel.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Returns a [`BackgroundTask`] that copies or moves a directory into the mods directory,
+/// and then adds it as a new mod.
+fn stage_directory(
+    mods_dir: PathBuf,
+    source: PathBuf,
+    name: String,
+    mode: CopyOrMove,
+    cancel: Arc<AtomicBool>,
+) -> BackgroundTask {
+    Box::new(move |status: &StatusString| {
+        {
+            let mut s = status.lock().expect("lock is not poisoned");
+            s.clear();
+            let verb = match mode {
+                CopyOrMove::Copy => "Copying",
+                CopyOrMove::Move => "Moving",
+            };
+            let _ = write!(s, "{verb} {} to mod {name}", display_name(&source));
+        }
+
+        let result = StagedInstall::stage_dir(&mods_dir, &source, mode, &cancel);
+        let finalizer: Finalizer = Box::new(move |mm: &mut ModManagerUi| {
+            mm.directory_import_cancel = None;
+            match result {
+                Ok(staged) => match mm.instance.add_staged_mod(&name, staged) {
+                    Ok(_) => mm.mod_added(),
+                    Err(err) => error!("failed to create mod '{}': {}", name, err),
+                },
+                Err(StageDirError::Cancelled) => info!("creating mod '{}' was cancelled", name),
+                Err(err) => error!(?err, "failed to create mod '{}'", name),
+            }
+        });
+        Some(finalizer)
+    })
+}
+
+fn display_name(path: &Path) -> String {
+    path.file_name().unwrap_or(path.as_os_str()).display().to_string()
+}
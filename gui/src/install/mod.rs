@@ -22,7 +22,6 @@ use std::mem;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::sync::Arc;
-use std::sync::mpsc::Sender;
 use std::task::{Context, Poll};
 use std::thread::{self, JoinHandle};
 use std::time::Instant;
@@ -31,31 +30,40 @@ use anyhow::Context as _;
 use compact_str::CompactString;
 use eframe::egui;
 use egui::{
-    CentralPanel, Checkbox, CornerRadius, Frame, RichText, Sides, TextStyle, Ui, Vec2, ViewportCommand, ViewportId,
+    CentralPanel, Checkbox, CornerRadius, Frame, RichText, ScrollArea, Sides, TextStyle, Ui, Vec2, ViewportCommand,
+    ViewportId,
 };
 use foldhash::HashMap;
 use futures::task::noop_waker;
 use nary_tree::NodeId;
 use nary_tree::iter_mut::Lender;
 use rfd::AsyncFileDialog;
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 
 use mmm_core::file_tree::{Counters, FileTree, TreeNodeKind, TreeNodeRef};
-use mmm_core::instance::Instance;
+use mmm_core::instance::{Instance, ModIndex};
 use mmm_edit::EditableInstance;
 use mmm_edit::archive::{Archive, ExtractSelection};
-use mmm_edit::install::staging::StagedInstall;
+use mmm_edit::downloads::store_download;
+use mmm_edit::install::bain::BainPackage;
+use mmm_edit::install::omod::{self, OmodPackage};
+use mmm_edit::install::staging::{CopyOrMove, StagedInstall};
 use mmm_edit::util::node_ord;
 
-use crate::ModManagerUi;
-use crate::background_task::{BackgroundTask, Finalizer, StatusString};
+use crate::background_task::{Finalizer, StatusString, TaskQueue};
 use crate::tree::{TreeDisplay, dnd_handle_actions_fn};
 use crate::utils::{Viewport, ViewportResult, show_immediate};
+use crate::{ModManagerUi, delete_directories};
+
+/// Extensions of the archive files that can be installed.
+pub const ARCHIVE_EXTENSIONS: [&str; 5] = ["7z", "omod", "rar", "tar", "zip"];
 
 pub struct OngoingModInstallation {
     viewport: Option<Box<Viewport>>,
     state: State,
-    background_task_queue: Sender<BackgroundTask>,
+    background_task_queue: TaskQueue,
+    /// Name of the mod whose files are being replaced, if this is a reinstallation.
+    reinstall: Option<CompactString>,
 }
 
 #[allow(
@@ -65,7 +73,7 @@ pub struct OngoingModInstallation {
 enum State {
     FilePicker(Pin<Box<dyn Future<Output = Option<rfd::FileHandle>> + Send>>),
     Opening {
-        handle: Option<JoinHandle<anyhow::Result<Archive>>>,
+        handle: Option<JoinHandle<anyhow::Result<(Archive, Option<OmodPackage>)>>>,
         counter: Arc<Counters>,
         previous_count: usize,
         text: CompactString,
@@ -78,6 +86,9 @@ enum State {
         extract_selection: ExtractSelection,
         tree_display: TreeDisplay,
         dir_checkbox_cache: HashMap<NodeId, CheckboxState>,
+        bain: Option<(BainPackage, Vec<bool>)>,
+        omod: Option<OmodPackage>,
+        path: Arc<Path>,
     },
     Closing,
     Error(Box<str>),
@@ -90,9 +101,9 @@ enum CheckboxState {
 }
 
 impl OngoingModInstallation {
-    pub fn new_with_file_picker(frame: &eframe::Frame, background_task_queue: Sender<BackgroundTask>) -> Self {
+    pub fn new_with_file_picker(frame: &eframe::Frame, background_task_queue: TaskQueue) -> Self {
         let picker = AsyncFileDialog::new()
-            .add_filter("Archive file", &["7z", "rar", "tar", "zip"])
+            .add_filter("Archive file", &ARCHIVE_EXTENSIONS)
             .set_parent(frame)
             .pick_file();
         let picker = Box::pin(picker);
@@ -101,6 +112,20 @@ impl OngoingModInstallation {
             viewport: None,
             state: State::FilePicker(picker),
             background_task_queue,
+            reinstall: None,
+        }
+    }
+
+    /// Like [`new_with_file_picker`](Self::new_with_file_picker), but replaces the files of an existing mod
+    /// instead of creating a new one.
+    pub fn new_reinstall_with_file_picker(
+        frame: &eframe::Frame,
+        background_task_queue: TaskQueue,
+        mod_name: CompactString,
+    ) -> Self {
+        Self {
+            reinstall: Some(mod_name),
+            ..Self::new_with_file_picker(frame, background_task_queue)
         }
     }
 
@@ -114,8 +139,12 @@ impl OngoingModInstallation {
             let counter = counter_clone;
 
             debug!("opening archive '{}' for installation", path.display());
-            let archive = Archive::open(path, counter).context("failed to open archive")?;
-            Ok(archive)
+            let mut archive = Archive::open(path, counter).context("failed to open archive")?;
+            let omod = omod::is_omod(archive.tree())
+                .then(|| OmodPackage::read(&mut archive))
+                .transpose()
+                .context("failed to read OMOD package")?;
+            Ok((archive, omod))
         })?;
 
         Ok(State::Opening {
@@ -161,10 +190,24 @@ impl OngoingModInstallation {
                 if handle.as_ref().expect("not joined yet").is_finished() {
                     let handle = handle.take().expect("not joined yet");
                     self.state = match handle.join() {
-                        Ok(Ok(archive)) => {
-                            let mod_name = path.file_stem().and_then(OsStr::to_str).unwrap_or_default().to_owned();
-
-                            let extract_selection = ExtractSelection::new(&archive);
+                        Ok(Ok((archive, omod))) => {
+                            let mod_name = match &self.reinstall {
+                                Some(name) => name.to_string(),
+                                None => path.file_stem().and_then(OsStr::to_str).unwrap_or_default().to_owned(),
+                            };
+
+                            let mut extract_selection = ExtractSelection::new(&archive);
+                            extract_selection.unwrap_single_top_level_dir();
+
+                            let bain = omod
+                                .is_none()
+                                .then(|| BainPackage::detect(extract_selection.tree()))
+                                .flatten()
+                                .map(|package| {
+                                    let choices = package.default_choices();
+                                    package.apply(&mut extract_selection, &choices);
+                                    (package, choices)
+                                });
 
                             State::ExtractDialog {
                                 mod_name,
@@ -173,11 +216,14 @@ impl OngoingModInstallation {
                                 extract_selection,
                                 tree_display: TreeDisplay::new(),
                                 dir_checkbox_cache: HashMap::default(),
+                                bain,
+                                omod,
+                                path: Arc::clone(path),
                             }
                         }
                         Ok(Err(err)) => {
                             error!(?err, ?path, "failed to open archive");
-                            State::Error(format!("Failed to open archive:\n{}", err).into_boxed_str())
+                            State::Error(format!("Failed to open archive:\n{:#}", err).into_boxed_str())
                         }
                         Err(_) => {
                             error!("archive read thread panicked");
@@ -227,91 +273,143 @@ impl OngoingModInstallation {
         let State::ExtractDialog {
             mod_name,
             mod_already_exists,
+            archive,
             extract_selection,
             tree_display,
             dir_checkbox_cache,
+            bain,
+            omod,
             ..
         } = &mut self.state
         else {
             unreachable!()
         };
 
-        Self::mod_name(ui, instance, mod_name, mod_already_exists);
-
-        let label_fn = |ui: &mut Ui, tree: &mut FileTree<bool>, id: &NodeId| {
+        if self.reinstall.is_some() {
             ui.horizontal(|ui| {
-                let is_dir = matches!(tree.get(*id).expect("node exists").data().kind, TreeNodeKind::Dir);
+                ui.label("Reinstalling");
+                ui.strong(mod_name.as_str());
+            });
+        } else {
+            Self::mod_name(ui, instance, mod_name, mod_already_exists);
+        }
 
-                let changed = if is_dir {
-                    let (mut extract, indeterminate) = match dir_checkbox_cache.get(id) {
-                        Some(CheckboxState::Partial) => (false, true),
-                        Some(CheckboxState::Unchecked) => (false, false),
-                        None => (true, false),
-                    };
-                    let previous = extract;
-                    ui.add(Checkbox::without_text(&mut extract).indeterminate(indeterminate));
-                    let changed = extract != previous;
+        if let Some(package) = omod {
+            ui.label(format!(
+                "This is an OMOD package of {} {}, by {}. These files will be installed:",
+                package.name(),
+                package.version(),
+                package.author()
+            ));
+            let list_height = ui.available_height() - ui.style().spacing.interact_size.y;
+            Frame::new()
+                .stroke(ui.style().visuals.window_stroke)
+                .corner_radius(CornerRadius::same(4))
+                .show(ui, |ui| {
+                    ScrollArea::vertical()
+                        .max_height(list_height)
+                        .auto_shrink(false)
+                        .show(ui, |ui| {
+                            for file in package.files() {
+                                ui.label(file.as_str());
+                            }
+                        });
+                });
+        } else {
+            if let Some((package, choices)) = bain {
+                let mut changed = false;
+                ui.label("This is a BAIN package. Select the options to install:");
+                ui.horizontal_wrapped(|ui| {
+                    for (option, chosen) in package.options().iter().zip(choices.iter_mut()) {
+                        changed |= ui.checkbox(chosen, option.as_str()).changed();
+                    }
+                });
 
-                    if changed {
-                        let mut iter = tree.get_mut(*id).expect("node exists").traverse_post_order();
-                        while let Some(mut node) = iter.next() {
-                            match &mut node.data().kind {
-                                TreeNodeKind::File(e) => *e = extract,
-                                TreeNodeKind::Dir => {
-                                    if extract {
-                                        dir_checkbox_cache.remove(&node.node_id());
-                                    } else {
-                                        dir_checkbox_cache.insert(node.node_id(), CheckboxState::Unchecked);
+                if changed {
+                    *extract_selection = ExtractSelection::new(archive);
+                    extract_selection.unwrap_single_top_level_dir();
+                    package.apply(extract_selection, choices);
+                    *tree_display = TreeDisplay::new();
+                    dir_checkbox_cache.clear();
+                }
+                ui.separator();
+            }
+
+            let label_fn = |ui: &mut Ui, tree: &mut FileTree<bool>, id: &NodeId| {
+                ui.horizontal(|ui| {
+                    let is_dir = matches!(tree.get(*id).expect("node exists").data().kind, TreeNodeKind::Dir);
+
+                    let changed = if is_dir {
+                        let (mut extract, indeterminate) = match dir_checkbox_cache.get(id) {
+                            Some(CheckboxState::Partial) => (false, true),
+                            Some(CheckboxState::Unchecked) => (false, false),
+                            None => (true, false),
+                        };
+                        let previous = extract;
+                        ui.add(Checkbox::without_text(&mut extract).indeterminate(indeterminate));
+                        let changed = extract != previous;
+
+                        if changed {
+                            let mut iter = tree.get_mut(*id).expect("node exists").traverse_post_order();
+                            while let Some(mut node) = iter.next() {
+                                match &mut node.data().kind {
+                                    TreeNodeKind::File(e) => *e = extract,
+                                    TreeNodeKind::Dir => {
+                                        if extract {
+                                            dir_checkbox_cache.remove(&node.node_id());
+                                        } else {
+                                            dir_checkbox_cache.insert(node.node_id(), CheckboxState::Unchecked);
+                                        }
                                     }
                                 }
                             }
                         }
-                    }
 
-                    changed
-                } else {
-                    let mut node = tree.get_mut(*id).expect("node exists");
-                    if let TreeNodeKind::File(extract) = &mut node.data().kind {
-                        let previous = *extract;
-                        ui.checkbox(extract, ());
-                        *extract != previous
+                        changed
                     } else {
-                        unreachable!()
-                    }
-                };
-
-                let node = tree.get(*id).expect("node exists");
-                if changed {
-                    let parent = node.parent().expect("has parent");
-                    let root_id = tree.root_id().expect("has root node");
-                    update_checkbox_cache(dir_checkbox_cache, &parent, root_id);
-                }
+                        let mut node = tree.get_mut(*id).expect("node exists");
+                        if let TreeNodeKind::File(extract) = &mut node.data().kind {
+                            let previous = *extract;
+                            ui.checkbox(extract, ());
+                            *extract != previous
+                        } else {
+                            unreachable!()
+                        }
+                    };
 
-                ui.label(node.data().name.as_str());
-            });
-        };
+                    let node = tree.get(*id).expect("node exists");
+                    if changed {
+                        let parent = node.parent().expect("has parent");
+                        let root_id = tree.root_id().expect("has root node");
+                        update_checkbox_cache(dir_checkbox_cache, &parent, root_id);
+                    }
 
-        let handle_actions_fn = dnd_handle_actions_fn(|tree, dnd| {
-            assert!(matches!(
-                tree.get(dnd.target).expect("node exists").data().kind,
-                TreeNodeKind::Dir
-            ));
+                    ui.label(node.data().name.as_str());
+                });
+            };
 
-            for node in dnd.source {
-                tree.get_mut(node).unwrap().append_to(dnd.target).unwrap();
-            }
+            let handle_actions_fn = dnd_handle_actions_fn(|tree, dnd| {
+                assert!(matches!(
+                    tree.get(dnd.target).expect("node exists").data().kind,
+                    TreeNodeKind::Dir
+                ));
 
-            tree.get_mut(dnd.target).unwrap().sort_children_by(node_ord);
-        });
+                for node in dnd.source {
+                    tree.get_mut(node).unwrap().append_to(dnd.target).unwrap();
+                }
 
-        let tree_height = ui.available_height() - ui.style().spacing.interact_size.y;
-        Frame::new()
-            .stroke(ui.style().visuals.window_stroke)
-            .corner_radius(CornerRadius::same(4))
-            .show(ui, |ui| {
-                tree_display.display(ui, extract_selection.tree(), label_fn, handle_actions_fn, tree_height);
+                tree.get_mut(dnd.target).unwrap().sort_children_by(node_ord);
             });
 
+            let tree_height = ui.available_height() - ui.style().spacing.interact_size.y;
+            Frame::new()
+                .stroke(ui.style().visuals.window_stroke)
+                .corner_radius(CornerRadius::same(4))
+                .show(ui, |ui| {
+                    tree_display.display(ui, extract_selection.tree(), label_fn, handle_actions_fn, tree_height);
+                });
+        }
+
         Sides::new().show(
             ui,
             |_| (),
@@ -320,14 +418,34 @@ impl OngoingModInstallation {
                     ui.send_viewport_cmd(ViewportCommand::Close);
                 }
 
-                if ui.button("Install").clicked() {
-                    let State::ExtractDialog { mod_name, mut archive, extract_selection, .. } =
-                        mem::replace(&mut self.state, State::Closing)
+                if ui
+                    .button(if self.reinstall.is_some() {
+                        "Reinstall"
+                    } else {
+                        "Install"
+                    })
+                    .clicked()
+                {
+                    let State::ExtractDialog {
+                        mod_name,
+                        mut archive,
+                        extract_selection,
+                        omod,
+                        path,
+                        ..
+                    } = mem::replace(&mut self.state, State::Closing)
                     else {
                         unreachable!()
                     };
 
-                    let mods_dir = instance.arc_dir();
+                    let reinstall = self.reinstall.is_some();
+                    let task_name = if reinstall {
+                        format!("Reinstalling mod '{mod_name}'")
+                    } else {
+                        format!("Installing mod '{mod_name}'")
+                    };
+                    let mods_dir = instance.mods_dir();
+                    let downloads_dir = instance.downloads_dir();
                     let task = Box::new(move |status: &StatusString| {
                         {
                             let mut s = status.lock().expect("lock is not poisoned");
@@ -335,8 +453,11 @@ impl OngoingModInstallation {
                             let _ = write!(s, "Installing mod {}", mod_name);
                         }
 
-                        let staged_mod = match StagedInstall::stage_archive(&mods_dir, &mut archive, &extract_selection)
-                        {
+                        let staged_mod = match &omod {
+                            Some(package) => StagedInstall::stage_omod(&mods_dir, &mut archive, package),
+                            None => StagedInstall::stage_archive(&mods_dir, &mut archive, &extract_selection),
+                        };
+                        let staged_mod = match staged_mod {
                             Ok(m) => m,
                             Err(err) => {
                                 error!(?err, "failed to extract archive");
@@ -344,13 +465,43 @@ impl OngoingModInstallation {
                             }
                         };
 
+                        let source = match store_download(&downloads_dir, &path, CopyOrMove::Copy) {
+                            Ok(name) => Some(name),
+                            Err(err) => {
+                                warn!("failed to store archive '{}' in downloads: {}", path.display(), err);
+                                None
+                            }
+                        };
+
                         let finalizer: Finalizer = Box::new(move |mm: &mut ModManagerUi| {
-                            if let Err(err) = mm.instance.add_staged_mod(&mod_name, staged_mod) {
-                                error!("failed to add staged mod: {}", err);
+                            if reinstall {
+                                let Some(idx) = mm.instance.mods().position(|decl| decl.name() == &mod_name) else {
+                                    error!("mod '{}' was removed before it could be reinstalled", mod_name);
+                                    return;
+                                };
+
+                                match mm.instance.reinstall_mod(idx, staged_mod) {
+                                    Ok(old_files) => {
+                                        debug!("reinstalled mod {}", &mod_name);
+                                        record_source(mm, idx, source.as_deref());
+                                        let task_name = format!("Deleting the old files of '{mod_name}'");
+                                        mm.spawn_background_task(task_name, delete_directories(vec![old_files]));
+                                    }
+                                    Err(err) => error!("failed to reinstall mod: {}", err),
+                                }
                                 return;
                             }
 
+                            let idx = match mm.instance.add_staged_mod(&mod_name, staged_mod) {
+                                Ok(idx) => idx,
+                                Err(err) => {
+                                    error!("failed to add staged mod: {}", err);
+                                    return;
+                                }
+                            };
+
                             debug!("installed mod {}", &mod_name);
+                            record_source(mm, idx, source.as_deref());
                             mm.mod_added();
 
                             // TODO: highlight newly installed mod
@@ -358,7 +509,7 @@ impl OngoingModInstallation {
                         Some(finalizer)
                     });
 
-                    if self.background_task_queue.send(task).is_err() {
+                    if self.background_task_queue.push(task_name, task).is_err() {
                         error!("background task panicked");
                     }
                 }
@@ -392,6 +543,14 @@ impl OngoingModInstallation {
     }
 }
 
+fn record_source(mm: &mut ModManagerUi, idx: ModIndex, source: Option<&str>) {
+    if let Some(source) = source
+        && let Err(err) = mm.instance.set_mod_source(idx, Some(source))
+    {
+        error!("failed to record source of mod: {}", err);
+    }
+}
+
 fn update_checkbox_cache(cache: &mut HashMap<NodeId, CheckboxState>, parent: &TreeNodeRef<bool>, root_id: NodeId) {
     assert_eq!(parent.data().kind, TreeNodeKind::Dir);
     let parent_id = parent.node_id();
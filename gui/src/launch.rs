@@ -0,0 +1,238 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Deploying the mods and launching the game, by running `mmm-deploy` and supervising it.
+
+use std::env;
+use std::io::{self, BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use compact_str::CompactString;
+use eframe::egui;
+use egui::{Button, Context, Ui};
+use tracing::{error, info, warn};
+
+use mmm_core::instance::Instance;
+use mmm_edit::EditableInstance;
+
+use crate::ModManagerUi;
+use crate::settings::DeploySettings;
+
+/// Name of the deployment executable, which is looked for next to the GUI's executable, and then in `PATH`.
+const DEPLOY_EXECUTABLE: &str = "mmm-deploy";
+/// Number of lines of `mmm-deploy`'s output kept to show when it fails.
+const OUTPUT_LINES: usize = 20;
+
+#[derive(Default)]
+pub struct GameLauncher {
+    session: Option<GameSession>,
+    /// Why the last deployment failed, shown until the next launch.
+    error: Option<String>,
+}
+
+/// A running `mmm-deploy` process.
+struct GameSession {
+    child: Child,
+    profile: CompactString,
+    /// The most recent lines written by `mmm-deploy` to stderr, where it writes its status messages.
+    output: Arc<Mutex<Vec<String>>>,
+}
+
+impl GameLauncher {
+    /// Returns `true` while the mods are deployed, and the game may be running.
+    pub const fn is_running(&self) -> bool {
+        self.session.is_some()
+    }
+
+    /// Checks whether `mmm-deploy` exited, and if so, records whether it failed.
+    fn poll(&mut self) {
+        let Some(session) = &mut self.session else {
+            return;
+        };
+        let status = match session.child.try_wait() {
+            Ok(Some(status)) => status,
+            Ok(None) => return,
+            Err(err) => {
+                error!("failed to check whether the deployment is still running: {}", err);
+                return;
+            }
+        };
+
+        let session = self.session.take().expect("session exists");
+        if status.success() {
+            info!("deployment of profile '{}' ended", session.profile);
+        } else {
+            let message = failure_message(status, &session.output.lock().expect("lock is not poisoned"));
+            error!("deployment of profile '{}' failed: {}", session.profile, message);
+            self.error = Some(message);
+        }
+    }
+}
+
+impl GameSession {
+    fn spawn(instance: &EditableInstance, settings: &DeploySettings, ctx: &Context) -> io::Result<Self> {
+        let profile = instance.current_profile().clone();
+        let mut command = Command::new(deploy_executable());
+        settings.apply(&mut command);
+        let mut child = command
+            .arg("--profile")
+            .arg(profile.as_str())
+            .arg(instance.dir())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let stderr = child.stderr.take().expect("stderr is piped");
+        let output_clone = Arc::clone(&output);
+        let ctx = ctx.clone();
+        let reader = thread::Builder::new().name("deploy output".to_owned()).spawn(move || {
+            for line in BufReader::new(stderr).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                let mut output = output_clone.lock().expect("lock is not poisoned");
+                if output.len() == OUTPUT_LINES {
+                    let _ = output.remove(0);
+                }
+                output.push(line);
+                drop(output);
+                ctx.request_repaint();
+            }
+            ctx.request_repaint();
+        });
+        if let Err(err) = reader {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(err);
+        }
+
+        Ok(Self { child, profile, output })
+    }
+
+    /// Asks `mmm-deploy` to stop the game and remove the deployment.
+    fn stop(&mut self) {
+        #[cfg(unix)]
+        {
+            use rustix::process::{Pid, Signal, kill_process};
+            if let Err(err) = kill_process(Pid::from_child(&self.child), Signal::TERM) {
+                error!("failed to stop deployment: {}", err);
+            }
+        }
+        #[cfg(not(unix))]
+        if let Err(err) = self.child.kill() {
+            error!("failed to stop deployment: {}", err);
+        }
+    }
+}
+
+impl Drop for GameSession {
+    fn drop(&mut self) {
+        // `mmm-deploy` removes the deployment on its own once the game exits
+        if let Ok(None) = self.child.try_wait() {
+            warn!("leaving deployment of profile '{}' running", self.profile);
+        }
+    }
+}
+
+impl ModManagerUi {
+    /// Shows the button that deploys the mods and launches the game, or stops it if it's running.
+    pub(crate) fn launch_button(&mut self, ui: &mut Ui) {
+        self.game.poll();
+
+        if let Some(session) = &mut self.game.session {
+            if ui
+                .button("Stop")
+                .on_hover_text("Stop the game and remove the deployment")
+                .clicked()
+            {
+                session.stop();
+            }
+            ui.ctx().request_repaint_after(Duration::from_secs(1));
+            return;
+        }
+
+        let settings = self.instance.settings();
+        let problem = if settings.game_path.is_none() {
+            Some("The instance has no game directory")
+        } else if settings.executable.is_none() && settings.steam_app_id.is_none() {
+            Some("The instance has no game executable or Steam app ID")
+        } else {
+            None
+        };
+        let response = ui.add_enabled(problem.is_none(), Button::new("▶ Launch"));
+        let response = match problem {
+            Some(problem) => response.on_disabled_hover_text(problem),
+            None => response.on_hover_text("Deploy the enabled mods and launch the game"),
+        };
+        if !response.clicked() {
+            return;
+        }
+
+        // `mmm-deploy` reads the instance data from disk
+        if let Err(err) = self.instance.save_blocking() {
+            self.game.error = Some(format!("Failed to save instance data: {err}"));
+            return;
+        }
+        self.game.error = None;
+        match GameSession::spawn(&self.instance, &self.settings.deploy, ui.ctx()) {
+            Ok(session) => {
+                info!("deploying profile '{}'", session.profile);
+                self.game.session = Some(session);
+            }
+            Err(err) => self.game.error = Some(format!("Failed to run {DEPLOY_EXECUTABLE}: {err}")),
+        }
+    }
+
+    /// Shows whether the mods are deployed, and the latest status message of the deployment.
+    pub(crate) fn game_status(&mut self, ui: &mut Ui) {
+        if let Some(session) = &self.game.session {
+            let output = session.output.lock().expect("lock is not poisoned");
+            let text = match output.last() {
+                Some(line) => format!("Deployment of '{}': {line}", session.profile),
+                None => format!("Deploying '{}'", session.profile),
+            };
+            drop(output);
+            ui.label(text);
+            ui.separator();
+        } else if let Some(err) = &self.game.error {
+            ui.colored_label(ui.visuals().error_fg_color, "Deployment failed")
+                .on_hover_text(err.as_str());
+            ui.separator();
+        }
+    }
+}
+
+fn deploy_executable() -> PathBuf {
+    env::current_exe()
+        .ok()
+        .map(|exe| exe.with_file_name(DEPLOY_EXECUTABLE))
+        .filter(|path| path.is_file())
+        .unwrap_or_else(|| PathBuf::from(DEPLOY_EXECUTABLE))
+}
+
+fn failure_message(status: ExitStatus, output: &[String]) -> String {
+    let mut message = format!("{DEPLOY_EXECUTABLE} {status}");
+    for line in output {
+        message.push('\n');
+        message.push_str(line);
+    }
+    message
+}
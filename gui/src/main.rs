@@ -16,58 +16,101 @@
 #![forbid(unsafe_code)]
 
 mod background_task;
+mod columns;
+mod conflicts;
 mod details;
+mod drop_install;
+mod import_dir;
 mod install;
+mod launch;
+mod notifications;
+mod settings;
+mod start;
 mod tree;
+mod url_install;
 mod utils;
 
 use std::collections::hash_map::Entry;
+use std::error::Error as _;
 use std::ffi::OsStr;
 use std::fmt::Write;
 use std::fs;
-use std::path::PathBuf;
-use std::sync::Arc;
-use std::sync::mpsc::{Receiver, Sender};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
 
 use anyhow::Context as _;
 use clap::Parser;
+use compact_str::CompactString;
 use eframe::{App, Frame, NativeOptions, egui, egui_wgpu, wgpu};
 use egui::{
-    Align, CentralPanel, Color32, Context, Id, Layout, Modal, Panel, Popup, ScrollArea, Sense, Sides, Stroke,
-    TextStyle, TextWrapMode, Ui,
+    Align, Button, CentralPanel, Color32, ComboBox, Context, Grid, Id, Layout, Modal, Panel, Popup, RichText,
+    ScrollArea, Sense, Sides, Stroke, TextEdit, TextStyle, TextWrapMode, Ui,
 };
 use egui_extras::{Column, TableBuilder};
 use egui_wgpu::{WgpuSetup, WgpuSetupCreateNew};
 use foldhash::{HashMap, HashSet};
 use tracing::{Level, error, info};
 use tracing_subscriber::EnvFilter;
+use tracing_subscriber::layer::SubscriberExt as _;
 use wgpu::{PowerPreference, PresentMode};
 
-use mmm_core::instance::{Instance, ModDeclaration, ModEntryKind, ModIndex, ModOrderIndex};
-use mmm_edit::EditableInstance;
+use mmm_core::instance::{Instance, ModDeclaration, ModEntryKind, ModIndex, ModLabel, ModOrderEntry, ModOrderIndex};
+use mmm_edit::disk_usage::DiskUsageCache;
+use mmm_edit::modlist::{ModListFormat, export_mod_list, parse_mod_list};
+use mmm_edit::util::name_ord;
+use mmm_edit::watch::InstanceWatcher;
+use mmm_edit::{BulkRenameProblem, EditableInstance, RenamePattern, SortCriterion, SortScope, TrashEntry};
 
-use crate::background_task::{BackgroundTask, Finalizer, StatusString, spawn_background_thread};
+use crate::background_task::{BackgroundTask, Finalizer, StatusString, TaskQueue, spawn_background_thread};
+use crate::columns::{SortColumn, TableSort};
+use crate::conflicts::{ConflictCounts, ConflictPanel};
 use crate::details::ModDetailsWindow;
+use crate::import_dir::DirectoryImport;
 use crate::install::OngoingModInstallation;
+use crate::launch::GameLauncher;
+use crate::notifications::{NotificationLayer, Notifications};
+use crate::settings::Settings;
+use crate::start::StartScreen;
+use crate::url_install::UrlInstall;
+use crate::utils::{format_size, label_color};
 
 const APP_NAME: &str = "zone.monterra.modmanager";
 
 #[derive(Parser)]
 struct Args {
-    instance_path: PathBuf,
+    /// Instance to open. If omitted, a list of recently opened instances is shown instead.
+    instance_path: Option<PathBuf>,
 }
 
 fn main() -> anyhow::Result<()> {
-    tracing_setup();
-    let instance = {
-        let args = Args::parse();
-        EditableInstance::open(&args.instance_path).context("failed to open instance")?
+    let notifications = tracing_setup();
+    let settings = Settings::load().unwrap_or_else(|err| {
+        error!("failed to load settings: {:#}", anyhow::Error::from(err));
+        Settings::default()
+    });
+    let instance = match Args::parse().instance_path {
+        Some(path) => Some(EditableInstance::open(&path).context("failed to open instance")?),
+        None => None,
     };
 
-    let options = native_options(&instance);
+    let options = native_options(instance.as_ref().map(Instance::dir));
 
     // https://github.com/emilk/egui/issues/5815
-    if let Err(err) = eframe::run_native(APP_NAME, options, Box::new(|_ctx| Ok(ModManagerUi::new(instance)))) {
+    if let Err(err) = eframe::run_native(
+        APP_NAME,
+        options,
+        Box::new(|cc| {
+            settings.theme.apply(&cc.egui_ctx);
+            let app: Box<dyn App> = match instance {
+                Some(instance) => ModManagerUi::new(instance, settings, notifications, &cc.egui_ctx),
+                None => StartScreen::new(settings, notifications, &cc.egui_ctx),
+            };
+            Ok(app)
+        }),
+    ) {
         error!("failed to create graphics context: {err}");
         std::process::exit(1);
     }
@@ -75,10 +118,18 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn native_options(instance: &EditableInstance) -> NativeOptions {
+/// Returns the window title to use while the specified instance is open.
+fn window_title(instance_dir: Option<&Path>) -> String {
+    match instance_dir {
+        Some(dir) => format!("mmm — {}", dir.display()),
+        None => "mmm".to_owned(),
+    }
+}
+
+fn native_options(instance_dir: Option<&Path>) -> NativeOptions {
     let mut options = NativeOptions::default();
     options.viewport.app_id = Some(APP_NAME.into()); // https://github.com/emilk/egui/issues/7872
-    options.viewport.title = Some(format!("mmm — {}", instance.dir().display()));
+    options.viewport.title = Some(window_title(instance_dir));
 
     // egui defaults to `AutoVsync` (https://github.com/emilk/egui/blob/0.34.3/crates/egui-wgpu/src/lib.rs#L335)
     // which selects `FifoRelaxed` if available, which we don't need.
@@ -115,20 +166,62 @@ fn native_options(instance: &EditableInstance) -> NativeOptions {
 
 pub struct ModManagerUi {
     instance: EditableInstance,
-    background_task_queue: Sender<BackgroundTask>,
+    background_task_queue: TaskQueue,
     background_task_finalizer_queue: Receiver<Finalizer>,
     background_task_status: StatusString,
     selection: HashSet<ModOrderIndex>,
     last_selected: Option<ModOrderIndex>,
+    /// Text typed into the filter box. Only mods whose names contain it are shown in the table.
+    mod_filter: String,
+    /// Separators whose groups are hidden in the table.
+    collapsed_separators: HashSet<ModIndex>,
+    inline_rename: Option<InlineRename>,
+    settings: Settings,
+    /// Copy of the settings being edited in the settings dialog, if it's open.
+    settings_draft: Option<Settings>,
+    /// How the rows of the table are sorted, or `None` to show them in priority order.
+    table_sort: Option<TableSort>,
+    conflict_counts: ConflictCounts,
     open_mod_details: HashMap<ModIndex, ModDetailsWindow>,
+    conflict_panel: ConflictPanel,
     create_new_mod_modal: CreateNewModModal,
     rename_mod_modal: RenameModModal,
     remove_selected_mods_modal: RemoveSelectedModsModal,
+    bulk_rename_modal: BulkRenameModal,
+    target_modal: TargetModal,
+    trash_modal: TrashModal,
+    import_mod_list_modal: ImportModListModal,
+    profiles_modal: ProfilesModal,
+    /// Name typed into the profile switcher to create a profile with.
+    new_profile_name: String,
     ongoing_mod_installs: Vec<OngoingModInstallation>,
+    disk_usage: Arc<Mutex<DiskUsageCache>>,
+    directory_import: Option<DirectoryImport>,
+    directory_import_cancel: Option<Arc<AtomicBool>>,
+    url_install: Option<UrlInstall>,
+    url_install_cancel: Option<Arc<AtomicBool>>,
+    watcher: Option<InstanceWatcher>,
+    game: GameLauncher,
+    notifications: Arc<Notifications>,
 }
 
 impl ModManagerUi {
-    fn new(instance: EditableInstance) -> Box<Self> {
+    fn new(
+        instance: EditableInstance,
+        mut settings: Settings,
+        notifications: Arc<Notifications>,
+        ctx: &Context,
+    ) -> Box<Self> {
+        notifications.set_context(ctx);
+        settings.add_recent_instance(instance.dir());
+        if let Err(err) = settings.save() {
+            error!("failed to save settings: {:#}", anyhow::Error::from(err));
+        }
+        let ctx = ctx.clone();
+        let watcher = InstanceWatcher::new(&instance, move || ctx.request_repaint())
+            .inspect_err(|err| error!("failed to watch instance for changes: {}", err))
+            .ok();
+
         let (background_task_queue, background_task_finalizer_queue, background_task_status) =
             spawn_background_thread().expect("failed to spawn background task thread");
 
@@ -139,29 +232,68 @@ impl ModManagerUi {
             background_task_status,
             selection: HashSet::default(),
             last_selected: None,
+            mod_filter: String::new(),
+            collapsed_separators: HashSet::default(),
+            inline_rename: None,
+            settings,
+            settings_draft: None,
+            table_sort: None,
+            conflict_counts: ConflictCounts::default(),
             open_mod_details: HashMap::default(),
+            conflict_panel: ConflictPanel::default(),
             create_new_mod_modal: CreateNewModModal::default(),
             rename_mod_modal: RenameModModal::default(),
             remove_selected_mods_modal: RemoveSelectedModsModal::default(),
+            bulk_rename_modal: BulkRenameModal::default(),
+            target_modal: TargetModal::default(),
+            trash_modal: TrashModal::default(),
+            import_mod_list_modal: ImportModListModal::default(),
+            profiles_modal: ProfilesModal::default(),
+            new_profile_name: String::new(),
             ongoing_mod_installs: Vec::new(),
+            disk_usage: Arc::default(),
+            directory_import: None,
+            directory_import_cancel: None,
+            url_install: None,
+            url_install_cancel: None,
+            watcher,
+            game: GameLauncher::default(),
+            notifications,
         })
     }
 }
 
 impl App for ModManagerUi {
-    fn logic(&mut self, _ctx: &Context, _frame: &mut Frame) {
+    fn logic(&mut self, ctx: &Context, _frame: &mut Frame) {
         while let Ok(finalizer) = self.background_task_finalizer_queue.try_recv() {
             finalizer(self);
         }
 
-        self.instance.save();
+        self.handle_external_changes();
+
+        if let Some(delay) = self.instance.save() {
+            ctx.request_repaint_after(delay);
+        }
     }
 
     fn ui(&mut self, ui: &mut Ui, frame: &mut Frame) {
+        self.dropped_archives_ui(ui);
+
         Panel::bottom(Id::new("status")).show_inside(ui, |ui| {
             self.status_bar(ui);
         });
 
+        if self.conflict_panel.open {
+            Panel::right(Id::new("conflicts"))
+                .resizable(true)
+                .show_inside(ui, |ui| {
+                    let selected = self.single_selected_mod();
+                    if let Some(task) = self.conflict_panel.show(ui, &self.instance, selected) {
+                        self.spawn_background_task("Finding file conflicts", task);
+                    }
+                });
+        }
+
         CentralPanel::default().show_inside(ui, |ui| {
             self.center_panel(ui, frame);
         });
@@ -170,12 +302,65 @@ impl App for ModManagerUi {
             .retain(|idx, window| window.update(ui, &self.instance, *idx).into());
         self.ongoing_mod_installs
             .retain_mut(|install| install.update(ui, &self.instance).into());
+        self.notifications.toasts(ui.ctx());
 
-        self.instance.save();
+        if let Some(delay) = self.instance.save() {
+            ui.ctx().request_repaint_after(delay);
+        }
     }
 }
 
 impl ModManagerUi {
+    fn handle_external_changes(&mut self) {
+        let Some(watcher) = &self.watcher else {
+            return;
+        };
+
+        let changes = watcher.changes(&self.instance);
+        if changes.data {
+            info!("instance data was modified by another process, reloading");
+            match self.instance.reload() {
+                Ok(()) => {
+                    // indices are no longer valid
+                    self.selection.clear();
+                    self.last_selected = None;
+                    self.open_mod_details.clear();
+                    self.collapsed_separators.clear();
+                    self.inline_rename = None;
+                }
+                Err(err) => error!("failed to reload instance data: {}", err),
+            }
+        }
+        if changes.mods {
+            self.disk_usage.lock().expect("lock is not poisoned").clear();
+        }
+    }
+
+    /// Returns the selected mod, if exactly one is selected.
+    fn single_selected_mod(&self) -> Option<ModIndex> {
+        if self.selection.len() != 1 {
+            return None;
+        }
+        let order_index = *self.selection.iter().next().expect("selection has one entry");
+        self.instance.mod_order().get(order_index).map(ModOrderEntry::mod_index)
+    }
+
+    /// Opens the details window of the specified mod, or brings it to the front if it's already open.
+    fn open_mod_details(&mut self, mod_index: ModIndex) {
+        let entry = self
+            .open_mod_details
+            .entry(mod_index)
+            .and_modify(ModDetailsWindow::raise);
+        if matches!(entry, Entry::Vacant(_)) {
+            match ModDetailsWindow::new(&self.instance, mod_index) {
+                Ok(window) => {
+                    entry.or_insert(window);
+                }
+                Err(err) => error!(?err, "failed to read mod directory"),
+            }
+        }
+    }
+
     fn mod_added(&mut self) {
         self.ongoing_mod_installs
             .iter_mut()
@@ -195,6 +380,26 @@ impl ModManagerUi {
             self.open_mod_details.insert(idx.saturating_sub(1u32), window);
         }
 
+        if self
+            .inline_rename
+            .as_ref()
+            .is_some_and(|rename| rename.mod_index >= removed_mod)
+        {
+            self.inline_rename = None;
+        }
+
+        self.collapsed_separators = mem::take(&mut self.collapsed_separators)
+            .into_iter()
+            .filter(|idx| *idx != removed_mod)
+            .map(|idx| {
+                if idx > removed_mod {
+                    idx.saturating_sub(1u32)
+                } else {
+                    idx
+                }
+            })
+            .collect();
+
         self.ongoing_mod_installs
             .iter_mut()
             .for_each(OngoingModInstallation::clear_mod_already_exists_state);
@@ -202,6 +407,29 @@ impl ModManagerUi {
 
     fn center_panel(&mut self, ui: &mut Ui, frame: &mut Frame) {
         ui.horizontal(|ui| {
+            self.profile_switcher(ui);
+            self.launch_button(ui);
+            ui.separator();
+            // mod files can't be moved or replaced while they're deployed
+            let deployed = self.game.is_running();
+
+            let undo_description = self.instance.undo_description().map(|d| format!("Undo: {d}"));
+            let undo = ui
+                .add_enabled(undo_description.is_some(), Button::new("Undo"))
+                .on_hover_text(undo_description.unwrap_or_default())
+                .clicked();
+            let redo_description = self.instance.redo_description().map(|d| format!("Redo: {d}"));
+            let redo = ui
+                .add_enabled(redo_description.is_some(), Button::new("Redo"))
+                .on_hover_text(redo_description.unwrap_or_default())
+                .clicked();
+            if undo {
+                self.undo();
+            } else if redo {
+                self.redo();
+            }
+            ui.separator();
+
             let response = ui.button("Add mod");
             Popup::menu(&response).show(|ui| {
                 if ui.button("Create empty mod").clicked() {
@@ -214,6 +442,25 @@ impl ModManagerUi {
                     self.create_new_mod_modal.open = true;
                 }
 
+                if ui.button("Rescan mods directory").clicked() {
+                    match self.instance.rescan() {
+                        Ok(added) if !added.is_empty() => {
+                            info!("found {} new mods", added.len());
+                            self.mod_added();
+                        }
+                        Ok(_) => {}
+                        Err(err) => error!("failed to rescan mods directory: {}", err),
+                    }
+                }
+
+                if ui.button("Create from folder").clicked() && self.directory_import.is_none() {
+                    self.directory_import = Some(DirectoryImport::new_with_folder_picker(frame));
+                }
+
+                if ui.button("Install from URL").clicked() && self.url_install.is_none() {
+                    self.url_install = Some(UrlInstall::default());
+                }
+
                 if ui.button("Install from file").clicked() {
                     self.ongoing_mod_installs
                         .push(OngoingModInstallation::new_with_file_picker(
@@ -223,25 +470,203 @@ impl ModManagerUi {
                 }
             });
 
-            if ui.button("Rename selected").clicked()
+            if ui.add_enabled(!deployed, Button::new("Reinstall selected")).clicked()
+                && let Some(selection) = self.get_single_selected_mod()
+            {
+                let mod_decl = self.instance.mod_by_order_index(selection);
+                if mod_decl.kind() == ModEntryKind::Mod {
+                    self.ongoing_mod_installs
+                        .push(OngoingModInstallation::new_reinstall_with_file_picker(
+                            frame,
+                            self.background_task_queue.clone(),
+                            mod_decl.name().clone(),
+                        ));
+                }
+            }
+
+            if ui
+                .add_enabled(!deployed, Button::new("Change install location"))
+                .clicked()
+                && let Some(selection) = self.get_single_selected_mod()
+            {
+                self.target_modal.open(&self.instance, selection);
+            }
+
+            if ui.add_enabled(!deployed, Button::new("Rename selected")).clicked()
                 && let Some(selection) = self.get_single_selected_mod()
             {
                 self.rename_mod_modal.open(&self.instance, selection);
             }
 
-            if ui.button("Remove selected").clicked() {
-                self.remove_selected_mods_modal.open(&self.instance, &self.selection);
+            if ui.add_enabled(!deployed, Button::new("Bulk rename selected")).clicked() && !self.selection.is_empty() {
+                self.bulk_rename_modal.open(&self.instance, &self.selection);
+            }
+
+            if ui.add_enabled(!deployed, Button::new("Remove selected")).clicked() {
+                self.remove_selected_mods();
+            }
+
+            if ui.add_enabled(!deployed, Button::new("Trash")).clicked() {
+                self.trash_modal.open(&self.instance);
             }
 
+            let response = ui.button("Move selected");
+            Popup::menu(&response).show(|ui| {
+                let new_selection = if ui.button("Up").clicked() {
+                    Some(self.instance.move_mods_up(&self.selection))
+                } else if ui.button("Down").clicked() {
+                    Some(self.instance.move_mods_down(&self.selection))
+                } else if ui.button("To top").clicked() {
+                    Some(self.instance.move_mods_to_top(&self.selection))
+                } else if ui.button("To bottom").clicked() {
+                    Some(self.instance.move_mods_to_bottom(&self.selection))
+                } else {
+                    None
+                };
+
+                if let Some(new_selection) = new_selection {
+                    self.selection = new_selection;
+                    self.last_selected = None;
+                }
+            });
+
+            let response = ui.button("Label selected");
+            Popup::menu(&response).show(|ui| {
+                for label in ModLabel::ALL {
+                    let text = RichText::new(format!("● {}", label.name())).color(label_color(label));
+                    if ui.button(text).clicked() {
+                        self.instance.set_mods_label(&self.selection, Some(label));
+                    }
+                }
+                ui.separator();
+                if ui.button("Clear label").clicked() {
+                    self.instance.set_mods_label(&self.selection, None);
+                }
+            });
+
             if ui.button("Toggle selected").clicked() {
-                for idx in self.selection.iter().copied() {
-                    self.instance.toggle_mod_enabled(idx);
+                self.instance.toggle_mods_enabled(&self.selection);
+            }
+
+            let response = ui.button("Sort");
+            Popup::menu(&response).show(|ui| {
+                let criterion = if ui.button("By name").clicked() {
+                    Some(SortCriterion::Name)
+                } else if ui.button("By category").clicked() {
+                    Some(SortCriterion::Category)
+                } else if ui.button("By install date").clicked() {
+                    Some(SortCriterion::InstallDate)
+                } else {
+                    None
+                };
+
+                if let Some(criterion) = criterion {
+                    let scope = if self.selection.len() > 1 {
+                        SortScope::Selection(&self.selection)
+                    } else {
+                        SortScope::All
+                    };
+                    self.instance.sort_mod_order(criterion, scope);
+                }
+            });
+
+            let response = ui.button("Copy mod list");
+            Popup::menu(&response).show(|ui| {
+                let format = if ui.button("As text").clicked() {
+                    Some(ModListFormat::Text)
+                } else if ui.button("As CSV").clicked() {
+                    Some(ModListFormat::Csv)
+                } else if ui.button("As Markdown").clicked() {
+                    Some(ModListFormat::Markdown)
+                } else {
+                    None
+                };
+
+                if let Some(format) = format {
+                    ui.ctx().copy_text(export_mod_list(&self.instance, format));
+                }
+            });
+
+            if ui.button("Import mod list").clicked() {
+                self.import_mod_list_modal.open = true;
+            }
+
+            ui.toggle_value(&mut self.conflict_panel.open, "Conflicts");
+            ui.menu_button("Columns", |ui| {
+                if self.settings.columns.checkboxes(ui) {
+                    self.save_settings();
                 }
+            });
+
+            if ui.button("Compute sizes").clicked() {
+                let cache = Arc::clone(&self.disk_usage);
+                let dirs: Vec<_> = self
+                    .instance
+                    .mods()
+                    .iter()
+                    .filter_map(|m| self.instance.mod_dir(m))
+                    .collect();
+                self.spawn_background_task(
+                    "Computing mod sizes",
+                    Box::new(move |status| {
+                        status
+                            .lock()
+                            .expect("lock is not poisoned")
+                            .push_str("Computing mod sizes");
+                        DiskUsageCache::refresh(&cache, dirs);
+                        None
+                    }),
+                );
+            }
+
+            if ui.button("Enable all").clicked() {
+                self.instance.enable_all_mods();
+            }
+
+            if ui.button("Disable all").clicked() {
+                self.instance.disable_all_mods();
+            }
+
+            if ui.button("Settings").clicked() {
+                self.settings_draft = Some(self.settings.clone());
             }
         });
 
         ui.separator();
 
+        ui.horizontal(|ui| {
+            let filter_edit = ui.add(
+                TextEdit::singleline(&mut self.mod_filter)
+                    .hint_text("Filter mods")
+                    .desired_width(200.0),
+            );
+            let cleared = ui
+                .add_enabled(!self.mod_filter.is_empty(), Button::new("✖").small())
+                .on_hover_text("Clear filter")
+                .clicked();
+            if cleared {
+                self.mod_filter.clear();
+            }
+            if filter_edit.changed() {
+                self.deselect_filtered_out_mods();
+            }
+        });
+
+        // don't keep sorting by a column that was hidden
+        if self
+            .table_sort
+            .is_some_and(|sort| !self.settings.columns.shows(sort.column))
+        {
+            self.table_sort = None;
+        }
+        if self.settings.columns.conflicts
+            && let Some(task) = self.conflict_counts.update(&self.instance)
+        {
+            self.spawn_background_task("Counting file conflicts", task);
+        }
+
+        self.keyboard_shortcuts(ui);
+
         ScrollArea::horizontal().show(ui, |ui| {
             self.table_ui(ui);
         });
@@ -249,18 +674,258 @@ impl ModManagerUi {
         self.create_empty_mod_modal(ui);
         self.rename_mod_modal(ui);
         self.remove_selected_mods_modal(ui);
+        self.bulk_rename_modal(ui);
+        self.target_modal(ui);
+        self.directory_import_ui(ui);
+        self.url_install_ui(ui);
+        self.trash_modal(ui);
+        self.import_mod_list_modal(ui);
+        self.profiles_modal(ui);
+        self.settings_modal(ui);
+    }
+
+    fn profile_switcher(&mut self, ui: &mut Ui) {
+        let current = self.instance.current_profile().clone();
+        let current_display_name = self
+            .instance
+            .profiles()
+            .get(&current)
+            .map_or(current.as_str(), |profile| profile.display_name())
+            .to_owned();
+
+        let mut switch_to = None;
+        ComboBox::from_id_salt("profile")
+            .selected_text(current_display_name)
+            .show_ui(ui, |ui| {
+                for (name, profile) in self.instance.profiles() {
+                    if ui
+                        .selectable_label(*name == current, profile.display_name())
+                        .on_hover_text(name.as_str())
+                        .clicked()
+                    {
+                        switch_to = Some(name.clone());
+                    }
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let text_edit = ui.add(
+                        TextEdit::singleline(&mut self.new_profile_name)
+                            .hint_text("New profile")
+                            .desired_width(120.0),
+                    );
+                    let entered = text_edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    let name_valid = !self.new_profile_name.trim().is_empty();
+                    let clicked = ui.add_enabled(name_valid, Button::new("Create")).clicked();
+                    if name_valid && (entered || clicked) {
+                        switch_to = Some(self.instance.add_profile(&self.new_profile_name));
+                        self.new_profile_name.clear();
+                    }
+                });
+
+                if ui.button("Manage profiles…").clicked() {
+                    self.profiles_modal.open = true;
+                }
+            });
+
+        if let Some(name) = switch_to
+            && name != current
+        {
+            self.instance.switch_to_profile(name);
+            self.current_profile_changed();
+        }
+    }
+
+    /// Resets state that refers to the previous profile's mod order.
+    fn current_profile_changed(&mut self) {
+        // the same order indices refer to different mods in another profile
+        self.selection.clear();
+        self.last_selected = None;
+    }
+
+    fn undo(&mut self) {
+        if self.instance.undo() {
+            // undoing invalidates order indices, and may switch to another profile
+            self.current_profile_changed();
+        }
+    }
+
+    fn redo(&mut self) {
+        if self.instance.redo() {
+            self.current_profile_changed();
+        }
+    }
+
+    /// Returns `true` if a modal is open, which should receive input instead of the mod table.
+    fn is_modal_open(&self) -> bool {
+        self.create_new_mod_modal.open
+            || self.rename_mod_modal.open
+            || self.remove_selected_mods_modal.is_open()
+            || !self.bulk_rename_modal.mods.is_empty()
+            || self.target_modal.mod_idx.is_some()
+            || self.trash_modal.entries.is_some()
+            || self.import_mod_list_modal.open
+            || self.profiles_modal.open
+            || self.settings_draft.is_some()
+            || self.directory_import.is_some()
+            || self.url_install.is_some()
+    }
+
+    /// Handles keyboard shortcuts that act on the mod table.
+    fn keyboard_shortcuts(&mut self, ui: &Ui) {
+        if self.is_modal_open() || ui.ctx().wants_keyboard_input() {
+            return;
+        }
+
+        let (redo, undo, delete, rename, toggle, select_all, move_up, move_down, deselect) = ui.input_mut(|i| {
+            (
+                // checked first, as Ctrl+Z would also match Ctrl+Shift+Z
+                i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::Z),
+                i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Delete),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::F2),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Space),
+                i.consume_key(egui::Modifiers::COMMAND, egui::Key::A),
+                i.consume_key(egui::Modifiers::COMMAND, egui::Key::ArrowUp),
+                i.consume_key(egui::Modifiers::COMMAND, egui::Key::ArrowDown),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+            )
+        });
+
+        let deployed = self.game.is_running();
+        if redo {
+            self.redo();
+        } else if undo {
+            self.undo();
+        } else if delete && !deployed && !self.selection.is_empty() {
+            self.remove_selected_mods();
+        } else if rename
+            && !deployed
+            && let Some(selection) = self.get_single_selected_mod()
+        {
+            self.rename_mod_modal.open(&self.instance, selection);
+        } else if toggle && !self.selection.is_empty() {
+            // separators can't be enabled
+            let mods: HashSet<ModOrderIndex> = self
+                .selection
+                .iter()
+                .copied()
+                .filter(|idx| self.instance.mod_by_order_index(*idx).kind() == ModEntryKind::Mod)
+                .collect();
+            self.instance.toggle_mods_enabled(&mods);
+        } else if select_all {
+            self.selection = self.visible_rows().into_iter().collect();
+            self.last_selected = None;
+        } else if (move_up || move_down) && !self.selection.is_empty() && self.table_sort.is_none() {
+            self.selection = if move_up {
+                self.instance.move_mods_up(&self.selection)
+            } else {
+                self.instance.move_mods_down(&self.selection)
+            };
+            self.last_selected = None;
+        } else if deselect {
+            self.selection.clear();
+            self.last_selected = None;
+        }
+    }
+
+    /// Returns the mod order indices of the rows shown in the table, in the order they're shown.
+    fn visible_rows(&self) -> Vec<ModOrderIndex> {
+        let filter = self.mod_filter.trim().to_lowercase();
+        let mod_order = self.instance.mod_order();
+        let mods = self.instance.mods();
+        let mut in_collapsed_group = false;
+        let mut rows: Vec<ModOrderIndex> = mod_order
+            .iter_enumerated()
+            .filter(|(_, entry)| {
+                let mod_decl = &mods[entry.mod_index()];
+                if mod_decl.kind() == ModEntryKind::Separator {
+                    // groups are only contiguous when shown in priority order
+                    in_collapsed_group =
+                        self.table_sort.is_none() && self.collapsed_separators.contains(&entry.mod_index());
+                } else if in_collapsed_group {
+                    return false;
+                }
+                filter.is_empty() || mod_decl.name().to_lowercase().contains(&filter)
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+
+        if let Some(sort) = self.table_sort {
+            let mod_decl = |idx: &ModOrderIndex| &mods[mod_order[*idx].mod_index()];
+            // the sort is stable, so rows that compare equal stay in priority order
+            match sort.column {
+                SortColumn::Name => rows.sort_by(|a, b| {
+                    sort.compare(Some(mod_decl(a).name()), Some(mod_decl(b).name()), |a, b| {
+                        name_ord(a, b)
+                    })
+                }),
+                SortColumn::Version => rows
+                    .sort_by(|a, b| sort.compare(mod_decl(a).version(), mod_decl(b).version(), |a, b| name_ord(a, b))),
+                SortColumn::Category => rows.sort_by(|a, b| {
+                    sort.compare(mod_decl(a).category(), mod_decl(b).category(), |a, b| name_ord(a, b))
+                }),
+                SortColumn::Size => {
+                    let disk_usage = self.disk_usage.lock().expect("lock is not poisoned");
+                    let mut keyed: Vec<_> = rows
+                        .into_iter()
+                        .map(|idx| {
+                            let size = self
+                                .instance
+                                .mod_dir(mod_decl(&idx))
+                                .and_then(|dir| disk_usage.get(&dir))
+                                .map(|usage| usage.size);
+                            (size, idx)
+                        })
+                        .collect();
+                    keyed.sort_by(|(a, _), (b, _)| sort.compare(*a, *b, Ord::cmp));
+                    rows = keyed.into_iter().map(|(_, idx)| idx).collect();
+                }
+                SortColumn::Conflicts => rows.sort_by(|a, b| {
+                    let count = |idx: &ModOrderIndex| {
+                        self.conflict_counts
+                            .get(mod_order[*idx].mod_index())
+                            .map(|count| count.total())
+                    };
+                    sort.compare(count(a), count(b), Ord::cmp)
+                }),
+            }
+        }
+        rows
+    }
+
+    /// Removes mods hidden by the filter or in collapsed groups from the selection,
+    /// so that actions don't apply to mods that aren't shown.
+    fn deselect_filtered_out_mods(&mut self) {
+        let visible: HashSet<ModOrderIndex> = self.visible_rows().into_iter().collect();
+        self.selection.retain(|idx| visible.contains(idx));
+        if self.last_selected.is_some_and(|idx| !self.selection.contains(&idx)) {
+            self.last_selected = None;
+        }
     }
 
     fn table_ui(&mut self, ui: &mut Ui) {
         let (modifiers, pointer) = ui.input(|input| (input.modifiers, input.pointer.interact_pos()));
+        let visible = self.visible_rows();
+
+        let columns = self.settings.columns;
+        // the mod order can't be rearranged while the rows are shown in a different order
+        let dnd_enabled = self.table_sort.is_none();
 
         let available_height = ui.available_height();
-        let table = TableBuilder::new(ui)
+        let mut table = TableBuilder::new(ui)
+            .id_salt(("mods", columns))
             .striped(true)
             .resizable(true)
             .cell_layout(Layout::left_to_right(Align::Center))
             .column(Column::exact(18.0))
-            .column(Column::remainder().at_least(40.0).clip(true).resizable(true))
+            .column(Column::remainder().at_least(40.0).clip(true).resizable(true));
+        for shown in [columns.version, columns.category, columns.size, columns.conflicts] {
+            if shown {
+                table = table.column(Column::auto().clip(true).resizable(true));
+            }
+        }
+        let table = table
             .column(Column::auto())
             .min_scrolled_height(0.0)
             .max_scroll_height(available_height)
@@ -270,6 +935,10 @@ impl ModManagerUi {
         #[derive(Copy, Clone)]
         struct ModDnDPayload;
 
+        let separator_fill = ui.visuals().widgets.inactive.weak_bg_fill;
+        let deployed = self.game.is_running();
+        let mut separator_to_toggle = None;
+        let mut inline_rename_result = None;
         let mut dnd_hover_line = None;
         let mut dnd_drop_index = None;
         table
@@ -277,25 +946,61 @@ impl ModManagerUi {
                 header.col(|ui| {
                     ui.strong("Enabled");
                 });
+                let sort = &mut self.table_sort;
+                header.col(|ui| sort_header(ui, sort, SortColumn::Name, "Mod name"));
+                if columns.version {
+                    header.col(|ui| sort_header(ui, sort, SortColumn::Version, "Version"));
+                }
+                if columns.category {
+                    header.col(|ui| sort_header(ui, sort, SortColumn::Category, "Category"));
+                }
+                if columns.size {
+                    header.col(|ui| sort_header(ui, sort, SortColumn::Size, "Size"));
+                }
+                if columns.conflicts {
+                    header.col(|ui| sort_header(ui, sort, SortColumn::Conflicts, "Conflicts"));
+                }
                 header.col(|ui| {
-                    ui.strong("Mod name");
-                });
-                header.col(|ui| {
-                    ui.strong("Priority");
+                    let text = RichText::new("Priority").strong();
+                    if ui.add(Button::new(text).frame(false)).clicked() {
+                        *sort = None;
+                    }
                 });
             })
             .body(|body| {
                 let mut entry_to_toggle = None;
 
-                let total_rows = self.instance.mod_order().len();
-                body.rows(18.0, total_rows, |mut row| {
-                    let row_index = ModOrderIndex::from(row.index());
+                body.rows(18.0, visible.len(), |mut row| {
+                    let row_index = visible[row.index()];
                     let order_entry = self.instance.mod_order()[row_index];
                     let mod_decl = &self.instance.mods()[order_entry.mod_index()];
 
-                    row.set_selected(self.selection.contains(&row_index));
+                    let selected = self.selection.contains(&row_index);
+                    row.set_selected(selected);
 
-                    if mod_decl.kind() == ModEntryKind::Mod {
+                    // separators are drawn as a band across the row, in the color of their label,
+                    // unless selected, so that the selection stays visible
+                    let is_separator = mod_decl.kind() == ModEntryKind::Separator;
+                    let band = (is_separator && !selected).then(|| {
+                        mod_decl
+                            .label()
+                            .map_or(separator_fill, |label| label_color(label).gamma_multiply(0.35))
+                    });
+                    let collapsed = is_separator && self.collapsed_separators.contains(&order_entry.mod_index());
+
+                    if is_separator {
+                        row.col(|ui| {
+                            paint_band(ui, band);
+                            let (icon, hover) = if collapsed {
+                                ("⏵", "Expand")
+                            } else {
+                                ("⏷", "Collapse")
+                            };
+                            if ui.add(Button::new(icon).frame(false)).on_hover_text(hover).clicked() {
+                                separator_to_toggle = Some(order_entry.mod_index());
+                            }
+                        });
+                    } else {
                         let mut enabled = order_entry.enabled;
                         row.col(|ui| {
                             ui.checkbox(&mut enabled, ());
@@ -303,37 +1008,124 @@ impl ModManagerUi {
                         if enabled != order_entry.enabled {
                             entry_to_toggle = Some(row_index);
                         }
-                    } else {
-                        row.col(|_| {});
                     }
 
-                    row.col(|ui| {
+                    let (name_rect, _) = row.col(|ui| {
+                        paint_band(ui, band);
+                        if let Some(rename) = &mut self.inline_rename
+                            && rename.mod_index == order_entry.mod_index()
+                        {
+                            inline_rename_result = rename.show(ui, &self.instance);
+                            return;
+                        }
+
                         let name = mod_decl.name().as_str();
-                        if mod_decl.kind() == ModEntryKind::Separator {
+                        if is_separator {
                             ui.strong(name);
+                            let group_len = self
+                                .instance
+                                .mod_order()
+                                .iter()
+                                .skip(usize::from(row_index) + 1)
+                                .take_while(|entry| {
+                                    self.instance.mods()[entry.mod_index()].kind() != ModEntryKind::Separator
+                                })
+                                .count();
+                            ui.weak(if collapsed {
+                                format!("({group_len} hidden)")
+                            } else {
+                                format!("({group_len})")
+                            });
                         } else {
+                            if let Some(label) = mod_decl.label() {
+                                ui.colored_label(label_color(label), "●").on_hover_text(label.name());
+                            }
                             ui.label(name);
                         }
+
+                        if let Some(note) = self.instance.mod_note(order_entry.mod_index()) {
+                            ui.weak("🗒").on_hover_text(note.as_str());
+                        }
                     });
 
+                    if columns.version {
+                        row.col(|ui| {
+                            paint_band(ui, band);
+                            if let Some(version) = mod_decl.version() {
+                                ui.label(version.as_str());
+                            }
+                        });
+                    }
+
+                    if columns.category {
+                        row.col(|ui| {
+                            paint_band(ui, band);
+                            if let Some(category) = mod_decl.category() {
+                                ui.label(category.as_str());
+                            }
+                        });
+                    }
+
+                    if columns.size {
+                        row.col(|ui| {
+                            paint_band(ui, band);
+                            let usage = self
+                                .instance
+                                .mod_dir(mod_decl)
+                                .and_then(|dir| self.disk_usage.lock().expect("lock is not poisoned").get(&dir));
+                            if let Some(usage) = usage {
+                                ui.label(format_size(usage.size))
+                                    .on_hover_text(format!("{} files", usage.files));
+                            }
+                        });
+                    }
+
+                    if columns.conflicts {
+                        row.col(|ui| {
+                            paint_band(ui, band);
+                            if mod_decl.kind() != ModEntryKind::Mod {
+                                return;
+                            }
+                            match self.conflict_counts.get(order_entry.mod_index()) {
+                                Some(count) if count.total() > 0 => {
+                                    ui.label(format!("+{} −{}", count.winning, count.losing))
+                                        .on_hover_text(format!(
+                                            "Overrides {} files of other mods, and {} of its files are overridden",
+                                            count.winning, count.losing
+                                        ));
+                                }
+                                Some(_) => {}
+                                None => {
+                                    ui.spinner();
+                                }
+                            }
+                        });
+                    }
+
                     row.col(|ui| {
+                        paint_band(ui, band);
                         ui.label(row_index.to_string());
                     });
 
                     let response = row.response();
                     if response.clicked() {
                         if modifiers.shift {
-                            if let Some(last) = self.last_selected {
+                            let last_position = self
+                                .last_selected
+                                .and_then(|last| visible.iter().position(|idx| *idx == last));
+                            if let Some(last_position) = last_position {
                                 if !modifiers.ctrl {
                                     self.selection.clear();
                                 }
 
-                                let range = if row_index < last {
-                                    row_index.inclusive_range_to(last)
+                                // select the rows in between as they're shown, skipping those hidden by the filter
+                                let position = row.index();
+                                let range = if position < last_position {
+                                    &visible[position..=last_position]
                                 } else {
-                                    last.inclusive_range_to(row_index)
+                                    &visible[last_position..=position]
                                 };
-                                self.selection.extend(range);
+                                self.selection.extend(range.iter().copied());
                                 self.last_selected = Some(row_index);
                             } else {
                                 self.selection.insert(row_index);
@@ -354,19 +1146,23 @@ impl ModManagerUi {
                         }
                     }
 
-                    if response.double_clicked() && mod_decl.kind() == ModEntryKind::Mod {
-                        let entry = self
-                            .open_mod_details
-                            .entry(order_entry.mod_index())
-                            .and_modify(ModDetailsWindow::raise);
-                        if matches!(entry, Entry::Vacant(_)) {
-                            match ModDetailsWindow::new(&self.instance, order_entry.mod_index()) {
-                                Ok(window) => {
-                                    entry.or_insert(window);
-                                }
-                                Err(err) => error!(?err, "failed to spawn thread"),
-                            }
+                    // double-clicking the name renames the mod in place, unless its files are deployed
+                    let rename_clicked =
+                        response.double_clicked() && !deployed && pointer.is_some_and(|pos| name_rect.contains(pos));
+                    if rename_clicked {
+                        self.inline_rename = Some(InlineRename::new(order_entry.mod_index(), mod_decl.name()));
+                    } else if is_separator && response.double_clicked() {
+                        separator_to_toggle = Some(order_entry.mod_index());
+                    }
+                    if mod_decl.kind() == ModEntryKind::Mod {
+                        if response.double_clicked() && !rename_clicked {
+                            self.open_mod_details(order_entry.mod_index());
                         }
+                        response.context_menu(|ui| {
+                            if ui.button("Browse files").clicked() {
+                                self.open_mod_details(order_entry.mod_index());
+                            }
+                        });
                     }
 
                     if response.drag_started() && !self.selection.contains(&row_index) {
@@ -375,7 +1171,9 @@ impl ModManagerUi {
                         self.last_selected = Some(row_index);
                     }
 
-                    response.dnd_set_drag_payload(ModDnDPayload);
+                    if dnd_enabled {
+                        response.dnd_set_drag_payload(ModDnDPayload);
+                    }
 
                     if response.dnd_hover_payload::<ModDnDPayload>().is_some()
                         && let Some(pointer) = pointer
@@ -406,19 +1204,41 @@ impl ModManagerUi {
                 }
             });
 
+        if let Some(accepted) = inline_rename_result
+            && let Some(rename) = self.inline_rename.take()
+            && accepted
+            && rename.input != self.instance.mods()[rename.mod_index].name().as_str()
+        {
+            self.rename_mod(rename.mod_index, &rename.input);
+        }
+
+        if let Some(separator) = separator_to_toggle {
+            if !self.collapsed_separators.remove(&separator) {
+                self.collapsed_separators.insert(separator);
+            }
+            self.deselect_filtered_out_mods();
+        }
+
         if let Some((range, y)) = dnd_hover_line {
             const STROKE: Stroke = Stroke { width: 2.0, color: Color32::WHITE };
             ui.painter().hline(range, y, STROKE);
         }
 
         if let Some(drop_index) = dnd_drop_index {
-            let selection_len = self.selection.len();
-            let drop_index = self.instance.move_mods(&self.selection, drop_index);
+            // Dragging a lone separator takes its group along, unless shift is held.
+            let moved = match self.get_single_selected_mod() {
+                Some(idx)
+                    if self.instance.mod_by_order_index(idx).kind() == ModEntryKind::Separator
+                        && !ui.input(|i| i.modifiers.shift) =>
+                {
+                    self.instance.move_separator_group(idx, drop_index)
+                }
+                _ => self.instance.move_mods(&self.selection, drop_index),
+            };
 
             // indices are no longer valid
             self.selection.clear();
-            self.selection
-                .extend(drop_index.inclusive_range_to(drop_index.saturating_add(selection_len).saturating_sub(1u32)));
+            self.selection.extend(moved.into_values());
         }
     }
 
@@ -437,6 +1257,7 @@ impl ModManagerUi {
             ui.label("Name:");
             let text_exit = ui.text_edit_singleline(&mut self.create_new_mod_modal.input);
             let mut accepted = text_exit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            name_taken_hint(ui, &self.instance, &mut self.create_new_mod_modal.input);
 
             Sides::new().show(
                 ui,
@@ -498,6 +1319,9 @@ impl ModManagerUi {
             });
             let text_exit = ui.text_edit_singleline(&mut self.rename_mod_modal.input);
             let mut accepted = text_exit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if self.rename_mod_modal.input != mod_decl.name().as_str() {
+                name_taken_hint(ui, &self.instance, &mut self.rename_mod_modal.input);
+            }
 
             Sides::new().show(
                 ui,
@@ -514,14 +1338,8 @@ impl ModManagerUi {
             );
 
             if accepted && ModDeclaration::is_name_valid(&self.rename_mod_modal.input) {
-                if let Err(err) = self.instance.rename_mod(mod_idx, &self.rename_mod_modal.input) {
-                    error!("failed to rename mod to '{}': {}", &self.rename_mod_modal.input, err);
-                }
-
-                self.ongoing_mod_installs
-                    .iter_mut()
-                    .for_each(OngoingModInstallation::clear_mod_already_exists_state);
-
+                let name = self.rename_mod_modal.input.clone();
+                self.rename_mod(mod_idx, &name);
                 ui.close();
             }
         });
@@ -549,44 +1367,9 @@ impl ModManagerUi {
                         ui.close();
                     }
 
-                    if ui.button("Delete").clicked() {
-                        // Sort indices and iterate backwards so that they can be removed in order without being invalidated.
-                        self.remove_selected_mods_modal.selected.sort_unstable();
-                        let paths: Vec<_> = self
-                            .remove_selected_mods_modal
-                            .selected
-                            .iter()
-                            .rev()
-                            .filter_map(|idx| self.instance.remove_mod(*idx))
-                            .collect();
-                        while let Some(idx) = self.remove_selected_mods_modal.selected.pop() {
-                            self.mod_removed(idx);
-                        }
-
-                        let task: BackgroundTask = Box::new(move |status| {
-                            for path in paths {
-                                {
-                                    let mut s = status.lock().expect("lock is not poisoned");
-                                    s.clear();
-                                    let _ = write!(
-                                        s,
-                                        "Deleting mod {}",
-                                        path.file_name().unwrap_or(OsStr::new("?")).display()
-                                    );
-                                }
-
-                                info!("removing mod directory '{}'", path.display());
-                                if let Err(err) = fs::remove_dir_all(&path) {
-                                    error!("failed to delete '{}': {}", path.display(), err);
-                                }
-                            }
-
-                            None
-                        });
-                        self.spawn_background_task(task);
-                        self.selection.clear();
-                        self.last_selected = None;
-
+                    if ui.button("Move to trash").clicked() {
+                        let mods = mem::take(&mut self.remove_selected_mods_modal.selected);
+                        self.trash_mods(mods);
                         ui.close();
                     }
                 },
@@ -598,28 +1381,531 @@ impl ModManagerUi {
         }
     }
 
-    fn status_bar(&mut self, ui: &mut Ui) {
-        let status = self.background_task_status.lock().expect("lock is not poisoned");
-        ui.label(status.as_str());
+    fn rename_mod(&mut self, mod_idx: ModIndex, name: &str) {
+        if let Err(err) = self.instance.rename_mod(mod_idx, name) {
+            error!("failed to rename mod to '{}': {}", name, err);
+        }
+
+        self.ongoing_mod_installs
+            .iter_mut()
+            .for_each(OngoingModInstallation::clear_mod_already_exists_state);
     }
 
-    fn spawn_background_task(&self, task: BackgroundTask) {
-        if self.background_task_queue.send(task).is_err() {
-            error!("background task panicked");
+    /// Moves the selected mods to the trash, asking first unless disabled in the settings.
+    fn remove_selected_mods(&mut self) {
+        if self.settings.confirm_removal {
+            self.remove_selected_mods_modal.open(&self.instance, &self.selection);
+        } else {
+            let mods = self
+                .selection
+                .iter()
+                .map(|idx| self.instance.mod_order()[*idx].mod_index())
+                .collect();
+            self.trash_mods(mods);
         }
     }
 
-    fn get_single_selected_mod(&self) -> Option<ModOrderIndex> {
-        if self.selection.len() != 1 {
-            return None;
+    fn trash_mods(&mut self, mut mods: Vec<ModIndex>) {
+        // Sort indices and iterate backwards so that they can be removed in order without being invalidated.
+        mods.sort_unstable();
+        while let Some(idx) = mods.pop() {
+            match self.instance.trash_mod(idx) {
+                Ok(()) => self.mod_removed(idx),
+                Err(err) => error!(
+                    "failed to move mod '{}' to the trash: {}",
+                    self.instance.mods()[idx].name(),
+                    err
+                ),
+            }
         }
-        self.selection.iter().next().copied()
+
+        self.selection.clear();
+        self.last_selected = None;
     }
-}
 
-#[derive(Debug, Default)]
-struct CreateNewModModal {
-    open: bool,
+    fn target_modal(&mut self, ui: &mut Ui) {
+        let Some(mod_idx) = self.target_modal.mod_idx else {
+            return;
+        };
+
+        let modal = Modal::new(Id::new("mod_target")).show(ui.ctx(), |ui| {
+            ui.set_width(350.0);
+            ui.heading("Change install location");
+            ui.horizontal(|ui| {
+                ui.label("Install");
+                ui.label(self.instance.mods()[mod_idx].name().as_str());
+                ui.label("into:");
+            });
+
+            let input = &mut self.target_modal.input;
+            ui.horizontal(|ui| {
+                for (text, preset) in [
+                    ("Game root", ""),
+                    ("Data", "Data"),
+                    ("SKSE plugins", "Data/SKSE/Plugins"),
+                ] {
+                    if ui.button(text).clicked() {
+                        input.clear();
+                        input.push_str(preset);
+                    }
+                }
+            });
+            ui.text_edit_singleline(input)
+                .on_hover_text("Path relative to the game directory. Leave empty for the game directory itself.");
+
+            let trimmed = input.trim().trim_matches('/');
+            let valid = trimmed.is_empty() || ModDeclaration::is_target_valid(trimmed);
+            if !valid {
+                ui.colored_label(ui.visuals().error_fg_color, "Invalid path.");
+            }
+
+            Sides::new().show(
+                ui,
+                |_| (),
+                |ui| {
+                    if ui.button("Cancel").clicked() {
+                        ui.close();
+                    }
+
+                    if ui.add_enabled(valid, Button::new("OK")).clicked() {
+                        if let Err(err) = self.instance.set_mod_target(mod_idx, Some(&self.target_modal.input)) {
+                            error!("failed to change install location: {}", err);
+                        }
+                        ui.close();
+                    }
+                },
+            );
+        });
+
+        if modal.should_close() {
+            self.target_modal = TargetModal::default();
+        }
+    }
+
+    fn bulk_rename_modal(&mut self, ui: &mut Ui) {
+        if self.bulk_rename_modal.mods.is_empty() {
+            return;
+        }
+
+        let modal = Modal::new(Id::new("bulk_rename")).show(ui.ctx(), |ui| {
+            ui.set_width(500.0);
+            ui.heading("Rename mods");
+
+            let state = &mut self.bulk_rename_modal;
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut state.affix, false, "Find and replace");
+                ui.radio_value(&mut state.affix, true, "Add prefix/suffix");
+            });
+            Grid::new("bulk_rename_pattern").num_columns(2).show(ui, |ui| {
+                if state.affix {
+                    ui.label("Prefix:");
+                    ui.text_edit_singleline(&mut state.first);
+                    ui.end_row();
+                    ui.label("Suffix:");
+                    ui.text_edit_singleline(&mut state.second);
+                } else {
+                    ui.label("Find:");
+                    ui.text_edit_singleline(&mut state.first);
+                    ui.end_row();
+                    ui.label("Replace with:");
+                    ui.text_edit_singleline(&mut state.second);
+                }
+                ui.end_row();
+            });
+            ui.add_space(4.0);
+
+            let pattern = state.pattern();
+            let preview = self.instance.preview_bulk_rename(&state.mods, pattern);
+            let has_problems = preview.iter().any(|entry| entry.problem.is_some());
+
+            const TEXT_STYLE: TextStyle = TextStyle::Body;
+            let row_height = ui.text_style_height(&TEXT_STYLE);
+            ScrollArea::both()
+                .max_height(300.0)
+                .show_rows(ui, row_height, preview.len(), |ui, rows| {
+                    ui.style_mut().wrap_mode = Some(TextWrapMode::Extend);
+
+                    for entry in preview.get(rows).expect("range is within bounds") {
+                        let old_name = self.instance.mods()[entry.idx].name();
+                        let text = format!("{old_name} → {}", entry.new_name);
+                        match entry.problem {
+                            None => ui.label(text),
+                            Some(BulkRenameProblem::InvalidName) => {
+                                ui.colored_label(ui.visuals().error_fg_color, format!("{text} (invalid name)"))
+                            }
+                            Some(BulkRenameProblem::Collision) => {
+                                ui.colored_label(ui.visuals().error_fg_color, format!("{text} (name already in use)"))
+                            }
+                        };
+                    }
+                });
+
+            Sides::new().show(
+                ui,
+                |_| (),
+                |ui| {
+                    if ui.button("Cancel").clicked() {
+                        ui.close();
+                    }
+
+                    if ui.add_enabled(!has_problems, Button::new("Rename")).clicked() {
+                        let state = &self.bulk_rename_modal;
+                        match self.instance.bulk_rename(&state.mods, state.pattern()) {
+                            Ok(()) => ui.close(),
+                            Err(err) => error!("failed to rename mods: {}", err),
+                        }
+                    }
+                },
+            );
+        });
+
+        if modal.should_close() {
+            self.bulk_rename_modal = BulkRenameModal::default();
+        }
+    }
+
+    fn trash_modal(&mut self, ui: &mut Ui) {
+        let Some(mut entries) = self.trash_modal.entries.take() else {
+            return;
+        };
+
+        let mut restore = None;
+        let mut purge = None;
+        let mut purge_all = false;
+
+        let modal = Modal::new(Id::new("trash")).show(ui.ctx(), |ui| {
+            ui.set_width(400.0);
+            ui.heading("Trash");
+
+            if entries.is_empty() {
+                ui.label("The trash is empty.");
+            } else {
+                ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    for (i, entry) in entries.iter().enumerate() {
+                        Sides::new().show(
+                            ui,
+                            |ui| {
+                                ui.label(entry.name().as_str());
+                            },
+                            |ui| {
+                                if ui.button("Delete").clicked() {
+                                    purge = Some(i);
+                                }
+                                if ui.button("Restore").clicked() {
+                                    restore = Some(i);
+                                }
+                            },
+                        );
+                    }
+                });
+            }
+
+            ui.add_space(4.0);
+            Sides::new().show(
+                ui,
+                |_| (),
+                |ui| {
+                    if ui.button("Close").clicked() {
+                        ui.close();
+                    }
+
+                    if ui
+                        .add_enabled(!entries.is_empty(), Button::new("Empty trash"))
+                        .clicked()
+                    {
+                        purge_all = true;
+                    }
+                },
+            );
+        });
+
+        if let Some(i) = restore {
+            let entry = entries.remove(i);
+            let name = entry.name().clone();
+            match self.instance.restore_from_trash(entry) {
+                Ok(_) => self.mod_added(),
+                Err(err) => error!("failed to restore mod '{}' from the trash: {}", name, err),
+            }
+        } else if let Some(i) = purge {
+            let entry = entries.remove(i);
+            let name = format!("Deleting '{}' from the trash", entry.name());
+            self.spawn_background_task(name, purge_trash_entries(vec![entry]));
+        } else if purge_all {
+            self.spawn_background_task("Emptying the trash", purge_trash_entries(mem::take(&mut entries)));
+        }
+
+        if !modal.should_close() {
+            self.trash_modal.entries = Some(entries);
+        }
+    }
+
+    fn import_mod_list_modal(&mut self, ui: &mut Ui) {
+        if !self.import_mod_list_modal.open {
+            return;
+        }
+
+        let modal = Modal::new(Id::new("import_mod_list")).show(ui.ctx(), |ui| {
+            ui.set_width(500.0);
+            ui.heading("Import mod list");
+            ui.label("Paste a mod list exported as text, CSV or Markdown:");
+
+            ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                ui.add(
+                    TextEdit::multiline(&mut self.import_mod_list_modal.input)
+                        .code_editor()
+                        .desired_width(f32::INFINITY),
+                );
+            });
+
+            if let Some(message) = &self.import_mod_list_modal.message {
+                ui.label(message.as_str());
+            }
+
+            Sides::new().show(
+                ui,
+                |_| (),
+                |ui| {
+                    if ui.button("Close").clicked() {
+                        ui.close();
+                    }
+
+                    if ui.button("Apply").clicked() {
+                        let input = &self.import_mod_list_modal.input;
+                        let message = match parse_mod_list(input, ModListFormat::detect(input)) {
+                            Ok(entries) => {
+                                let report = self.instance.apply_mod_list(&entries);
+                                self.selection.clear();
+                                self.last_selected = None;
+
+                                let mut message =
+                                    format!("Applied {} entries.", entries.len() - report.unmatched.len());
+                                if !report.unmatched.is_empty() {
+                                    let _ = write!(message, "\nNot found: {}", report.unmatched.join(", "));
+                                }
+                                if !report.unlisted.is_empty() {
+                                    let _ = write!(
+                                        message,
+                                        "\n{} mods not in the list were disabled.",
+                                        report.unlisted.len()
+                                    );
+                                }
+                                message
+                            }
+                            Err(err) => format!("Failed to parse mod list: {err}"),
+                        };
+                        self.import_mod_list_modal.message = Some(message);
+                    }
+                },
+            );
+        });
+
+        if modal.should_close() {
+            self.import_mod_list_modal = ImportModListModal::default();
+        }
+    }
+
+    fn profiles_modal(&mut self, ui: &mut Ui) {
+        if !self.profiles_modal.open {
+            return;
+        }
+
+        let previous_profile = self.instance.current_profile().clone();
+        let mut action = None;
+
+        let modal = Modal::new(Id::new("profiles")).show(ui.ctx(), |ui| {
+            ui.set_width(500.0);
+            ui.heading("Profiles");
+
+            let state = &mut self.profiles_modal;
+            let mods = self.instance.mods();
+            let mod_count = mods.iter().filter(|m| m.kind() == ModEntryKind::Mod).count();
+            let can_delete = self.instance.profiles().len() > 1;
+            let confirm_deletion = self.settings.confirm_profile_deletion;
+
+            ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                for (name, profile) in self.instance.profiles() {
+                    let enabled_count = profile
+                        .mod_order
+                        .iter()
+                        .filter(|entry| entry.enabled && mods[entry.mod_index()].kind() == ModEntryKind::Mod)
+                        .count();
+                    let is_current = *name == previous_profile;
+                    let is_renaming = state.renaming.as_ref().is_some_and(|(renamed, _)| renamed == name);
+                    let mut submit_rename = false;
+                    let mut start_rename = false;
+
+                    Sides::new().show(
+                        ui,
+                        |ui| {
+                            if let Some((_, input)) = &mut state.renaming
+                                && is_renaming
+                            {
+                                let text_edit = ui.add(TextEdit::singleline(input).desired_width(160.0));
+                                if text_edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                                    submit_rename = true;
+                                }
+                            } else {
+                                let mut text = RichText::new(profile.display_name());
+                                if is_current {
+                                    text = text.strong();
+                                }
+                                ui.label(text).on_hover_text(name.as_str());
+                            }
+                            ui.weak(format!("{enabled_count} of {mod_count} mods enabled"));
+                        },
+                        |ui| {
+                            if state.confirm_delete.as_ref() == Some(name) {
+                                if ui.button("Cancel").clicked() {
+                                    state.confirm_delete = None;
+                                }
+                                if ui.button("Confirm").clicked() {
+                                    action = Some(ProfileAction::Delete(name.clone()));
+                                }
+                                return;
+                            }
+
+                            if ui.add_enabled(can_delete, Button::new("Delete")).clicked() {
+                                if confirm_deletion {
+                                    state.confirm_delete = Some(name.clone());
+                                } else {
+                                    action = Some(ProfileAction::Delete(name.clone()));
+                                }
+                            }
+                            if ui.button("Duplicate").clicked() {
+                                action = Some(ProfileAction::Duplicate(name.clone()));
+                            }
+                            if is_renaming {
+                                if ui.button("Save").clicked() {
+                                    action = Some(ProfileAction::Rename);
+                                }
+                            } else if ui.button("Rename").clicked() {
+                                start_rename = true;
+                            }
+                            if ui.add_enabled(!is_current, Button::new("Switch")).clicked() {
+                                action = Some(ProfileAction::Switch(name.clone()));
+                            }
+                        },
+                    );
+
+                    if submit_rename {
+                        action = Some(ProfileAction::Rename);
+                    }
+                    if start_rename {
+                        state.renaming = Some((name.clone(), profile.display_name().to_owned()));
+                    }
+                }
+            });
+
+            ui.add_space(4.0);
+            ui.horizontal(|ui| {
+                let text_edit = ui.add(
+                    TextEdit::singleline(&mut state.new_name)
+                        .hint_text("New profile")
+                        .desired_width(160.0),
+                );
+                let entered = text_edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                let name_valid = !state.new_name.trim().is_empty();
+                let clicked = ui.add_enabled(name_valid, Button::new("Create")).clicked();
+                if name_valid && (entered || clicked) {
+                    action = Some(ProfileAction::Create);
+                }
+            });
+
+            ui.add_space(4.0);
+            Sides::new().show(
+                ui,
+                |_| (),
+                |ui| {
+                    if ui.button("Close").clicked() {
+                        ui.close();
+                    }
+                },
+            );
+        });
+
+        match action {
+            Some(ProfileAction::Create) => {
+                let _ = self.instance.add_profile(&self.profiles_modal.new_name);
+                self.profiles_modal.new_name.clear();
+            }
+            Some(ProfileAction::Delete(name)) => {
+                if let Err(err) = self.instance.remove_profile(&name) {
+                    error!("failed to remove profile '{}': {}", name, err);
+                }
+                self.profiles_modal.confirm_delete = None;
+            }
+            Some(ProfileAction::Duplicate(name)) => {
+                if let Some(profile) = self.instance.profiles().get(&name) {
+                    let display_name = format!("{} (copy)", profile.display_name());
+                    let _ = self.instance.duplicate_profile(&name, &display_name);
+                }
+            }
+            Some(ProfileAction::Rename) => {
+                if let Some((name, input)) = self.profiles_modal.renaming.take()
+                    && !input.trim().is_empty()
+                {
+                    self.instance.rename_profile(&name, &input);
+                }
+            }
+            Some(ProfileAction::Switch(name)) => self.instance.switch_to_profile(name),
+            None => (),
+        }
+
+        if *self.instance.current_profile() != previous_profile {
+            self.current_profile_changed();
+        }
+
+        if modal.should_close() {
+            self.profiles_modal = ProfilesModal::default();
+        }
+    }
+
+    fn status_bar(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            if let Some(err) = self.instance.write_error() {
+                let mut text = format!("Failed to save instance data: {err}");
+                if let Some(source) = err.source() {
+                    let _ = write!(text, ": {source}");
+                }
+                ui.colored_label(ui.visuals().error_fg_color, text);
+                ui.separator();
+            }
+
+            self.game_status(ui);
+            self.notifications.list_button(ui);
+            self.background_task_queue.list_button(ui, &self.background_task_status);
+
+            let status = self.background_task_status.lock().expect("lock is not poisoned");
+            ui.label(status.as_str());
+            drop(status);
+
+            if self.directory_import_cancel.is_some() && ui.button("Cancel folder import").clicked() {
+                self.cancel_directory_import();
+            }
+
+            if self.url_install_cancel.is_some() && ui.button("Cancel download").clicked() {
+                self.cancel_url_install();
+            }
+        });
+    }
+
+    fn spawn_background_task(&self, name: impl Into<String>, task: BackgroundTask) {
+        if self.background_task_queue.push(name, task).is_err() {
+            error!("background task panicked");
+        }
+    }
+
+    fn get_single_selected_mod(&self) -> Option<ModOrderIndex> {
+        if self.selection.len() != 1 {
+            return None;
+        }
+        self.selection.iter().next().copied()
+    }
+}
+
+#[derive(Debug, Default)]
+struct CreateNewModModal {
+    open: bool,
     kind: ModEntryKind,
     input: String,
 }
@@ -639,6 +1925,103 @@ impl RenameModModal {
     }
 }
 
+/// Text field replacing the name of a mod in the table, to rename it in place.
+#[derive(Debug)]
+struct InlineRename {
+    mod_index: ModIndex,
+    input: String,
+    focused: bool,
+}
+
+impl InlineRename {
+    fn new(mod_index: ModIndex, name: &str) -> Self {
+        Self { mod_index, input: name.to_owned(), focused: false }
+    }
+
+    /// Shows the text field, returning `Some(true)` if the new name was submitted,
+    /// or `Some(false)` if renaming was cancelled.
+    fn show(&mut self, ui: &mut Ui, instance: &EditableInstance) -> Option<bool> {
+        let current_name = instance.mods()[self.mod_index].name();
+        let problem = if !ModDeclaration::is_name_valid(&self.input) {
+            Some("Invalid name.")
+        } else if self.input != current_name.as_str() && instance.is_mod_name_taken(&self.input) {
+            Some("A mod with this name already exists.")
+        } else {
+            None
+        };
+
+        let mut text_edit = TextEdit::singleline(&mut self.input).desired_width(f32::INFINITY);
+        if problem.is_some() {
+            text_edit = text_edit.text_color(ui.visuals().error_fg_color);
+        }
+        let mut response = ui.add(text_edit);
+        if let Some(problem) = problem {
+            response = response.on_hover_text(problem);
+        }
+        if !self.focused {
+            response.request_focus();
+            self.focused = true;
+        }
+
+        if !response.lost_focus() {
+            return None;
+        }
+        if !ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            return Some(false);
+        }
+        if problem.is_some() {
+            // keep editing until the name is fixed, or renaming is cancelled with Escape
+            response.request_focus();
+            return None;
+        }
+        Some(true)
+    }
+}
+
+#[derive(Debug, Default)]
+struct TargetModal {
+    mod_idx: Option<ModIndex>,
+    input: String,
+}
+
+impl TargetModal {
+    fn open(&mut self, instance: &EditableInstance, selected_mod: ModOrderIndex) {
+        let mod_decl = instance.mod_by_order_index(selected_mod);
+        if mod_decl.kind() != ModEntryKind::Mod {
+            return;
+        }
+        self.mod_idx = Some(instance.mod_order()[selected_mod].mod_index());
+        self.input = mod_decl.target().map(ToString::to_string).unwrap_or_default();
+    }
+}
+
+#[derive(Debug, Default)]
+struct BulkRenameModal {
+    mods: Vec<ModIndex>,
+    affix: bool,
+    /// Text to find, or prefix.
+    first: String,
+    /// Replacement text, or suffix.
+    second: String,
+}
+
+impl BulkRenameModal {
+    fn open(&mut self, instance: &EditableInstance, selection: &HashSet<ModOrderIndex>) {
+        *self = Self::default();
+        self.mods
+            .extend(selection.iter().map(|idx| instance.mod_order()[*idx].mod_index()));
+        self.mods.sort_unstable_by_key(|idx| instance.mods()[*idx].name());
+    }
+
+    fn pattern(&self) -> RenamePattern<'_> {
+        if self.affix {
+            RenamePattern::Affix { prefix: &self.first, suffix: &self.second }
+        } else {
+            RenamePattern::FindReplace { find: &self.first, replace: &self.second }
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 struct RemoveSelectedModsModal {
     pub selected: Vec<ModIndex>,
@@ -697,15 +2080,134 @@ impl RemoveSelectedModsModal {
     }
 }
 
-fn tracing_setup() {
+#[derive(Debug, Default)]
+struct ImportModListModal {
+    open: bool,
+    input: String,
+    message: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct ProfilesModal {
+    open: bool,
+    /// Name typed to create a profile with.
+    new_name: String,
+    /// The profile being renamed, and the display name typed for it.
+    renaming: Option<(CompactString, String)>,
+    /// The profile whose deletion is awaiting confirmation.
+    confirm_delete: Option<CompactString>,
+}
+
+/// An action taken in the [`ProfilesModal`], applied after it's drawn.
+enum ProfileAction {
+    Create,
+    Delete(CompactString),
+    Duplicate(CompactString),
+    Rename,
+    Switch(CompactString),
+}
+
+#[derive(Debug, Default)]
+struct TrashModal {
+    entries: Option<Vec<TrashEntry>>,
+}
+
+impl TrashModal {
+    fn open(&mut self, instance: &EditableInstance) {
+        match instance.trash_entries() {
+            Ok(entries) => self.entries = Some(entries),
+            Err(err) => error!("failed to read trash: {}", err),
+        }
+    }
+}
+
+/// Fills the background of a table cell, reaching into the spacing between cells, if `fill` is set.
+fn paint_band(ui: &Ui, fill: Option<Color32>) {
+    if let Some(fill) = fill {
+        let rect = ui
+            .max_rect()
+            .expand2(egui::vec2(ui.spacing().item_spacing.x / 2.0, 0.0));
+        ui.painter().rect_filled(rect, 0.0, fill);
+    }
+}
+
+/// Shows a clickable header for a column the table can be sorted by.
+fn sort_header(ui: &mut Ui, sort: &mut Option<TableSort>, column: SortColumn, text: &str) {
+    let text = RichText::new(TableSort::header_text(*sort, column, text)).strong();
+    if ui.add(Button::new(text).frame(false)).clicked() {
+        *sort = TableSort::clicked(*sort, column);
+    }
+}
+
+/// Shows a warning if `input` is already used by another mod, with a button to replace it with a free name.
+fn name_taken_hint(ui: &mut Ui, instance: &EditableInstance, input: &mut String) {
+    if !ModDeclaration::is_name_valid(input) || !instance.is_mod_name_taken(input) {
+        return;
+    }
+
+    ui.colored_label(ui.visuals().warn_fg_color, "A mod with this name already exists.");
+    let suggestion = instance.suggest_mod_name(input);
+    if ui.button(format!("Use \"{suggestion}\"")).clicked() {
+        *input = suggestion.into();
+    }
+}
+
+/// Returns a [`BackgroundTask`] that permanently deletes the specified trash entries.
+fn purge_trash_entries(entries: Vec<TrashEntry>) -> BackgroundTask {
+    Box::new(move |status| {
+        for entry in entries {
+            {
+                let mut s = status.lock().expect("lock is not poisoned");
+                s.clear();
+                let _ = write!(s, "Deleting mod {}", entry.name());
+            }
+
+            let path = entry.path().to_owned();
+            if let Err(err) = entry.purge() {
+                error!("failed to delete '{}': {}", path.display(), err);
+            }
+        }
+
+        None
+    })
+}
+
+/// Returns a [`BackgroundTask`] that deletes the specified mod directories.
+fn delete_directories(paths: Vec<PathBuf>) -> BackgroundTask {
+    Box::new(move |status| {
+        for path in paths {
+            {
+                let mut s = status.lock().expect("lock is not poisoned");
+                s.clear();
+                let _ = write!(
+                    s,
+                    "Deleting mod {}",
+                    path.file_name().unwrap_or(OsStr::new("?")).display()
+                );
+            }
+
+            info!("removing mod directory '{}'", path.display());
+            if let Err(err) = fs::remove_dir_all(&path) {
+                error!("failed to delete '{}': {}", path.display(), err);
+            }
+        }
+
+        None
+    })
+}
+
+fn tracing_setup() -> Arc<Notifications> {
     let filter = EnvFilter::builder()
         .with_default_directive(Level::DEBUG.into())
         .from_env()
         .expect("invalid logging configuration");
 
+    let notifications = Arc::new(Notifications::default());
     let collector = tracing_subscriber::fmt()
         .with_writer(std::io::stderr)
         .with_env_filter(filter)
-        .finish();
+        .finish()
+        .with(NotificationLayer(Arc::clone(&notifications)));
     tracing::subscriber::set_global_default(collector).expect("failed to set global logger");
+    notifications
 }
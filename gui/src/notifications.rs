@@ -0,0 +1,201 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! In-app notifications of the errors and warnings logged by the application.
+//!
+//! Events are captured by a [`tracing`] layer, so failures in background threads and in the edit crate
+//! are shown to the user, not only written to the log. New notifications pop up as toasts for a few seconds,
+//! and are kept in a list opened from the status bar.
+
+use std::collections::VecDeque;
+use std::fmt::{self, Write};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use eframe::egui;
+use egui::{Align2, Area, Color32, Context, Frame, Id, Popup, RichText, ScrollArea, Ui, vec2};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::{Layer, layer};
+
+/// How long a notification is shown as a toast.
+const TOAST_DURATION: Duration = Duration::from_secs(6);
+/// Maximum number of notifications kept in the list.
+const MAX_NOTIFICATIONS: usize = 100;
+
+struct Notification {
+    level: Level,
+    message: String,
+    time: Instant,
+    /// Whether the toast was closed before it expired.
+    dismissed: bool,
+}
+
+/// Notifications shared between the [`NotificationLayer`] and the UI.
+#[derive(Default)]
+pub struct Notifications {
+    list: Mutex<VecDeque<Notification>>,
+    /// Context to repaint when a notification arrives, set once the UI is created.
+    ctx: OnceLock<Context>,
+}
+
+impl Notifications {
+    /// Sets the context to repaint when a notification arrives.
+    pub fn set_context(&self, ctx: &Context) {
+        let _ = self.ctx.set(ctx.clone());
+    }
+
+    fn push(&self, level: Level, message: String) {
+        let mut list = self.list.lock().expect("lock is not poisoned");
+        if list.len() == MAX_NOTIFICATIONS {
+            let _ = list.pop_front();
+        }
+        list.push_back(Notification {
+            level,
+            message,
+            time: Instant::now(),
+            dismissed: false,
+        });
+        drop(list);
+
+        if let Some(ctx) = self.ctx.get() {
+            ctx.request_repaint();
+        }
+    }
+
+    /// Shows the notifications that arrived in the last few seconds in the bottom right corner.
+    pub fn toasts(&self, ctx: &Context) {
+        let mut list = self.list.lock().expect("lock is not poisoned");
+        let now = Instant::now();
+        let Some(oldest) = list
+            .iter()
+            .filter(|n| !n.dismissed && now.duration_since(n.time) < TOAST_DURATION)
+            .map(|n| n.time)
+            .min()
+        else {
+            return;
+        };
+        ctx.request_repaint_after(TOAST_DURATION.saturating_sub(now.duration_since(oldest)));
+
+        Area::new(Id::new("toasts"))
+            .anchor(Align2::RIGHT_BOTTOM, vec2(-8.0, -32.0))
+            .show(ctx, |ui| {
+                for notification in list
+                    .iter_mut()
+                    .filter(|n| !n.dismissed && now.duration_since(n.time) < TOAST_DURATION)
+                {
+                    Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.set_max_width(400.0);
+                        ui.horizontal(|ui| {
+                            ui.colored_label(level_color(ui, notification.level), notification.message.as_str());
+                            if ui.small_button("✖").clicked() {
+                                notification.dismissed = true;
+                            }
+                        });
+                    });
+                }
+            });
+    }
+
+    /// Shows a button with the number of notifications, which opens a list of them, if there are any.
+    pub fn list_button(&self, ui: &mut Ui) {
+        let mut list = self.list.lock().expect("lock is not poisoned");
+        if list.is_empty() {
+            return;
+        }
+
+        let has_errors = list.iter().any(|n| n.level == Level::ERROR);
+        let text = format!("🔔 {}", list.len());
+        let response = if has_errors {
+            ui.button(RichText::new(text).color(ui.visuals().error_fg_color))
+        } else {
+            ui.button(text)
+        };
+
+        let mut clear = false;
+        Popup::menu(&response).show(|ui| {
+            ui.set_max_width(500.0);
+            ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                for notification in list.iter().rev() {
+                    let age = notification.time.elapsed().as_secs();
+                    ui.horizontal(|ui| {
+                        ui.weak(format_age(age));
+                        ui.colored_label(level_color(ui, notification.level), notification.message.as_str());
+                    });
+                }
+            });
+            ui.separator();
+            clear = ui.button("Clear").clicked();
+        });
+        if clear {
+            list.clear();
+        }
+        ui.separator();
+    }
+}
+
+fn level_color(ui: &Ui, level: Level) -> Color32 {
+    if level == Level::ERROR {
+        ui.visuals().error_fg_color
+    } else {
+        ui.visuals().warn_fg_color
+    }
+}
+
+fn format_age(secs: u64) -> String {
+    match secs {
+        0..60 => "now".to_owned(),
+        60..3600 => format!("{}m ago", secs / 60),
+        _ => format!("{}h ago", secs / 3600),
+    }
+}
+
+/// [`Layer`] that turns warnings and errors logged by this application's crates into notifications.
+pub struct NotificationLayer(pub Arc<Notifications>);
+
+impl<S: Subscriber> Layer<S> for NotificationLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: layer::Context<'_, S>) {
+        let metadata = event.metadata();
+        // more verbose levels compare greater
+        if *metadata.level() > Level::WARN {
+            return;
+        }
+        // skip messages from dependencies, such as the graphics stack
+        let krate = metadata.target().split("::").next().unwrap_or_default();
+        if krate != "mmm" && !krate.starts_with("mmm_") {
+            return;
+        }
+
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        self.0.push(*metadata.level(), visitor.message + &visitor.fields);
+    }
+}
+
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            let _ = write!(self.fields, " ({}: {:?})", field.name(), value);
+        }
+    }
+}
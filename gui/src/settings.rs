@@ -0,0 +1,306 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Application settings, read from `$XDG_CONFIG_HOME/mmm/gui.toml` at startup.
+//!
+//! ```toml
+//! theme = "system"
+//! confirm-removal = true
+//! confirm-profile-deletion = true
+//! recent-instances = ["/home/user/games/some-game"]
+//!
+//! [columns]
+//! version = true
+//! category = true
+//! size = true
+//! conflicts = false
+//!
+//! # Options passed to mmm-deploy by the launch button
+//! [deploy]
+//! backend = "overlay"
+//! skip-missing = false
+//! check-purity = false
+//! shared-saves = false
+//! ```
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use eframe::egui;
+use egui::{ComboBox, Context, Id, Modal, Sides, ThemePreference, Ui};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::error;
+
+use crate::ModManagerUi;
+use crate::columns::TableColumns;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Settings {
+    pub theme: Theme,
+    /// Ask before moving mods to the trash.
+    pub confirm_removal: bool,
+    /// Ask before deleting a profile.
+    pub confirm_profile_deletion: bool,
+    /// Instances listed on the start screen, most recently opened first.
+    pub recent_instances: Vec<PathBuf>,
+    /// Optional columns shown in the mod table.
+    pub columns: TableColumns,
+    pub deploy: DeploySettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            confirm_removal: true,
+            confirm_profile_deletion: true,
+            recent_instances: Vec::new(),
+            columns: TableColumns::default(),
+            deploy: DeploySettings::default(),
+        }
+    }
+}
+
+/// Whether the interface is dark or light.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    /// Follow the theme of the desktop environment.
+    #[default]
+    System,
+    Dark,
+    Light,
+}
+
+impl Theme {
+    pub const ALL: [Self; 3] = [Self::System, Self::Dark, Self::Light];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::System => "Follow system",
+            Self::Dark => "Dark",
+            Self::Light => "Light",
+        }
+    }
+
+    /// Applies the theme to the whole application.
+    pub fn apply(self, ctx: &Context) {
+        ctx.set_theme(match self {
+            Self::System => ThemePreference::System,
+            Self::Dark => ThemePreference::Dark,
+            Self::Light => ThemePreference::Light,
+        });
+    }
+}
+
+/// Options passed to `mmm-deploy` when launching the game.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct DeploySettings {
+    pub backend: DeployBackend,
+    /// Deploy without the enabled mods whose directory is missing, instead of failing.
+    pub skip_missing: bool,
+    /// Report files in the game directory that seem to be left over from other mod managers.
+    pub check_purity: bool,
+    /// Let the game use its own saves, instead of the profile's.
+    pub shared_saves: bool,
+}
+
+impl DeploySettings {
+    /// Adds the command line options corresponding to these settings to `command`.
+    pub fn apply(&self, command: &mut Command) {
+        command.arg("--backend").arg(self.backend.name());
+        if self.skip_missing {
+            command.arg("--skip-missing");
+        }
+        if self.check_purity {
+            command.arg("--check-purity");
+        }
+        if self.shared_saves {
+            command.arg("--shared-saves");
+        }
+    }
+}
+
+/// How `mmm-deploy` places the mod files in the game directory.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DeployBackend {
+    #[default]
+    Overlay,
+    Symlink,
+    Hardlink,
+    Copy,
+}
+
+impl DeployBackend {
+    pub const ALL: [Self; 4] = [Self::Overlay, Self::Symlink, Self::Hardlink, Self::Copy];
+
+    /// Returns the name of the backend, as accepted by `mmm-deploy --backend`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Overlay => "overlay",
+            Self::Symlink => "symlink",
+            Self::Hardlink => "hardlink",
+            Self::Copy => "copy",
+        }
+    }
+}
+
+/// Maximum number of entries in [`Settings::recent_instances`].
+const MAX_RECENT_INSTANCES: usize = 10;
+
+impl Settings {
+    /// Moves `dir` to the top of the recently opened instances, adding it if it's not there.
+    pub fn add_recent_instance(&mut self, dir: &Path) {
+        self.recent_instances.retain(|recent| recent != dir);
+        self.recent_instances.insert(0, dir.to_owned());
+        self.recent_instances.truncate(MAX_RECENT_INSTANCES);
+    }
+
+    /// Reads the settings file, returning the default settings if it doesn't exist.
+    pub fn load() -> Result<Self, SettingsError> {
+        let Some(path) = settings_path() else {
+            return Ok(Self::default());
+        };
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(source) => return Err(SettingsError::Read { path, source }),
+        };
+        toml::from_str(&contents).map_err(|source| SettingsError::Parse { path, source })
+    }
+
+    /// Writes the settings file, creating the configuration directory if needed.
+    pub fn save(&self) -> Result<(), SettingsError> {
+        let Some(path) = settings_path() else {
+            return Err(SettingsError::NoConfigDir);
+        };
+        let contents = toml::to_string_pretty(self).expect("settings are serializable");
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|source| SettingsError::Write { path: path.clone(), source })?;
+        }
+        fs::write(&path, contents).map_err(|source| SettingsError::Write { path, source })
+    }
+}
+
+/// Returns the path of the settings file, if the user's configuration directory is known.
+fn settings_path() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_dir.join("mmm").join("gui.toml"))
+}
+
+#[derive(Debug, Error)]
+pub enum SettingsError {
+    #[error("the user's configuration directory is unknown")]
+    NoConfigDir,
+    #[error("failed to parse settings file '{path}'")]
+    Parse { path: PathBuf, source: toml::de::Error },
+    #[error("failed to read settings file '{path}'")]
+    Read { path: PathBuf, source: io::Error },
+    #[error("failed to write settings file '{path}'")]
+    Write { path: PathBuf, source: io::Error },
+}
+
+impl ModManagerUi {
+    /// Writes the settings file, logging any error.
+    pub(crate) fn save_settings(&self) {
+        if let Err(err) = self.settings.save() {
+            error!("failed to save settings: {:#}", anyhow::Error::from(err));
+        }
+    }
+
+    /// Shows the settings dialog, if it's open. Changes are only applied when saved.
+    pub(crate) fn settings_modal(&mut self, ui: &mut Ui) {
+        let Some(draft) = &mut self.settings_draft else {
+            return;
+        };
+
+        let mut save = false;
+        let modal = Modal::new(Id::new("settings")).show(ui.ctx(), |ui| {
+            ui.set_width(400.0);
+            ui.heading("Settings");
+
+            ComboBox::from_label("Theme")
+                .selected_text(draft.theme.name())
+                .show_ui(ui, |ui| {
+                    for theme in Theme::ALL {
+                        ui.selectable_value(&mut draft.theme, theme, theme.name());
+                    }
+                });
+            ui.separator();
+
+            ui.label("Confirmations");
+            ui.checkbox(&mut draft.confirm_removal, "Ask before moving mods to the trash");
+            ui.checkbox(&mut draft.confirm_profile_deletion, "Ask before deleting a profile");
+            ui.separator();
+
+            ui.label("Columns shown in the mod table");
+            ui.horizontal(|ui| {
+                let _ = draft.columns.checkboxes(ui);
+            });
+            ui.separator();
+
+            ui.label("Deployment");
+            ComboBox::from_label("Backend")
+                .selected_text(draft.deploy.backend.name())
+                .show_ui(ui, |ui| {
+                    for backend in DeployBackend::ALL {
+                        ui.selectable_value(&mut draft.deploy.backend, backend, backend.name());
+                    }
+                });
+            ui.checkbox(
+                &mut draft.deploy.skip_missing,
+                "Skip enabled mods whose directory is missing",
+            );
+            ui.checkbox(
+                &mut draft.deploy.check_purity,
+                "Report files left over from other mod managers",
+            );
+            ui.checkbox(&mut draft.deploy.shared_saves, "Let the game use its own saves");
+
+            Sides::new().show(
+                ui,
+                |_| (),
+                |ui| {
+                    if ui.button("Cancel").clicked() {
+                        ui.close();
+                    }
+                    if ui.button("Save").clicked() {
+                        save = true;
+                        ui.close();
+                    }
+                },
+            );
+        });
+
+        if save && let Some(draft) = self.settings_draft.take() {
+            draft.theme.apply(ui.ctx());
+            self.settings = draft;
+            self.save_settings();
+        }
+        if modal.should_close() {
+            self.settings_draft = None;
+        }
+    }
+}
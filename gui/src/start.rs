@@ -0,0 +1,192 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Start screen, shown when no instance is specified on the command line.
+
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
+
+use anyhow::Context as _;
+use eframe::{App, Frame, egui};
+use egui::{Button, CentralPanel, Context, Grid, RichText, ScrollArea, Ui, ViewportCommand};
+use futures::task::noop_waker;
+use rfd::AsyncFileDialog;
+use tracing::error;
+
+use mmm_core::instance::Instance;
+use mmm_edit::EditableInstance;
+
+use crate::notifications::Notifications;
+use crate::settings::Settings;
+use crate::{ModManagerUi, window_title};
+
+/// Lets the user pick an instance to open, then shows the [`ModManagerUi`] for it.
+pub enum StartScreen {
+    Picking(InstancePicker),
+    Open(Box<ModManagerUi>),
+}
+
+impl StartScreen {
+    pub fn new(settings: Settings, notifications: Arc<Notifications>, ctx: &Context) -> Box<Self> {
+        notifications.set_context(ctx);
+        Box::new(Self::Picking(InstancePicker {
+            settings,
+            notifications,
+            folder_picker: None,
+        }))
+    }
+}
+
+impl App for StartScreen {
+    fn logic(&mut self, ctx: &Context, frame: &mut Frame) {
+        if let Self::Open(manager) = self {
+            manager.logic(ctx, frame);
+        }
+    }
+
+    fn ui(&mut self, ui: &mut Ui, frame: &mut Frame) {
+        match self {
+            Self::Picking(picker) => {
+                if let Some(instance) = picker.show(ui, frame) {
+                    ui.send_viewport_cmd(ViewportCommand::Title(window_title(Some(instance.dir()))));
+                    let settings = mem::take(&mut picker.settings);
+                    let notifications = Arc::clone(&picker.notifications);
+                    *self = Self::Open(ModManagerUi::new(instance, settings, notifications, ui.ctx()));
+                }
+            }
+            Self::Open(manager) => manager.ui(ui, frame),
+        }
+    }
+}
+
+pub struct InstancePicker {
+    settings: Settings,
+    notifications: Arc<Notifications>,
+    folder_picker: Option<(
+        FolderPurpose,
+        Pin<Box<dyn Future<Output = Option<rfd::FileHandle>> + Send>>,
+    )>,
+}
+
+/// What to do with the folder chosen in the folder picker.
+#[derive(Copy, Clone)]
+enum FolderPurpose {
+    Open,
+    Create,
+}
+
+impl InstancePicker {
+    /// Shows the start screen, returning the instance the user opened, if any.
+    fn show(&mut self, ui: &mut Ui, frame: &Frame) -> Option<EditableInstance> {
+        let mut opened = self.poll_folder_picker();
+        let mut remove = None;
+
+        CentralPanel::default().show_inside(ui, |ui| {
+            ui.heading("Open an instance");
+            ui.add_space(8.0);
+
+            ui.label("Recent instances");
+            if self.settings.recent_instances.is_empty() {
+                ui.weak("No instance has been opened yet.");
+            } else {
+                ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    Grid::new("recent_instances")
+                        .num_columns(2)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (i, dir) in self.settings.recent_instances.iter().enumerate() {
+                                let exists = dir.is_dir();
+                                let text = dir.display().to_string();
+                                let text = if exists {
+                                    RichText::new(text)
+                                } else {
+                                    RichText::new(text).weak()
+                                };
+                                let response = ui
+                                    .add_enabled(exists, Button::new(text).frame(false))
+                                    .on_disabled_hover_text("This directory no longer exists");
+                                if response.clicked() {
+                                    opened = open_instance(dir, FolderPurpose::Open);
+                                }
+                                if ui.small_button("✖").on_hover_text("Remove from this list").clicked() {
+                                    remove = Some(i);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            }
+            ui.add_space(8.0);
+
+            ui.add_enabled_ui(self.folder_picker.is_none(), |ui| {
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Browse…")
+                        .on_hover_text("Open an existing instance")
+                        .clicked()
+                    {
+                        self.pick_folder(FolderPurpose::Open, frame);
+                    }
+                    if ui
+                        .button("Create…")
+                        .on_hover_text("Create a new instance in an empty folder")
+                        .clicked()
+                    {
+                        self.pick_folder(FolderPurpose::Create, frame);
+                    }
+                });
+            });
+        });
+
+        if let Some(i) = remove {
+            let _ = self.settings.recent_instances.remove(i);
+            if let Err(err) = self.settings.save() {
+                error!("failed to save settings: {:#}", anyhow::Error::from(err));
+            }
+        }
+
+        self.notifications.toasts(ui.ctx());
+        opened
+    }
+
+    fn pick_folder(&mut self, purpose: FolderPurpose, frame: &Frame) {
+        let picker = AsyncFileDialog::new().set_parent(frame).pick_folder();
+        self.folder_picker = Some((purpose, Box::pin(picker)));
+    }
+
+    fn poll_folder_picker(&mut self) -> Option<EditableInstance> {
+        let (purpose, picker) = self.folder_picker.as_mut()?;
+        match picker.as_mut().poll(&mut TaskContext::from_waker(&noop_waker())) {
+            Poll::Pending => None,
+            Poll::Ready(folder) => {
+                let purpose = *purpose;
+                self.folder_picker = None;
+                open_instance(&PathBuf::from(folder?), purpose)
+            }
+        }
+    }
+}
+
+/// Opens or creates the instance at `dir`, logging any error.
+fn open_instance(dir: &Path, purpose: FolderPurpose) -> Option<EditableInstance> {
+    let result = match purpose {
+        FolderPurpose::Open => EditableInstance::open(dir).context("failed to open instance"),
+        FolderPurpose::Create => EditableInstance::create(dir).context("failed to create instance"),
+    };
+    result.inspect_err(|err| error!("{:#}", err)).ok()
+}
@@ -30,6 +30,7 @@ pub struct TreeDisplay {
     state: TreeViewState<NodeId>,
     queue: Vec<NodeId>,
     parent_stack: Vec<NodeId>,
+    open_dirs: Vec<NodeId>,
     id: Id,
 }
 
@@ -39,10 +40,16 @@ impl TreeDisplay {
             state: TreeViewState::default(),
             queue: Vec::new(),
             parent_stack: Vec::new(),
+            open_dirs: Vec::new(),
             id: Id::new(("tree", Instant::now())),
         }
     }
 
+    /// Returns the directories that were shown open the last time the tree was [displayed](Self::display).
+    pub fn open_dirs(&self) -> &[NodeId] {
+        &self.open_dirs
+    }
+
     pub fn display<T>(
         &mut self,
         ui: &mut Ui,
@@ -63,6 +70,7 @@ impl TreeDisplay {
                         self.parent_stack.push(root_id);
 
                         self.queue.clear();
+                        self.open_dirs.clear();
                         if let Some(first) = tree.root().expect("has root node").first_child() {
                             self.queue.push(first.node_id());
                         }
@@ -90,6 +98,9 @@ impl TreeDisplay {
                             let open = builder.node(NodeConfig::new(tree, &node_id, &mut label_fn));
 
                             let node = tree.get(node_id).expect("node exists");
+                            if open && let TreeNodeKind::Dir = node.data().kind {
+                                self.open_dirs.push(node_id);
+                            }
                             if let Some(next_sibling) = node.next_sibling() {
                                 self.queue.push(next_sibling.node_id());
                             }
@@ -0,0 +1,207 @@
+// Copyright © 2026 Joaquim Monteiro
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Dialog for downloading and installing an archive from a URL.
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use eframe::egui;
+use egui::{Button, Id, Modal, Sides, Ui};
+use tracing::{error, info};
+
+use mmm_core::instance::{Instance, ModDeclaration};
+use mmm_edit::install::stage_archive_with_defaults;
+use mmm_edit::install::url::{DownloadError, DownloadProgress, Sha256Checksum, download_archive, file_name_from_url};
+
+use crate::ModManagerUi;
+use crate::background_task::{BackgroundTask, Finalizer, StatusString};
+use crate::utils::format_size;
+
+#[derive(Debug, Default)]
+pub struct UrlInstall {
+    url: String,
+    checksum: String,
+    name: String,
+}
+
+impl ModManagerUi {
+    pub(crate) fn url_install_ui(&mut self, ui: &mut Ui) {
+        let Some(install) = &mut self.url_install else {
+            return;
+        };
+
+        let file_name = file_name_from_url(install.url.trim());
+        if install.name.is_empty()
+            && let Some(file_name) = &file_name
+        {
+            install.name = Path::new(file_name)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or_default()
+                .to_owned();
+        }
+        let checksum = (!install.checksum.trim().is_empty()).then(|| Sha256Checksum::from_hex(&install.checksum));
+        let name_exists = self.instance.mods().iter().any(|m| m.name() == install.name.as_str());
+        let busy = self.url_install_cancel.is_some();
+        let valid = file_name.is_some()
+            && !matches!(checksum, Some(Err(_)))
+            && ModDeclaration::is_name_valid(&install.name)
+            && !name_exists;
+        let mut accepted = false;
+
+        let modal = Modal::new(Id::new("install_from_url")).show(ui.ctx(), |ui| {
+            ui.set_width(400.0);
+            ui.heading("Install from URL");
+
+            ui.label("URL:");
+            ui.text_edit_singleline(&mut install.url);
+            if !install.url.is_empty() && file_name.is_none() {
+                ui.colored_label(ui.visuals().error_fg_color, "The URL must point to an archive file.");
+            }
+
+            ui.label("SHA-256 checksum (optional):");
+            ui.text_edit_singleline(&mut install.checksum);
+            if let Some(Err(err)) = &checksum {
+                ui.colored_label(ui.visuals().error_fg_color, err.to_string());
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("Mod name:");
+                ui.text_edit_singleline(&mut install.name);
+            });
+            if name_exists {
+                ui.colored_label(ui.visuals().error_fg_color, "A mod with this name already exists.");
+            }
+            if busy {
+                ui.label("Another download is still in progress.");
+            }
+
+            Sides::new().show(
+                ui,
+                |_| (),
+                |ui| {
+                    if ui.button("Cancel").clicked() {
+                        ui.close();
+                    }
+
+                    if ui.add_enabled(valid && !busy, Button::new("Install")).clicked() {
+                        accepted = true;
+                        ui.close();
+                    }
+                },
+            );
+        });
+
+        if accepted {
+            let cancel = Arc::new(AtomicBool::new(false));
+            self.url_install_cancel = Some(Arc::clone(&cancel));
+            let task_name = format!("Installing '{}' from a URL", install.name);
+            let task = download_and_stage(
+                self.instance.downloads_dir(),
+                self.instance.mods_dir(),
+                install.url.trim().to_owned(),
+                checksum.and_then(Result::ok),
+                install.name.clone(),
+                cancel,
+            );
+            self.spawn_background_task(task_name, task);
+        }
+
+        if modal.should_close() {
+            self.url_install = None;
+        }
+    }
+
+    pub(crate) fn cancel_url_install(&self) {
+        if let Some(cancel) = &self.url_install_cancel {
+            cancel.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Returns a [`BackgroundTask`] that downloads an archive into the downloads directory,
+/// extracts it into the mods directory, and then adds it as a new mod.
+fn download_and_stage(
+    downloads_dir: PathBuf,
+    mods_dir: PathBuf,
+    url: String,
+    checksum: Option<Sha256Checksum>,
+    name: String,
+    cancel: Arc<AtomicBool>,
+) -> BackgroundTask {
+    Box::new(move |status: &StatusString| {
+        let progress = DownloadProgress::default();
+        let result = thread::scope(|s| {
+            let handle = s.spawn(|| download_archive(&url, &downloads_dir, checksum, &progress, &cancel));
+            while !handle.is_finished() {
+                {
+                    let mut s = status.lock().expect("lock is not poisoned");
+                    s.clear();
+                    let _ = write!(s, "Downloading {name}: {}", format_size(progress.downloaded()));
+                    if let Some(total) = progress.total() {
+                        let _ = write!(s, " of {}", format_size(total));
+                    }
+                }
+                thread::sleep(Duration::from_millis(100));
+            }
+            handle.join().expect("download thread does not panic")
+        });
+
+        let download = match result {
+            Ok(download) => download,
+            Err(DownloadError::Cancelled) => {
+                info!("download of '{}' was cancelled", url);
+                return Some(Box::new(|mm: &mut ModManagerUi| mm.url_install_cancel = None) as Finalizer);
+            }
+            Err(err) => {
+                error!(?err, "failed to download '{}'", url);
+                return Some(Box::new(|mm: &mut ModManagerUi| mm.url_install_cancel = None) as Finalizer);
+            }
+        };
+
+        {
+            let mut s = status.lock().expect("lock is not poisoned");
+            s.clear();
+            let _ = write!(s, "Installing mod {name}");
+        }
+        let result = stage_archive_with_defaults(&mods_dir, &downloads_dir.join(download.as_str()));
+
+        let finalizer: Finalizer = Box::new(move |mm: &mut ModManagerUi| {
+            mm.url_install_cancel = None;
+            let staged = match result {
+                Ok(staged) => staged,
+                Err(err) => {
+                    error!(?err, "failed to extract downloaded archive");
+                    return;
+                }
+            };
+            match mm.instance.add_staged_mod(&name, staged) {
+                Ok(idx) => {
+                    if let Err(err) = mm.instance.set_mod_source(idx, Some(&download)) {
+                        error!("failed to record source of mod: {}", err);
+                    }
+                    mm.mod_added();
+                }
+                Err(err) => error!("failed to create mod '{}': {}", name, err),
+            }
+        });
+        Some(finalizer)
+    })
+}
@@ -16,7 +16,9 @@
 use std::cell::Cell;
 
 use eframe::egui;
-use egui::{Vec2, ViewportBuilder, ViewportId};
+use egui::{Color32, Vec2, ViewportBuilder, ViewportId};
+
+use mmm_core::instance::ModLabel;
 
 pub struct Viewport {
     pub id: ViewportId,
@@ -60,3 +62,39 @@ impl From<ViewportResult> for bool {
         matches!(value, ViewportResult::Keep)
     }
 }
+
+/// Formats a size in bytes for display, using binary prefixes.
+#[must_use]
+#[allow(clippy::cast_precision_loss, reason = "the displayed value is rounded anyway")]
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next_unit in &UNITS[1..] {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next_unit;
+    }
+    format!("{size:.1} {unit}")
+}
+
+/// Returns the color used to display a [`ModLabel`].
+#[must_use]
+pub const fn label_color(label: ModLabel) -> Color32 {
+    match label {
+        ModLabel::Red => Color32::from_rgb(0xe0, 0x40, 0x40),
+        ModLabel::Orange => Color32::from_rgb(0xf0, 0x90, 0x30),
+        ModLabel::Yellow => Color32::from_rgb(0xe8, 0xd0, 0x30),
+        ModLabel::Green => Color32::from_rgb(0x40, 0xc0, 0x50),
+        ModLabel::Blue => Color32::from_rgb(0x40, 0x80, 0xe0),
+        ModLabel::Purple => Color32::from_rgb(0xa0, 0x50, 0xd0),
+        ModLabel::Gray => Color32::from_rgb(0x90, 0x90, 0x90),
+    }
+}